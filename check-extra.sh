@@ -10,6 +10,8 @@ cargo tomlfmt --dryrun --path yash-arith/Cargo.toml
 cargo tomlfmt --dryrun --path yash-builtin/Cargo.toml
 cargo tomlfmt --dryrun --path yash-env/Cargo.toml
 cargo tomlfmt --dryrun --path yash-fnmatch/Cargo.toml
+cargo tomlfmt --dryrun --path yash-lineedit/Cargo.toml
+cargo tomlfmt --dryrun --path yash-prompt/Cargo.toml
 cargo tomlfmt --dryrun --path yash-quote/Cargo.toml
 cargo tomlfmt --dryrun --path yash-semantics/Cargo.toml
 cargo tomlfmt --dryrun --path yash-syntax/Cargo.toml
@@ -24,6 +26,8 @@ cargo build --package 'yash-builtin' --all-targets
 cargo build --package 'yash-builtin' --all-targets --no-default-features
 cargo build --package 'yash-env' --all-targets
 cargo build --package 'yash-fnmatch' --all-targets
+cargo build --package 'yash-lineedit' --all-targets
+cargo build --package 'yash-prompt' --all-targets
 cargo build --package 'yash-quote' --all-targets
 cargo build --package 'yash-semantics' --all-targets
 cargo build --package 'yash-syntax' --all-targets
@@ -36,6 +40,8 @@ cargo build --package 'yash-syntax' --all-targets --features annotate-snippets
 cargo test --package 'yash-builtin' --no-default-features -- $quiet
 #cargo test --package 'yash-env' -- $quiet
 #cargo test --package 'yash-fnmatch' -- $quiet
+#cargo test --package 'yash-lineedit' -- $quiet
+#cargo test --package 'yash-prompt' -- $quiet
 #cargo test --package 'yash-quote' -- $quiet
 #cargo test --package 'yash-semantics' -- $quiet
 #cargo test --package 'yash-syntax' -- $quiet
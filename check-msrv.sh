@@ -32,6 +32,14 @@ update_workspace_member yash-fnmatch
 cargo +nightly update -Z direct-minimal-versions
 cargo +1.65.0 test --package yash-fnmatch -- $quiet
 
+update_workspace_member yash-lineedit
+cargo +nightly update -Z direct-minimal-versions
+cargo +1.75.0 test --package yash-lineedit -- $quiet
+
+update_workspace_member yash-prompt
+cargo +nightly update -Z direct-minimal-versions
+cargo +1.75.0 test --package yash-prompt -- $quiet
+
 update_workspace_member yash-quote
 cargo +nightly update -Z direct-minimal-versions
 cargo +1.65.0 test --package yash-quote -- $quiet
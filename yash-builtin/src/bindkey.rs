@@ -0,0 +1,172 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Bindkey built-in
+//!
+//! The **`bindkey`** built-in prints or modifies the [key
+//! bindings](yash_env::keymap) of the line editor.
+//!
+//! # Synopsis
+//!
+//! ```sh
+//! bindkey -aev [keyseq [command]]
+//! ```
+//!
+//! ```sh
+//! bindkey -l
+//! ```
+//!
+//! # Description
+//!
+//! With the `-a`, `-e`, or `-v` option, the built-in operates on the key
+//! bindings of the vi command mode, the emacs mode, or the vi insert mode,
+//! respectively.
+//!
+//! Without operands, the built-in prints all the key bindings of the mode.
+//! Each binding is printed as a command line that invokes the bindkey
+//! built-in to restore the binding.
+//!
+//! With a *keyseq* operand, the built-in prints the binding for the key
+//! sequence.
+//!
+//! With *keyseq* and *command* operands, the built-in binds the key sequence
+//! to the editing command. If *command* is a hyphen (`-`), the binding for the
+//! key sequence is removed.
+//!
+//! With the `-l` option, the built-in prints the names of all the editing
+//! commands.
+//!
+//! # Options
+//!
+//! - **`-a`** (**`--vi-command`**): Operates on the vi command mode.
+//! - **`-e`** (**`--emacs`**): Operates on the emacs mode.
+//! - **`-v`** (**`--vi-insert`**): Operates on the vi insert mode.
+//! - **`-l`** (**`--list`**): Prints the names of the editing commands.
+//!
+//! # Operands
+//!
+//! The *keyseq* operand is a key sequence, that is, a sequence of characters
+//! the terminal sends when the user types one or more keys. In the operand,
+//! a backslash introduces a special notation:
+//!
+//! - `\\` denotes a backslash.
+//! - `\^X` denotes the control character typed with the Ctrl key and *X*,
+//!   where *X* is a letter or one of `@[\]^_`. For example, `\^A` is Ctrl-A
+//!   and `\^[` is the escape character. `\^?` denotes the delete character.
+//!
+//! The *command* operand is the name of an editing command, such as
+//! `backward-char` or `kill-line`.
+//!
+//! # Errors
+//!
+//! It is an error if:
+//!
+//! - none of the `-a`, `-e`, `-v`, and `-l` options is specified,
+//! - more than one of them is specified,
+//! - the key sequence is empty or contains an invalid notation,
+//! - the command name is unknown, or
+//! - a binding to be printed or removed does not exist.
+//!
+//! # Exit status
+//!
+//! Zero unless an error occurs.
+//!
+//! # Portability
+//!
+//! The bindkey built-in is not specified in POSIX. Zsh has a built-in of the
+//! same name with different options and key sequence notation.
+
+use crate::common::output;
+use crate::common::report_error;
+use crate::common::report_failure;
+use yash_env::keymap::Mode;
+use yash_env::semantics::Field;
+use yash_env::Env;
+
+/// Key sequence specified as an operand
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeySequence {
+    /// Characters in the key sequence
+    pub keys: String,
+    /// Operand that specified the key sequence
+    pub field: Field,
+}
+
+/// Parsed command line arguments for the `bindkey` built-in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Print the names of all editing commands
+    ListCommands,
+    /// Print all the bindings of the mode
+    PrintAll(Mode),
+    /// Print the binding for the key sequence
+    Print(Mode, KeySequence),
+    /// Bind the key sequence to the editing command
+    Bind(Mode, KeySequence, yash_env::keymap::Command),
+    /// Remove the binding for the key sequence
+    Unbind(Mode, KeySequence),
+}
+
+pub mod keyseq;
+pub mod semantics;
+pub mod syntax;
+
+/// Entry point for executing the `bindkey` built-in
+pub async fn main(env: &mut Env, args: Vec<Field>) -> crate::Result {
+    match syntax::parse(env, args) {
+        Ok(command) => match command.execute(env) {
+            Ok(result) => output(env, &result).await,
+            Err(e) => report_failure(env, &e).await,
+        },
+        Err(e) => report_error(env, &e).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use yash_env::keymap::Lookup;
+    use yash_env::semantics::ExitStatus;
+
+    #[test]
+    fn binding_and_printing() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["-e", r"\^Xa", "kill-line"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(
+            env.key_bindings.get(Mode::Emacs).lookup("\x18a"),
+            Lookup::Command(yash_env::keymap::Command::KillLine, false)
+        );
+    }
+
+    #[test]
+    fn missing_mode_is_error() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies([r"\^A"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::ERROR));
+    }
+
+    #[test]
+    fn unbinding_unbound_sequence_is_failure() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["-v", "q", "-"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::FAILURE));
+    }
+}
@@ -0,0 +1,113 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Notation of key sequences
+//!
+//! This module converts between the notation of key sequences used in the
+//! operands of the bindkey built-in and the characters in the sequences.
+
+use thiserror::Error;
+
+/// Error in the notation of a key sequence
+#[derive(Clone, Copy, Debug, Eq, Error, Hash, PartialEq)]
+pub enum Error {
+    /// The key sequence is empty.
+    #[error("empty key sequence")]
+    Empty,
+    /// A backslash is followed by an unknown notation.
+    #[error("invalid backslash notation")]
+    InvalidNotation,
+}
+
+/// Converts the notation to the key sequence.
+pub fn parse(notation: &str) -> Result<String, Error> {
+    let mut keys = String::with_capacity(notation.len());
+    let mut chars = notation.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            keys.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => keys.push('\\'),
+            Some('^') => match chars.next() {
+                Some('?') => keys.push('\x7F'),
+                Some(c @ ('@'..='_' | 'a'..='z')) => {
+                    keys.push((c.to_ascii_uppercase() as u8 & 0x1F) as char)
+                }
+                _ => return Err(Error::InvalidNotation),
+            },
+            _ => return Err(Error::InvalidNotation),
+        }
+    }
+    if keys.is_empty() {
+        Err(Error::Empty)
+    } else {
+        Ok(keys)
+    }
+}
+
+/// Converts the key sequence to the notation.
+///
+/// The result can be converted back to the key sequence with [`parse`].
+#[must_use]
+pub fn format(keys: &str) -> String {
+    let mut notation = String::with_capacity(keys.len());
+    for c in keys.chars() {
+        match c {
+            '\\' => notation.push_str("\\\\"),
+            '\x7F' => notation.push_str("\\^?"),
+            c if c.is_ascii_control() => {
+                notation.push_str("\\^");
+                notation.push((c as u8 | 0x40) as char);
+            }
+            c => notation.push(c),
+        }
+    }
+    notation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_notations() {
+        assert_eq!(parse("abc"), Ok("abc".to_string()));
+        assert_eq!(parse(r"\\"), Ok("\\".to_string()));
+        assert_eq!(parse(r"\^A\^z"), Ok("\x01\x1A".to_string()));
+        assert_eq!(parse(r"\^[[A"), Ok("\x1B[A".to_string()));
+        assert_eq!(parse(r"\^@\^?"), Ok("\0\x7F".to_string()));
+    }
+
+    #[test]
+    fn parsing_errors() {
+        assert_eq!(parse(""), Err(Error::Empty));
+        assert_eq!(parse("\\"), Err(Error::InvalidNotation));
+        assert_eq!(parse(r"\n"), Err(Error::InvalidNotation));
+        assert_eq!(parse(r"\^"), Err(Error::InvalidNotation));
+        assert_eq!(parse(r"\^1"), Err(Error::InvalidNotation));
+    }
+
+    #[test]
+    fn format_round_trip() {
+        assert_eq!(format("\x1B[A"), r"\^[[A");
+        assert_eq!(format("a\\\x7F"), r"a\\\^?");
+        for keys in ["\x01\x1A", "\x1Bf", "\0x\\", "あ"] {
+            assert_eq!(parse(&format(keys)).as_deref(), Ok(keys));
+        }
+    }
+}
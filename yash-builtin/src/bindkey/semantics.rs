@@ -0,0 +1,188 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Core runtime behavior of the `bindkey` built-in
+
+use super::keyseq;
+use super::Command;
+use super::KeySequence;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use thiserror::Error;
+use yash_env::keymap::Lookup;
+use yash_env::keymap::Mode;
+use yash_env::Env;
+use yash_quote::quoted;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::MessageBase;
+
+/// Errors that can occur while executing the `bindkey` built-in
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum Error {
+    /// The key sequence to be printed or unbound is not bound.
+    #[error("key sequence is not bound")]
+    NotBound(KeySequence),
+}
+
+impl MessageBase for Error {
+    fn message_title(&self) -> Cow<str> {
+        self.to_string().into()
+    }
+
+    fn main_annotation(&self) -> Annotation<'_> {
+        match self {
+            Error::NotBound(keys) => Annotation::new(
+                AnnotationType::Error,
+                format!("{}: not bound", keys.field.value).into(),
+                &keys.field.origin,
+            ),
+        }
+    }
+}
+
+/// Returns the option that selects the mode.
+fn mode_option(mode: Mode) -> char {
+    match mode {
+        Mode::ViInsert => 'v',
+        Mode::ViCommand => 'a',
+        Mode::Emacs => 'e',
+        _ => unreachable!("unsupported mode: {mode:?}"),
+    }
+}
+
+/// Formats a binding as a command line that invokes the bindkey built-in.
+fn format_binding(output: &mut String, mode: Mode, keys: &str, command: impl std::fmt::Display) {
+    let notation = keyseq::format(keys);
+    writeln!(
+        output,
+        "bindkey -{} {} {}",
+        mode_option(mode),
+        quoted(&notation),
+        command
+    )
+    .unwrap();
+}
+
+impl Command {
+    /// Executes the `bindkey` built-in.
+    ///
+    /// Returns the text to be printed to the standard output.
+    pub fn execute(self, env: &mut Env) -> Result<String, Error> {
+        let mut output = String::new();
+        match self {
+            Command::ListCommands => {
+                for command in yash_env::keymap::Command::iter() {
+                    writeln!(output, "{command}").unwrap();
+                }
+            }
+            Command::PrintAll(mode) => {
+                for (keys, command) in env.key_bindings.get(mode).iter() {
+                    format_binding(&mut output, mode, keys, command);
+                }
+            }
+            Command::Print(mode, keys) => match env.key_bindings.get(mode).lookup(&keys.keys) {
+                Lookup::Command(command, _) => {
+                    format_binding(&mut output, mode, &keys.keys, command)
+                }
+                _ => return Err(Error::NotBound(keys)),
+            },
+            Command::Bind(mode, keys, command) => {
+                env.key_bindings.get_mut(mode).bind(keys.keys, command);
+            }
+            Command::Unbind(mode, keys) => {
+                if env.key_bindings.get_mut(mode).unbind(&keys.keys).is_none() {
+                    return Err(Error::NotBound(keys));
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yash_env::keymap::Command as EditCommand;
+    use yash_env::keymap::KeyMap;
+    use yash_env::semantics::Field;
+
+    fn key_sequence(keys: &str) -> KeySequence {
+        KeySequence {
+            keys: keys.to_string(),
+            field: Field::dummy(keyseq::format(keys)),
+        }
+    }
+
+    #[test]
+    fn list_commands() {
+        let mut env = Env::new_virtual();
+        let output = Command::ListCommands.execute(&mut env).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"accept-line"), "{lines:?}");
+        assert!(lines.contains(&"yank-pop"), "{lines:?}");
+        assert_eq!(lines.len(), EditCommand::iter().count());
+    }
+
+    #[test]
+    fn print_all_bindings() {
+        let mut env = Env::new_virtual();
+        let mut keymap = KeyMap::new(EditCommand::SelfInsert);
+        keymap.bind("\x01", EditCommand::BeginningOfLine);
+        keymap.bind("\\", EditCommand::Noop);
+        *env.key_bindings.get_mut(Mode::Emacs) = keymap;
+
+        let output = Command::PrintAll(Mode::Emacs).execute(&mut env).unwrap();
+        assert_eq!(
+            output,
+            "bindkey -e '\\^A' beginning-of-line\nbindkey -e '\\\\' noop\n"
+        );
+    }
+
+    #[test]
+    fn print_binding() {
+        let mut env = Env::new_virtual();
+        let command = Command::Print(Mode::ViCommand, key_sequence("x"));
+        let output = command.execute(&mut env).unwrap();
+        assert_eq!(output, "bindkey -a x kill-char\n");
+
+        let command = Command::Print(Mode::ViCommand, key_sequence("\x1B["));
+        let result = command.execute(&mut env);
+        assert_eq!(result, Err(Error::NotBound(key_sequence("\x1B["))));
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let mut env = Env::new_virtual();
+        let command = Command::Bind(Mode::ViInsert, key_sequence("\x18"), EditCommand::Undo);
+        assert_eq!(command.execute(&mut env), Ok(String::new()));
+        assert_eq!(
+            env.key_bindings.get(Mode::ViInsert).lookup("\x18"),
+            Lookup::Command(EditCommand::Undo, false)
+        );
+
+        let command = Command::Unbind(Mode::ViInsert, key_sequence("\x18"));
+        assert_eq!(command.execute(&mut env), Ok(String::new()));
+        assert_eq!(
+            env.key_bindings.get(Mode::ViInsert).lookup("\x18"),
+            Lookup::Default(EditCommand::SelfInsert)
+        );
+
+        let command = Command::Unbind(Mode::ViInsert, key_sequence("\x18"));
+        let result = command.execute(&mut env);
+        assert_eq!(result, Err(Error::NotBound(key_sequence("\x18"))));
+    }
+}
@@ -0,0 +1,263 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line argument parser for the bindkey built-in
+
+use super::keyseq;
+use super::Command;
+use super::KeySequence;
+use crate::common::syntax::parse_arguments;
+use crate::common::syntax::ConflictingOptionError;
+use crate::common::syntax::OptionOccurrence;
+use crate::common::syntax::OptionSpec;
+use thiserror::Error;
+use yash_env::keymap::Mode;
+use yash_env::semantics::Field;
+use yash_env::Env;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::Message;
+
+/// Error in parsing command line arguments
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error occurred in the common parser.
+    #[error(transparent)]
+    CommonError(#[from] crate::common::syntax::ParseError<'static>),
+
+    /// More than one of the `-a`, `-e`, `-l`, and `-v` options is specified.
+    #[error(transparent)]
+    ConflictingOption(#[from] ConflictingOptionError<'static>),
+
+    /// None of the `-a`, `-e`, `-l`, and `-v` options is specified.
+    #[error("no mode option specified")]
+    MissingMode,
+
+    /// The key sequence operand is invalid.
+    #[error("{1}")]
+    InvalidKeySequence(Field, keyseq::Error),
+
+    /// The command operand does not name an editing command.
+    #[error("no such editing command")]
+    NoSuchCommand(Field),
+
+    /// Too many operands are given.
+    #[error("unexpected operand")]
+    UnexpectedOperands(Vec<Field>),
+}
+
+impl Error {
+    /// Converts this error into a message.
+    pub fn to_message(&self) -> Message {
+        use Error::*;
+        let (field, label) = match self {
+            CommonError(e) => return e.into(),
+            ConflictingOption(e) => return e.into(),
+            MissingMode => {
+                return Message {
+                    r#type: AnnotationType::Error,
+                    title: self.to_string().into(),
+                    annotations: vec![],
+                    footers: vec![],
+                }
+            }
+            InvalidKeySequence(field, _) | NoSuchCommand(field) => (field, self.to_string()),
+            UnexpectedOperands(operands) => (&operands[0], self.to_string()),
+        };
+        Message {
+            r#type: AnnotationType::Error,
+            title: self.to_string().into(),
+            annotations: vec![Annotation::new(
+                AnnotationType::Error,
+                format!("{}: {}", field.value, label).into(),
+                &field.origin,
+            )],
+            footers: vec![],
+        }
+    }
+}
+
+impl<'a> From<&'a Error> for Message<'a> {
+    #[inline]
+    fn from(e: &'a Error) -> Self {
+        e.to_message()
+    }
+}
+
+/// Result of parsing command line arguments
+pub type Result = std::result::Result<Command, Error>;
+
+const OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec::new().short('a').long("vi-command"),
+    OptionSpec::new().short('e').long("emacs"),
+    OptionSpec::new().short('l').long("list"),
+    OptionSpec::new().short('v').long("vi-insert"),
+];
+
+/// Editing mode or the list option selected by the options
+enum Selection {
+    List,
+    Mode(Mode),
+}
+
+fn selection_for_option(option: &OptionOccurrence) -> Selection {
+    match option.spec.get_short() {
+        Some('a') => Selection::Mode(Mode::ViCommand),
+        Some('e') => Selection::Mode(Mode::Emacs),
+        Some('l') => Selection::List,
+        Some('v') => Selection::Mode(Mode::ViInsert),
+        _ => unreachable!(),
+    }
+}
+
+fn key_sequence(field: Field) -> std::result::Result<KeySequence, Error> {
+    match keyseq::parse(&field.value) {
+        Ok(keys) => Ok(KeySequence { keys, field }),
+        Err(e) => Err(Error::InvalidKeySequence(field, e)),
+    }
+}
+
+/// Parses command line arguments for the bindkey built-in.
+pub fn parse(env: &Env, args: Vec<Field>) -> Result {
+    let parser_mode = crate::common::syntax::Mode::with_env(env);
+    let (mut options, operands) = parse_arguments(OPTION_SPECS, parser_mode, args)?;
+
+    // The same option may be repeated, but different options conflict.
+    options.dedup_by_key(|option| option.spec.get_short());
+    let selection = match options.len() {
+        0 => return Err(Error::MissingMode),
+        1 => selection_for_option(&options[0]),
+        _ => return Err(ConflictingOptionError::new(options).into()),
+    };
+
+    let mode = match selection {
+        Selection::Mode(mode) => mode,
+        Selection::List if operands.is_empty() => return Ok(Command::ListCommands),
+        Selection::List => return Err(Error::UnexpectedOperands(operands)),
+    };
+
+    let mut operands = operands.into_iter();
+    let Some(keys) = operands.next() else {
+        return Ok(Command::PrintAll(mode));
+    };
+    let keys = key_sequence(keys)?;
+    let Some(command) = operands.next() else {
+        return Ok(Command::Print(mode, keys));
+    };
+    let rest: Vec<Field> = operands.collect();
+    if !rest.is_empty() {
+        return Err(Error::UnexpectedOperands(rest));
+    }
+
+    if command.value == "-" {
+        return Ok(Command::Unbind(mode, keys));
+    }
+    match command.value.parse() {
+        Ok(command) => Ok(Command::Bind(mode, keys, command)),
+        Err(_) => Err(Error::NoSuchCommand(command)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use yash_env::keymap::Command as EditCommand;
+
+    #[test]
+    fn list_option() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-l"]));
+        assert_eq!(result, Ok(Command::ListCommands));
+
+        let result = parse(&env, Field::dummies(["--list", "foo"]));
+        assert_matches!(result, Err(Error::UnexpectedOperands(operands)) => {
+            assert_eq!(operands, Field::dummies(["foo"]));
+        });
+    }
+
+    #[test]
+    fn mode_options() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-a"]));
+        assert_eq!(result, Ok(Command::PrintAll(Mode::ViCommand)));
+        let result = parse(&env, Field::dummies(["--emacs"]));
+        assert_eq!(result, Ok(Command::PrintAll(Mode::Emacs)));
+        let result = parse(&env, Field::dummies(["-vv"]));
+        assert_eq!(result, Ok(Command::PrintAll(Mode::ViInsert)));
+    }
+
+    #[test]
+    fn missing_mode() {
+        let env = Env::new_virtual();
+        let result = parse(&env, vec![]);
+        assert_eq!(result, Err(Error::MissingMode));
+    }
+
+    #[test]
+    fn conflicting_options() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-a", "-e"]));
+        assert_matches!(result, Err(Error::ConflictingOption(_)));
+        let result = parse(&env, Field::dummies(["-el"]));
+        assert_matches!(result, Err(Error::ConflictingOption(_)));
+    }
+
+    #[test]
+    fn print_binding() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-e", r"\^A"]));
+        assert_matches!(result, Ok(Command::Print(Mode::Emacs, keys)) => {
+            assert_eq!(keys.keys, "\x01");
+            assert_eq!(keys.field.value, r"\^A");
+        });
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-v", "q", "accept-line"]));
+        assert_matches!(result, Ok(Command::Bind(Mode::ViInsert, keys, command)) => {
+            assert_eq!(keys.keys, "q");
+            assert_eq!(command, EditCommand::AcceptLine);
+        });
+
+        let result = parse(&env, Field::dummies(["-v", "q", "-"]));
+        assert_matches!(result, Ok(Command::Unbind(Mode::ViInsert, keys)) => {
+            assert_eq!(keys.keys, "q");
+        });
+    }
+
+    #[test]
+    fn invalid_operands() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-e", r"\x"]));
+        assert_matches!(result, Err(Error::InvalidKeySequence(field, keyseq::Error::InvalidNotation)) => {
+            assert_eq!(field.value, r"\x");
+        });
+
+        let result = parse(&env, Field::dummies(["-e", "q", "no-such-command"]));
+        assert_matches!(result, Err(Error::NoSuchCommand(field)) => {
+            assert_eq!(field.value, "no-such-command");
+        });
+
+        let result = parse(&env, Field::dummies(["-e", "q", "noop", "x"]));
+        assert_matches!(result, Err(Error::UnexpectedOperands(operands)) => {
+            assert_eq!(operands, Field::dummies(["x"]));
+        });
+    }
+}
@@ -0,0 +1,181 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Complete built-in
+//!
+//! The **`complete`** built-in registers completion functions and adds
+//! candidates for [command line completion](yash_env::completion).
+//!
+//! # Synopsis
+//!
+//! ```sh
+//! complete -F function command...
+//! ```
+//!
+//! ```sh
+//! complete -r [command...]
+//! ```
+//!
+//! ```sh
+//! complete [-cfjv] [word...]
+//! ```
+//!
+//! ```sh
+//! complete
+//! ```
+//!
+//! # Description
+//!
+//! With the `-F` option, the built-in registers the *function* as the
+//! completion function for the *command*s. When the line editor completes an
+//! argument of a command that has a completion function, the function is
+//! called to generate candidates. The positional parameters of the function
+//! are the words of the command up to the cursor, starting with the command
+//! name. The last positional parameter is the word being completed, with
+//! quotes removed.
+//!
+//! With the `-r` option, the built-in removes the completion functions for
+//! the *command*s, or all the completion functions if no *command* is given.
+//!
+//! The third form can be used only in a completion function. The built-in
+//! adds the *word*s and the candidates generated by the options to the
+//! candidates for the word being completed. Candidates that do not start
+//! with the word being completed are ignored.
+//!
+//! Without any options or operands, the built-in prints the registered
+//! completion functions as command lines that invoke the built-in to restore
+//! them.
+//!
+//! # Options
+//!
+//! - **`-F`** *function* (**`--function`**): Registers the completion
+//!   function.
+//! - **`-r`** (**`--remove`**): Removes completion functions.
+//! - **`-c`** (**`--command`**): Generates command names.
+//! - **`-f`** (**`--filename`**): Generates filenames.
+//! - **`-j`** (**`--job`**): Generates job IDs.
+//! - **`-v`** (**`--variable`**): Generates variable names.
+//!
+//! # Errors
+//!
+//! It is an error if:
+//!
+//! - the `-F` option is specified without *command*s,
+//! - the `-F` and `-r` options are used together or with the `-c`, `-f`,
+//!   `-j`, or `-v` option, or
+//! - candidates are added outside a completion function.
+//!
+//! # Exit status
+//!
+//! Zero unless an error occurs.
+//!
+//! # Portability
+//!
+//! The complete built-in is not specified in POSIX. Bash has a built-in of
+//! the same name with many more options. This implementation supports only a
+//! small subset of them, and the candidates are added in a different way.
+
+use crate::common::output;
+use crate::common::report_error;
+use crate::common::report_simple_error;
+use yash_env::semantics::Field;
+use yash_env::Env;
+
+/// Kind of candidates generated by an option
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Generator {
+    /// Command names (`-c`)
+    Command,
+    /// Filenames (`-f`)
+    Filename,
+    /// Job IDs (`-j`)
+    Job,
+    /// Variable names (`-v`)
+    Variable,
+}
+
+/// Parsed command line arguments for the `complete` built-in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Print the registered completion functions
+    Print,
+    /// Register the completion function for the commands
+    Register {
+        function: Field,
+        commands: Vec<Field>,
+    },
+    /// Remove the completion functions for the commands, or all if empty
+    Remove(Vec<Field>),
+    /// Add candidates to the ongoing completion
+    Add {
+        generators: Vec<Generator>,
+        words: Vec<Field>,
+    },
+}
+
+pub mod semantics;
+pub mod syntax;
+
+/// Entry point for executing the `complete` built-in
+pub async fn main(env: &mut Env, args: Vec<Field>) -> crate::Result {
+    match syntax::parse(env, args) {
+        Ok(command) => match command.execute(env) {
+            Ok(result) => output(env, &result).await,
+            Err(e) => report_simple_error(env, &e.to_string()).await,
+        },
+        Err(e) => report_error(env, &e).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use yash_env::completion::Request;
+    use yash_env::semantics::ExitStatus;
+
+    #[test]
+    fn registering_function() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["-F", "_git", "git"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(env.completion.functions["git"], "_git");
+    }
+
+    #[test]
+    fn adding_candidates_in_completion() {
+        let mut env = Env::new_virtual();
+        env.completion.request = Some(Request {
+            target: "b".to_string(),
+            ..Request::default()
+        });
+        let args = Field::dummies(["bar", "foo", "baz"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        let request = env.completion.request.unwrap();
+        let values: Vec<_> = request.candidates.iter().map(|c| &c.value).collect();
+        assert_eq!(values, ["bar", "baz"]);
+    }
+
+    #[test]
+    fn adding_candidates_outside_completion_is_error() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["-c"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::ERROR));
+    }
+}
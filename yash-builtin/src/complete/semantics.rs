@@ -0,0 +1,153 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Core runtime behavior of the `complete` built-in
+
+use super::Command;
+use super::Generator;
+use std::fmt::Write as _;
+use thiserror::Error;
+use yash_env::completion::Candidate;
+use yash_env::Env;
+use yash_quote::quoted;
+use yash_semantics::completion::command_names;
+use yash_semantics::completion::filenames;
+use yash_semantics::completion::job_ids;
+use yash_semantics::completion::variable_names;
+
+/// Errors that can occur while executing the `complete` built-in
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum Error {
+    /// Candidates are added outside a completion function.
+    #[error("candidates can be added only in a completion function")]
+    NotCompleting,
+}
+
+/// Generates candidates for the target word.
+fn generate(env: &mut Env, generator: Generator, target: &str) -> Vec<Candidate> {
+    match generator {
+        Generator::Command => command_names(env, target),
+        Generator::Filename => filenames(env, target),
+        Generator::Job => job_ids(env, target),
+        Generator::Variable => variable_names(env, target),
+    }
+}
+
+impl Command {
+    /// Executes the `complete` built-in.
+    ///
+    /// Returns the text to be printed to the standard output.
+    pub fn execute(self, env: &mut Env) -> Result<String, Error> {
+        let mut output = String::new();
+        match self {
+            Command::Print => {
+                for (command, function) in &env.completion.functions {
+                    let (function, command) = (quoted(function), quoted(command));
+                    writeln!(output, "complete -F {function} {command}").unwrap();
+                }
+            }
+            Command::Register { function, commands } => {
+                for command in commands {
+                    let function = function.value.clone();
+                    env.completion.functions.insert(command.value, function);
+                }
+            }
+            Command::Remove(commands) if commands.is_empty() => env.completion.functions.clear(),
+            Command::Remove(commands) => {
+                for command in commands {
+                    env.completion.functions.remove(&command.value);
+                }
+            }
+            Command::Add { generators, words } => {
+                let Some(target) = env.completion.request.as_ref().map(|r| r.target.clone()) else {
+                    return Err(Error::NotCompleting);
+                };
+                let mut candidates: Vec<Candidate> = words
+                    .into_iter()
+                    .map(|word| Candidate::new(word.value))
+                    .collect();
+                for generator in generators {
+                    candidates.extend(generate(env, generator, &target));
+                }
+                let request = env.completion.request.as_mut().unwrap();
+                for candidate in candidates {
+                    request.add(candidate);
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yash_env::completion::Request;
+    use yash_env::semantics::Field;
+    use yash_env::variable::Scope;
+
+    #[test]
+    fn register_print_and_remove() {
+        let mut env = Env::new_virtual();
+        let command = Command::Register {
+            function: Field::dummy("_ls"),
+            commands: Field::dummies(["ls", "my dir"]),
+        };
+        assert_eq!(command.execute(&mut env), Ok(String::new()));
+
+        let output = Command::Print.execute(&mut env).unwrap();
+        assert_eq!(output, "complete -F _ls ls\ncomplete -F _ls 'my dir'\n");
+
+        let command = Command::Remove(Field::dummies(["ls"]));
+        assert_eq!(command.execute(&mut env), Ok(String::new()));
+        let output = Command::Print.execute(&mut env).unwrap();
+        assert_eq!(output, "complete -F _ls 'my dir'\n");
+
+        assert_eq!(Command::Remove(vec![]).execute(&mut env), Ok(String::new()));
+        assert!(env.completion.functions.is_empty());
+    }
+
+    #[test]
+    fn add_generated_candidates() {
+        let mut env = Env::new_virtual();
+        env.variables
+            .get_or_new("HOME", Scope::Global)
+            .assign("/", None)
+            .unwrap();
+        env.completion.request = Some(Request {
+            target: "HO".to_string(),
+            ..Request::default()
+        });
+        let command = Command::Add {
+            generators: vec![Generator::Variable],
+            words: Field::dummies(["HOST", "PATH"]),
+        };
+        assert_eq!(command.execute(&mut env), Ok(String::new()));
+        let request = env.completion.request.unwrap();
+        let values: Vec<_> = request.candidates.iter().map(|c| &c.value).collect();
+        assert_eq!(values, ["HOST", "HOME"]);
+    }
+
+    #[test]
+    fn add_outside_completion() {
+        let mut env = Env::new_virtual();
+        let command = Command::Add {
+            generators: vec![],
+            words: Field::dummies(["foo"]),
+        };
+        assert_eq!(command.execute(&mut env), Err(Error::NotCompleting));
+    }
+}
@@ -0,0 +1,222 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line argument parser for the complete built-in
+
+use super::Command;
+use super::Generator;
+use crate::common::syntax::parse_arguments;
+use crate::common::syntax::ConflictingOptionError;
+use crate::common::syntax::Mode;
+use crate::common::syntax::OptionArgumentSpec;
+use crate::common::syntax::OptionSpec;
+use thiserror::Error;
+use yash_env::semantics::Field;
+use yash_env::Env;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::Message;
+
+/// Error in parsing command line arguments
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error occurred in the common parser.
+    #[error(transparent)]
+    CommonError(#[from] crate::common::syntax::ParseError<'static>),
+
+    /// Options that cannot be used together are specified.
+    #[error(transparent)]
+    ConflictingOptions(#[from] ConflictingOptionError<'static>),
+
+    /// The `-F` option is specified without commands.
+    #[error("no command specified")]
+    MissingCommand(Field),
+}
+
+impl Error {
+    /// Converts this error into a message.
+    pub fn to_message(&self) -> Message {
+        match self {
+            Error::CommonError(e) => e.into(),
+            Error::ConflictingOptions(e) => e.into(),
+            Error::MissingCommand(function) => Message {
+                r#type: AnnotationType::Error,
+                title: self.to_string().into(),
+                annotations: vec![Annotation::new(
+                    AnnotationType::Error,
+                    format!("{}: completion function for no command", function.value).into(),
+                    &function.origin,
+                )],
+                footers: vec![],
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a Error> for Message<'a> {
+    #[inline]
+    fn from(e: &'a Error) -> Self {
+        e.to_message()
+    }
+}
+
+/// Result of parsing command line arguments
+pub type Result = std::result::Result<Command, Error>;
+
+const OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec::new().short('c').long("command"),
+    OptionSpec::new()
+        .short('F')
+        .long("function")
+        .argument(OptionArgumentSpec::Required),
+    OptionSpec::new().short('f').long("filename"),
+    OptionSpec::new().short('j').long("job"),
+    OptionSpec::new().short('r').long("remove"),
+    OptionSpec::new().short('v').long("variable"),
+];
+
+/// Parses command line arguments for the complete built-in.
+pub fn parse(env: &Env, args: Vec<Field>) -> Result {
+    let mode = Mode::with_env(env);
+    let (options, operands) = parse_arguments(OPTION_SPECS, mode, args)?;
+
+    let mut function = None;
+    let mut remove = None;
+    let mut generator_options = Vec::new();
+    let mut generators = Vec::new();
+    for option in options {
+        let generator = match option.spec.get_short() {
+            Some('c') => Generator::Command,
+            Some('f') => Generator::Filename,
+            Some('j') => Generator::Job,
+            Some('v') => Generator::Variable,
+            Some('F') => {
+                function = Some(option);
+                continue;
+            }
+            Some('r') => {
+                remove = Some(option);
+                continue;
+            }
+            _ => unreachable!(),
+        };
+        if !generators.contains(&generator) {
+            generators.push(generator);
+        }
+        generator_options.push(option);
+    }
+
+    let mut conflicting: Vec<_> = function.iter().chain(&remove).cloned().collect();
+    if !conflicting.is_empty() {
+        conflicting.extend(generator_options.first().cloned());
+    }
+    if conflicting.len() > 1 {
+        return Err(ConflictingOptionError::new(conflicting).into());
+    }
+
+    if let Some(function) = function {
+        let function = function.argument.unwrap();
+        if operands.is_empty() {
+            return Err(Error::MissingCommand(function));
+        }
+        let commands = operands;
+        return Ok(Command::Register { function, commands });
+    }
+    if remove.is_some() {
+        return Ok(Command::Remove(operands));
+    }
+    if generators.is_empty() && operands.is_empty() {
+        return Ok(Command::Print);
+    }
+    Ok(Command::Add {
+        generators,
+        words: operands,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn print() {
+        let env = Env::new_virtual();
+        assert_eq!(parse(&env, vec![]), Ok(Command::Print));
+    }
+
+    #[test]
+    fn register() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-F", "_ls", "ls", "dir"]));
+        assert_eq!(
+            result,
+            Ok(Command::Register {
+                function: Field::dummy("_ls"),
+                commands: Field::dummies(["ls", "dir"]),
+            })
+        );
+
+        let result = parse(&env, Field::dummies(["-F", "_ls"]));
+        assert_eq!(result, Err(Error::MissingCommand(Field::dummy("_ls"))));
+    }
+
+    #[test]
+    fn remove() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-r"]));
+        assert_eq!(result, Ok(Command::Remove(vec![])));
+
+        let result = parse(&env, Field::dummies(["-r", "ls"]));
+        assert_eq!(result, Ok(Command::Remove(Field::dummies(["ls"]))));
+    }
+
+    #[test]
+    fn add() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-vfv", "foo"]));
+        assert_eq!(
+            result,
+            Ok(Command::Add {
+                generators: vec![Generator::Variable, Generator::Filename],
+                words: Field::dummies(["foo"]),
+            })
+        );
+
+        let result = parse(&env, Field::dummies(["--", "-r"]));
+        assert_eq!(
+            result,
+            Ok(Command::Add {
+                generators: vec![],
+                words: Field::dummies(["-r"]),
+            })
+        );
+    }
+
+    #[test]
+    fn conflicting_options() {
+        let env = Env::new_virtual();
+        for args in [
+            &["-F", "f", "-r", "ls"][..],
+            &["-F", "f", "-c", "ls"],
+            &["-r", "-j"],
+        ] {
+            let result = parse(&env, Field::dummies(args.iter().copied()));
+            assert_matches!(result, Err(Error::ConflictingOptions(_)), "{args:?}");
+        }
+    }
+}
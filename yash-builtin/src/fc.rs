@@ -0,0 +1,154 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Fc built-in
+//!
+//! The **`fc`** built-in lists, edits, and re-executes entries of the
+//! [command history](yash_env::history).
+//!
+//! # Synopsis
+//!
+//! ```sh
+//! fc [-r] [-e editor] [first [last]]
+//! ```
+//!
+//! ```sh
+//! fc -l [-nr] [first [last]]
+//! ```
+//!
+//! ```sh
+//! fc -s [old=new] [first]
+//! ```
+//!
+//! # Description
+//!
+//! Without the `-l` or `-s` option, the built-in writes the entries from
+//! *first* to *last* to a temporary file and invokes an editor on the file.
+//! After the editor exits successfully, the built-in prints the content of the
+//! file to the standard output, adds it to the history, and executes it as
+//! commands. If the editor fails, the commands are not executed. The editor
+//! is specified by the `-e` option, the `FCEDIT` variable, or defaults to
+//! `ed`. The editor name may contain arguments, as it is parsed as a command
+//! line followed by the pathname of the temporary file.
+//!
+//! With the `-l` option, the built-in prints the entries from *first* to
+//! *last*, each preceded by the entry number.
+//!
+//! With the `-s` option, the built-in re-executes the entry *first* without
+//! invoking an editor. If *old*`=`*new* is given, the first occurrence of
+//! *old* in the entry is replaced with *new* before the execution. The
+//! executed command is printed and added to the history.
+//!
+//! In an interactive shell, the most recent entry of the history is the
+//! command line that invoked the built-in. It is not regarded as an entry
+//! that can be selected by the operands. When commands are re-executed, the
+//! command line that invoked the built-in is removed from the history and
+//! the executed commands are added instead.
+//!
+//! # Options
+//!
+//! - **`-e`** *editor* (**`--editor`**): Specifies the editor.
+//! - **`-l`** (**`--list`**): Prints entries instead of executing them.
+//! - **`-n`** (**`--no-numbers`**): Omits the entry numbers when printing.
+//!   This option is ignored without the `-l` option.
+//! - **`-r`** (**`--reverse`**): Reverses the order of the entries.
+//! - **`-s`** (**`--silent`**): Re-executes an entry without editing.
+//!
+//! # Operands
+//!
+//! The *first* and *last* operands select the range of entries. An operand
+//! can be:
+//!
+//! - A positive integer, which is the number of an entry.
+//! - A negative integer, which is the position counted from the most recent
+//!   entry. `-1` is the most recent entry, `-2` is the one before, and so on.
+//! - A string, which selects the most recent entry that starts with the
+//!   string.
+//!
+//! A number beyond the range of the history selects the oldest or the most
+//! recent entry. If *first* is after *last*, the entries are processed in
+//! reverse order.
+//!
+//! With the `-l` option, *first* defaults to `-16` and *last* to `-1`.
+//! Otherwise, *first* defaults to `-1` and *last* to *first*.
+//!
+//! # Errors
+//!
+//! It is an error if the `-l` and `-s` options are used together, the `-e`,
+//! `-n`, or `-r` option is used with an incompatible option, or there are too
+//! many operands. It is also an error if the history is empty, no entry starts
+//! with a string operand, or the temporary file cannot be used.
+//!
+//! # Exit status
+//!
+//! When commands are executed, the exit status of the built-in is that of the
+//! last executed command. If the editor fails, the exit status is that of
+//! the editor. Otherwise, the exit status is zero unless an error occurs.
+//!
+//! # Portability
+//!
+//! The fc built-in is specified in POSIX. The long options are not portable.
+//! The format of the temporary file and how the editor is invoked may differ
+//! between shells.
+
+use crate::common::report_error;
+use yash_env::semantics::Field;
+use yash_env::Env;
+
+/// Replacement applied to the re-executed command by the `-s` option
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Replacement {
+    /// String to be replaced
+    pub old: String,
+    /// String that replaces `old`
+    pub new: String,
+}
+
+/// Parsed command line arguments for the `fc` built-in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Print entries
+    List {
+        first: Option<Field>,
+        last: Option<Field>,
+        /// Whether to print entry numbers
+        numbers: bool,
+        reverse: bool,
+    },
+    /// Edit and re-execute entries
+    Edit {
+        editor: Option<Field>,
+        first: Option<Field>,
+        last: Option<Field>,
+        reverse: bool,
+    },
+    /// Re-execute an entry without editing
+    Reexecute {
+        replacement: Option<Replacement>,
+        first: Option<Field>,
+    },
+}
+
+pub mod semantics;
+pub mod syntax;
+
+/// Entry point for executing the `fc` built-in
+pub async fn main(env: &mut Env, args: Vec<Field>) -> crate::Result {
+    match syntax::parse(env, args) {
+        Ok(command) => command.execute(env).await,
+        Err(e) => report_error(env, &e).await,
+    }
+}
@@ -0,0 +1,525 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Core runtime behavior of the `fc` built-in
+
+use super::Command;
+use crate::common::output;
+use crate::common::report_failure;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fmt::Write as _;
+use std::num::NonZeroU64;
+use std::ops::ControlFlow::Break;
+use thiserror::Error;
+use yash_env::history;
+use yash_env::history::Entry;
+use yash_env::io::Fd;
+use yash_env::option::Interactive;
+use yash_env::option::State::On;
+use yash_env::semantics::ExitStatus;
+use yash_env::semantics::Field;
+use yash_env::system::Errno;
+use yash_env::system::Mode;
+use yash_env::system::OFlag;
+use yash_env::variable::Value;
+use yash_env::Env;
+use yash_env::System;
+use yash_quote::quoted;
+use yash_semantics::ReadEvalLoop;
+use yash_syntax::input::Memory;
+use yash_syntax::parser::lex::Lexer;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::MessageBase;
+use yash_syntax::source::Location;
+use yash_syntax::source::Source;
+
+/// Errors that can occur while executing the `fc` built-in
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum Error {
+    /// The history has no entries to select.
+    #[error("history is empty")]
+    EmptyHistory(Location),
+
+    /// No entry starts with the string operand.
+    #[error("no such history entry")]
+    NoSuchEntry(Field),
+
+    /// The temporary file for the editor cannot be created or read.
+    #[error("cannot use temporary file: {1}")]
+    TemporaryFile(Location, Errno),
+}
+
+impl MessageBase for Error {
+    fn message_title(&self) -> Cow<str> {
+        self.to_string().into()
+    }
+
+    fn main_annotation(&self) -> Annotation<'_> {
+        match self {
+            Error::EmptyHistory(location) => Annotation::new(
+                AnnotationType::Error,
+                "no entries to select".into(),
+                location,
+            ),
+            Error::NoSuchEntry(field) => Annotation::new(
+                AnnotationType::Error,
+                format!("{}: no such entry", field.value).into(),
+                &field.origin,
+            ),
+            Error::TemporaryFile(location, errno) => {
+                Annotation::new(AnnotationType::Error, errno.desc().into(), location)
+            }
+        }
+    }
+}
+
+/// Returns the location of the currently executing built-in.
+fn builtin_location(env: &Env) -> Location {
+    env.stack.current_builtin().map_or_else(
+        || Location::dummy("fc"),
+        |builtin| builtin.name.origin.clone(),
+    )
+}
+
+/// Returns the history entries that can be selected by the operands.
+///
+/// In an interactive shell, the most recent entry is the command line that
+/// invoked the built-in, so it is excluded.
+fn candidates(env: &Env) -> Result<Vec<Entry>, Error> {
+    let mut entries = env.history.entries().cloned().collect::<Vec<_>>();
+    if env.options.get(Interactive) == On {
+        entries.pop();
+    }
+    if entries.is_empty() {
+        return Err(Error::EmptyHistory(builtin_location(env)));
+    }
+    Ok(entries)
+}
+
+/// Resolves an operand to an index into the candidates.
+///
+/// If the operand is `None`, `default` is used as a relative position from
+/// the most recent entry. The candidates must not be empty.
+fn resolve(operand: Option<&Field>, default: i64, candidates: &[Entry]) -> Result<usize, Error> {
+    let last = candidates.len() - 1;
+    let number = match operand {
+        None => default,
+        Some(field) => match field.value.parse::<i64>() {
+            Ok(number) => number,
+            Err(_) => {
+                return candidates
+                    .iter()
+                    .rposition(|entry| entry.text.starts_with(&field.value))
+                    .ok_or_else(|| Error::NoSuchEntry(field.clone()))
+            }
+        },
+    };
+
+    if number > 0 {
+        let index = candidates.partition_point(|entry| i64::from(entry.number) < number);
+        Ok(index.min(last))
+    } else {
+        let offset = usize::try_from(number.unsigned_abs()).unwrap_or(usize::MAX);
+        Ok(candidates.len().saturating_sub(offset).min(last))
+    }
+}
+
+/// Selects the entries in the range from `first` to `last`.
+///
+/// The entries are returned in the order from `first` to `last`, reversed if
+/// `reverse` is true.
+fn select(
+    env: &Env,
+    first: Option<&Field>,
+    last: Option<&Field>,
+    default_first: i64,
+    default_last: Option<i64>,
+    reverse: bool,
+) -> Result<Vec<Entry>, Error> {
+    let mut candidates = candidates(env)?;
+    let first = resolve(first, default_first, &candidates)?;
+    let last = match (last, default_last) {
+        (None, None) => first,
+        (last, default_last) => resolve(last, default_last.unwrap_or(-1), &candidates)?,
+    };
+
+    let (start, end) = (first.min(last), first.max(last));
+    candidates.truncate(end + 1);
+    candidates.drain(..start);
+    if (first > last) != reverse {
+        candidates.reverse();
+    }
+    Ok(candidates)
+}
+
+/// Returns the value of the scalar variable.
+fn scalar<'a>(env: &'a Env, name: &str) -> Option<&'a str> {
+    match &env.variables.get(name)?.value {
+        Some(Value::Scalar(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Creates a new temporary file and returns its pathname and descriptor.
+fn create_temporary_file(env: &mut Env) -> Result<(String, Fd), Errno> {
+    let dir = scalar(env, "TMPDIR").filter(|dir| !dir.is_empty());
+    let dir = dir.unwrap_or("/tmp").trim_end_matches('/').to_owned();
+    let pid = env.system.getpid();
+    let flags = OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_CLOEXEC;
+    let mode = Mode::S_IRUSR | Mode::S_IWUSR;
+
+    for count in 0.. {
+        let path = format!("{dir}/yash-fc-{pid}-{count}");
+        let c_path = CString::new(path.as_str()).map_err(|_| Errno::EILSEQ)?;
+        match env.system.open(&c_path, flags, mode) {
+            Ok(fd) => return Ok((path, fd)),
+            Err(Errno::EEXIST) if count < 100 => continue,
+            Err(errno) => return Err(errno),
+        }
+    }
+    unreachable!()
+}
+
+/// Reads the whole content of the file.
+fn read_file(env: &mut Env, path: &CString) -> Result<String, Errno> {
+    let fd = env
+        .system
+        .open(path, OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())?;
+    let mut content = Vec::new();
+    let mut buffer = [0; 4096];
+    let result = loop {
+        match env.system.read(fd, &mut buffer) {
+            Ok(0) => break Ok(()),
+            Ok(count) => content.extend_from_slice(&buffer[..count]),
+            Err(Errno::EINTR) => continue,
+            Err(errno) => break Err(errno),
+        }
+    };
+    _ = env.system.close(fd);
+    result.map(|()| String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Executes a command string.
+async fn run(env: &mut Env, code: &str, original: Location) -> crate::Result {
+    let input = Box::new(Memory::new(code));
+    let start_line_number = NonZeroU64::new(1).unwrap();
+    let source = Source::Fc { original };
+    let mut lexer = Lexer::new(input, start_line_number, source);
+    let divert = ReadEvalLoop::new(&RefCell::new(&mut *env), &mut lexer)
+        .run()
+        .await;
+    crate::Result::with_exit_status_and_divert(env.exit_status, divert)
+}
+
+/// Prints, records, and executes the commands.
+///
+/// In an interactive shell, the command line that invoked the built-in is
+/// replaced with the executed commands in the history.
+async fn reexecute(env: &mut Env, code: String) -> crate::Result {
+    let result = output(env, &code).await;
+    if result.exit_status() != ExitStatus::SUCCESS {
+        return result;
+    }
+
+    if env.options.get(Interactive) == On {
+        history::replace_last(env, &code);
+    } else {
+        history::record(env, &code);
+        history::flush(env);
+    }
+
+    run(env, &code, builtin_location(env)).await
+}
+
+/// Writes the entries to a temporary file, invokes the editor on the file,
+/// and returns the edited content.
+///
+/// If the editor fails, the result of the editor is returned as an error.
+async fn edit(
+    env: &mut Env,
+    editor: Option<Field>,
+    entries: Vec<Entry>,
+) -> Result<Result<String, crate::Result>, Error> {
+    let location = builtin_location(env);
+    let (path, fd) = create_temporary_file(env)
+        .map_err(|errno| Error::TemporaryFile(location.clone(), errno))?;
+    let c_path = CString::new(path.as_str()).unwrap();
+
+    let mut content = String::new();
+    for entry in entries {
+        writeln!(content, "{}", entry.text).unwrap();
+    }
+    let write_result = env.system.write_all(fd, content.as_bytes()).await;
+    _ = env.system.close(fd);
+    if let Err(errno) = write_result {
+        _ = env.system.unlink(&c_path);
+        return Err(Error::TemporaryFile(location, errno));
+    }
+
+    let (editor, original) = match editor {
+        Some(editor) => (editor.value, editor.origin),
+        None => {
+            let editor = scalar(env, "FCEDIT").filter(|editor| !editor.is_empty());
+            (editor.unwrap_or("ed").to_owned(), location.clone())
+        }
+    };
+    let code = format!("{} {}\n", editor, quoted(&path));
+    let result = run(env, &code, original).await;
+
+    let content =
+        if result.exit_status() == ExitStatus::SUCCESS && !matches!(result.divert(), Break(_)) {
+            Ok(read_file(env, &c_path).map_err(|errno| Error::TemporaryFile(location, errno)))
+        } else {
+            Err(result)
+        };
+    _ = env.system.unlink(&c_path);
+
+    match content {
+        Ok(Ok(content)) => Ok(Ok(content)),
+        Ok(Err(e)) => Err(e),
+        Err(result) => Ok(Err(result)),
+    }
+}
+
+impl Command {
+    /// Executes the `fc` built-in.
+    pub async fn execute(self, env: &mut Env) -> crate::Result {
+        match self.execute_inner(env).await {
+            Ok(result) => result,
+            Err(e) => report_failure(env, &e).await,
+        }
+    }
+
+    async fn execute_inner(self, env: &mut Env) -> Result<crate::Result, Error> {
+        match self {
+            Command::List {
+                first,
+                last,
+                numbers,
+                reverse,
+            } => {
+                let entries = select(env, first.as_ref(), last.as_ref(), -16, Some(-1), reverse)?;
+                let mut text = String::new();
+                for entry in entries {
+                    let body = entry.text.replace('\n', "\n\t");
+                    if numbers {
+                        writeln!(text, "{}\t{}", entry.number, body).unwrap();
+                    } else {
+                        writeln!(text, "\t{}", body).unwrap();
+                    }
+                }
+                Ok(output(env, &text).await)
+            }
+
+            Command::Edit {
+                editor,
+                first,
+                last,
+                reverse,
+            } => {
+                let entries = select(env, first.as_ref(), last.as_ref(), -1, None, reverse)?;
+                match edit(env, editor, entries).await? {
+                    Ok(code) => Ok(reexecute(env, code).await),
+                    Err(result) => Ok(result),
+                }
+            }
+
+            Command::Reexecute { replacement, first } => {
+                let entries = select(env, first.as_ref(), None, -1, None, false)?;
+                let mut code = entries.into_iter().next().unwrap().text;
+                if let Some(replacement) = replacement {
+                    code = code.replacen(&replacement.old, &replacement.new, 1);
+                }
+                code.push('\n');
+                Ok(reexecute(env, code).await)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Replacement;
+    use super::*;
+    use crate::tests::assert_stdout;
+    use assert_matches::assert_matches;
+    use futures_util::FutureExt as _;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::variable::Scope;
+    use yash_env::VirtualSystem;
+
+    fn env_with_history(entries: &[&str]) -> (Env, VirtualSystem) {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.builtins.extend(crate::BUILTINS.iter().cloned());
+        for entry in entries {
+            env.history.add(entry);
+        }
+        (env, system)
+    }
+
+    #[test]
+    fn listing_entries() {
+        let (mut env, system) = env_with_history(&["echo 1", "echo 2", "if :\nthen :\nfi"]);
+        let command = Command::List {
+            first: Some(Field::dummy("2")),
+            last: None,
+            numbers: true,
+            reverse: false,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_stdout(&system.state, |stdout| {
+            assert_eq!(stdout, "2\techo 2\n3\tif :\n\tthen :\n\tfi\n")
+        });
+    }
+
+    #[test]
+    fn listing_entries_in_reverse_without_numbers() {
+        let (mut env, system) = env_with_history(&["echo 1", "echo 2", "echo 3"]);
+        let command = Command::List {
+            first: Some(Field::dummy("echo 1")),
+            last: Some(Field::dummy("-2")),
+            numbers: false,
+            reverse: true,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_stdout(&system.state, |stdout| {
+            assert_eq!(stdout, "\techo 2\n\techo 1\n")
+        });
+    }
+
+    #[test]
+    fn reexecuting_with_replacement() {
+        let (mut env, system) = env_with_history(&["x=1", "echo"]);
+        let command = Command::Reexecute {
+            replacement: Some(Replacement {
+                old: "1".to_owned(),
+                new: "2".to_owned(),
+            }),
+            first: Some(Field::dummy("x")),
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(
+            env.variables.get("x").unwrap().value,
+            Some(Value::scalar("2"))
+        );
+        assert_stdout(&system.state, |stdout| assert_eq!(stdout, "x=2\n"));
+    }
+
+    #[test]
+    fn fc_command_is_removed_from_history_file() {
+        let (mut env, system) = env_with_history(&[]);
+        env.options.set(Interactive, On);
+        env.variables
+            .get_or_new("HISTFILE", Scope::Global)
+            .assign("/history", None)
+            .unwrap();
+        history::record(&mut env, "x=1\n");
+        history::record(&mut env, "fc -s 1=2 x\n");
+        history::flush(&mut env);
+        let command = Command::Reexecute {
+            replacement: Some(Replacement {
+                old: "1".to_owned(),
+                new: "2".to_owned(),
+            }),
+            first: Some(Field::dummy("x")),
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(env.history.iter().collect::<Vec<_>>(), ["x=1", "x=2"]);
+
+        let state = system.state.borrow();
+        let file = state.file_system.get("/history").unwrap();
+        assert_matches!(&file.borrow().body, FileBody::Regular { content, .. } => {
+            assert_eq!(std::str::from_utf8(content), Ok(":x=1\n:x=2\n"));
+        });
+    }
+
+    #[test]
+    fn editing_and_reexecuting() {
+        let (mut env, system) = env_with_history(&["x=1"]);
+        let command = Command::Edit {
+            editor: Some(Field::dummy(":")),
+            first: None,
+            last: None,
+            reverse: false,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(
+            env.variables.get("x").unwrap().value,
+            Some(Value::scalar("1"))
+        );
+        assert_stdout(&system.state, |stdout| assert_eq!(stdout, "x=1\n"));
+
+        // The temporary file is removed.
+        let state = system.state.borrow();
+        let tmp = state.file_system.get("/tmp").unwrap();
+        let tmp = tmp.borrow();
+        assert_matches!(&tmp.body, FileBody::Directory { files } => {
+            assert!(files.is_empty(), "{files:?}");
+        });
+    }
+
+    #[test]
+    fn failing_editor_prevents_execution() {
+        let (mut env, system) = env_with_history(&["x=1"]);
+        env.variables
+            .get_or_new("FCEDIT", Scope::Global)
+            .assign("false", None)
+            .unwrap();
+        let command = Command::Edit {
+            editor: None,
+            first: None,
+            last: None,
+            reverse: false,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::FAILURE));
+        assert_eq!(env.variables.get("x"), None);
+        assert_stdout(&system.state, |stdout| assert_eq!(stdout, ""));
+    }
+
+    #[test]
+    fn empty_history_is_failure() {
+        let (mut env, _) = env_with_history(&[]);
+        let command = Command::Reexecute {
+            replacement: None,
+            first: None,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::FAILURE));
+    }
+
+    #[test]
+    fn nonexistent_prefix_is_failure() {
+        let (mut env, _) = env_with_history(&["echo"]);
+        let command = Command::List {
+            first: Some(Field::dummy("ls")),
+            last: None,
+            numbers: true,
+            reverse: false,
+        };
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::FAILURE));
+    }
+}
@@ -0,0 +1,347 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line argument parser for the fc built-in
+
+use super::Command;
+use super::Replacement;
+use crate::common::syntax::parse_arguments;
+use crate::common::syntax::ConflictingOptionError;
+use crate::common::syntax::Mode;
+use crate::common::syntax::OptionArgumentSpec;
+use crate::common::syntax::OptionOccurrence;
+use crate::common::syntax::OptionSpec;
+use thiserror::Error;
+use yash_env::semantics::Field;
+use yash_env::Env;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::Message;
+
+/// Error in parsing command line arguments
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error occurred in the common parser.
+    #[error(transparent)]
+    CommonError(#[from] crate::common::syntax::ParseError<'static>),
+
+    /// Options that cannot be used together are specified.
+    #[error(transparent)]
+    ConflictingOptions(#[from] ConflictingOptionError<'static>),
+
+    /// Too many operands are given.
+    #[error("too many operands")]
+    TooManyOperands(Vec<Field>),
+}
+
+impl Error {
+    /// Converts this error into a message.
+    pub fn to_message(&self) -> Message {
+        match self {
+            Error::CommonError(e) => e.into(),
+            Error::ConflictingOptions(e) => e.into(),
+            Error::TooManyOperands(operands) => Message {
+                r#type: AnnotationType::Error,
+                title: self.to_string().into(),
+                annotations: vec![Annotation::new(
+                    AnnotationType::Error,
+                    format!("{}: unexpected operand", operands[0].value).into(),
+                    &operands[0].origin,
+                )],
+                footers: vec![],
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a Error> for Message<'a> {
+    #[inline]
+    fn from(e: &'a Error) -> Self {
+        e.to_message()
+    }
+}
+
+/// Result of parsing command line arguments
+pub type Result = std::result::Result<Command, Error>;
+
+const OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec::new()
+        .short('e')
+        .long("editor")
+        .argument(OptionArgumentSpec::Required),
+    OptionSpec::new().short('l').long("list"),
+    OptionSpec::new().short('n').long("no-numbers"),
+    OptionSpec::new().short('r').long("reverse"),
+    OptionSpec::new().short('s').long("silent"),
+];
+
+/// Tests whether the argument is a negative integer.
+fn is_negative_integer(value: &str) -> bool {
+    value
+        .strip_prefix('-')
+        .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Inserts `--` before the first operand if it is a negative integer.
+///
+/// Without this, an operand like `-1` would be parsed as an option.
+fn separate_negative_operand(args: &mut Vec<Field>) {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        let value = arg.value.as_str();
+        if is_negative_integer(value) {
+            let separator = Field {
+                value: "--".to_owned(),
+                origin: arg.origin.clone(),
+            };
+            args.insert(index, separator);
+            return;
+        }
+        if value == "--" || value == "-" || !value.starts_with('-') {
+            return;
+        }
+
+        // Skip the option argument given as a separate field
+        let takes_next = match value.strip_prefix("--") {
+            Some(long) => !long.contains('=') && "editor".starts_with(long),
+            None => value[1..].find('e') == Some(value.len() - 2),
+        };
+        index += if takes_next { 2 } else { 1 };
+    }
+}
+
+/// Returns an error if both options are specified.
+fn check_conflict(
+    first: &Option<OptionOccurrence<'static>>,
+    second: &Option<OptionOccurrence<'static>>,
+) -> std::result::Result<(), Error> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            let options = vec![first.clone(), second.clone()];
+            Err(ConflictingOptionError::new(options).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Splits off operands beyond the maximum number.
+fn check_operand_count(operands: &mut Vec<Field>, max: usize) -> std::result::Result<(), Error> {
+    if operands.len() > max {
+        Err(Error::TooManyOperands(operands.split_off(max)))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses command line arguments for the fc built-in.
+pub fn parse(env: &Env, mut args: Vec<Field>) -> Result {
+    separate_negative_operand(&mut args);
+    let mode = Mode::with_env(env);
+    let (options, mut operands) = parse_arguments(OPTION_SPECS, mode, args)?;
+
+    let mut editor = None;
+    let mut list = None;
+    let mut no_numbers = None;
+    let mut reverse = None;
+    let mut silent = None;
+    for option in options {
+        match option.spec.get_short() {
+            Some('e') => editor = Some(option),
+            Some('l') => list = Some(option),
+            Some('n') => no_numbers = Some(option),
+            Some('r') => reverse = Some(option),
+            Some('s') => silent = Some(option),
+            _ => unreachable!(),
+        }
+    }
+
+    check_conflict(&list, &silent)?;
+    check_conflict(&editor, &list)?;
+    check_conflict(&editor, &silent)?;
+    check_conflict(&no_numbers, &silent)?;
+    check_conflict(&no_numbers, &editor)?;
+    check_conflict(&reverse, &silent)?;
+
+    let reverse = reverse.is_some();
+    if silent.is_some() {
+        check_operand_count(&mut operands, 2)?;
+        let mut operands = operands.into_iter();
+        let mut first = operands.next();
+        let replacement = match &first {
+            Some(operand) if operand.value.contains('=') => {
+                let (old, new) = operand.value.split_once('=').unwrap();
+                let replacement = Replacement {
+                    old: old.to_owned(),
+                    new: new.to_owned(),
+                };
+                first = operands.next();
+                Some(replacement)
+            }
+            _ => None,
+        };
+        if let Some(extra) = operands.next() {
+            return Err(Error::TooManyOperands(vec![extra]));
+        }
+        return Ok(Command::Reexecute { replacement, first });
+    }
+
+    check_operand_count(&mut operands, 2)?;
+    let mut operands = operands.into_iter();
+    let first = operands.next();
+    let last = operands.next();
+    if list.is_some() {
+        let numbers = no_numbers.is_none();
+        Ok(Command::List {
+            first,
+            last,
+            numbers,
+            reverse,
+        })
+    } else {
+        let editor = editor.and_then(|option| option.argument);
+        Ok(Command::Edit {
+            editor,
+            first,
+            last,
+            reverse,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn default_edit() {
+        let env = Env::new_virtual();
+        let result = parse(&env, vec![]);
+        assert_eq!(
+            result,
+            Ok(Command::Edit {
+                editor: None,
+                first: None,
+                last: None,
+                reverse: false,
+            })
+        );
+
+        let args = Field::dummies(["-r", "-e", "vi", "3", "-1"]);
+        let result = parse(&env, args);
+        assert_eq!(
+            result,
+            Ok(Command::Edit {
+                editor: Some(Field::dummy("vi")),
+                first: Some(Field::dummy("3")),
+                last: Some(Field::dummy("-1")),
+                reverse: true,
+            })
+        );
+    }
+
+    #[test]
+    fn list() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-lnr", "echo"]));
+        assert_eq!(
+            result,
+            Ok(Command::List {
+                first: Some(Field::dummy("echo")),
+                last: None,
+                numbers: false,
+                reverse: true,
+            })
+        );
+    }
+
+    #[test]
+    fn reexecute() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-s"]));
+        assert_eq!(
+            result,
+            Ok(Command::Reexecute {
+                replacement: None,
+                first: None,
+            })
+        );
+
+        let result = parse(&env, Field::dummies(["-s", "a=b=c", "ls"]));
+        assert_eq!(
+            result,
+            Ok(Command::Reexecute {
+                replacement: Some(Replacement {
+                    old: "a".to_owned(),
+                    new: "b=c".to_owned(),
+                }),
+                first: Some(Field::dummy("ls")),
+            })
+        );
+
+        let result = parse(&env, Field::dummies(["-s", "ls", "-1"]));
+        assert_eq!(result, Err(Error::TooManyOperands(Field::dummies(["-1"]))));
+    }
+
+    #[test]
+    fn negative_first_operand() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-l", "-5", "-2"]));
+        assert_eq!(
+            result,
+            Ok(Command::List {
+                first: Some(Field::dummy("-5")),
+                last: Some(Field::dummy("-2")),
+                numbers: true,
+                reverse: false,
+            })
+        );
+
+        let result = parse(&env, Field::dummies(["-re", "vi", "-3"]));
+        assert_eq!(
+            result,
+            Ok(Command::Edit {
+                editor: Some(Field::dummy("vi")),
+                first: Some(Field::dummy("-3")),
+                last: None,
+                reverse: true,
+            })
+        );
+    }
+
+    #[test]
+    fn conflicting_options() {
+        let env = Env::new_virtual();
+        for args in [
+            &["-l", "-s"][..],
+            &["-l", "-e", "vi"],
+            &["-s", "-e", "vi"],
+            &["-s", "-r"],
+            &["-n", "-e", "vi"],
+        ] {
+            let result = parse(&env, Field::dummies(args.iter().copied()));
+            assert_matches!(result, Err(Error::ConflictingOptions(_)), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn too_many_operands() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-l", "1", "2", "3"]));
+        assert_eq!(result, Err(Error::TooManyOperands(Field::dummies(["3"]))));
+    }
+}
@@ -0,0 +1,152 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! History built-in
+//!
+//! The **`history`** built-in prints or modifies the [command
+//! history](yash_env::history).
+//!
+//! # Synopsis
+//!
+//! ```sh
+//! history [count]
+//! ```
+//!
+//! ```sh
+//! history [-c] [-d entry] [-r file] [-w file]
+//! ```
+//!
+//! # Description
+//!
+//! Without options, the built-in prints the history entries, each preceded
+//! by the entry number. If *count* is given, only the last *count* entries
+//! are printed.
+//!
+//! With options, the built-in modifies the history as specified by the
+//! options, in the order they are given.
+//!
+//! # Options
+//!
+//! - **`-c`** (**`--clear`**): Removes all entries from the history.
+//! - **`-d`** *entry* (**`--delete`**): Removes the specified entry. A
+//!   positive integer *entry* is the number of the entry. A negative integer
+//!   refers to an entry counting from the most recent entry, which is `-1`.
+//! - **`-r`** *file* (**`--read`**): Reads entries from the file and adds them
+//!   to the history.
+//! - **`-w`** *file* (**`--write`**): Writes all the entries to the file,
+//!   replacing the existing content of the file.
+//!
+//! The files are read and written in the same format as the history file
+//! specified by the `HISTFILE` variable.
+//!
+//! # Operands
+//!
+//! The *count* operand is a non-negative integer that specifies the number
+//! of entries to print.
+//!
+//! # Errors
+//!
+//! It is an error if the *count* operand is specified with options, or the
+//! entry or count is not a valid integer. It is also an error if the entry to
+//! be removed does not exist or a file cannot be read or written.
+//!
+//! # Exit status
+//!
+//! Zero unless an error occurs.
+//!
+//! # Portability
+//!
+//! The history built-in is not specified in POSIX. Other shells have a
+//! built-in of the same name with different options and output format.
+
+use crate::common::output;
+use crate::common::report_error;
+use crate::common::report_failure;
+use yash_env::semantics::Field;
+use yash_env::Env;
+
+/// Operation that modifies the history
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    /// Remove all entries
+    Clear,
+    /// Remove an entry
+    ///
+    /// The integer is the number of the entry if positive, or the position
+    /// counted from the most recent entry if negative.
+    Delete(Field, i64),
+    /// Read entries from a file
+    Read(Field),
+    /// Write all entries to a file
+    Write(Field),
+}
+
+/// Parsed command line arguments for the `history` built-in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Print the last entries, or all entries if `None`
+    Print(Option<usize>),
+    /// Modify the history
+    Modify(Vec<Action>),
+}
+
+pub mod semantics;
+pub mod syntax;
+
+/// Entry point for executing the `history` built-in
+pub async fn main(env: &mut Env, args: Vec<Field>) -> crate::Result {
+    match syntax::parse(env, args) {
+        Ok(command) => match command.execute(env) {
+            Ok(result) => output(env, &result).await,
+            Err(e) => report_failure(env, &e).await,
+        },
+        Err(e) => report_error(env, &e).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use yash_env::semantics::ExitStatus;
+
+    #[test]
+    fn deleting_entry() {
+        let mut env = Env::new_virtual();
+        env.history.add("echo 1");
+        env.history.add("echo 2");
+        let args = Field::dummies(["-d", "-1"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::SUCCESS));
+        assert_eq!(env.history.iter().collect::<Vec<_>>(), ["echo 1"]);
+    }
+
+    #[test]
+    fn invalid_count_is_error() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["foo"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::ERROR));
+    }
+
+    #[test]
+    fn deleting_nonexistent_entry_is_failure() {
+        let mut env = Env::new_virtual();
+        let args = Field::dummies(["-d", "3"]);
+        let result = main(&mut env, args).now_or_never().unwrap();
+        assert_eq!(result, crate::Result::new(ExitStatus::FAILURE));
+    }
+}
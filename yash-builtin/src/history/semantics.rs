@@ -0,0 +1,223 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Core runtime behavior of the `history` built-in
+
+use super::Action;
+use super::Command;
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::fmt::Write as _;
+use thiserror::Error;
+use yash_env::history;
+use yash_env::semantics::Field;
+use yash_env::system::Errno;
+use yash_env::Env;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::MessageBase;
+
+/// Errors that can occur while executing the `history` built-in
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum Error {
+    /// The entry to be removed does not exist.
+    #[error("no such history entry")]
+    NoSuchEntry(Field),
+
+    /// The file cannot be read.
+    #[error("cannot read history file: {1}")]
+    ReadError(Field, Errno),
+
+    /// The file cannot be written.
+    #[error("cannot write history file: {1}")]
+    WriteError(Field, Errno),
+}
+
+impl MessageBase for Error {
+    fn message_title(&self) -> Cow<str> {
+        self.to_string().into()
+    }
+
+    fn main_annotation(&self) -> Annotation<'_> {
+        let (field, label) = match self {
+            Error::NoSuchEntry(field) => (field, "no such entry"),
+            Error::ReadError(field, errno) | Error::WriteError(field, errno) => {
+                (field, errno.desc())
+            }
+        };
+        Annotation::new(
+            AnnotationType::Error,
+            format!("{}: {}", field.value, label).into(),
+            &field.origin,
+        )
+    }
+}
+
+/// Converts the field to a pathname.
+fn path(field: &Field) -> Result<CString, Errno> {
+    CString::new(field.value.as_str()).map_err(|_| Errno::EILSEQ)
+}
+
+impl Action {
+    /// Performs the action.
+    fn execute(self, env: &mut Env) -> Result<(), Error> {
+        match self {
+            Action::Clear => env.history.clear(),
+
+            Action::Delete(field, index) => {
+                let number = if index > 0 {
+                    u32::try_from(index).ok()
+                } else {
+                    usize::try_from(index.unsigned_abs())
+                        .ok()
+                        .and_then(|offset| env.history.len().checked_sub(offset))
+                        .and_then(|index| env.history.entries().nth(index))
+                        .map(|entry| entry.number)
+                };
+                match number.and_then(|number| env.history.remove(number)) {
+                    Some(_) => (),
+                    None => return Err(Error::NoSuchEntry(field)),
+                }
+            }
+
+            Action::Read(field) => {
+                let result = path(&field).and_then(|path| history::read_file(env, &path));
+                result.map_err(|errno| Error::ReadError(field, errno))?
+            }
+
+            Action::Write(field) => {
+                let result = path(&field).and_then(|path| history::write_file(env, &path));
+                result.map_err(|errno| Error::WriteError(field, errno))?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Command {
+    /// Executes the `history` built-in.
+    ///
+    /// Returns the text to be printed to the standard output. If an action
+    /// fails, the remaining actions are not performed.
+    pub fn execute(self, env: &mut Env) -> Result<String, Error> {
+        match self {
+            Command::Print(count) => {
+                let count = count.unwrap_or(usize::MAX);
+                let skip = env.history.len().saturating_sub(count);
+                let mut output = String::new();
+                for entry in env.history.entries().skip(skip) {
+                    let text = entry.text.replace('\n', "\n\t");
+                    writeln!(output, "{:5}\t{}", entry.number, text).unwrap();
+                }
+                Ok(output)
+            }
+
+            Command::Modify(actions) => {
+                for action in actions {
+                    action.execute(env)?;
+                }
+                Ok(String::new())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use yash_env::system::r#virtual::INode;
+    use yash_env::VirtualSystem;
+
+    fn env_with_history(lines: &[&str]) -> Env {
+        let mut env = Env::new_virtual();
+        for line in lines {
+            env.history.add(line);
+        }
+        env
+    }
+
+    #[test]
+    fn printing_all_entries() {
+        let mut env = env_with_history(&["echo 1", "for i in 1\ndo :\ndone"]);
+        let output = Command::Print(None).execute(&mut env).unwrap();
+        assert_eq!(output, "    1\techo 1\n    2\tfor i in 1\n\tdo :\n\tdone\n");
+    }
+
+    #[test]
+    fn printing_last_entries() {
+        let mut env = env_with_history(&["a", "b", "c"]);
+        let output = Command::Print(Some(2)).execute(&mut env).unwrap();
+        assert_eq!(output, "    2\tb\n    3\tc\n");
+        let output = Command::Print(Some(0)).execute(&mut env).unwrap();
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn clearing_and_deleting() {
+        let mut env = env_with_history(&["a", "b", "c", "d"]);
+        let actions = vec![
+            Action::Delete(Field::dummy("2"), 2),
+            Action::Delete(Field::dummy("-1"), -1),
+        ];
+        let result = Command::Modify(actions).execute(&mut env);
+        assert_eq!(result, Ok(String::new()));
+        assert_eq!(env.history.iter().collect::<Vec<_>>(), ["a", "c"]);
+
+        let result = Command::Modify(vec![Action::Clear]).execute(&mut env);
+        assert_eq!(result, Ok(String::new()));
+        assert!(env.history.is_empty());
+    }
+
+    #[test]
+    fn deleting_nonexistent_entries() {
+        let mut env = env_with_history(&["a", "b"]);
+        for index in [3, -3, i64::MIN] {
+            let field = Field::dummy(index.to_string());
+            let actions = vec![Action::Delete(field.clone(), index)];
+            let result = Command::Modify(actions).execute(&mut env);
+            assert_eq!(result, Err(Error::NoSuchEntry(field)));
+        }
+        assert_eq!(env.history.len(), 2);
+    }
+
+    #[test]
+    fn reading_file() {
+        let system = VirtualSystem::new();
+        let file = Rc::new(RefCell::new(INode::new(":echo 1\n:echo 2\n")));
+        system
+            .state
+            .borrow_mut()
+            .file_system
+            .save("/history", file)
+            .unwrap();
+        let mut env = Env::with_system(Box::new(system));
+        let actions = vec![Action::Read(Field::dummy("/history"))];
+        let result = Command::Modify(actions).execute(&mut env);
+        assert_eq!(result, Ok(String::new()));
+        assert_eq!(env.history.iter().collect::<Vec<_>>(), ["echo 1", "echo 2"]);
+    }
+
+    #[test]
+    fn reading_nonexistent_file() {
+        let mut env = Env::new_virtual();
+        let field = Field::dummy("/no/such/file");
+        let actions = vec![Action::Read(field.clone())];
+        let result = Command::Modify(actions).execute(&mut env);
+        assert_eq!(result, Err(Error::ReadError(field, Errno::ENOENT)));
+    }
+}
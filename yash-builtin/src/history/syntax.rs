@@ -0,0 +1,207 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line argument parser for the history built-in
+
+use super::Action;
+use super::Command;
+use crate::common::syntax::parse_arguments;
+use crate::common::syntax::Mode;
+use crate::common::syntax::OptionArgumentSpec;
+use crate::common::syntax::OptionSpec;
+use thiserror::Error;
+use yash_env::semantics::Field;
+use yash_env::Env;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::Message;
+
+/// Error in parsing command line arguments
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error occurred in the common parser.
+    #[error(transparent)]
+    CommonError(#[from] crate::common::syntax::ParseError<'static>),
+
+    /// The argument to the `-d` option is not a non-zero integer.
+    #[error("invalid history entry")]
+    InvalidEntry(Field),
+
+    /// The count operand is not a non-negative integer.
+    #[error("invalid count")]
+    InvalidCount(Field),
+
+    /// Operands are given with options or more than one operand is given.
+    #[error("unexpected operand")]
+    UnexpectedOperands(Vec<Field>),
+}
+
+impl Error {
+    /// Converts this error into a message.
+    pub fn to_message(&self) -> Message {
+        let field = match self {
+            Error::CommonError(e) => return e.into(),
+            Error::InvalidEntry(field) | Error::InvalidCount(field) => field,
+            Error::UnexpectedOperands(operands) => &operands[0],
+        };
+        Message {
+            r#type: AnnotationType::Error,
+            title: self.to_string().into(),
+            annotations: vec![Annotation::new(
+                AnnotationType::Error,
+                format!("{}: {}", field.value, self).into(),
+                &field.origin,
+            )],
+            footers: vec![],
+        }
+    }
+}
+
+impl<'a> From<&'a Error> for Message<'a> {
+    #[inline]
+    fn from(e: &'a Error) -> Self {
+        e.to_message()
+    }
+}
+
+/// Result of parsing command line arguments
+pub type Result = std::result::Result<Command, Error>;
+
+const OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec::new().short('c').long("clear"),
+    OptionSpec::new()
+        .short('d')
+        .long("delete")
+        .argument(OptionArgumentSpec::Required),
+    OptionSpec::new()
+        .short('r')
+        .long("read")
+        .argument(OptionArgumentSpec::Required),
+    OptionSpec::new()
+        .short('w')
+        .long("write")
+        .argument(OptionArgumentSpec::Required),
+];
+
+/// Parses command line arguments for the history built-in.
+pub fn parse(env: &Env, args: Vec<Field>) -> Result {
+    let mode = Mode::with_env(env);
+    let (options, mut operands) = parse_arguments(OPTION_SPECS, mode, args)?;
+
+    if options.is_empty() {
+        if operands.len() > 1 {
+            return Err(Error::UnexpectedOperands(operands.split_off(1)));
+        }
+        return match operands.pop() {
+            None => Ok(Command::Print(None)),
+            Some(count) => match count.value.parse() {
+                Ok(count) => Ok(Command::Print(Some(count))),
+                Err(_) => Err(Error::InvalidCount(count)),
+            },
+        };
+    }
+
+    if !operands.is_empty() {
+        return Err(Error::UnexpectedOperands(operands));
+    }
+
+    options
+        .into_iter()
+        .map(|option| match (option.spec.get_short(), option.argument) {
+            (Some('c'), _) => Ok(Action::Clear),
+            (Some('d'), Some(entry)) => match entry.value.parse() {
+                Ok(0) | Err(_) => Err(Error::InvalidEntry(entry)),
+                Ok(index) => Ok(Action::Delete(entry, index)),
+            },
+            (Some('r'), Some(file)) => Ok(Action::Read(file)),
+            (Some('w'), Some(file)) => Ok(Action::Write(file)),
+            _ => unreachable!(),
+        })
+        .collect::<std::result::Result<_, _>>()
+        .map(Command::Modify)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn printing() {
+        let env = Env::new_virtual();
+        let result = parse(&env, vec![]);
+        assert_eq!(result, Ok(Command::Print(None)));
+        let result = parse(&env, Field::dummies(["10"]));
+        assert_eq!(result, Ok(Command::Print(Some(10))));
+    }
+
+    #[test]
+    fn invalid_count() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-1"]));
+        assert_matches!(result, Err(Error::CommonError(_)));
+        let result = parse(&env, Field::dummies(["x"]));
+        assert_eq!(result, Err(Error::InvalidCount(Field::dummy("x"))));
+        let result = parse(&env, Field::dummies(["1", "2"]));
+        assert_eq!(
+            result,
+            Err(Error::UnexpectedOperands(Field::dummies(["2"])))
+        );
+    }
+
+    #[test]
+    fn options_in_order() {
+        let env = Env::new_virtual();
+        let args = Field::dummies(["-w", "file", "-c", "--delete=-2", "-rother"]);
+        let result = parse(&env, args);
+        assert_matches!(result, Ok(Command::Modify(actions)) => {
+            assert_matches!(&actions[..], [
+                Action::Write(file),
+                Action::Clear,
+                Action::Delete(entry, -2),
+                Action::Read(other),
+            ] => {
+                assert_eq!(file.value, "file");
+                assert_eq!(entry.value, "-2");
+                assert_eq!(other.value, "other");
+            });
+        });
+    }
+
+    #[test]
+    fn invalid_entry() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-d", "0"]));
+        assert_matches!(result, Err(Error::InvalidEntry(field)) => {
+            assert_eq!(field.value, "0");
+        });
+        let result = parse(&env, Field::dummies(["-d", "foo"]));
+        assert_matches!(result, Err(Error::InvalidEntry(field)) => {
+            assert_eq!(field.value, "foo");
+        });
+    }
+
+    #[test]
+    fn operands_with_options() {
+        let env = Env::new_virtual();
+        let result = parse(&env, Field::dummies(["-c", "5"]));
+        assert_eq!(
+            result,
+            Err(Error::UnexpectedOperands(Field::dummies(["5"])))
+        );
+    }
+}
@@ -50,12 +50,15 @@
 
 pub mod alias;
 pub mod bg;
+pub mod bindkey;
 pub mod r#break;
 pub mod cd;
 pub mod colon;
 #[cfg(feature = "yash-semantics")]
 pub mod command;
 pub mod common;
+#[cfg(feature = "yash-semantics")]
+pub mod complete;
 pub mod r#continue;
 #[cfg(feature = "yash-semantics")]
 pub mod eval;
@@ -64,8 +67,11 @@ pub mod exec;
 pub mod exit;
 pub mod export;
 pub mod r#false;
+#[cfg(feature = "yash-semantics")]
+pub mod fc;
 pub mod fg;
 pub mod getopts;
+pub mod history;
 pub mod jobs;
 pub mod kill;
 pub mod pwd;
@@ -133,6 +139,13 @@ pub const BUILTINS: &[(&str, Builtin)] = &[
             execute: |env, args| Box::pin(bg::main(env, args)),
         },
     ),
+    (
+        "bindkey",
+        Builtin {
+            r#type: Elective,
+            execute: |env, args| Box::pin(bindkey::main(env, args)),
+        },
+    ),
     (
         "break",
         Builtin {
@@ -155,6 +168,14 @@ pub const BUILTINS: &[(&str, Builtin)] = &[
             execute: |env, args| Box::pin(command::main(env, args)),
         },
     ),
+    #[cfg(feature = "yash-semantics")]
+    (
+        "complete",
+        Builtin {
+            r#type: Elective,
+            execute: |env, args| Box::pin(complete::main(env, args)),
+        },
+    ),
     (
         "continue",
         Builtin {
@@ -199,6 +220,14 @@ pub const BUILTINS: &[(&str, Builtin)] = &[
             execute: |env, args| Box::pin(r#false::main(env, args)),
         },
     ),
+    #[cfg(feature = "yash-semantics")]
+    (
+        "fc",
+        Builtin {
+            r#type: Mandatory,
+            execute: |env, args| Box::pin(fc::main(env, args)),
+        },
+    ),
     (
         "fg",
         Builtin {
@@ -213,6 +242,13 @@ pub const BUILTINS: &[(&str, Builtin)] = &[
             execute: |env, args| Box::pin(getopts::main(env, args)),
         },
     ),
+    (
+        "history",
+        Builtin {
+            r#type: Elective,
+            execute: |env, args| Box::pin(history::main(env, args)),
+        },
+    ),
     (
         "jobs",
         Builtin {
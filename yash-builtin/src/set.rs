@@ -303,18 +303,25 @@ mod tests {
             assert_eq!(
                 stdout,
                 "allexport        on
+braceexpand      off
+caseglob         on
 clobber          on
 cmdline          off
+dotglob          off
+emacs            off
 errexit          off
 exec             on
+extendedglob     off
 glob             on
 hashondefinition off
 ignoreeof        off
 interactive      off
 log              on
 login            off
+markdirs         off
 monitor          off
 notify           off
+nullglob         off
 posixlycorrect   off
 stdin            off
 unset            off
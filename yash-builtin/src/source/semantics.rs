@@ -18,6 +18,7 @@
 
 use super::Command;
 use crate::common::report_failure;
+use std::cell::RefCell;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::num::NonZeroU64;
@@ -66,7 +67,9 @@ impl Command {
             origin: self.file.origin,
         };
         let mut lexer = Lexer::new(input, start_line_number, source);
-        let divert = ReadEvalLoop::new(env, &mut lexer).run().await;
+        let divert = ReadEvalLoop::new(&RefCell::new(&mut **env), &mut lexer)
+            .run()
+            .await;
 
         _ = env.system.close(fd);
 
@@ -0,0 +1,110 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line completion
+//!
+//! When the user requests completion in the line editor, the word under the
+//! cursor is completed with [candidates](Candidate) generated for the
+//! context. For commands that have a completion function registered in the
+//! [`Completion`] of the environment, the function is called to generate
+//! candidates. While the function is running, the ongoing [`Request`] is
+//! available in the environment so that the `complete` built-in can add
+//! candidates to it.
+
+use std::collections::BTreeMap;
+
+/// Candidate for the word being completed
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Candidate {
+    /// Text that replaces the word being completed
+    pub value: String,
+    /// Whether the candidate is a complete word
+    ///
+    /// If true and the candidate is the only one, the word is terminated
+    /// after the candidate is inserted. This is false for directory names,
+    /// which are usually followed by more pathname components.
+    pub ends_word: bool,
+}
+
+impl Candidate {
+    /// Creates a candidate that is a complete word.
+    #[must_use]
+    pub fn new<S: Into<String>>(value: S) -> Self {
+        let value = value.into();
+        Candidate {
+            value,
+            ends_word: true,
+        }
+    }
+}
+
+/// Ongoing completion request
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    /// Words preceding the word being completed in the current command,
+    /// starting with the command name
+    pub words: Vec<String>,
+    /// Word being completed, with quotes removed
+    pub target: String,
+    /// Candidates collected so far
+    pub candidates: Vec<Candidate>,
+}
+
+impl Request {
+    /// Adds a candidate.
+    ///
+    /// The candidate is ignored if it does not start with the target word or
+    /// the same candidate has already been added.
+    pub fn add(&mut self, candidate: Candidate) {
+        if candidate.value.starts_with(&self.target)
+            && !self.candidates.iter().any(|c| c.value == candidate.value)
+        {
+            self.candidates.push(candidate);
+        }
+    }
+}
+
+/// Completion settings and state in the environment
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Completion {
+    /// Names of the functions that generate candidates, keyed by the name of
+    /// the command whose arguments are completed
+    pub functions: BTreeMap<String, String>,
+    /// Request being processed by a completion function
+    pub request: Option<Request>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_candidates() {
+        let mut request = Request {
+            target: "fo".to_string(),
+            ..Request::default()
+        };
+        request.add(Candidate::new("foo"));
+        request.add(Candidate::new("bar"));
+        request.add(Candidate::new("foo"));
+        request.add(Candidate {
+            value: "fox/".to_string(),
+            ends_word: false,
+        });
+        let values: Vec<_> = request.candidates.iter().map(|c| &c.value).collect();
+        assert_eq!(values, ["foo", "fox/"]);
+    }
+}
@@ -0,0 +1,755 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command history
+//!
+//! An interactive shell remembers the command lines entered by the user in
+//! the [`History`]. The line editor allows the user to recall the entries.
+//!
+//! The [`record`] and [`record_continuation`] functions add command lines to
+//! the history in the environment, applying the following variables:
+//!
+//! - `HISTSIZE`: the maximum number of entries kept in the history. The
+//!   oldest entries are removed when the history gets larger. The default is
+//!   [`DEFAULT_SIZE`].
+//! - `HISTRMDUP`: the number of most recent entries that are checked for
+//!   duplicates. When a new entry is added, older entries with the same
+//!   command line among them are removed. The default is 0, in which case no
+//!   duplicates are removed.
+//! - `HISTFILE`: the pathname of the history file.
+//!
+//! # History file
+//!
+//! If `HISTFILE` is set, the interactive shell [loads](load) the history from
+//! the file on startup and appends each new entry to the file. In the file,
+//! each line of an entry is written as a line prefixed with a tag: `:` for
+//! the first line of an entry and `+` for a continuation line. Lines without
+//! a tag are read as separate entries.
+//!
+//! More than one shell may share the same history file. To prevent the file
+//! from being corrupted by concurrent updates, the shell locks the file with
+//! `flock` while reading or writing it. The lines of an entry are kept in
+//! memory until the entry is complete and then [flushed](flush) to the file
+//! at once so that the lines are not interleaved with entries written by
+//! another shell. Since every shell appends its entries
+//! to the file, the file may grow larger than `HISTSIZE`. When loading the
+//! file, the shell rewrites it to remove redundant entries.
+
+use crate::io::Fd;
+use crate::system::FlockArg;
+use crate::system::Mode;
+use crate::system::OFlag;
+use crate::variable::Value;
+use crate::Env;
+use crate::System;
+use std::ffi::CStr;
+use std::ffi::CString;
+
+/// Default maximum number of history entries
+pub const DEFAULT_SIZE: usize = 500;
+
+/// Entry in the history
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// Number that identifies the entry
+    ///
+    /// Entries are numbered in the order they were added, starting from 1.
+    /// The number of an entry does not change when other entries are removed.
+    pub number: u32,
+    /// Command line without a trailing newline
+    pub text: String,
+}
+
+/// Command history
+///
+/// A history is a list of command lines, the oldest first. Each entry is a
+/// command without a trailing newline. A command that spans more than one
+/// line is recorded as a single entry containing newlines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct History {
+    entries: Vec<Entry>,
+    next_number: u32,
+    /// Lines of the last entry that have not been written to the history file
+    unsaved: String,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            entries: Vec::new(),
+            next_number: 1,
+            unsaved: String::new(),
+        }
+    }
+}
+
+impl History {
+    /// Creates an empty history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries in the history.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Tests whether the history is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entry at the given index.
+    ///
+    /// Index 0 is the oldest entry.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.text.as_str())
+    }
+
+    /// Returns an iterator over the entries, the oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.entries.iter().map(|entry| entry.text.as_str())
+    }
+
+    /// Returns an iterator over the entries with their numbers, the oldest
+    /// first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    /// Returns the index of the entry that has the given number.
+    #[must_use]
+    pub fn index_of(&self, number: u32) -> Option<usize> {
+        self.entries
+            .binary_search_by_key(&number, |entry| entry.number)
+            .ok()
+    }
+
+    /// Adds a new entry to the history.
+    ///
+    /// A trailing newline is removed from the line. Empty lines are not added.
+    pub fn add(&mut self, line: &str) {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        if !line.is_empty() {
+            let number = self.next_number;
+            self.next_number = number.wrapping_add(1).max(1);
+            let text = line.to_owned();
+            self.entries.push(Entry { number, text });
+        }
+    }
+
+    /// Appends a continuation line to the last entry.
+    ///
+    /// This function is used for the second and following lines of a command
+    /// that spans more than one line. A trailing newline is removed from the
+    /// line. If the history is empty, the line is added as a new entry.
+    pub fn add_continuation(&mut self, line: &str) {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        match self.entries.last_mut() {
+            Some(last) => {
+                last.text.push('\n');
+                last.text.push_str(line);
+            }
+            None => self.add(line),
+        }
+    }
+
+    /// Removes the entry that has the given number.
+    pub fn remove(&mut self, number: u32) -> Option<Entry> {
+        let index = self.index_of(number)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Removes all entries.
+    ///
+    /// Entries added after clearing are numbered continuously from the
+    /// removed entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Removes entries that are the same as `text` from the `count` most
+    /// recent entries.
+    pub fn remove_duplicates(&mut self, text: &str, count: usize) {
+        let start = self.entries.len().saturating_sub(count);
+        let mut index = 0;
+        self.entries.retain(|entry| {
+            index += 1;
+            index <= start || entry.text != text
+        });
+    }
+
+    /// Removes the oldest entries so that the history has at most `max_len`
+    /// entries.
+    pub fn limit(&mut self, max_len: usize) {
+        let excess = self.entries.len().saturating_sub(max_len);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Returns the value of a non-empty scalar variable.
+fn scalar<'a>(env: &'a Env, name: &str) -> Option<&'a str> {
+    match &env.variables.get(name)?.value {
+        Some(Value::Scalar(value)) if !value.is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+/// Returns the maximum number of history entries specified by `HISTSIZE`.
+///
+/// If the variable is not set or not a valid number, [`DEFAULT_SIZE`] is
+/// returned.
+#[must_use]
+pub fn size(env: &Env) -> usize {
+    scalar(env, "HISTSIZE")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_SIZE)
+}
+
+/// Returns the number of entries checked for duplicates specified by
+/// `HISTRMDUP`.
+fn rmdup_count(env: &Env) -> usize {
+    scalar(env, "HISTRMDUP")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Returns the pathname of the history file specified by `HISTFILE`.
+#[must_use]
+pub fn file_path(env: &Env) -> Option<CString> {
+    CString::new(scalar(env, "HISTFILE")?).ok()
+}
+
+/// Adds an entry to the history without writing to the history file.
+fn push(env: &mut Env, text: &str) {
+    let rmdup = rmdup_count(env);
+    env.history.remove_duplicates(text, rmdup);
+    env.history.add(text);
+}
+
+/// Records a command line in the history.
+///
+/// The line is added to the history in the environment, applying `HISTRMDUP`
+/// and `HISTSIZE`. The line is written to the history file when the entry is
+/// [flushed](flush). The previous entry is flushed before the new entry is
+/// added. A trailing newline is removed from the line. Empty lines are not
+/// recorded.
+pub fn record(env: &mut Env, line: &str) {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let size = size(env);
+    if line.is_empty() || size == 0 {
+        return;
+    }
+    flush(env);
+    push(env, line);
+    env.history.limit(size);
+    format_entry(&mut env.history.unsaved, line);
+}
+
+/// Records a continuation line in the history.
+///
+/// The line is appended to the last entry of the history. The line is written
+/// to the history file together with the other lines of the entry when the
+/// entry is [flushed](flush). A trailing newline is removed from the line.
+pub fn record_continuation(env: &mut Env, line: &str) {
+    if env.history.is_empty() {
+        return record(env, line);
+    }
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    env.history.add_continuation(line);
+
+    let unsaved = &mut env.history.unsaved;
+    if !unsaved.is_empty() {
+        unsaved.push('+');
+        unsaved.push_str(line);
+        unsaved.push('\n');
+    }
+}
+
+/// Writes the last recorded entry to the history file.
+///
+/// If `HISTFILE` is set, the lines of the entry that have not been written
+/// yet are appended to the file with a single write while the file is
+/// locked. Errors writing the file are ignored.
+///
+/// The read-eval loop calls this function when it has read a complete
+/// command so that the entry is written as a whole.
+pub fn flush(env: &mut Env) {
+    let content = std::mem::take(&mut env.history.unsaved);
+    if content.is_empty() {
+        return;
+    }
+    if let Some(path) = file_path(env) {
+        _ = append_file(env, &path, &content);
+    }
+}
+
+/// Replaces the last entry of the history with a command line.
+///
+/// The last entry is removed from the history and the line is
+/// [recorded](record) in its place. If the removed entry has already been
+/// [flushed](flush) to the history file, it is removed from the end of the
+/// file as well, so that the file agrees with the history in memory. The new
+/// entry is written to the file immediately.
+pub fn replace_last(env: &mut Env, line: &str) {
+    let is_saved = env.history.unsaved.is_empty();
+    let last = env.history.entries().next_back().cloned();
+    if let Some(last) = &last {
+        env.history.remove(last.number);
+    }
+    env.history.unsaved.clear();
+    record(env, line);
+
+    let Some(last) = last.filter(|_| is_saved) else {
+        return flush(env);
+    };
+    let content = std::mem::take(&mut env.history.unsaved);
+    if let Some(path) = file_path(env) {
+        _ = replace_file_tail(env, &path, &last.text, &content);
+    }
+}
+
+/// Loads the history from the history file.
+///
+/// This function is called when an interactive shell starts up. If
+/// `HISTFILE` is set, the entries in the file are added to the history. If
+/// the file contains more entries than the history keeps, the file is
+/// rewritten with the remaining entries. Errors are ignored.
+pub fn load(env: &mut Env) {
+    if let Some(path) = file_path(env) {
+        _ = read_file_locked(env, &path, true);
+    }
+}
+
+/// Reads history entries from a file.
+///
+/// The entries in the file are added to the history in the environment,
+/// applying `HISTRMDUP` and `HISTSIZE`.
+pub fn read_file(env: &mut Env, path: &CStr) -> nix::Result<()> {
+    read_file_locked(env, path, false)
+}
+
+/// Writes all the history entries to a file.
+///
+/// The file is created if it does not exist. Existing content of the file is
+/// replaced.
+pub fn write_file(env: &mut Env, path: &CStr) -> nix::Result<()> {
+    let fd = open_locked(env, path, OFlag::O_WRONLY | OFlag::O_CREAT)?;
+    let result = overwrite(env, path);
+    _ = env.system.close(fd);
+    result
+}
+
+/// Appends an entry to the text of a history file.
+fn format_entry(content: &mut String, text: &str) {
+    let mut tag = ':';
+    for line in text.split('\n') {
+        content.push(tag);
+        content.push_str(line);
+        content.push('\n');
+        tag = '+';
+    }
+}
+
+/// Parses the text of a history file.
+fn parse_entries(content: &str) -> Vec<String> {
+    let mut entries: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if let Some(line) = line.strip_prefix('+') {
+            if let Some(last) = entries.last_mut() {
+                last.push('\n');
+                last.push_str(line);
+                continue;
+            }
+        }
+        let line = line.strip_prefix(':').unwrap_or(line);
+        if !line.is_empty() {
+            entries.push(line.to_owned());
+        }
+    }
+    entries
+}
+
+/// Opens a file and locks it exclusively.
+fn open_locked(env: &mut Env, path: &CStr, flags: OFlag) -> nix::Result<Fd> {
+    let mode = Mode::S_IRUSR | Mode::S_IWUSR;
+    let fd = env.system.open(path, flags | OFlag::O_CLOEXEC, mode)?;
+    if let Err(errno) = env.system.flock(fd, FlockArg::LockExclusive) {
+        _ = env.system.close(fd);
+        return Err(errno);
+    }
+    Ok(fd)
+}
+
+/// Reads the whole content of a file.
+fn read_all(env: &mut Env, fd: Fd) -> nix::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        match env.system.read(fd, &mut buffer) {
+            Ok(0) => break,
+            Ok(count) => content.extend_from_slice(&buffer[..count]),
+            Err(nix::errno::Errno::EINTR) => continue,
+            Err(errno) => return Err(errno),
+        }
+    }
+    Ok(content)
+}
+
+/// Writes the whole content to a file.
+fn write_all(env: &mut Env, fd: Fd, mut content: &[u8]) -> nix::Result<()> {
+    while !content.is_empty() {
+        match env.system.write(fd, content) {
+            Ok(count) => content = &content[count..],
+            Err(nix::errno::Errno::EINTR) => continue,
+            Err(errno) => return Err(errno),
+        }
+    }
+    Ok(())
+}
+
+/// Appends text to the history file.
+fn append_file(env: &mut Env, path: &CStr, content: &str) -> nix::Result<()> {
+    let flags = OFlag::O_WRONLY | OFlag::O_APPEND | OFlag::O_CREAT;
+    let fd = open_locked(env, path, flags)?;
+    let result = write_all(env, fd, content.as_bytes());
+    _ = env.system.close(fd);
+    result
+}
+
+/// Replaces the content of a file with all the history entries.
+///
+/// The caller must hold the lock on the file.
+fn overwrite(env: &mut Env, path: &CStr) -> nix::Result<()> {
+    let mut content = String::new();
+    for text in env.history.iter() {
+        format_entry(&mut content, text);
+    }
+    replace_content(env, path, content.as_bytes())
+}
+
+/// Replaces the content of a file.
+///
+/// The caller must hold the lock on the file.
+fn replace_content(env: &mut Env, path: &CStr, content: &[u8]) -> nix::Result<()> {
+    let flags = OFlag::O_WRONLY | OFlag::O_TRUNC | OFlag::O_CLOEXEC;
+    let fd = env.system.open(path, flags, Mode::empty())?;
+    let result = write_all(env, fd, content);
+    _ = env.system.close(fd);
+    result
+}
+
+/// Replaces the last entry in the history file.
+///
+/// If the file ends with the entry whose text is `old`, the entry is replaced
+/// with `new`, which is the formatted text of zero or more entries.
+/// Otherwise, `new` is appended to the file.
+fn replace_file_tail(env: &mut Env, path: &CStr, old: &str, new: &str) -> nix::Result<()> {
+    let fd = open_locked(env, path, OFlag::O_RDWR | OFlag::O_CREAT)?;
+    let result = read_all(env, fd).and_then(|content| {
+        let mut old_entry = String::new();
+        format_entry(&mut old_entry, old);
+        match content.strip_suffix(old_entry.as_bytes()) {
+            Some(rest) => replace_content(env, path, &[rest, new.as_bytes()].concat()),
+            None => write_all(env, fd, new.as_bytes()),
+        }
+    });
+    _ = env.system.close(fd);
+    result
+}
+
+/// Reads entries from a file while locking it.
+///
+/// If `compact` is true and some of the entries in the file have been
+/// discarded, the file is rewritten with the entries in the history.
+fn read_file_locked(env: &mut Env, path: &CStr, compact: bool) -> nix::Result<()> {
+    let fd = open_locked(env, path, OFlag::O_RDWR)?;
+    let result = read_all(env, fd).and_then(|content| {
+        let entries = parse_entries(&String::from_utf8_lossy(&content));
+        let count = entries.len();
+        for text in entries {
+            push(env, &text);
+        }
+        env.history.limit(size(env));
+        if compact && count > env.history.len() {
+            overwrite(env, path)
+        } else {
+            Ok(())
+        }
+    });
+    _ = env.system.close(fd);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::r#virtual::FileBody;
+    use crate::system::r#virtual::INode;
+    use crate::variable::Scope;
+    use crate::VirtualSystem;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn adding_entries() {
+        let mut history = History::new();
+        assert!(history.is_empty());
+
+        history.add("echo foo\n");
+        history.add("");
+        history.add("\n");
+        history.add("ls");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0), Some("echo foo"));
+        assert_eq!(history.get(1), Some("ls"));
+        assert_eq!(history.get(2), None);
+        assert_eq!(history.iter().rev().collect::<Vec<_>>(), ["ls", "echo foo"]);
+    }
+
+    #[test]
+    fn adding_continuation_lines() {
+        let mut history = History::new();
+        history.add_continuation("foo\n");
+        history.add("for i in 1 2\n");
+        history.add_continuation("do echo $i\n");
+        history.add_continuation("done\n");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0), Some("foo"));
+        assert_eq!(history.get(1), Some("for i in 1 2\ndo echo $i\ndone"));
+    }
+
+    #[test]
+    fn entry_numbers_are_kept_on_removal() {
+        let mut history = History::new();
+        history.add("one");
+        history.add("two");
+        history.add("three");
+        assert_eq!(
+            history.remove(2).map(|entry| entry.text),
+            Some("two".into())
+        );
+        assert_eq!(history.remove(2), None);
+        history.add("four");
+        let numbers: Vec<_> = history.entries().map(|entry| entry.number).collect();
+        assert_eq!(numbers, [1, 3, 4]);
+        assert_eq!(history.index_of(4), Some(2));
+
+        history.clear();
+        history.add("five");
+        assert_eq!(history.entries().next().unwrap().number, 5);
+    }
+
+    #[test]
+    fn removing_duplicates_and_limiting() {
+        let mut history = History::new();
+        for line in ["a", "b", "a", "c", "a"] {
+            history.add(line);
+        }
+        history.remove_duplicates("a", 2);
+        assert_eq!(history.iter().collect::<Vec<_>>(), ["a", "b", "a", "c"]);
+        history.remove_duplicates("a", 4);
+        assert_eq!(history.iter().collect::<Vec<_>>(), ["b", "c"]);
+
+        history.add("d");
+        history.limit(2);
+        assert_eq!(history.iter().collect::<Vec<_>>(), ["c", "d"]);
+    }
+
+    fn set_variable(env: &mut Env, name: &str, value: &str) {
+        env.variables
+            .get_or_new(name, Scope::Global)
+            .assign(value, None)
+            .unwrap();
+    }
+
+    fn file_content(system: &VirtualSystem, path: &str) -> String {
+        let state = system.state.borrow();
+        let file = state.file_system.get(path).unwrap();
+        let file = file.borrow();
+        match &file.body {
+            FileBody::Regular { content, .. } => String::from_utf8(content.clone()).unwrap(),
+            body => panic!("not a regular file: {body:?}"),
+        }
+    }
+
+    #[test]
+    fn recording_applies_variables() {
+        let mut env = Env::new_virtual();
+        set_variable(&mut env, "HISTSIZE", "3");
+        set_variable(&mut env, "HISTRMDUP", "2");
+        for line in ["a\n", "b\n", "", "a\n", "c\n", "b\n", "d\n"] {
+            record(&mut env, line);
+        }
+        assert_eq!(env.history.iter().collect::<Vec<_>>(), ["c", "b", "d"]);
+    }
+
+    #[test]
+    fn recording_appends_to_file() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        set_variable(&mut env, "HISTFILE", "/history");
+        record(&mut env, "for i in 1\n");
+        record_continuation(&mut env, "do echo\n");
+        record_continuation(&mut env, "done\n");
+        record(&mut env, "ls\n");
+        flush(&mut env);
+
+        assert_eq!(env.history.get(0), Some("for i in 1\ndo echo\ndone"));
+        assert_eq!(
+            file_content(&system, "/history"),
+            ":for i in 1\n+do echo\n+done\n:ls\n"
+        );
+    }
+
+    #[test]
+    fn entry_is_written_to_file_when_complete() {
+        let system = VirtualSystem::new();
+        let file = Rc::new(RefCell::new(INode::new(":other\n")));
+        system
+            .state
+            .borrow_mut()
+            .file_system
+            .save("/history", Rc::clone(&file))
+            .unwrap();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        set_variable(&mut env, "HISTFILE", "/history");
+        record(&mut env, "for i in 1\n");
+        record_continuation(&mut env, "do echo\n");
+        assert_eq!(file_content(&system, "/history"), ":other\n");
+
+        // Another shell appends an entry before this entry is complete.
+        file.borrow_mut().body = FileBody::new(":other\n:another\n");
+        record_continuation(&mut env, "done\n");
+        flush(&mut env);
+        assert_eq!(
+            file_content(&system, "/history"),
+            ":other\n:another\n:for i in 1\n+do echo\n+done\n"
+        );
+
+        flush(&mut env);
+        assert_eq!(
+            file_content(&system, "/history"),
+            ":other\n:another\n:for i in 1\n+do echo\n+done\n"
+        );
+    }
+
+    #[test]
+    fn replacing_flushed_entry_in_file() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        set_variable(&mut env, "HISTFILE", "/history");
+        record(&mut env, "echo one\n");
+        record(&mut env, "fc -s\n");
+        flush(&mut env);
+        assert_eq!(file_content(&system, "/history"), ":echo one\n:fc -s\n");
+
+        replace_last(&mut env, "echo one\n");
+        assert_eq!(
+            env.history.iter().collect::<Vec<_>>(),
+            ["echo one", "echo one"]
+        );
+        assert_eq!(file_content(&system, "/history"), ":echo one\n:echo one\n");
+    }
+
+    #[test]
+    fn replacing_unflushed_entry() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        set_variable(&mut env, "HISTFILE", "/history");
+        record(&mut env, "echo one\n");
+        flush(&mut env);
+        // Another shell appends an entry.
+        system
+            .state
+            .borrow_mut()
+            .file_system
+            .get("/history")
+            .unwrap()
+            .borrow_mut()
+            .body = FileBody::new(":echo one\n:other\n");
+        record(&mut env, "fc -s\n");
+
+        replace_last(&mut env, "echo two\n");
+        assert_eq!(
+            env.history.iter().collect::<Vec<_>>(),
+            ["echo one", "echo two"]
+        );
+        assert_eq!(
+            file_content(&system, "/history"),
+            ":echo one\n:other\n:echo two\n"
+        );
+    }
+
+    #[test]
+    fn loading_compacts_file() {
+        let system = VirtualSystem::new();
+        let content = ":one\n:two\n+2\nthree\n\n:four\n";
+        let file = Rc::new(RefCell::new(INode::new(content)));
+        system
+            .state
+            .borrow_mut()
+            .file_system
+            .save("/history", file)
+            .unwrap();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        set_variable(&mut env, "HISTFILE", "/history");
+        set_variable(&mut env, "HISTSIZE", "3");
+
+        load(&mut env);
+        assert_eq!(
+            env.history.iter().collect::<Vec<_>>(),
+            ["two\n2", "three", "four"]
+        );
+        assert_eq!(
+            file_content(&system, "/history"),
+            ":two\n+2\n:three\n:four\n"
+        );
+    }
+
+    #[test]
+    fn loading_without_file() {
+        let mut env = Env::new_virtual();
+        set_variable(&mut env, "HISTFILE", "/no/such/file");
+        load(&mut env);
+        assert!(env.history.is_empty());
+    }
+
+    #[test]
+    fn writing_and_reading_file() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.history.add("echo 1");
+        env.history.add("echo 2\necho 3");
+        let path = CString::new("/tmp/history").unwrap();
+        write_file(&mut env, &path).unwrap();
+        assert_eq!(
+            file_content(&system, "/tmp/history"),
+            ":echo 1\n:echo 2\n+echo 3\n"
+        );
+
+        env.history.clear();
+        read_file(&mut env, &path).unwrap();
+        assert_eq!(
+            env.history.iter().collect::<Vec<_>>(),
+            ["echo 1", "echo 2\necho 3"]
+        );
+    }
+}
@@ -31,6 +31,11 @@ use std::slice::from_mut;
 #[doc(no_inline)]
 pub use yash_syntax::input::*;
 
+mod ignore_eof;
+
+pub use self::ignore_eof::IgnoreEof;
+pub use self::ignore_eof::MAX_EOF_COUNT;
+
 /// Input function that reads from a file descriptor.
 ///
 /// An instance of `FdReader` contains a [`SharedSystem`] to interact with the
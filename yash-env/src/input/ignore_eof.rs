@@ -0,0 +1,229 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Input decorator that implements the `ignoreeof` option
+
+use super::Context;
+use super::Input;
+use super::Result;
+use crate::io::Fd;
+use crate::option::Option::{IgnoreEof as IgnoreEofOption, Interactive};
+use crate::option::State::On;
+use crate::system::System;
+use crate::Env;
+use async_trait::async_trait;
+use std::cell::RefCell;
+
+/// Maximum number of consecutive end-of-files ignored
+///
+/// After this many end-of-files are ignored in a row, the next end-of-file is
+/// passed through so that the shell can exit even if the user cannot stop
+/// sending end-of-files.
+pub const MAX_EOF_COUNT: usize = 50;
+
+/// Input decorator that ignores end-of-file on a terminal
+///
+/// An `IgnoreEof` wraps an inner [`Input`] that reads from a file descriptor.
+/// When the inner input returns an empty line, meaning the end of input, and
+/// all the following conditions are met, the decorator prints the `message`
+/// to the standard error and reads another line from the inner input instead
+/// of returning the empty line:
+///
+/// - The [`IgnoreEof`](crate::option::Option::IgnoreEof) option is on.
+/// - The [`Interactive`] option is on.
+/// - The file descriptor is a terminal.
+/// - Fewer than [`MAX_EOF_COUNT`] end-of-files have been ignored in a row.
+///
+/// If the inner input prints a prompt, the decorator should wrap the
+/// prompting input so that the prompt is printed again after the message.
+#[derive(Clone, Debug)]
+pub struct IgnoreEof<'a, 'b, T> {
+    inner: T,
+    fd: Fd,
+    env: &'a RefCell<&'b mut Env>,
+    message: String,
+    eof_count: usize,
+}
+
+impl<'a, 'b, T> IgnoreEof<'a, 'b, T> {
+    /// Creates a new `IgnoreEof` decorator.
+    ///
+    /// `fd` is the file descriptor the inner input reads from. It is tested
+    /// with [`isatty`](System::isatty) when the end of input is reached.
+    pub fn new(inner: T, fd: Fd, env: &'a RefCell<&'b mut Env>, message: String) -> Self {
+        IgnoreEof {
+            inner,
+            fd,
+            env,
+            message,
+            eof_count: 0,
+        }
+    }
+
+    /// Tests whether the end-of-file should be ignored.
+    fn should_ignore(&self) -> bool {
+        if self.eof_count >= MAX_EOF_COUNT {
+            return false;
+        }
+        let env = self.env.borrow();
+        env.options.get(IgnoreEofOption) == On
+            && env.options.get(Interactive) == On
+            && env.system.isatty(self.fd).unwrap_or(false)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> Input for IgnoreEof<'_, '_, T>
+where
+    T: Input,
+{
+    async fn next_line(&mut self, context: &Context) -> Result {
+        loop {
+            let line = self.inner.next_line(context).await?;
+            if !line.is_empty() || !self.should_ignore() {
+                self.eof_count = 0;
+                return Ok(line);
+            }
+
+            self.eof_count += 1;
+            let mut system = self.env.borrow().system.clone();
+            system.print_error(&self.message).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Memory;
+    use crate::option::State::Off;
+    use crate::system::r#virtual::FileBody;
+    use crate::system::r#virtual::SystemState;
+    use crate::system::r#virtual::VirtualSystem;
+    use futures_util::FutureExt as _;
+
+    fn assert_stderr<F: FnOnce(&str)>(state: &RefCell<SystemState>, f: F) {
+        let state = state.borrow();
+        let stderr = state.file_system.get("/dev/stderr").unwrap();
+        let stderr = stderr.borrow();
+        match &stderr.body {
+            FileBody::Regular { content, .. } => f(std::str::from_utf8(content).unwrap()),
+            body => panic!("unexpected file body: {body:?}"),
+        }
+    }
+
+    /// Input that returns the given lines and then end-of-files forever
+    struct Lines(Vec<&'static str>);
+
+    #[async_trait(?Send)]
+    impl Input for Lines {
+        async fn next_line(&mut self, _: &Context) -> Result {
+            if self.0.is_empty() {
+                Ok(String::new())
+            } else {
+                Ok(self.0.remove(0).to_owned())
+            }
+        }
+    }
+
+    fn terminal_system() -> VirtualSystem {
+        let system = VirtualSystem::new();
+        {
+            let state = system.state.borrow();
+            let stdin = state.file_system.get("/dev/stdin").unwrap();
+            stdin.borrow_mut().body = FileBody::Terminal {
+                content: Default::default(),
+            };
+        }
+        system
+    }
+
+    fn next_line<T: Input>(input: &mut T) -> String {
+        input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn eof_ignored_on_terminal() {
+        let system = terminal_system();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(IgnoreEofOption, On);
+        env.options.set(Interactive, On);
+        let ref_env = RefCell::new(&mut env);
+        let inner = Lines(vec!["echo 1\n", "", "", "echo 2\n"]);
+        let mut input = IgnoreEof::new(inner, Fd::STDIN, &ref_env, "EOF!\n".to_owned());
+
+        assert_eq!(next_line(&mut input), "echo 1\n");
+        assert_eq!(next_line(&mut input), "echo 2\n");
+        assert_stderr(&system.state, |stderr| assert_eq!(stderr, "EOF!\nEOF!\n"));
+    }
+
+    #[test]
+    fn eof_passed_after_max_count() {
+        let system = terminal_system();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(IgnoreEofOption, On);
+        env.options.set(Interactive, On);
+        let ref_env = RefCell::new(&mut env);
+        let mut input = IgnoreEof::new(Lines(vec![]), Fd::STDIN, &ref_env, "!".to_owned());
+
+        assert_eq!(next_line(&mut input), "");
+        assert_stderr(&system.state, |stderr| {
+            assert_eq!(stderr, "!".repeat(MAX_EOF_COUNT))
+        });
+    }
+
+    #[test]
+    fn eof_not_ignored_with_option_off() {
+        let system = terminal_system();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(IgnoreEofOption, Off);
+        env.options.set(Interactive, On);
+        let ref_env = RefCell::new(&mut env);
+        let mut input = IgnoreEof::new(Memory::new(""), Fd::STDIN, &ref_env, "!".to_owned());
+
+        assert_eq!(next_line(&mut input), "");
+        assert_stderr(&system.state, |stderr| assert_eq!(stderr, ""));
+    }
+
+    #[test]
+    fn eof_not_ignored_in_non_interactive_shell() {
+        let system = terminal_system();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(IgnoreEofOption, On);
+        let ref_env = RefCell::new(&mut env);
+        let mut input = IgnoreEof::new(Memory::new(""), Fd::STDIN, &ref_env, "!".to_owned());
+
+        assert_eq!(next_line(&mut input), "");
+        assert_stderr(&system.state, |stderr| assert_eq!(stderr, ""));
+    }
+
+    #[test]
+    fn eof_not_ignored_on_non_terminal() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(IgnoreEofOption, On);
+        env.options.set(Interactive, On);
+        let ref_env = RefCell::new(&mut env);
+        let mut input = IgnoreEof::new(Memory::new(""), Fd::STDIN, &ref_env, "!".to_owned());
+
+        assert_eq!(next_line(&mut input), "");
+        assert_stderr(&system.state, |stderr| assert_eq!(stderr, ""));
+    }
+}
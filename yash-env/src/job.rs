@@ -40,7 +40,12 @@
 //! The [`JobList::set_last_async_pid`] function remembers the process ID of the
 //! last executed asynchronous command, which will be the value of the `$!`
 //! special parameter.
+//!
+//! Child processes started for process substitutions are not jobs. They are
+//! recorded as [`ProcessSubst`]s in the environment so that the shell can wait
+//! for them when the command using them finishes.
 
+use crate::io::Fd;
 use crate::semantics::ExitStatus;
 use crate::trap::Signal;
 use nix::sys::wait::WaitStatus;
@@ -270,6 +275,22 @@ impl Job {
     }
 }
 
+/// Child process running a process substitution
+///
+/// See [`Env::process_substs`](crate::Env::process_substs) for how this is
+/// used.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProcessSubst {
+    /// Process ID of the child process
+    pub pid: Pid,
+
+    /// File descriptor for the pipe end kept open in the shell
+    ///
+    /// This is `None` if the pipe end has been moved to the target of a
+    /// process redirection.
+    pub fd: Option<Fd>,
+}
+
 /// Partially mutable reference to [`Job`].
 ///
 /// This struct is a specialized reference type for `Job`. It provides limited
@@ -0,0 +1,780 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Editing commands and key bindings
+//!
+//! The line editor interprets each key sequence typed by the user as an
+//! editing [`Command`] according to the [`KeyMap`] of the current [`Mode`].
+//! The key maps for all the modes are stored in the [`KeyBindings`] of the
+//! environment so that the user can customize them with the `bindkey`
+//! built-in.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Bound;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Editing command
+///
+/// Each key sequence typed by the user is mapped to a command in the
+/// [`KeyMap`] of the current [`Mode`]. The command names returned by
+/// [`name`](Self::name) are used to identify commands in key binding
+/// configuration.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum Command {
+    /// Does nothing.
+    Noop,
+    /// Rings the bell.
+    Alert,
+    /// Inserts the typed character.
+    SelfInsert,
+    /// Inserts the next typed character literally.
+    ExpectVerbatim,
+    /// Finishes editing and accepts the line.
+    AcceptLine,
+    /// Signals end-of-file if the line is empty.
+    EofIfEmpty,
+    /// Clears the screen and redraws the line.
+    ClearAndRedraw,
+    /// Completes the word before the cursor.
+    Complete,
+
+    /// Moves the cursor forward by one character.
+    ForwardChar,
+    /// Moves the cursor backward by one character.
+    BackwardChar,
+    /// Moves the cursor to the beginning of the line.
+    BeginningOfLine,
+    /// Moves the cursor to the end of the line.
+    EndOfLine,
+    /// Moves the cursor to the first non-blank character.
+    FirstNonblank,
+    /// Moves the cursor to the column specified by the count.
+    GoToColumn,
+    /// Moves the cursor to the beginning of the next word.
+    ForwardViword,
+    /// Moves the cursor to the beginning of the previous word.
+    BackwardViword,
+    /// Moves the cursor to the end of the current or next word.
+    EndOfViword,
+    /// Moves the cursor to the beginning of the next bigword.
+    ForwardBigword,
+    /// Moves the cursor to the beginning of the previous bigword.
+    BackwardBigword,
+    /// Moves the cursor to the end of the current or next bigword.
+    EndOfBigword,
+    /// Moves the cursor to the end of the current or next emacs word.
+    ForwardEmacsword,
+    /// Moves the cursor to the beginning of the current or previous emacs
+    /// word.
+    BackwardEmacsword,
+
+    /// Deletes the character under the cursor.
+    DeleteChar,
+    /// Deletes the character before the cursor.
+    BackwardDeleteChar,
+    /// Deletes the word before the cursor.
+    BackwardDeleteViword,
+    /// Deletes the text before the cursor.
+    BackwardDeleteLine,
+    /// Deletes the character under the cursor, saving it for putting.
+    KillChar,
+    /// Deletes the character before the cursor, saving it for putting.
+    BackwardKillChar,
+    /// Deletes the character under the cursor, or signals end-of-file if the
+    /// line is empty.
+    EofOrDelete,
+    /// Kills the text from the cursor to the end of the line.
+    KillLine,
+    /// Kills the text before the cursor.
+    BackwardKillLine,
+    /// Kills the text from the cursor to the end of the emacs word.
+    KillEmacsword,
+    /// Kills the text from the beginning of the emacs word to the cursor.
+    BackwardKillEmacsword,
+    /// Kills the text from the beginning of the bigword to the cursor.
+    BackwardKillBigword,
+    /// Inserts the last killed text.
+    Yank,
+    /// Replaces the just yanked text with the previously killed text.
+    YankPop,
+    /// Swaps the character before the cursor with the character under the
+    /// cursor.
+    TransposeChars,
+    /// Swaps the emacs word before the cursor with the next emacs word.
+    TransposeWords,
+    /// Converts the emacs word after the cursor to upper case.
+    UpcaseEmacsword,
+    /// Converts the emacs word after the cursor to lower case.
+    DowncaseEmacsword,
+    /// Capitalizes the emacs word after the cursor.
+    CapitalizeEmacsword,
+
+    /// Replaces the line with the previous history entry.
+    PrevHistory,
+    /// Replaces the line with the next history entry.
+    NextHistory,
+    /// Replaces the line with the oldest history entry.
+    BeginningOfHistory,
+    /// Replaces the line with the new line being edited.
+    EndOfHistory,
+    /// Starts an incremental search backward in the history.
+    EmacsSearchBackward,
+    /// Starts an incremental search forward in the history.
+    EmacsSearchForward,
+
+    /// Starts a count for the next command.
+    DigitArgument,
+    /// Switches to the vi insert mode.
+    SetmodeViinsert,
+    /// Switches to the vi command mode.
+    SetmodeVicommand,
+    /// Switches to the vi insert mode after moving the cursor forward.
+    ViAppend,
+    /// Switches to the vi insert mode after moving the cursor to the
+    /// beginning of the line.
+    ViInsertBeginning,
+    /// Switches to the vi insert mode after moving the cursor to the end of
+    /// the line.
+    ViAppendToEol,
+    /// Moves the cursor to the next occurrence of the typed character.
+    ViFindChar,
+    /// Moves the cursor to the previous occurrence of the typed character.
+    ViFindCharRev,
+    /// Moves the cursor to just before the next occurrence of the typed
+    /// character.
+    ViTillChar,
+    /// Moves the cursor to just after the previous occurrence of the typed
+    /// character.
+    ViTillCharRev,
+    /// Repeats the last find command.
+    ViRefindChar,
+    /// Repeats the last find command in the opposite direction.
+    ViRefindCharRev,
+    /// Deletes the text up to the position the next motion command moves to.
+    ViDeleteTo,
+    /// Deletes the text up to the position the next motion command moves to
+    /// and switches to the vi insert mode.
+    ViChangeTo,
+    /// Saves the text up to the position the next motion command moves to.
+    ViCopyTo,
+    /// Deletes the text from the cursor to the end of the line.
+    ViDeleteToEol,
+    /// Deletes the text from the cursor to the end of the line and switches
+    /// to the vi insert mode.
+    ViChangeToEol,
+    /// Deletes the whole line and switches to the vi insert mode.
+    ViChangeLine,
+    /// Deletes the character under the cursor and switches to the vi insert
+    /// mode.
+    ViSubstitute,
+    /// Inserts the saved text after the cursor.
+    ViPutAfter,
+    /// Inserts the saved text before the cursor.
+    ViPutBefore,
+    /// Replaces the character under the cursor with the typed character.
+    ViReplaceChar,
+    /// Switches the case of the character under the cursor.
+    ViChangeCase,
+    /// Searches the history backward for a typed pattern.
+    ViSearchBackward,
+    /// Searches the history forward for a typed pattern.
+    ViSearchForward,
+    /// Repeats the last search.
+    SearchAgain,
+    /// Repeats the last search in the opposite direction.
+    SearchAgainRev,
+
+    /// Cancels the last change.
+    Undo,
+    /// Cancels all changes to the line.
+    UndoAll,
+    /// Cancels the last undo.
+    CancelUndo,
+    /// Repeats the last change.
+    Redo,
+}
+
+/// Pairs of command names and commands, sorted by the name
+const COMMANDS: &[(&str, Command)] = &[
+    ("accept-line", Command::AcceptLine),
+    ("alert", Command::Alert),
+    ("backward-bigword", Command::BackwardBigword),
+    ("backward-char", Command::BackwardChar),
+    ("backward-delete-char", Command::BackwardDeleteChar),
+    ("backward-delete-line", Command::BackwardDeleteLine),
+    ("backward-delete-viword", Command::BackwardDeleteViword),
+    ("backward-emacsword", Command::BackwardEmacsword),
+    ("backward-kill-bigword", Command::BackwardKillBigword),
+    ("backward-kill-char", Command::BackwardKillChar),
+    ("backward-kill-emacsword", Command::BackwardKillEmacsword),
+    ("backward-kill-line", Command::BackwardKillLine),
+    ("backward-viword", Command::BackwardViword),
+    ("beginning-of-history", Command::BeginningOfHistory),
+    ("beginning-of-line", Command::BeginningOfLine),
+    ("cancel-undo", Command::CancelUndo),
+    ("capitalize-emacsword", Command::CapitalizeEmacsword),
+    ("clear-and-redraw", Command::ClearAndRedraw),
+    ("complete", Command::Complete),
+    ("delete-char", Command::DeleteChar),
+    ("digit-argument", Command::DigitArgument),
+    ("downcase-emacsword", Command::DowncaseEmacsword),
+    ("emacs-search-backward", Command::EmacsSearchBackward),
+    ("emacs-search-forward", Command::EmacsSearchForward),
+    ("end-of-bigword", Command::EndOfBigword),
+    ("end-of-history", Command::EndOfHistory),
+    ("end-of-line", Command::EndOfLine),
+    ("end-of-viword", Command::EndOfViword),
+    ("eof-if-empty", Command::EofIfEmpty),
+    ("eof-or-delete", Command::EofOrDelete),
+    ("expect-verbatim", Command::ExpectVerbatim),
+    ("first-nonblank", Command::FirstNonblank),
+    ("forward-bigword", Command::ForwardBigword),
+    ("forward-char", Command::ForwardChar),
+    ("forward-emacsword", Command::ForwardEmacsword),
+    ("forward-viword", Command::ForwardViword),
+    ("go-to-column", Command::GoToColumn),
+    ("kill-char", Command::KillChar),
+    ("kill-emacsword", Command::KillEmacsword),
+    ("kill-line", Command::KillLine),
+    ("next-history", Command::NextHistory),
+    ("noop", Command::Noop),
+    ("prev-history", Command::PrevHistory),
+    ("redo", Command::Redo),
+    ("search-again", Command::SearchAgain),
+    ("search-again-rev", Command::SearchAgainRev),
+    ("self-insert", Command::SelfInsert),
+    ("setmode-vicommand", Command::SetmodeVicommand),
+    ("setmode-viinsert", Command::SetmodeViinsert),
+    ("transpose-chars", Command::TransposeChars),
+    ("transpose-words", Command::TransposeWords),
+    ("undo", Command::Undo),
+    ("undo-all", Command::UndoAll),
+    ("upcase-emacsword", Command::UpcaseEmacsword),
+    ("vi-append", Command::ViAppend),
+    ("vi-append-to-eol", Command::ViAppendToEol),
+    ("vi-change-case", Command::ViChangeCase),
+    ("vi-change-line", Command::ViChangeLine),
+    ("vi-change-to", Command::ViChangeTo),
+    ("vi-change-to-eol", Command::ViChangeToEol),
+    ("vi-copy-to", Command::ViCopyTo),
+    ("vi-delete-to", Command::ViDeleteTo),
+    ("vi-delete-to-eol", Command::ViDeleteToEol),
+    ("vi-find-char", Command::ViFindChar),
+    ("vi-find-char-rev", Command::ViFindCharRev),
+    ("vi-insert-beginning", Command::ViInsertBeginning),
+    ("vi-put-after", Command::ViPutAfter),
+    ("vi-put-before", Command::ViPutBefore),
+    ("vi-refind-char", Command::ViRefindChar),
+    ("vi-refind-char-rev", Command::ViRefindCharRev),
+    ("vi-replace-char", Command::ViReplaceChar),
+    ("vi-search-backward", Command::ViSearchBackward),
+    ("vi-search-forward", Command::ViSearchForward),
+    ("vi-substitute", Command::ViSubstitute),
+    ("vi-till-char", Command::ViTillChar),
+    ("vi-till-char-rev", Command::ViTillCharRev),
+    ("yank", Command::Yank),
+    ("yank-pop", Command::YankPop),
+];
+
+impl Command {
+    /// Returns the name of the command.
+    ///
+    /// The name can be converted back to the command with
+    /// [`from_str`](Self::from_str).
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        COMMANDS
+            .iter()
+            .find(|&&(_, command)| command == self)
+            .map(|&(name, _)| name)
+            .unwrap()
+    }
+
+    /// Returns an iterator over all the commands in the order of their names.
+    pub fn iter() -> impl Iterator<Item = Command> {
+        COMMANDS.iter().map(|&(_, command)| command)
+    }
+
+    /// Whether this command moves the cursor without modifying the line.
+    ///
+    /// Motion commands can be used as the operand of the
+    /// [`ViDeleteTo`](Self::ViDeleteTo), [`ViChangeTo`](Self::ViChangeTo), and
+    /// [`ViCopyTo`](Self::ViCopyTo) commands.
+    #[must_use]
+    pub const fn is_motion(self) -> bool {
+        use Command::*;
+        matches!(
+            self,
+            ForwardChar
+                | BackwardChar
+                | BeginningOfLine
+                | EndOfLine
+                | FirstNonblank
+                | GoToColumn
+                | ForwardViword
+                | BackwardViword
+                | EndOfViword
+                | ForwardBigword
+                | BackwardBigword
+                | EndOfBigword
+                | ForwardEmacsword
+                | BackwardEmacsword
+                | ViFindChar
+                | ViFindCharRev
+                | ViTillChar
+                | ViTillCharRev
+                | ViRefindChar
+                | ViRefindCharRev
+        )
+    }
+}
+
+/// Prints the command name.
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/// Error returned when parsing an unknown command name
+#[derive(Clone, Copy, Debug, Eq, Error, Hash, PartialEq)]
+#[error("no such editing command")]
+pub struct NoSuchCommand;
+
+/// Parses a command name.
+impl FromStr for Command {
+    type Err = NoSuchCommand;
+    fn from_str(name: &str) -> Result<Self, NoSuchCommand> {
+        COMMANDS
+            .binary_search_by_key(&name, |&(name, _)| name)
+            .map(|index| COMMANDS[index].1)
+            .map_err(|_| NoSuchCommand)
+    }
+}
+
+/// Editing mode
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum Mode {
+    /// Vi insert mode
+    ViInsert,
+    /// Vi command mode
+    ViCommand,
+    /// Emacs mode
+    Emacs,
+}
+
+/// Result of looking up a key sequence in a [`KeyMap`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Lookup {
+    /// The sequence is bound to the command.
+    ///
+    /// The Boolean is true if the sequence is also a prefix of another bound
+    /// sequence.
+    Command(Command, bool),
+    /// The sequence is not bound but is a prefix of a bound sequence.
+    Prefix,
+    /// The sequence is not bound, so the default command applies.
+    Default(Command),
+}
+
+/// Mapping from key sequences to commands
+///
+/// A key sequence is a string of characters the terminal sends when the user
+/// types a key. Special keys like arrow keys usually send an escape sequence
+/// of more than one character.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMap {
+    bindings: BTreeMap<String, Command>,
+    default: Command,
+}
+
+/// Escape sequences sent by special keys
+///
+/// Each special key may send different sequences depending on the terminal
+/// and its keypad mode, so all of them are bound.
+const ESCAPE_SEQUENCES: &[(&str, SpecialKey)] = &[
+    ("\x1B[A", SpecialKey::Up),
+    ("\x1BOA", SpecialKey::Up),
+    ("\x1B[B", SpecialKey::Down),
+    ("\x1BOB", SpecialKey::Down),
+    ("\x1B[C", SpecialKey::Right),
+    ("\x1BOC", SpecialKey::Right),
+    ("\x1B[D", SpecialKey::Left),
+    ("\x1BOD", SpecialKey::Left),
+    ("\x1B[H", SpecialKey::Home),
+    ("\x1BOH", SpecialKey::Home),
+    ("\x1B[1~", SpecialKey::Home),
+    ("\x1B[7~", SpecialKey::Home),
+    ("\x1B[F", SpecialKey::End),
+    ("\x1BOF", SpecialKey::End),
+    ("\x1B[4~", SpecialKey::End),
+    ("\x1B[8~", SpecialKey::End),
+    ("\x1B[3~", SpecialKey::Delete),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SpecialKey {
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    Delete,
+}
+
+impl SpecialKey {
+    fn command(self) -> Command {
+        match self {
+            SpecialKey::Up => Command::PrevHistory,
+            SpecialKey::Down => Command::NextHistory,
+            SpecialKey::Right => Command::ForwardChar,
+            SpecialKey::Left => Command::BackwardChar,
+            SpecialKey::Home => Command::BeginningOfLine,
+            SpecialKey::End => Command::EndOfLine,
+            SpecialKey::Delete => Command::DeleteChar,
+        }
+    }
+}
+
+impl KeyMap {
+    /// Creates a key map with no bindings.
+    ///
+    /// Any key sequence is mapped to the `default` command.
+    #[must_use]
+    pub fn new(default: Command) -> Self {
+        let bindings = BTreeMap::new();
+        Self { bindings, default }
+    }
+
+    /// Creates the default key map for the mode.
+    #[must_use]
+    pub fn default_for(mode: Mode) -> Self {
+        match mode {
+            Mode::ViInsert => Self::default_vi_insert(),
+            Mode::ViCommand => Self::default_vi_command(),
+            Mode::Emacs => Self::default_emacs(),
+        }
+    }
+
+    fn default_vi_insert() -> Self {
+        use Command::*;
+        let mut map = Self::new(SelfInsert);
+        map.bind_special_keys();
+        for (keys, command) in [
+            ("\n", AcceptLine),
+            ("\r", AcceptLine),
+            ("\x1B", SetmodeVicommand),
+            ("\x08", BackwardDeleteChar),
+            ("\x7F", BackwardDeleteChar),
+            ("\x17", BackwardDeleteViword),
+            ("\x15", BackwardDeleteLine),
+            ("\x04", EofIfEmpty),
+            ("\x16", ExpectVerbatim),
+            ("\x0C", ClearAndRedraw),
+            ("\t", Complete),
+        ] {
+            map.bind(keys, command);
+        }
+        map
+    }
+
+    fn default_vi_command() -> Self {
+        use Command::*;
+        let mut map = Self::new(Alert);
+        map.bind_special_keys();
+        for (keys, command) in [
+            ("\n", AcceptLine),
+            ("\r", AcceptLine),
+            ("\x1B", Noop),
+            ("\x04", EofIfEmpty),
+            ("\x0C", ClearAndRedraw),
+            ("\x12", CancelUndo),
+            ("\x08", BackwardChar),
+            ("\x7F", BackwardChar),
+            ("h", BackwardChar),
+            ("l", ForwardChar),
+            (" ", ForwardChar),
+            ("0", DigitArgument),
+            ("1", DigitArgument),
+            ("2", DigitArgument),
+            ("3", DigitArgument),
+            ("4", DigitArgument),
+            ("5", DigitArgument),
+            ("6", DigitArgument),
+            ("7", DigitArgument),
+            ("8", DigitArgument),
+            ("9", DigitArgument),
+            ("^", FirstNonblank),
+            ("$", EndOfLine),
+            ("|", GoToColumn),
+            ("w", ForwardViword),
+            ("W", ForwardBigword),
+            ("b", BackwardViword),
+            ("B", BackwardBigword),
+            ("e", EndOfViword),
+            ("E", EndOfBigword),
+            ("f", ViFindChar),
+            ("F", ViFindCharRev),
+            ("t", ViTillChar),
+            ("T", ViTillCharRev),
+            (";", ViRefindChar),
+            (",", ViRefindCharRev),
+            ("i", SetmodeViinsert),
+            ("a", ViAppend),
+            ("I", ViInsertBeginning),
+            ("A", ViAppendToEol),
+            ("x", KillChar),
+            ("X", BackwardKillChar),
+            ("d", ViDeleteTo),
+            ("c", ViChangeTo),
+            ("y", ViCopyTo),
+            ("D", ViDeleteToEol),
+            ("C", ViChangeToEol),
+            ("S", ViChangeLine),
+            ("s", ViSubstitute),
+            ("p", ViPutAfter),
+            ("P", ViPutBefore),
+            ("r", ViReplaceChar),
+            ("~", ViChangeCase),
+            ("u", Undo),
+            ("U", UndoAll),
+            (".", Redo),
+            ("k", PrevHistory),
+            ("-", PrevHistory),
+            ("j", NextHistory),
+            ("+", NextHistory),
+            ("/", ViSearchBackward),
+            ("?", ViSearchForward),
+            ("n", SearchAgain),
+            ("N", SearchAgainRev),
+        ] {
+            map.bind(keys, command);
+        }
+        map
+    }
+
+    fn default_emacs() -> Self {
+        use Command::*;
+        let mut map = Self::new(SelfInsert);
+        map.bind_special_keys();
+        for (keys, command) in [
+            ("\n", AcceptLine),
+            ("\r", AcceptLine),
+            ("\x1B", Noop),
+            ("\x07", Alert),
+            ("\x04", EofOrDelete),
+            ("\x0C", ClearAndRedraw),
+            ("\x16", ExpectVerbatim),
+            ("\x11", ExpectVerbatim),
+            ("\x06", ForwardChar),
+            ("\x02", BackwardChar),
+            ("\x01", BeginningOfLine),
+            ("\x05", EndOfLine),
+            ("\x1Bf", ForwardEmacsword),
+            ("\x1Bb", BackwardEmacsword),
+            ("\x08", BackwardDeleteChar),
+            ("\x7F", BackwardDeleteChar),
+            ("\x0B", KillLine),
+            ("\x15", BackwardKillLine),
+            ("\x1Bd", KillEmacsword),
+            ("\x1B\x08", BackwardKillEmacsword),
+            ("\x1B\x7F", BackwardKillEmacsword),
+            ("\x17", BackwardKillBigword),
+            ("\x19", Yank),
+            ("\x1By", YankPop),
+            ("\x14", TransposeChars),
+            ("\x1Bt", TransposeWords),
+            ("\x1Bu", UpcaseEmacsword),
+            ("\x1Bl", DowncaseEmacsword),
+            ("\x1Bc", CapitalizeEmacsword),
+            ("\x1F", Undo),
+            ("\x18\x15", Undo),
+            ("\x1Br", UndoAll),
+            ("\x10", PrevHistory),
+            ("\x0E", NextHistory),
+            ("\x1B<", BeginningOfHistory),
+            ("\x1B>", EndOfHistory),
+            ("\x12", EmacsSearchBackward),
+            ("\x13", EmacsSearchForward),
+            ("\t", Complete),
+        ] {
+            map.bind(keys, command);
+        }
+        map
+    }
+
+    fn bind_special_keys(&mut self) {
+        for &(keys, key) in ESCAPE_SEQUENCES {
+            self.bind(keys, key.command());
+        }
+    }
+
+    /// Binds a key sequence to a command.
+    ///
+    /// Returns the command previously bound to the sequence, if any.
+    pub fn bind<S: Into<String>>(&mut self, keys: S, command: Command) -> Option<Command> {
+        self.bindings.insert(keys.into(), command)
+    }
+
+    /// Removes the binding for a key sequence.
+    ///
+    /// Returns the command that was bound to the sequence, if any.
+    pub fn unbind(&mut self, keys: &str) -> Option<Command> {
+        self.bindings.remove(keys)
+    }
+
+    /// Returns an iterator over the bindings, sorted by the key sequence.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Command)> {
+        self.bindings
+            .iter()
+            .map(|(keys, &command)| (keys.as_str(), command))
+    }
+
+    /// Returns the command applied to unbound key sequences.
+    #[must_use]
+    pub fn default_command(&self) -> Command {
+        self.default
+    }
+
+    /// Looks up a key sequence.
+    #[must_use]
+    pub fn lookup(&self, keys: &str) -> Lookup {
+        let mut following = self
+            .bindings
+            .range::<str, _>((Bound::Included(keys), Bound::Unbounded));
+        let exact = match following.next() {
+            Some((k, &command)) if k == keys => Some(command),
+            Some((k, _)) if k.starts_with(keys) => return Lookup::Prefix,
+            _ => None,
+        };
+        match exact {
+            Some(command) => {
+                let is_prefix = following.next().is_some_and(|(k, _)| k.starts_with(keys));
+                Lookup::Command(command, is_prefix)
+            }
+            None => Lookup::Default(self.default),
+        }
+    }
+}
+
+/// Key maps for all the editing modes
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyBindings {
+    vi_insert: KeyMap,
+    vi_command: KeyMap,
+    emacs: KeyMap,
+}
+
+/// Creates the default key maps for all the modes.
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            vi_insert: KeyMap::default_for(Mode::ViInsert),
+            vi_command: KeyMap::default_for(Mode::ViCommand),
+            emacs: KeyMap::default_for(Mode::Emacs),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Returns the key map for the mode.
+    #[must_use]
+    pub fn get(&self, mode: Mode) -> &KeyMap {
+        match mode {
+            Mode::ViInsert => &self.vi_insert,
+            Mode::ViCommand => &self.vi_command,
+            Mode::Emacs => &self.emacs,
+        }
+    }
+
+    /// Returns the key map for the mode.
+    pub fn get_mut(&mut self, mode: Mode) -> &mut KeyMap {
+        match mode {
+            Mode::ViInsert => &mut self.vi_insert,
+            Mode::ViCommand => &mut self.vi_command,
+            Mode::Emacs => &mut self.emacs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_names_are_sorted() {
+        assert!(COMMANDS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn command_name_round_trip() {
+        for command in Command::iter() {
+            assert_eq!(command.name().parse(), Ok(command));
+        }
+        assert_eq!("no-such-command".parse::<Command>(), Err(NoSuchCommand));
+    }
+
+    #[test]
+    fn lookup_exact_and_prefix() {
+        let map = KeyMap::default_for(Mode::ViInsert);
+        assert_eq!(
+            map.lookup("\n"),
+            Lookup::Command(Command::AcceptLine, false)
+        );
+        assert_eq!(
+            map.lookup("\x1B"),
+            Lookup::Command(Command::SetmodeVicommand, true)
+        );
+        assert_eq!(map.lookup("\x1B["), Lookup::Prefix);
+        assert_eq!(
+            map.lookup("\x1B[A"),
+            Lookup::Command(Command::PrevHistory, false)
+        );
+        assert_eq!(map.lookup("a"), Lookup::Default(Command::SelfInsert));
+        assert_eq!(map.lookup("\x1B[Z"), Lookup::Default(Command::SelfInsert));
+    }
+
+    #[test]
+    fn emacs_meta_sequences() {
+        let map = KeyMap::default_for(Mode::Emacs);
+        assert_eq!(map.lookup("\x1B"), Lookup::Command(Command::Noop, true));
+        assert_eq!(
+            map.lookup("\x1Bf"),
+            Lookup::Command(Command::ForwardEmacsword, false)
+        );
+        assert_eq!(map.lookup("\x18"), Lookup::Prefix);
+        assert_eq!(
+            map.lookup("\x18\x15"),
+            Lookup::Command(Command::Undo, false)
+        );
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let mut map = KeyMap::new(Command::Alert);
+        assert_eq!(map.bind("q", Command::AcceptLine), None);
+        assert_eq!(map.bind("q", Command::Undo), Some(Command::AcceptLine));
+        assert_eq!(map.lookup("q"), Lookup::Command(Command::Undo, false));
+        assert_eq!(map.unbind("q"), Some(Command::Undo));
+        assert_eq!(map.lookup("q"), Lookup::Default(Command::Alert));
+    }
+}
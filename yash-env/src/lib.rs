@@ -33,11 +33,15 @@
 
 use self::builtin::getopts::GetoptsState;
 use self::builtin::Builtin;
+use self::completion::Completion;
 use self::function::FunctionSet;
+use self::history::History;
 use self::io::Fd;
 use self::job::JobList;
 use self::job::Pid;
 use self::job::ProcessState;
+use self::job::ProcessSubst;
+use self::keymap::KeyBindings;
 use self::option::On;
 use self::option::OptionSet;
 use self::option::{AllExport, ErrExit, Monitor};
@@ -94,6 +98,9 @@ pub struct Env {
     /// Built-in utilities available in the environment
     pub builtins: HashMap<&'static str, Builtin>,
 
+    /// Command line completion settings and state
+    pub completion: Completion,
+
     /// Exit status of the last executed command
     pub exit_status: ExitStatus,
 
@@ -103,9 +110,15 @@ pub struct Env {
     /// State of the previous invocation of the `getopts` built-in
     pub getopts_state: Option<GetoptsState>,
 
+    /// Command history
+    pub history: History,
+
     /// Jobs managed in the environment
     pub jobs: JobList,
 
+    /// Key bindings for the line editor
+    pub key_bindings: KeyBindings,
+
     /// Process group ID of the main shell process
     pub main_pgid: Pid,
 
@@ -117,6 +130,13 @@ pub struct Env {
     /// Shell option settings
     pub options: OptionSet,
 
+    /// Process substitutions started by the commands being executed
+    ///
+    /// When a command performs a process substitution, the child process is
+    /// appended to this list. When the command finishes, the shell closes the
+    /// pipe ends and waits for the child processes started during the command.
+    pub process_substs: Vec<ProcessSubst>,
+
     /// Runtime execution context stack
     pub stack: Stack,
 
@@ -148,13 +168,17 @@ impl Env {
             aliases: Default::default(),
             arg0: Default::default(),
             builtins: Default::default(),
+            completion: Default::default(),
             exit_status: Default::default(),
             functions: Default::default(),
             getopts_state: Default::default(),
+            history: Default::default(),
             jobs: Default::default(),
+            key_bindings: Default::default(),
             main_pgid: system.getpgrp(),
             main_pid: system.getpid(),
             options: Default::default(),
+            process_substs: Default::default(),
             stack: Default::default(),
             traps: Default::default(),
             tty: Default::default(),
@@ -180,13 +204,17 @@ impl Env {
             aliases: self.aliases.clone(),
             arg0: self.arg0.clone(),
             builtins: self.builtins.clone(),
+            completion: self.completion.clone(),
             exit_status: self.exit_status,
             functions: self.functions.clone(),
             getopts_state: self.getopts_state.clone(),
+            history: self.history.clone(),
             jobs: self.jobs.clone(),
+            key_bindings: self.key_bindings.clone(),
             main_pgid: self.main_pgid,
             main_pid: self.main_pid,
             options: self.options,
+            process_substs: self.process_substs.clone(),
             stack: self.stack.clone(),
             traps: self.traps.clone(),
             tty: self.tty,
@@ -379,6 +407,61 @@ impl Env {
         }
     }
 
+    /// Reports jobs that have stopped or finished.
+    ///
+    /// This function [updates all subshell
+    /// statuses](Self::update_all_subshell_statuses) and prints a
+    /// [report](job::fmt::Report) to the standard error for each job whose
+    /// `state_changed` flag is set and that is no longer running. Reported jobs
+    /// that have finished are removed from `self.jobs`, and the others are
+    /// [marked as reported](job::JobRefMut::state_reported). As an exception,
+    /// a finished job whose process ID is the [last asynchronous process
+    /// ID](JobList::last_async_pid) is kept so that the `wait` built-in can
+    /// still obtain its exit status.
+    ///
+    /// An interactive shell calls this function before printing a prompt so
+    /// that the user is notified of background jobs.
+    pub async fn report_job_status_changes(&mut self) {
+        use self::job::fmt::{Marker, Report};
+
+        self.update_all_subshell_statuses();
+
+        let current_job = self.jobs.current_job();
+        let previous_job = self.jobs.previous_job();
+        let mut message = String::new();
+        let mut indices = Vec::new();
+        for (index, job) in &self.jobs {
+            if !job.state_changed || job.state == ProcessState::Running {
+                continue;
+            }
+            let marker = if current_job == Some(index) {
+                Marker::CurrentJob
+            } else if previous_job == Some(index) {
+                Marker::PreviousJob
+            } else {
+                Marker::None
+            };
+            let report = Report { index, marker, job };
+            message.push_str(&format!("{report}\n"));
+            indices.push(index);
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        self.system.print_error(&message).await;
+
+        for index in indices {
+            let last_async_pid = self.jobs.last_async_pid();
+            let mut job = self.jobs.get_mut(index).unwrap();
+            if job.state.is_alive() || job.pid == last_async_pid {
+                job.state_reported();
+            } else {
+                self.jobs.remove(index);
+            }
+        }
+    }
+
     /// Get an existing variable or create a new one.
     ///
     /// This method is a thin wrapper around [`VariableSet::get_or_new`].
@@ -434,10 +517,13 @@ impl Env {
 }
 
 pub mod builtin;
+pub mod completion;
 pub mod function;
+pub mod history;
 pub mod input;
 pub mod io;
 pub mod job;
+pub mod keymap;
 pub mod option;
 pub mod pwd;
 pub mod semantics;
@@ -453,6 +539,7 @@ mod tests {
     use crate::io::MIN_INTERNAL_FD;
     use crate::job::Job;
     use crate::subshell::Subshell;
+    use crate::system::r#virtual::FileBody;
     use crate::system::r#virtual::INode;
     use crate::system::r#virtual::SystemState;
     use crate::system::Errno;
@@ -489,6 +576,71 @@ mod tests {
         }
     }
 
+    fn stderr(state: &RefCell<SystemState>) -> String {
+        let state = state.borrow();
+        let stderr = state.file_system.get("/dev/stderr").unwrap();
+        let stderr = stderr.borrow();
+        match &stderr.body {
+            FileBody::Regular { content, .. } => String::from_utf8(content.clone()).unwrap(),
+            body => panic!("unexpected file body: {body:?}"),
+        }
+    }
+
+    #[test]
+    fn report_job_status_changes_reports_stopped_and_finished_jobs() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+
+        let mut job = Job::new(Pid(10));
+        job.name = "running".to_string();
+        let i10 = env.jobs.add(job);
+        let mut job = Job::new(Pid(11));
+        job.name = "stopped".to_string();
+        job.state = ProcessState::Stopped(Signal::SIGTSTP);
+        let i11 = env.jobs.add(job);
+        let mut job = Job::new(Pid(12));
+        job.name = "done".to_string();
+        job.state = ProcessState::Exited(ExitStatus(3));
+        let i12 = env.jobs.add(job);
+        let mut job = Job::new(Pid(13));
+        job.name = "reported".to_string();
+        job.state = ProcessState::Stopped(Signal::SIGSTOP);
+        job.state_changed = false;
+        let i13 = env.jobs.add(job);
+        env.jobs.set_current_job(i11).unwrap();
+
+        env.report_job_status_changes().now_or_never().unwrap();
+        assert_eq!(
+            stderr(&state),
+            "[2] + Stopped(SIGTSTP)     stopped\n[3]   Done(3)              done\n"
+        );
+        assert!(env.jobs[i10].state_changed);
+        assert!(!env.jobs[i11].state_changed);
+        assert_eq!(env.jobs.get(i12), None);
+        assert!(!env.jobs[i13].state_changed);
+
+        // Reported jobs are not reported again.
+        env.report_job_status_changes().now_or_never().unwrap();
+        assert_eq!(
+            stderr(&state),
+            "[2] + Stopped(SIGTSTP)     stopped\n[3]   Done(3)              done\n"
+        );
+    }
+
+    #[test]
+    fn report_job_status_changes_keeps_last_async_job() {
+        let mut env = Env::new_virtual();
+        let mut job = Job::new(Pid(10));
+        job.state = ProcessState::Exited(ExitStatus(5));
+        let index = env.jobs.add(job);
+        env.jobs.set_last_async_pid(Pid(10));
+
+        env.report_job_status_changes().now_or_never().unwrap();
+        assert_eq!(env.jobs[index].state, ProcessState::Exited(ExitStatus(5)));
+        assert!(!env.jobs[index].state_changed);
+    }
+
     #[test]
     fn wait_for_signal_remembers_signal_in_trap_set() {
         in_virtual_system(|mut env, state| async move {
@@ -101,15 +101,29 @@ impl From<State> for bool {
 pub enum Option {
     /// Makes all variables exported when they are assigned.
     AllExport,
+    /// Enables brace expansion.
+    BraceExpand,
+    /// Makes pathname expansion case-sensitive.
+    CaseGlob,
     /// Allows overwriting and truncating an existing file with the `>`
     /// redirection.
     Clobber,
     /// Executes a command string specified as a command line argument.
     CmdLine,
+    /// Makes pathname expansion match filenames starting with a period.
+    DotGlob,
+    /// Enables emacs-like command line editing.
+    Emacs,
     /// Makes the shell to exit when a command returns a non-zero exit status.
     ErrExit,
     /// Makes the shell to actually run commands.
     Exec,
+    /// Enables extended pattern matching features.
+    ///
+    /// This option enables the ksh-style extended patterns such as
+    /// `@(foo|bar)` in pattern matching and recursive directory scanning with
+    /// `**` in pathname expansion.
+    ExtendedGlob,
     /// Enables pathname expansion.
     Glob,
     /// Performs command search for each command in a function on its
@@ -125,10 +139,14 @@ pub enum Option {
     Log,
     /// Sources the profile file on startup.
     Login,
+    /// Appends a slash to directory names produced by pathname expansion.
+    MarkDirs,
     /// Enables job control.
     Monitor,
     /// Automatically reports the results of asynchronous jobs.
     Notify,
+    /// Makes pathname expansion expand to nothing if no pathname matches.
+    NullGlob,
     /// Disables most non-POSIX extensions.
     PosixlyCorrect,
     /// Reads commands from the standard input.
@@ -165,18 +183,25 @@ impl Option {
     pub const fn short_name(self) -> std::option::Option<(char, State)> {
         match self {
             AllExport => Some(('a', On)),
+            BraceExpand => None,
+            CaseGlob => None,
             Clobber => Some(('C', Off)),
             CmdLine => Some(('c', On)),
+            DotGlob => None,
+            Emacs => None,
             ErrExit => Some(('e', On)),
             Exec => Some(('n', Off)),
+            ExtendedGlob => None,
             Glob => Some(('f', Off)),
             HashOnDefinition => Some(('h', On)),
             IgnoreEof => None,
             Interactive => Some(('i', On)),
             Log => None,
             Login => Some(('l', On)),
+            MarkDirs => None,
             Monitor => Some(('m', On)),
             Notify => Some(('b', On)),
+            NullGlob => None,
             PosixlyCorrect => None,
             Stdin => Some(('s', On)),
             Unset => Some(('u', Off)),
@@ -194,18 +219,25 @@ impl Option {
     pub const fn long_name(self) -> &'static str {
         match self {
             AllExport => "allexport",
+            BraceExpand => "braceexpand",
+            CaseGlob => "caseglob",
             Clobber => "clobber",
             CmdLine => "cmdline",
+            DotGlob => "dotglob",
+            Emacs => "emacs",
             ErrExit => "errexit",
             Exec => "exec",
+            ExtendedGlob => "extendedglob",
             Glob => "glob",
             HashOnDefinition => "hashondefinition",
             IgnoreEof => "ignoreeof",
             Interactive => "interactive",
             Log => "log",
             Login => "login",
+            MarkDirs => "markdirs",
             Monitor => "monitor",
             Notify => "notify",
+            NullGlob => "nullglob",
             PosixlyCorrect => "posixlycorrect",
             Stdin => "stdin",
             Unset => "unset",
@@ -258,18 +290,25 @@ impl FromStr for Option {
     fn from_str(name: &str) -> Result<Self, FromStrError> {
         const OPTIONS: &[(&str, Option)] = &[
             ("allexport", AllExport),
+            ("braceexpand", BraceExpand),
+            ("caseglob", CaseGlob),
             ("clobber", Clobber),
             ("cmdline", CmdLine),
+            ("dotglob", DotGlob),
+            ("emacs", Emacs),
             ("errexit", ErrExit),
             ("exec", Exec),
+            ("extendedglob", ExtendedGlob),
             ("glob", Glob),
             ("hashondefinition", HashOnDefinition),
             ("ignoreeof", IgnoreEof),
             ("interactive", Interactive),
             ("log", Log),
             ("login", Login),
+            ("markdirs", MarkDirs),
             ("monitor", Monitor),
             ("notify", Notify),
+            ("nullglob", NullGlob),
             ("posixlycorrect", PosixlyCorrect),
             ("stdin", Stdin),
             ("unset", Unset),
@@ -452,10 +491,10 @@ pub struct OptionSet {
 /// Defines the default option set.
 ///
 /// Note that the default set is not empty. The following options are enabled by
-/// default: `Clobber`, `Exec`, `Glob`, `Log`, `Unset`
+/// default: `CaseGlob`, `Clobber`, `Exec`, `Glob`, `Log`, `Unset`
 impl Default for OptionSet {
     fn default() -> Self {
-        let enabled_options = Clobber | Exec | Glob | Log | Unset;
+        let enabled_options = CaseGlob | Clobber | Exec | Glob | Log | Unset;
         OptionSet { enabled_options }
     }
 }
@@ -485,10 +524,18 @@ impl OptionSet {
     /// Some options should not be changed after the shell startup, but that
     /// does not affect the behavior of this function.
     ///
-    /// TODO: What if an option that is mutually exclusive with another is set?
+    /// The `Emacs` and `Vi` options are mutually exclusive. Enabling one of
+    /// them disables the other.
     pub fn set(&mut self, option: Option, state: State) {
         match state {
-            On => self.enabled_options.insert(option),
+            On => {
+                match option {
+                    Emacs => self.enabled_options.remove(Vi),
+                    Vi => self.enabled_options.remove(Emacs),
+                    _ => false,
+                };
+                self.enabled_options.insert(option)
+            }
             Off => self.enabled_options.remove(option),
         };
     }
@@ -530,7 +577,7 @@ mod tests {
     fn from_str_unambiguous_abbreviation() {
         assert_eq!(Option::from_str("allexpor"), Ok(AllExport));
         assert_eq!(Option::from_str("a"), Ok(AllExport));
-        assert_eq!(Option::from_str("n"), Ok(Notify));
+        assert_eq!(Option::from_str("no"), Ok(Notify));
     }
 
     #[test]
@@ -567,7 +614,7 @@ mod tests {
     fn parse_unambiguous_abbreviation() {
         assert_eq!(parse_long("allexpor"), Ok((AllExport, On)));
         assert_eq!(parse_long("not"), Ok((Notify, On)));
-        assert_eq!(parse_long("non"), Ok((Notify, Off)));
+        assert_eq!(parse_long("nono"), Ok((Notify, Off)));
         assert_eq!(parse_long("un"), Ok((Unset, On)));
         assert_eq!(parse_long("noun"), Ok((Unset, Off)));
     }
@@ -591,6 +638,20 @@ mod tests {
         assert_eq!(parse_long("LOG"), Err(NoSuchOption));
     }
 
+    #[test]
+    fn emacs_and_vi_are_exclusive() {
+        let mut options = OptionSet::default();
+        options.set(Vi, On);
+        options.set(Emacs, On);
+        assert_eq!(options.get(Vi), Off);
+        assert_eq!(options.get(Emacs), On);
+        options.set(Vi, On);
+        assert_eq!(options.get(Vi), On);
+        assert_eq!(options.get(Emacs), Off);
+        options.set(Emacs, Off);
+        assert_eq!(options.get(Vi), On);
+    }
+
     #[test]
     fn test_canonicalize() {
         assert_eq!(canonicalize(""), "");
@@ -46,6 +46,8 @@ pub use nix::fcntl::AtFlags;
 #[doc(no_inline)]
 pub use nix::fcntl::FdFlag;
 #[doc(no_inline)]
+pub use nix::fcntl::FlockArg;
+#[doc(no_inline)]
 pub use nix::fcntl::OFlag;
 #[doc(no_inline)]
 pub use nix::sys::signal::SigSet;
@@ -54,7 +56,13 @@ pub use nix::sys::signal::SigmaskHow;
 #[doc(no_inline)]
 pub use nix::sys::stat::{FileStat, Mode, SFlag};
 #[doc(no_inline)]
+pub use nix::sys::termios::SetArg;
+#[doc(no_inline)]
+pub use nix::sys::termios::Termios;
+#[doc(no_inline)]
 pub use nix::sys::time::TimeSpec;
+#[doc(no_inline)]
+pub use nix::unistd::Uid;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::cmp::Reverse;
@@ -135,6 +143,11 @@ pub trait System: Debug {
     /// `open` function.
     fn open_tmpfile(&mut self, parent_dir: &Path) -> nix::Result<Fd>;
 
+    /// Removes a directory entry.
+    ///
+    /// This is a thin wrapper around the `unlink` system call.
+    fn unlink(&mut self, path: &CStr) -> nix::Result<()>;
+
     /// Closes a file descriptor.
     ///
     /// This is a thin wrapper around the `close` system call.
@@ -162,6 +175,11 @@ pub trait System: Debug {
     /// This is a thin wrapper around the `fcntl` system call.
     fn fcntl_setfd(&mut self, fd: Fd, flags: FdFlag) -> nix::Result<()>;
 
+    /// Applies or removes an advisory lock on an open file.
+    ///
+    /// This is a thin wrapper around the `flock` system call.
+    fn flock(&mut self, fd: Fd, arg: FlockArg) -> nix::Result<()>;
+
     /// Tests if a file descriptor is associated with a terminal device.
     fn isatty(&self, fd: Fd) -> nix::Result<bool>;
 
@@ -347,6 +365,22 @@ pub trait System: Debug {
     /// This is a thin wrapper around the `tcsetpgrp` system call.
     fn tcsetpgrp(&mut self, fd: Fd, pgid: Pid) -> nix::Result<()>;
 
+    /// Returns the attributes of the terminal.
+    ///
+    /// This is a thin wrapper around the `tcgetattr` system call.
+    fn tcgetattr(&self, fd: Fd) -> nix::Result<Termios>;
+
+    /// Modifies the attributes of the terminal.
+    ///
+    /// This is a thin wrapper around the `tcsetattr` system call.
+    fn tcsetattr(&mut self, fd: Fd, action: SetArg, termios: &Termios) -> nix::Result<()>;
+
+    /// Returns the size of the terminal window.
+    ///
+    /// This is a thin wrapper around the `ioctl` system call with the
+    /// `TIOCGWINSZ` request.
+    fn tcgetwinsize(&self, fd: Fd) -> nix::Result<WindowSize>;
+
     /// Creates a new child process.
     ///
     /// This is a thin wrapper around the `fork` system call. Users of `Env`
@@ -400,6 +434,19 @@ pub trait System: Debug {
     /// Returns `Ok(None)` if the user is not found.
     fn getpwnam_dir(&self, name: &str) -> nix::Result<Option<PathBuf>>;
 
+    /// Returns the effective user ID of the current process.
+    fn geteuid(&self) -> Uid;
+
+    /// Returns the login name of the user with the given user ID.
+    ///
+    /// Returns `Ok(None)` if the user is not found.
+    fn getpwuid_name(&self, uid: Uid) -> nix::Result<Option<String>>;
+
+    /// Returns the name of the host.
+    ///
+    /// This is a thin wrapper around the `gethostname` system call.
+    fn gethostname(&self) -> nix::Result<OsString>;
+
     /// Returns the standard `$PATH` value where all standard utilities are
     /// expected to be found.
     ///
@@ -452,6 +499,17 @@ pub struct Times {
     pub children_system: f64,
 }
 
+/// Size of a terminal window
+///
+/// This structure is returned by [`System::tcgetwinsize`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WindowSize {
+    /// Number of rows (lines)
+    pub rows: u16,
+    /// Number of columns (characters per line)
+    pub columns: u16,
+}
+
 /// How to handle a signal.
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum SignalHandling {
@@ -902,6 +960,9 @@ impl System for SharedSystem {
     fn open_tmpfile(&mut self, parent_dir: &Path) -> nix::Result<Fd> {
         self.0.borrow_mut().open_tmpfile(parent_dir)
     }
+    fn unlink(&mut self, path: &CStr) -> nix::Result<()> {
+        self.0.borrow_mut().unlink(path)
+    }
     fn close(&mut self, fd: Fd) -> nix::Result<()> {
         self.0.borrow_mut().close(fd)
     }
@@ -917,6 +978,9 @@ impl System for SharedSystem {
     fn fcntl_setfd(&mut self, fd: Fd, flags: FdFlag) -> nix::Result<()> {
         self.0.borrow_mut().fcntl_setfd(fd, flags)
     }
+    fn flock(&mut self, fd: Fd, arg: FlockArg) -> nix::Result<()> {
+        self.0.borrow_mut().flock(fd, arg)
+    }
     fn isatty(&self, fd: Fd) -> nix::Result<bool> {
         self.0.borrow().isatty(fd)
     }
@@ -992,6 +1056,15 @@ impl System for SharedSystem {
     fn tcsetpgrp(&mut self, fd: Fd, pgid: Pid) -> nix::Result<()> {
         self.0.borrow_mut().tcsetpgrp(fd, pgid)
     }
+    fn tcgetattr(&self, fd: Fd) -> nix::Result<Termios> {
+        self.0.borrow().tcgetattr(fd)
+    }
+    fn tcsetattr(&mut self, fd: Fd, action: SetArg, termios: &Termios) -> nix::Result<()> {
+        self.0.borrow_mut().tcsetattr(fd, action, termios)
+    }
+    fn tcgetwinsize(&self, fd: Fd) -> nix::Result<WindowSize> {
+        self.0.borrow().tcgetwinsize(fd)
+    }
     fn new_child_process(&mut self) -> nix::Result<ChildProcessStarter> {
         self.0.borrow_mut().new_child_process()
     }
@@ -1015,6 +1088,15 @@ impl System for SharedSystem {
     fn getpwnam_dir(&self, name: &str) -> nix::Result<Option<PathBuf>> {
         self.0.borrow().getpwnam_dir(name)
     }
+    fn geteuid(&self) -> Uid {
+        self.0.borrow().geteuid()
+    }
+    fn getpwuid_name(&self, uid: Uid) -> nix::Result<Option<String>> {
+        self.0.borrow().getpwuid_name(uid)
+    }
+    fn gethostname(&self) -> nix::Result<OsString> {
+        self.0.borrow().gethostname()
+    }
     fn confstr_path(&self) -> nix::Result<OsString> {
         self.0.borrow().confstr_path()
     }
@@ -28,14 +28,19 @@ use super::Errno;
 use super::FdFlag;
 use super::FdSet;
 use super::FileStat;
+use super::FlockArg;
 use super::Mode;
 use super::OFlag;
+use super::SetArg;
 use super::SigSet;
 use super::SigmaskHow;
 use super::Signal;
 use super::System;
+use super::Termios;
 use super::TimeSpec;
 use super::Times;
+use super::Uid;
+use super::WindowSize;
 use crate::io::Fd;
 use crate::job::Pid;
 use crate::job::ProcessState;
@@ -59,6 +64,7 @@ use std::future::Future;
 use std::io::SeekFrom;
 use std::mem::MaybeUninit;
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::BorrowedFd;
 use std::os::unix::io::IntoRawFd;
 use std::path::Path;
 use std::pin::Pin;
@@ -219,6 +225,10 @@ impl System for RealSystem {
         }
     }
 
+    fn unlink(&mut self, path: &CStr) -> nix::Result<()> {
+        nix::unistd::unlink(path)
+    }
+
     fn close(&mut self, fd: Fd) -> nix::Result<()> {
         loop {
             match nix::unistd::close(fd.0) {
@@ -245,6 +255,15 @@ impl System for RealSystem {
         nix::fcntl::fcntl(fd.0, nix::fcntl::FcntlArg::F_SETFD(flags)).map(drop)
     }
 
+    fn flock(&mut self, fd: Fd, arg: FlockArg) -> nix::Result<()> {
+        loop {
+            match nix::fcntl::flock(fd.0, arg) {
+                Err(Errno::EINTR) => continue,
+                result => return result,
+            }
+        }
+    }
+
     fn isatty(&self, fd: Fd) -> nix::Result<bool> {
         nix::unistd::isatty(fd.0)
     }
@@ -426,6 +445,27 @@ impl System for RealSystem {
         nix::unistd::tcsetpgrp(fd.0, pgid.into())
     }
 
+    fn tcgetattr(&self, fd: Fd) -> nix::Result<Termios> {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd.0) };
+        nix::sys::termios::tcgetattr(fd)
+    }
+
+    fn tcsetattr(&mut self, fd: Fd, action: SetArg, termios: &Termios) -> nix::Result<()> {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd.0) };
+        nix::sys::termios::tcsetattr(fd, action, termios)
+    }
+
+    fn tcgetwinsize(&self, fd: Fd) -> nix::Result<WindowSize> {
+        let mut size = MaybeUninit::<nix::libc::winsize>::uninit();
+        let result = unsafe { nix::libc::ioctl(fd.0, nix::libc::TIOCGWINSZ, size.as_mut_ptr()) };
+        Errno::result(result)?;
+        let size = unsafe { size.assume_init() };
+        Ok(WindowSize {
+            rows: size.ws_row,
+            columns: size.ws_col,
+        })
+    }
+
     /// Creates a new child process.
     ///
     /// This implementation calls the `fork` system call and returns both in the
@@ -484,6 +524,18 @@ impl System for RealSystem {
         nix::unistd::User::from_name(name).map(|o| o.map(|passwd| passwd.dir))
     }
 
+    fn geteuid(&self) -> Uid {
+        nix::unistd::geteuid()
+    }
+
+    fn getpwuid_name(&self, uid: Uid) -> nix::Result<Option<String>> {
+        nix::unistd::User::from_uid(uid).map(|o| o.map(|passwd| passwd.name))
+    }
+
+    fn gethostname(&self) -> nix::Result<OsString> {
+        nix::unistd::gethostname()
+    }
+
     fn confstr_path(&self) -> nix::Result<OsString> {
         // TODO Support other platforms
         #[cfg(any(
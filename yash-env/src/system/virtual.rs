@@ -63,12 +63,17 @@ use super::Errno;
 use super::FdFlag;
 use super::FdSet;
 use super::FileStat;
+use super::FlockArg;
 use super::OFlag;
+use super::SetArg;
 use super::SigSet;
 use super::SigmaskHow;
 use super::Signal;
+use super::Termios;
 use super::TimeSpec;
 use super::Times;
+use super::Uid;
+use super::WindowSize;
 use super::AT_FDCWD;
 use crate::io::Fd;
 use crate::job::Pid;
@@ -225,6 +230,14 @@ impl VirtualSystem {
         f(&ofd)
     }
 
+    /// Returns `Err(Errno::ENOTTY)` unless the FD is open for a terminal.
+    fn ensure_terminal(&self, fd: Fd) -> nix::Result<()> {
+        self.with_open_file_description(fd, |ofd| match ofd.file.borrow().body {
+            FileBody::Terminal { .. } => Ok(()),
+            _ => Err(Errno::ENOTTY),
+        })
+    }
+
     /// Calls the given closure passing the open file description for the FD.
     ///
     /// Returns `Err(Errno::EBADF)` if the FD is not open.
@@ -315,6 +328,7 @@ fn stat(inode: &INode) -> Result<FileStat, Errno> {
         FileBody::Directory { files } => (SFlag::S_IFDIR, files.len()),
         FileBody::Fifo { content, .. } => (SFlag::S_IFIFO, content.len()),
         FileBody::Symlink { target } => (SFlag::S_IFLNK, target.as_os_str().len()),
+        FileBody::Terminal { content } => (SFlag::S_IFCHR, content.len()),
     };
     let mut result: FileStat = unsafe { MaybeUninit::zeroed().assume_init() };
     result.st_mode = type_flag.bits() | inode.permissions.0;
@@ -520,6 +534,27 @@ impl System for VirtualSystem {
         process.open_fd(body).map_err(|_| Errno::EMFILE)
     }
 
+    fn unlink(&mut self, path: &CStr) -> nix::Result<()> {
+        let path = self.resolve_relative_path(Path::new(OsStr::from_bytes(path.to_bytes())));
+        let name = path.file_name().ok_or(Errno::ENOENT)?;
+        let parent = path.parent().ok_or(Errno::ENOENT)?;
+        let dir = self.state.borrow().file_system.get(parent)?;
+        let mut dir = dir.borrow_mut();
+        let FileBody::Directory { files } = &mut dir.body else {
+            return Err(Errno::ENOTDIR);
+        };
+        match files.get(name) {
+            None => Err(Errno::ENOENT),
+            Some(file) if matches!(file.borrow().body, FileBody::Directory { .. }) => {
+                Err(Errno::EISDIR)
+            }
+            Some(_) => {
+                files.remove(name);
+                Ok(())
+            }
+        }
+    }
+
     fn close(&mut self, fd: Fd) -> nix::Result<()> {
         self.current_process_mut().close_fd(fd);
         Ok(())
@@ -559,8 +594,19 @@ impl System for VirtualSystem {
         Ok(())
     }
 
-    fn isatty(&self, _fd: Fd) -> nix::Result<bool> {
-        Ok(false)
+    /// Checks the file descriptor but does nothing else.
+    ///
+    /// The virtual system runs only one process at a time, so locks never
+    /// conflict.
+    fn flock(&mut self, fd: Fd, _arg: FlockArg) -> nix::Result<()> {
+        self.current_process().get_fd(fd).ok_or(Errno::EBADF)?;
+        Ok(())
+    }
+
+    fn isatty(&self, fd: Fd) -> nix::Result<bool> {
+        self.with_open_file_description(fd, |ofd| {
+            Ok(matches!(ofd.file.borrow().body, FileBody::Terminal { .. }))
+        })
     }
 
     fn read(&mut self, fd: Fd, buffer: &mut [u8]) -> nix::Result<usize> {
@@ -856,6 +902,39 @@ impl System for VirtualSystem {
         Ok(())
     }
 
+    /// Returns the attributes of the terminal.
+    ///
+    /// This function returns the value of [`SystemState::terminal_attributes`]
+    /// if the FD is open for a [`FileBody::Terminal`]. If the attributes have
+    /// not been set, the result has all fields zeroed.
+    fn tcgetattr(&self, fd: Fd) -> nix::Result<Termios> {
+        self.ensure_terminal(fd)?;
+        match &self.state.borrow().terminal_attributes {
+            Some(termios) => Ok(termios.clone()),
+            // SAFETY: `termios` is a plain C structure for which all-zero bytes
+            // are a valid value.
+            None => Ok(unsafe { MaybeUninit::<nix::libc::termios>::zeroed().assume_init() }.into()),
+        }
+    }
+
+    /// Modifies the attributes of the terminal.
+    ///
+    /// This function updates [`SystemState::terminal_attributes`] regardless
+    /// of the `action`.
+    fn tcsetattr(&mut self, fd: Fd, _action: SetArg, termios: &Termios) -> nix::Result<()> {
+        self.ensure_terminal(fd)?;
+        self.state.borrow_mut().terminal_attributes = Some(termios.clone());
+        Ok(())
+    }
+
+    /// Returns the size of the terminal window.
+    ///
+    /// This function returns the value of [`SystemState::window_size`].
+    fn tcgetwinsize(&self, fd: Fd) -> nix::Result<WindowSize> {
+        self.ensure_terminal(fd)?;
+        Ok(self.state.borrow().window_size)
+    }
+
     /// Creates a new child process.
     ///
     /// This implementation does not create any real child process. Instead,
@@ -1005,6 +1084,22 @@ impl System for VirtualSystem {
         Ok(state.home_dirs.get(name).cloned())
     }
 
+    fn geteuid(&self) -> Uid {
+        self.current_process().euid
+    }
+
+    fn getpwuid_name(&self, uid: Uid) -> nix::Result<Option<String>> {
+        let state = self.state.borrow();
+        Ok(state.user_names.get(&uid).cloned())
+    }
+
+    /// Returns the host name of the system.
+    ///
+    /// This function returns the value of [`SystemState::hostname`].
+    fn gethostname(&self) -> nix::Result<OsString> {
+        Ok(self.state.borrow().hostname.clone())
+    }
+
     /// Returns the standard path for the system.
     ///
     /// This function returns the value of [`SystemState::path`]. If it is empty,
@@ -1122,8 +1217,25 @@ pub struct SystemState {
     /// dictionary.
     pub home_dirs: HashMap<String, PathBuf>,
 
+    /// Map from user IDs to their login names
+    ///
+    /// [`VirtualSystem::getpwuid_name`] looks up its argument in this
+    /// dictionary.
+    pub user_names: HashMap<Uid, String>,
+
+    /// Host name returned by [`VirtualSystem::gethostname`]
+    pub hostname: OsString,
+
     /// Standard path returned by [`VirtualSystem::confstr_path`]
     pub path: OsString,
+
+    /// Terminal attributes set by [`VirtualSystem::tcsetattr`]
+    ///
+    /// The current implementation shares the attributes among all terminals.
+    pub terminal_attributes: Option<Termios>,
+
+    /// Window size returned by [`VirtualSystem::tcgetwinsize`]
+    pub window_size: WindowSize,
 }
 
 impl SystemState {
@@ -1249,6 +1361,7 @@ mod tests {
     use assert_matches::assert_matches;
     use futures_executor::LocalPool;
     use futures_util::FutureExt;
+    use nix::sys::termios::LocalFlags;
     use std::ffi::CString;
     use std::ffi::OsString;
     use std::future::pending;
@@ -1264,6 +1377,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn isatty_terminal() {
+        let system = VirtualSystem::new();
+        assert_eq!(system.isatty(Fd::STDIN), Ok(false));
+
+        let state = system.state.borrow();
+        let stdin = state.file_system.get("/dev/stdin").unwrap();
+        stdin.borrow_mut().body = FileBody::Terminal {
+            content: VecDeque::new(),
+        };
+        drop(state);
+        assert_eq!(system.isatty(Fd::STDIN), Ok(true));
+        assert_eq!(system.isatty(Fd(100)), Err(Errno::EBADF));
+    }
+
+    #[test]
+    fn terminal_attributes_and_window_size() {
+        let mut system = VirtualSystem::new();
+        assert_eq!(system.tcgetattr(Fd::STDIN), Err(Errno::ENOTTY));
+        assert_eq!(system.tcgetwinsize(Fd::STDIN), Err(Errno::ENOTTY));
+        assert_eq!(system.tcgetattr(Fd(100)), Err(Errno::EBADF));
+
+        {
+            let mut state = system.state.borrow_mut();
+            let stdin = state.file_system.get("/dev/stdin").unwrap();
+            stdin.borrow_mut().body = FileBody::Terminal {
+                content: VecDeque::new(),
+            };
+            state.window_size = WindowSize {
+                rows: 24,
+                columns: 80,
+            };
+        }
+        let mut termios = system.tcgetattr(Fd::STDIN).unwrap();
+        assert!(termios.local_flags.is_empty());
+        termios.local_flags = LocalFlags::ICANON | LocalFlags::ECHO;
+        system
+            .tcsetattr(Fd::STDIN, SetArg::TCSADRAIN, &termios)
+            .unwrap();
+        assert_eq!(system.tcgetattr(Fd::STDIN), Ok(termios));
+        assert_eq!(
+            system.tcgetwinsize(Fd::STDIN),
+            Ok(WindowSize {
+                rows: 24,
+                columns: 80
+            })
+        );
+    }
+
     #[test]
     fn fstatat_non_existent_file() {
         let system = VirtualSystem::new();
@@ -1702,6 +1864,24 @@ mod tests {
         assert_eq!(buffer[0..4], [1, 2, 3, 42]);
     }
 
+    #[test]
+    fn unlink_regular_file() {
+        let mut system = VirtualSystem::new();
+        let path = CString::new("/dir/file").unwrap();
+        let flags = OFlag::O_WRONLY | OFlag::O_CREAT;
+        let fd = system
+            .open(&path, flags, nix::sys::stat::Mode::empty())
+            .unwrap();
+        system.close(fd).unwrap();
+
+        assert_eq!(system.unlink(&path), Ok(()));
+        assert_eq!(system.unlink(&path), Err(Errno::ENOENT));
+        let dir = CString::new("/dir").unwrap();
+        assert_eq!(system.unlink(&dir), Err(Errno::EISDIR));
+        let state = system.state.borrow();
+        assert_eq!(state.file_system.get("/dir/file"), Err(Errno::ENOENT));
+    }
+
     #[test]
     fn open_tmpfile() {
         let mut system = VirtualSystem::new();
@@ -126,24 +126,59 @@ impl FileSystem {
 
     /// Returns a reference to the existing file at the specified path.
     ///
+    /// Symbolic links are followed if they appear as a non-last component of
+    /// the path or as the last component followed by a slash.
+    ///
     /// TODO Reject relative path
     pub fn get<P: AsRef<Path>>(&self, path: P) -> nix::Result<Rc<RefCell<INode>>> {
-        fn main(fs: &FileSystem, path: &Path) -> nix::Result<Rc<RefCell<INode>>> {
-            let components = path.components();
+        const _POSIX_SYMLOOP_MAX: usize = 8;
+
+        /// Computes the path that replaces the symbolic link at `dir`.
+        fn resolve_symlink(dir: &Path, target: &Path, rest: &Path, slash: bool) -> PathBuf {
+            let mut new_path = dir.to_path_buf();
+            new_path.pop();
+            new_path.push(target);
+            new_path.push(rest);
+            if slash {
+                new_path.as_mut_os_string().push("/");
+            }
+            new_path
+        }
+
+        fn main(fs: &FileSystem, path: &Path, depth: usize) -> nix::Result<Rc<RefCell<INode>>> {
+            let slash = path.as_os_str().as_bytes().ends_with(b"/");
+            let mut components = path.components();
             let mut nodes = vec![Rc::clone(&fs.root)];
-            for component in components {
+            let mut dir = PathBuf::from("/");
+            loop {
+                let node = Rc::clone(nodes.last().unwrap());
+                let node_ref = node.borrow();
+                let rest = components.as_path();
+                if let FileBody::Symlink { target } = &node_ref.body {
+                    if slash || rest.components().next().is_some() {
+                        if depth >= _POSIX_SYMLOOP_MAX {
+                            return Err(Errno::ELOOP);
+                        }
+                        let new_path = resolve_symlink(&dir, target, rest, slash);
+                        return main(fs, &new_path, depth + 1);
+                    }
+                }
+
+                let Some(component) = components.next() else {
+                    break;
+                };
                 let name = match component {
                     Component::Normal(name) => name,
                     Component::RootDir | Component::CurDir => continue,
                     Component::ParentDir => {
                         if nodes.len() > 1 {
                             nodes.pop();
+                            dir.pop();
                         }
                         continue;
                     }
                     _ => return Err(Errno::ENOENT),
                 };
-                let node_ref = nodes.last().unwrap().borrow();
                 let children = match &node_ref.body {
                     FileBody::Directory { files } => files,
                     _ => return Err(Errno::ENOTDIR),
@@ -151,18 +186,17 @@ impl FileSystem {
                 let child = Rc::clone(children.get(name).ok_or(Errno::ENOENT)?);
                 drop(node_ref);
                 nodes.push(child);
+                dir.push(name);
             }
 
             let node = nodes.pop().unwrap();
-            if path.as_os_str().as_bytes().ends_with(b"/")
-                && !matches!(&node.borrow().body, FileBody::Directory { .. })
-            {
+            if slash && !matches!(&node.borrow().body, FileBody::Directory { .. }) {
                 return Err(Errno::ENOTDIR);
             }
             Ok(node)
         }
 
-        main(self, path.as_ref())
+        main(self, path.as_ref(), 0)
     }
 }
 
@@ -215,6 +249,16 @@ pub enum FileBody {
         /// Path to the file referenced by this symlink
         target: PathBuf,
     },
+    /// Terminal device
+    ///
+    /// Reading from a terminal consumes bytes from the front of the `content`.
+    /// When the content is empty, reading returns zero bytes, which simulates
+    /// an end-of-file entered by the user. Bytes written to the terminal are
+    /// discarded.
+    Terminal {
+        /// Bytes that are pending to be read.
+        content: VecDeque<u8>,
+    },
     // TODO Other filetypes
 }
 
@@ -406,6 +450,30 @@ mod tests {
         assert_eq!(result, Err(Errno::ENOTDIR));
     }
 
+    #[test]
+    fn file_system_get_through_symlinks() {
+        let mut fs = FileSystem::default();
+        let file = Rc::new(RefCell::new(INode::new([123])));
+        _ = fs.save("/dir/sub/file", Rc::clone(&file));
+        let symlink = |target: &str| {
+            let body = FileBody::Symlink {
+                target: target.into(),
+            };
+            let permissions = Mode::default();
+            Rc::new(RefCell::new(INode { body, permissions }))
+        };
+        let absolute = symlink("/dir");
+        _ = fs.save("/absolute", Rc::clone(&absolute));
+        _ = fs.save("/dir/relative", symlink("sub"));
+        _ = fs.save("/loop", symlink("/loop"));
+
+        assert_eq!(fs.get("/absolute/sub/file").unwrap(), file);
+        assert_eq!(fs.get("/absolute/relative/file").unwrap(), file);
+        assert_eq!(fs.get("/absolute").unwrap(), absolute);
+        assert_eq!(fs.get("/absolute/").unwrap(), fs.get("/dir").unwrap());
+        assert_eq!(fs.get("/loop/file"), Err(Errno::ELOOP));
+    }
+
     #[test]
     fn empty_virtual_dir() {
         let mut dir = VirtualDir::new(std::iter::empty());
@@ -87,6 +87,7 @@ impl OpenFileDescription {
     pub fn is_ready_for_reading(&self) -> bool {
         match &self.file.borrow().body {
             FileBody::Regular { .. } | FileBody::Directory { .. } => true,
+            FileBody::Terminal { .. } => true,
             FileBody::Fifo {
                 content, writers, ..
             } => !self.is_readable || !content.is_empty() || *writers == 0,
@@ -100,6 +101,7 @@ impl OpenFileDescription {
     pub fn is_ready_for_writing(&self) -> bool {
         match &self.file.borrow().body {
             FileBody::Regular { .. } | FileBody::Directory { .. } => true,
+            FileBody::Terminal { .. } => true,
             FileBody::Fifo {
                 content, readers, ..
             } => *readers == 0 || PIPE_SIZE - content.len() >= PIPE_BUF,
@@ -148,6 +150,13 @@ impl OpenFileDescription {
                 }
                 Ok(count)
             }
+            FileBody::Terminal { content } => {
+                let count = buffer.len().min(content.len());
+                for (to, from) in buffer.iter_mut().zip(content.drain(..count)) {
+                    *to = from;
+                }
+                Ok(count)
+            }
             FileBody::Directory { .. } => Err(Errno::EISDIR),
             FileBody::Symlink { target: _ } => Err(Errno::ENOTSUP),
         }
@@ -198,6 +207,7 @@ impl OpenFileDescription {
                 debug_assert!(content.len() <= PIPE_SIZE);
                 Ok(buffer.len())
             }
+            FileBody::Terminal { .. } => Ok(buffer.len()),
             FileBody::Directory { .. } => Err(Errno::EISDIR),
             FileBody::Symlink { target: _ } => Err(Errno::ENOTSUP),
         }
@@ -211,7 +221,7 @@ impl OpenFileDescription {
         let len = match &self.file.borrow().body {
             FileBody::Regular { content, .. } => content.len(),
             FileBody::Directory { files, .. } => files.len(),
-            FileBody::Fifo { .. } => return Err(Errno::ESPIPE),
+            FileBody::Fifo { .. } | FileBody::Terminal { .. } => return Err(Errno::ESPIPE),
             FileBody::Symlink { target: _ } => return Err(Errno::ENOTSUP),
         };
         let base = match whence {
@@ -803,4 +813,35 @@ mod tests {
         let result = open_file.write(&[1; 1]);
         assert_eq!(result, Err(Errno::EPIPE));
     }
+
+    #[test]
+    fn terminal_read_and_write() {
+        let mut open_file = OpenFileDescription {
+            file: Rc::new(RefCell::new(INode {
+                body: FileBody::Terminal {
+                    content: VecDeque::from([1, 2, 3]),
+                },
+                permissions: Mode::default(),
+            })),
+            offset: 0,
+            is_readable: true,
+            is_writable: true,
+            is_appending: false,
+        };
+
+        let mut buffer = [100; 2];
+        let result = open_file.read(&mut buffer);
+        assert_eq!(result, Ok(2));
+        assert_eq!(buffer, [1, 2]);
+
+        let result = open_file.write(&[4, 5]);
+        assert_eq!(result, Ok(2));
+
+        let result = open_file.read(&mut buffer);
+        assert_eq!(result, Ok(1));
+        assert_eq!(buffer[..1], [3]);
+
+        let result = open_file.read(&mut buffer);
+        assert_eq!(result, Ok(0));
+    }
 }
@@ -26,6 +26,7 @@ use crate::system::resource::LimitPair;
 use crate::system::resource::Resource;
 use crate::system::resource::RLIM_INFINITY;
 use crate::system::SelectSystem;
+use crate::system::Uid;
 use crate::SignalHandling;
 use nix::sys::signal::SigSet;
 use nix::sys::signal::SigmaskHow;
@@ -61,6 +62,9 @@ pub struct Process {
     /// Working directory path
     pub(crate) cwd: PathBuf,
 
+    /// Effective user ID
+    pub(crate) euid: Uid,
+
     /// Execution state of the process
     pub(crate) state: ProcessState,
 
@@ -131,6 +135,7 @@ impl Process {
             fds: BTreeMap::new(),
             umask: Mode::default(),
             cwd: PathBuf::new(),
+            euid: Uid::from_raw(0),
             state: ProcessState::Running,
             state_has_changed: false,
             resumption_awaiters: Vec::new(),
@@ -150,6 +155,7 @@ impl Process {
     pub fn fork_from(ppid: Pid, parent: &Process) -> Process {
         let mut child = Self::with_parent_and_group(ppid, parent.pgid);
         child.fds = parent.fds.clone();
+        child.euid = parent.euid;
         child.signal_handlings = parent.signal_handlings.clone();
         child.blocked_signals = parent.blocked_signals;
         child.pending_signals = SigSet::empty();
@@ -257,6 +263,18 @@ impl Process {
         self.cwd = path
     }
 
+    /// Returns the effective user ID of this process.
+    #[inline(always)]
+    #[must_use]
+    pub fn euid(&self) -> Uid {
+        self.euid
+    }
+
+    /// Changes the effective user ID of this process.
+    pub fn set_euid(&mut self, euid: Uid) {
+        self.euid = euid
+    }
+
     /// Registers a waker that will be woken up when this process resumes.
     ///
     /// The given waker will be woken up when this process is resumed by
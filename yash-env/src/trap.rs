@@ -339,6 +339,54 @@ impl TrapSet {
         GrandState::set_internal_handler(system, entry, SignalHandling::Ignore)
     }
 
+    /// Installs internal handlers for `SIGTSTP` and `SIGCONT`.
+    ///
+    /// A line editor that puts the terminal in a special mode should install
+    /// the handlers by using this function while reading input. The handlers
+    /// allow catching the signals so that the editor can restore the terminal
+    /// mode before the shell is suspended and set the mode again when the
+    /// shell is resumed.
+    ///
+    /// `SIGTSTP` is not caught if the [stopper
+    /// handlers](Self::enable_stopper_handlers) have been enabled because a
+    /// job-controlling shell is not suspended by the signal.
+    pub fn enable_suspension_handlers<S: SignalSystem>(
+        &mut self,
+        system: &mut S,
+    ) -> Result<(), Errno> {
+        let entry = self.traps.entry(Condition::Signal(Signal::SIGCONT));
+        GrandState::set_internal_handler(system, entry, SignalHandling::Catch)?;
+
+        let entry = self.traps.entry(Condition::Signal(Signal::SIGTSTP));
+        if let Entry::Occupied(occupied) = &entry {
+            if occupied.get().internal_handler() == SignalHandling::Ignore {
+                return Ok(());
+            }
+        }
+        GrandState::set_internal_handler(system, entry, SignalHandling::Catch)
+    }
+
+    /// Uninstalls the internal handlers for `SIGTSTP` and `SIGCONT`.
+    ///
+    /// This function undoes [`enable_suspension_handlers`](Self::enable_suspension_handlers).
+    /// The stopper handler for `SIGTSTP` is left intact.
+    pub fn disable_suspension_handlers<S: SignalSystem>(
+        &mut self,
+        system: &mut S,
+    ) -> Result<(), Errno> {
+        self.disable_internal_handler(Signal::SIGCONT, system)?;
+
+        let entry = self.traps.entry(Condition::Signal(Signal::SIGTSTP));
+        match &entry {
+            Entry::Occupied(occupied)
+                if occupied.get().internal_handler() == SignalHandling::Catch =>
+            {
+                GrandState::set_internal_handler(system, entry, SignalHandling::Default)
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn disable_internal_handler<S: SignalSystem>(
         &mut self,
         signal: Signal,
@@ -378,6 +426,7 @@ impl TrapSet {
         system: &mut S,
     ) -> Result<(), Errno> {
         self.disable_internal_handler(Signal::SIGCHLD, system)?;
+        self.disable_internal_handler(Signal::SIGCONT, system)?;
         self.disable_terminator_handlers(system)?;
         self.disable_stopper_handlers(system)
     }
@@ -1262,6 +1311,33 @@ mod tests {
         assert_eq!(system.0[&Signal::SIGTTOU], SignalHandling::Ignore);
     }
 
+    #[test]
+    fn enabling_and_disabling_suspension_handlers() {
+        let mut system = DummySystem::default();
+        let mut trap_set = TrapSet::default();
+        trap_set.enable_suspension_handlers(&mut system).unwrap();
+        assert_eq!(system.0[&Signal::SIGTSTP], SignalHandling::Catch);
+        assert_eq!(system.0[&Signal::SIGCONT], SignalHandling::Catch);
+
+        trap_set.disable_suspension_handlers(&mut system).unwrap();
+        assert_eq!(system.0[&Signal::SIGTSTP], SignalHandling::Default);
+        assert_eq!(system.0[&Signal::SIGCONT], SignalHandling::Default);
+    }
+
+    #[test]
+    fn suspension_handlers_keep_stopper_handlers() {
+        let mut system = DummySystem::default();
+        let mut trap_set = TrapSet::default();
+        trap_set.enable_stopper_handlers(&mut system).unwrap();
+        trap_set.enable_suspension_handlers(&mut system).unwrap();
+        assert_eq!(system.0[&Signal::SIGTSTP], SignalHandling::Ignore);
+        assert_eq!(system.0[&Signal::SIGCONT], SignalHandling::Catch);
+
+        trap_set.disable_suspension_handlers(&mut system).unwrap();
+        assert_eq!(system.0[&Signal::SIGTSTP], SignalHandling::Ignore);
+        assert_eq!(system.0[&Signal::SIGCONT], SignalHandling::Default);
+    }
+
     #[test]
     fn disabling_internal_handlers_for_initially_defaulted_signals() {
         let mut system = DummySystem::default();
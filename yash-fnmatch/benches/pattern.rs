@@ -0,0 +1,153 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Benchmarks for pattern compilation and matching
+//!
+//! The `regex` variants reproduce the previous implementation, which compiled
+//! every pattern into a regular expression, for comparison.
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use regex::Regex;
+use regex::RegexBuilder;
+use yash_fnmatch::ast::Ast;
+use yash_fnmatch::without_escape;
+use yash_fnmatch::Config;
+use yash_fnmatch::Pattern;
+use yash_fnmatch::PatternCache;
+
+const PATTERNS: &[&str] = &["*.txt", "foo?bar*", "[a-z]*[0-9]", "-[!-]*"];
+
+const SUBJECTS: &[&str] = &[
+    "README.txt",
+    "foo_bar_baz",
+    "config.rs",
+    "--verbose",
+    "-v",
+    "version2",
+];
+
+fn case_config() -> Config {
+    let mut config = Config::default();
+    config.anchor_begin = true;
+    config.anchor_end = true;
+    config
+}
+
+fn regex(pattern: &str, config: &Config) -> Regex {
+    let ast = Ast::new(without_escape(pattern));
+    RegexBuilder::new(&ast.to_regex(config).unwrap())
+        .dot_matches_new_line(true)
+        .build()
+        .unwrap()
+}
+
+fn compile(c: &mut Criterion) {
+    let config = case_config();
+    let mut group = c.benchmark_group("compile");
+    group.bench_function("regex", |b| {
+        b.iter(|| {
+            for pattern in PATTERNS {
+                black_box(regex(pattern, &config));
+            }
+        })
+    });
+    group.bench_function("pattern", |b| {
+        b.iter(|| {
+            for pattern in PATTERNS {
+                let pattern = Pattern::parse_with_config(without_escape(pattern), config);
+                _ = black_box(pattern.unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Simulates a `case` command in a loop, which compiles every pattern for
+/// every subject.
+fn case_loop(c: &mut Criterion) {
+    let config = case_config();
+    let mut group = c.benchmark_group("case_loop");
+    group.bench_function("regex", |b| {
+        b.iter(|| {
+            for subject in SUBJECTS {
+                for pattern in PATTERNS {
+                    if regex(pattern, &config).is_match(subject) {
+                        break;
+                    }
+                }
+            }
+        })
+    });
+    group.bench_function("pattern", |b| {
+        b.iter(|| {
+            for subject in SUBJECTS {
+                for pattern in PATTERNS {
+                    let pattern = Pattern::parse_with_config(without_escape(pattern), config);
+                    if pattern.unwrap().is_match(subject) {
+                        break;
+                    }
+                }
+            }
+        })
+    });
+    group.bench_function("cache", |b| {
+        let mut cache = PatternCache::new(16);
+        b.iter(|| {
+            for subject in SUBJECTS {
+                for pattern in PATTERNS {
+                    let pattern = cache.get_or_parse(without_escape(pattern), config);
+                    if pattern.unwrap().is_match(subject) {
+                        break;
+                    }
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Compiles patterns that are matched by regular expressions, with and
+/// without a cache.
+fn cache(c: &mut Criterion) {
+    let mut config = case_config();
+    config.extended = true;
+    let patterns = ["@(start|stop)", "+([0-9])", "[[.ch.]]*"];
+    let mut group = c.benchmark_group("cache");
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            for pattern in patterns {
+                let pattern = Pattern::parse_with_config(without_escape(pattern), config);
+                black_box(pattern.unwrap().is_match("stop"));
+            }
+        })
+    });
+    group.bench_function("cache", |b| {
+        let mut cache = PatternCache::new(16);
+        b.iter(|| {
+            for pattern in patterns {
+                let pattern = cache.get_or_parse(without_escape(pattern), config);
+                black_box(pattern.unwrap().is_match("stop"));
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Matches precompiled patterns against a long text.
+fn long_text(c: &mut Criterion) {
+    let mut config = Config::default();
+    config.anchor_end = true;
+    let text = "a/b/c/".repeat(100) + "file.txt";
+    let mut group = c.benchmark_group("long_text");
+    let regex = regex("*.txt", &config);
+    group.bench_function("regex", |b| b.iter(|| black_box(regex.find(&text))));
+    let pattern = Pattern::parse_with_config(without_escape("*.txt"), config).unwrap();
+    group.bench_function("pattern", |b| b.iter(|| black_box(pattern.find(&text))));
+    group.finish();
+}
+
+criterion_group!(benches, compile, case_loop, cache, long_text);
+criterion_main!(benches);
@@ -5,7 +5,9 @@
 
 mod parse;
 mod regex;
+mod unicode;
 
+use crate::Config;
 use crate::PatternChar;
 use std::ops::RangeInclusive;
 
@@ -61,6 +63,56 @@ pub struct Bracket {
     pub items: Vec<BracketItem>,
 }
 
+/// Operator of an extended pattern
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ExtendedOp {
+    /// Zero or one occurrence of the alternatives (`?(...)`)
+    ZeroOrOne,
+    /// Any number of occurrences of the alternatives (`*(...)`)
+    ZeroOrMore,
+    /// One or more occurrences of the alternatives (`+(...)`)
+    OneOrMore,
+    /// Exactly one occurrence of the alternatives (`@(...)`)
+    ExactlyOne,
+    /// Anything that does not match any of the alternatives (`!(...)`)
+    Not,
+}
+
+impl ExtendedOp {
+    /// Returns the operator corresponding to the character preceding `(`.
+    #[must_use]
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '?' => Some(ExtendedOp::ZeroOrOne),
+            '*' => Some(ExtendedOp::ZeroOrMore),
+            '+' => Some(ExtendedOp::OneOrMore),
+            '@' => Some(ExtendedOp::ExactlyOne),
+            '!' => Some(ExtendedOp::Not),
+            _ => None,
+        }
+    }
+}
+
+/// Extended pattern (e.g. `@(foo|bar)`)
+///
+/// Extended patterns are recognized only if [`Config::extended`] is enabled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Extended {
+    /// Operator applied to the alternatives
+    pub op: ExtendedOp,
+    /// Patterns separated by `|`
+    pub alternatives: Vec<Ast>,
+}
+
+impl Extended {
+    /// Tests whether this pattern contains a negation (`!(...)`), possibly
+    /// nested.
+    #[must_use]
+    pub fn contains_negation(&self) -> bool {
+        self.op == ExtendedOp::Not || self.alternatives.iter().any(Ast::contains_negation)
+    }
+}
+
 /// Pattern component
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Atom {
@@ -72,6 +124,8 @@ pub enum Atom {
     AnyString,
     /// Bracket expression
     Bracket(Bracket),
+    /// Extended pattern
+    Extended(Extended),
 }
 
 /// Abstract syntax tree for a whole pattern
@@ -83,25 +137,57 @@ pub struct Ast {
 
 impl Ast {
     /// Parses a pattern string into an AST.
+    ///
+    /// This function does not recognize [extended patterns](Extended). Use
+    /// [`new_with_config`](Self::new_with_config) to enable them.
     #[inline]
     pub fn new<I>(pattern: I) -> Self
     where
         I: IntoIterator<Item = PatternChar>,
         <I as IntoIterator>::IntoIter: Clone,
     {
-        fn inner<I>(mut i: I) -> Ast
+        Self::new_with_config(pattern, &Config::default())
+    }
+
+    /// Parses a pattern string into an AST with a specified configuration.
+    ///
+    /// Only the `extended` option in `config` affects the results. The other
+    /// options are ignored.
+    ///
+    /// ```
+    /// # use yash_fnmatch::{ast::*, without_escape, Config};
+    /// let mut config = Config::default();
+    /// config.extended = true;
+    /// let ast = Ast::new_with_config(without_escape("@(a|b)"), &config);
+    /// assert_eq!(
+    ///     ast.atoms,
+    ///     [Atom::Extended(Extended {
+    ///         op: ExtendedOp::ExactlyOne,
+    ///         alternatives: vec![
+    ///             Ast { atoms: vec![Atom::Char('a')] },
+    ///             Ast { atoms: vec![Atom::Char('b')] },
+    ///         ],
+    ///     })]
+    /// );
+    /// ```
+    pub fn new_with_config<I>(pattern: I, config: &Config) -> Self
+    where
+        I: IntoIterator<Item = PatternChar>,
+        <I as IntoIterator>::IntoIter: Clone,
+    {
+        fn inner<I>(mut i: I, extended: bool) -> Ast
         where
             I: Iterator<Item = PatternChar> + Clone,
         {
             let mut atoms = Vec::new();
-            while let Some((atom, j)) = Atom::parse(i) {
+            while let Some((atom, j)) = Atom::parse(i, extended) {
                 atoms.push(atom);
                 i = j;
             }
             Ast { atoms }
         }
 
-        inner(pattern.into_iter())
+        inner(pattern.into_iter(), config.extended)
     }
 
     /// Tests whether this pattern is completely literal.
@@ -139,8 +225,34 @@ impl Ast {
             .collect()
     }
 
+    /// Tests whether this pattern contains a negation (`!(...)`).
+    ///
+    /// A pattern containing a negation cannot be converted to a regular
+    /// expression.
+    #[must_use]
+    pub fn contains_negation(&self) -> bool {
+        self.atoms.iter().any(|atom| match atom {
+            Atom::Extended(extended) => extended.contains_negation(),
+            _ => false,
+        })
+    }
+
+    /// Tests whether this pattern starts with a literal period.
+    ///
+    /// An extended pattern at the beginning is regarded as starting with a
+    /// literal period if any of its alternatives does, except for a negation.
     #[must_use]
     pub(crate) fn starts_with_literal_dot(&self) -> bool {
-        self.atoms.first() == Some(&Atom::Char('.'))
+        match self.atoms.first() {
+            Some(Atom::Char('.')) => true,
+            Some(Atom::Extended(extended)) => {
+                extended.op != ExtendedOp::Not
+                    && extended
+                        .alternatives
+                        .iter()
+                        .any(Ast::starts_with_literal_dot)
+            }
+            _ => false,
+        }
     }
 }
@@ -126,12 +126,61 @@ impl Bracket {
     }
 }
 
+impl Extended {
+    /// Parses the alternatives of an extended pattern (except the initial
+    /// `(`).
+    ///
+    /// If successful, returns the alternatives as well as an iterator that
+    /// yields characters following the closing parenthesis. Returns `None` if
+    /// the parenthesis is not closed.
+    fn parse_alternatives<I>(mut i: I) -> Option<(Vec<Ast>, I)>
+    where
+        I: Iterator<Item = PatternChar> + Clone,
+    {
+        let mut alternatives = Vec::new();
+        let mut atoms = Vec::new();
+        loop {
+            let mut j = i.clone();
+            match j.next()? {
+                PatternChar::Normal('|') => {
+                    alternatives.push(Ast {
+                        atoms: std::mem::take(&mut atoms),
+                    });
+                    i = j;
+                }
+                PatternChar::Normal(')') => {
+                    alternatives.push(Ast { atoms });
+                    return Some((alternatives, j));
+                }
+                _ => {
+                    let (atom, k) = Atom::parse(i, true)?;
+                    atoms.push(atom);
+                    i = k;
+                }
+            }
+        }
+    }
+}
+
 impl Atom {
-    pub(crate) fn parse<I>(mut i: I) -> Option<(Self, I)>
+    pub(crate) fn parse<I>(mut i: I, extended: bool) -> Option<(Self, I)>
     where
         I: Iterator<Item = PatternChar> + Clone,
     {
         i.next().map(|pc| {
+            if extended {
+                if let Some(op) = ExtendedOp::from_char(pc.char_value())
+                    .filter(|_| matches!(pc, PatternChar::Normal(_)))
+                {
+                    let mut j = i.clone();
+                    if j.next() == Some(PatternChar::Normal('(')) {
+                        if let Some((alternatives, k)) = Extended::parse_alternatives(j) {
+                            return (Atom::Extended(Extended { op, alternatives }), k);
+                        }
+                    }
+                }
+            }
+
             let atom = match pc {
                 PatternChar::Normal('?') => Atom::AnyChar,
                 PatternChar::Normal('*') => Atom::AnyString,
@@ -739,4 +788,140 @@ mod tests {
             })]
         );
     }
+
+    fn extended_config() -> Config {
+        Config {
+            extended: true,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn extended_patterns_disabled_by_default() {
+        let ast = Ast::new(without_escape("@(a)"));
+        assert_eq!(
+            ast.atoms,
+            [
+                Atom::Char('@'),
+                Atom::Char('('),
+                Atom::Char('a'),
+                Atom::Char(')'),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_pattern_operators() {
+        for (c, op) in [
+            ('?', ExtendedOp::ZeroOrOne),
+            ('*', ExtendedOp::ZeroOrMore),
+            ('+', ExtendedOp::OneOrMore),
+            ('@', ExtendedOp::ExactlyOne),
+            ('!', ExtendedOp::Not),
+        ] {
+            let pattern = format!("{c}(x)");
+            let ast = Ast::new_with_config(without_escape(&pattern), &extended_config());
+            assert_eq!(
+                ast.atoms,
+                [Atom::Extended(Extended {
+                    op,
+                    alternatives: vec![Ast {
+                        atoms: vec![Atom::Char('x')]
+                    }],
+                })],
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn extended_pattern_alternatives() {
+        let ast = Ast::new_with_config(without_escape("a+(|b?|[|])c"), &extended_config());
+        assert_eq!(
+            ast.atoms,
+            [
+                Atom::Char('a'),
+                Atom::Extended(Extended {
+                    op: ExtendedOp::OneOrMore,
+                    alternatives: vec![
+                        Ast { atoms: vec![] },
+                        Ast {
+                            atoms: vec![Atom::Char('b'), Atom::AnyChar]
+                        },
+                        Ast {
+                            atoms: vec![Atom::Bracket(Bracket {
+                                complement: false,
+                                items: vec![BracketItem::Atom(BracketAtom::Char('|'))]
+                            })]
+                        },
+                    ],
+                }),
+                Atom::Char('c'),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_extended_patterns() {
+        let ast = Ast::new_with_config(without_escape("!(*(a)|b)"), &extended_config());
+        assert_eq!(
+            ast.atoms,
+            [Atom::Extended(Extended {
+                op: ExtendedOp::Not,
+                alternatives: vec![
+                    Ast {
+                        atoms: vec![Atom::Extended(Extended {
+                            op: ExtendedOp::ZeroOrMore,
+                            alternatives: vec![Ast {
+                                atoms: vec![Atom::Char('a')]
+                            }],
+                        })]
+                    },
+                    Ast {
+                        atoms: vec![Atom::Char('b')]
+                    },
+                ],
+            })]
+        );
+    }
+
+    #[test]
+    fn unclosed_extended_pattern() {
+        let ast = Ast::new_with_config(without_escape("@(a|b"), &extended_config());
+        assert_eq!(
+            ast.atoms,
+            [
+                Atom::Char('@'),
+                Atom::Char('('),
+                Atom::Char('a'),
+                Atom::Char('|'),
+                Atom::Char('b'),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_extended_pattern() {
+        let ast = Ast::new_with_config(with_escape(r"\@(a)"), &extended_config());
+        assert_eq!(
+            ast.atoms,
+            [
+                Atom::Char('@'),
+                Atom::Char('('),
+                Atom::Char('a'),
+                Atom::Char(')'),
+            ]
+        );
+
+        let ast = Ast::new_with_config(with_escape(r"*\(a)"), &extended_config());
+        assert_eq!(
+            ast.atoms,
+            [
+                Atom::AnyString,
+                Atom::Char('('),
+                Atom::Char('a'),
+                Atom::Char(')'),
+            ]
+        );
+    }
 }
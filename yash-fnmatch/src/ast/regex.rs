@@ -26,15 +26,27 @@ impl BracketAtom {
     fn matches_multi_character(&self) -> bool {
         match self {
             BracketAtom::CollatingSymbol(value) | BracketAtom::EquivalenceClass(value) => {
-                value.len() > 1
+                value.chars().nth(1).is_some()
             }
             _ => false,
         }
     }
 
-    fn fmt_regex(&self, regex: &mut dyn Write) -> Result {
+    fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
         match self {
             BracketAtom::Char(c) => return BracketAtom::fmt_regex_char(*c, regex),
+            BracketAtom::EquivalenceClass(value) if config.unicode => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (None, _) => return Err(Error::EmptyCollatingSymbol),
+                    (Some(c), None) => {
+                        return unicode::equivalents(c)
+                            .into_iter()
+                            .try_for_each(|c| BracketAtom::fmt_regex_char(c, regex))
+                    }
+                    (Some(_), Some(_)) => regex.write_str(value),
+                }
+            }
             BracketAtom::CollatingSymbol(value) | BracketAtom::EquivalenceClass(value) => {
                 if !value.is_empty() {
                     regex.write_str(value)
@@ -43,11 +55,13 @@ impl BracketAtom {
                 }
             }
             BracketAtom::CharClass(class) => {
-                if ClassAsciiKind::from_name(class).is_some() {
-                    regex.write_fmt(format_args!("[:{class}:]"))
-                } else {
+                if ClassAsciiKind::from_name(class).is_none() {
                     return Err(Error::UndefinedCharClass(class.clone()));
                 }
+                match unicode::char_class(class).filter(|_| config.unicode) {
+                    Some(unicode_class) => regex.write_str(unicode_class),
+                    None => regex.write_fmt(format_args!("[:{class}:]")),
+                }
             }
         }
         .unwrap();
@@ -74,9 +88,9 @@ impl BracketItem {
         }
     }
 
-    fn fmt_regex(&self, regex: &mut dyn Write) -> Result {
+    fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
         match self {
-            BracketItem::Atom(a) => a.fmt_regex(regex),
+            BracketItem::Atom(a) => a.fmt_regex(config, regex),
             BracketItem::Range(range) => {
                 range.start().fmt_regex_single(regex)?;
                 regex.write_char('-').unwrap();
@@ -91,7 +105,7 @@ impl Bracket {
         self.items.iter().any(BracketItem::matches_multi_character)
     }
 
-    fn fmt_regex(&self, regex: &mut dyn Write) -> Result {
+    fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
         if self.items.is_empty() {
             return Err(Error::EmptyBracket);
         }
@@ -101,7 +115,7 @@ impl Bracket {
                 regex.write_char('^').unwrap();
             }
             for item in &self.items {
-                item.fmt_regex(regex)?;
+                item.fmt_regex(config, regex)?;
             }
             regex.write_char(']').unwrap();
         } else if !self.complement {
@@ -116,10 +130,10 @@ impl Bracket {
 
                 if !item.matches_multi_character() {
                     regex.write_char('[').unwrap();
-                    item.fmt_regex(regex)?;
+                    item.fmt_regex(config, regex)?;
                     regex.write_char(']').unwrap();
                 } else {
-                    item.fmt_regex(regex)?;
+                    item.fmt_regex(config, regex)?;
                 }
             }
             regex.write_char(')').unwrap();
@@ -127,7 +141,7 @@ impl Bracket {
             regex.write_str("[^").unwrap();
             for item in &self.items {
                 if !item.matches_multi_character() {
-                    item.fmt_regex(regex)?;
+                    item.fmt_regex(config, regex)?;
                 }
             }
             regex.write_char(']').unwrap();
@@ -137,7 +151,7 @@ impl Bracket {
 }
 
 impl Atom {
-    fn fmt_regex(&self, _config: &Config, regex: &mut dyn Write) -> Result {
+    fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
         match self {
             Atom::Char(c) => {
                 if SPECIAL_CHARS.contains(*c) {
@@ -147,8 +161,38 @@ impl Atom {
             }
             Atom::AnyChar => regex.write_char('.').unwrap(),
             Atom::AnyString => regex.write_str(".*").unwrap(),
-            Atom::Bracket(bracket) => bracket.fmt_regex(regex)?,
+            Atom::Bracket(bracket) => bracket.fmt_regex(config, regex)?,
+            Atom::Extended(extended) => extended.fmt_regex(config, regex)?,
+        }
+        Ok(())
+    }
+}
+
+impl Extended {
+    fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
+        let suffix = match self.op {
+            ExtendedOp::ZeroOrOne => "?",
+            ExtendedOp::ZeroOrMore => "*",
+            ExtendedOp::OneOrMore => "+",
+            ExtendedOp::ExactlyOne => "",
+            ExtendedOp::Not => return Err(Error::NegationInRegex),
+        };
+
+        regex.write_str("(?:").unwrap();
+        let mut first = true;
+        for alternative in &self.alternatives {
+            if first {
+                first = false;
+            } else {
+                regex.write_char('|').unwrap();
+            }
+            alternative
+                .atoms
+                .iter()
+                .try_for_each(|atom| atom.fmt_regex(config, regex))?;
         }
+        regex.write_char(')').unwrap();
+        regex.write_str(suffix).unwrap();
         Ok(())
     }
 }
@@ -156,8 +200,11 @@ impl Atom {
 impl Ast {
     /// Writes the AST as a regular expression.
     ///
-    /// Only the `anchor_begin` and `anchor_end` options in `config` affect the
-    /// results. The other options are ignored.
+    /// Only the `anchor_begin`, `anchor_end`, and `unicode` options in
+    /// `config` affect the results. The other options are ignored.
+    ///
+    /// A pattern that [contains a negation](Self::contains_negation) cannot be
+    /// converted, resulting in [`Error::NegationInRegex`].
     pub fn fmt_regex(&self, config: &Config, regex: &mut dyn Write) -> Result {
         if config.anchor_begin {
             regex.write_str(r"\A").unwrap();
@@ -176,8 +223,8 @@ impl Ast {
 
     /// Converts the AST to a regular expression.
     ///
-    /// Only the `anchor_begin` and `anchor_end` options in `config` affect the
-    /// results. The other options are ignored.
+    /// Only the `anchor_begin`, `anchor_end`, and `unicode` options in
+    /// `config` affect the results. The other options are ignored.
     pub fn to_regex(&self, config: &Config) -> std::result::Result<String, Error> {
         let mut regex = String::new();
         self.fmt_regex(config, &mut regex)?;
@@ -477,6 +524,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unicode_character_class() {
+        let config = Config {
+            unicode: true,
+            ..Config::default()
+        };
+        let bracket = Bracket {
+            complement: false,
+            items: vec![
+                BracketItem::Atom(BracketAtom::CharClass("upper".to_string())),
+                BracketItem::Atom(BracketAtom::CharClass("digit".to_string())),
+            ],
+        };
+        let atoms = vec![Atom::Bracket(bracket)];
+        let ast = Ast { atoms };
+        let regex = ast.to_regex(&config).unwrap();
+        assert_eq!(regex, r"[\p{Uppercase}\p{Nd}]");
+    }
+
+    #[test]
+    fn unicode_equivalence_class() {
+        let config = Config {
+            unicode: true,
+            ..Config::default()
+        };
+        let bracket = Bracket {
+            complement: false,
+            items: vec![BracketItem::Atom(BracketAtom::EquivalenceClass(
+                "か".to_string(),
+            ))],
+        };
+        let atoms = vec![Atom::Bracket(bracket)];
+        let ast = Ast { atoms };
+        let regex = ast.to_regex(&config).unwrap();
+        assert_eq!(regex, "[かが]");
+    }
+
     #[test]
     fn undefined_character_class() {
         let bracket = Bracket {
@@ -576,4 +660,40 @@ mod tests {
         let regex = ast.to_regex(&config).unwrap();
         assert_eq!(regex, r"\A1.9\z");
     }
+
+    #[test]
+    fn extended_patterns() {
+        let alternatives = vec![
+            Ast {
+                atoms: vec![Atom::Char('a')],
+            },
+            Ast {
+                atoms: vec![Atom::AnyChar, Atom::Char('.')],
+            },
+        ];
+        for (op, expected) in [
+            (ExtendedOp::ZeroOrOne, r"(?:a|.\.)?"),
+            (ExtendedOp::ZeroOrMore, r"(?:a|.\.)*"),
+            (ExtendedOp::OneOrMore, r"(?:a|.\.)+"),
+            (ExtendedOp::ExactlyOne, r"(?:a|.\.)"),
+        ] {
+            let alternatives = alternatives.clone();
+            let atoms = vec![Atom::Extended(Extended { op, alternatives })];
+            let ast = Ast { atoms };
+            let regex = ast.to_regex(&Config::default()).unwrap();
+            assert_eq!(regex, expected);
+        }
+    }
+
+    #[test]
+    fn negation_in_regex() {
+        let alternatives = vec![Ast {
+            atoms: vec![Atom::Char('a')],
+        }];
+        let op = ExtendedOp::Not;
+        let atoms = vec![Atom::Extended(Extended { op, alternatives })];
+        let ast = Ast { atoms };
+        let result = ast.to_regex(&Config::default());
+        assert_eq!(result, Err(Error::NegationInRegex));
+    }
 }
@@ -0,0 +1,130 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Unicode-aware bracket expression items
+//!
+//! The functions in this module are used in converting character classes and
+//! equivalence classes to regular expressions when [`Config::unicode`] is
+//! enabled.
+//!
+//! [`Config::unicode`]: crate::Config::unicode
+
+use unicode_normalization::char::compose;
+use unicode_normalization::char::decompose_canonical;
+use unicode_normalization::char::is_combining_mark;
+
+/// Returns the regular expression that matches characters in the character
+/// class.
+///
+/// The result is to be placed inside a bracket expression of the regular
+/// expression. The `name` must be one of the character class names
+/// [`ClassAsciiKind`](regex_syntax::ast::ClassAsciiKind) accepts.
+#[must_use]
+pub(super) fn char_class(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alnum" => r"\p{Alphabetic}\p{Nd}",
+        "alpha" => r"\p{Alphabetic}",
+        "ascii" => r"\x00-\x7F",
+        "blank" => r"\p{Zs}\t",
+        "cntrl" => r"\p{Cc}",
+        "digit" => r"\p{Nd}",
+        "graph" => r"[^\p{White_Space}\p{C}]",
+        "lower" => r"\p{Lowercase}",
+        "print" => r"[^\p{C}]",
+        "punct" => r"\p{P}\p{S}",
+        "space" => r"\p{White_Space}",
+        "upper" => r"\p{Uppercase}",
+        "word" => r"\w",
+        "xdigit" => r"0-9A-Fa-f",
+        _ => return None,
+    })
+}
+
+/// Ranges of combining marks that may be composed with a base character
+const COMBINING_MARKS: &[(char, char)] = &[
+    ('\u{0300}', '\u{036F}'),
+    ('\u{0483}', '\u{0489}'),
+    ('\u{0591}', '\u{05C7}'),
+    ('\u{0610}', '\u{065F}'),
+    ('\u{0900}', '\u{0DFF}'),
+    ('\u{1AB0}', '\u{1AFF}'),
+    ('\u{1DC0}', '\u{1DFF}'),
+    ('\u{20D0}', '\u{20FF}'),
+    ('\u{3099}', '\u{309A}'),
+];
+
+/// Returns the characters that are equivalent to the given character.
+///
+/// Two characters are equivalent if their canonical decompositions start with
+/// the same base character. For example, `e`, `é`, and `ệ` are equivalent.
+/// The result is sorted and contains `c` itself.
+#[must_use]
+pub(super) fn equivalents(c: char) -> Vec<char> {
+    let mut base = None;
+    decompose_canonical(c, |d| {
+        base.get_or_insert(d);
+    });
+    let base = base.unwrap_or(c);
+
+    let marks = COMBINING_MARKS
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .filter(|&m| is_combining_mark(m))
+        .collect::<Vec<char>>();
+
+    let mut result = vec![base];
+    let mut index = 0;
+    while let Some(&composed) = result.get(index) {
+        for &mark in &marks {
+            if let Some(new) = compose(composed, mark) {
+                if !result.contains(&new) {
+                    result.push(new);
+                }
+            }
+        }
+        index += 1;
+    }
+
+    if !result.contains(&c) {
+        result.push(c);
+    }
+    result.sort_unstable();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equivalents_of_base_character() {
+        let e = equivalents('e');
+        assert!(e.contains(&'e'));
+        assert!(e.contains(&'é'));
+        assert!(e.contains(&'è'));
+        assert!(e.contains(&'ê'));
+        assert!(e.contains(&'ệ'));
+        assert!(!e.contains(&'a'));
+        assert!(!e.contains(&'E'));
+        assert!(!e.contains(&'É'));
+    }
+
+    #[test]
+    fn equivalents_of_composed_character() {
+        assert_eq!(equivalents('é'), equivalents('e'));
+        assert_eq!(equivalents('ệ'), equivalents('e'));
+    }
+
+    #[test]
+    fn equivalents_of_character_without_variants() {
+        assert_eq!(equivalents('-'), ['-']);
+        assert_eq!(equivalents('あ'), ['あ']);
+    }
+
+    #[test]
+    fn equivalents_with_voiced_sound_mark() {
+        let ka = equivalents('か');
+        assert_eq!(ka, ['か', 'が']);
+        assert_eq!(equivalents('が'), ka);
+    }
+}
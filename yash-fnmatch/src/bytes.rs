@@ -0,0 +1,146 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Conversion of byte strings for matching
+//!
+//! Patterns and texts given as byte strings are converted to strings so that
+//! they can be matched in the same way as ordinary strings. Valid UTF-8
+//! sequences are decoded as usual. Each byte that is not part of a valid UTF-8
+//! sequence is mapped to a character in the range from U+10FF80 to U+10FFFF,
+//! the last 128 code points of the Supplementary Private Use Area-B.
+
+use std::ops::Range;
+use std::str::Chars;
+
+/// Returns the character that represents a byte not part of valid UTF-8.
+///
+/// The byte must be in the range from 0x80 to 0xFF.
+#[must_use]
+pub(crate) fn byte_char(byte: u8) -> char {
+    debug_assert!(byte >= 0x80, "{byte:#x} is valid UTF-8");
+    char::from_u32(0x10FF00 + u32::from(byte)).unwrap()
+}
+
+/// Iterator that decodes a byte string into characters
+///
+/// This iterator yields the characters of valid UTF-8 sequences in the byte
+/// string, and the results of [`byte_char`] for the other bytes.
+#[derive(Clone, Debug)]
+pub(crate) struct ByteChars<'a> {
+    valid: Chars<'a>,
+    rest: &'a [u8],
+}
+
+impl<'a> ByteChars<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteChars {
+            valid: "".chars(),
+            rest: bytes,
+        }
+    }
+
+    /// Returns the number of bytes the next character occupies in the byte
+    /// string along with the character.
+    fn next_with_len(&mut self) -> Option<(char, usize)> {
+        loop {
+            if let Some(c) = self.valid.next() {
+                return Some((c, c.len_utf8()));
+            }
+            let (&first, rest) = self.rest.split_first()?;
+            match std::str::from_utf8(self.rest) {
+                Ok(valid) => {
+                    self.valid = valid.chars();
+                    self.rest = &[];
+                }
+                Err(error) if error.valid_up_to() > 0 => {
+                    let (valid, rest) = self.rest.split_at(error.valid_up_to());
+                    self.valid = std::str::from_utf8(valid).unwrap().chars();
+                    self.rest = rest;
+                }
+                Err(_) => {
+                    self.rest = rest;
+                    return Some((byte_char(first), 1));
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for ByteChars<'_> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        self.next_with_len().map(|(c, _)| c)
+    }
+}
+
+/// Byte string converted to a string
+#[derive(Clone, Debug)]
+pub(crate) struct Decoded {
+    /// Result of the conversion
+    pub text: String,
+    /// Index in the original byte string for each char boundary in `text`
+    offsets: Vec<usize>,
+}
+
+impl Decoded {
+    /// Converts a byte string.
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut text = String::with_capacity(bytes.len() * 2);
+        let mut offsets = vec![0; 1];
+        let mut chars = ByteChars::new(bytes);
+        let mut offset = 0;
+        while let Some((c, len)) = chars.next_with_len() {
+            text.push(c);
+            offset += len;
+            offsets.resize(text.len(), 0);
+            offsets.push(offset);
+        }
+        Decoded { text, offsets }
+    }
+
+    /// Converts an index range in `text` to the range in the original byte
+    /// string.
+    #[must_use]
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.offsets[range.start]..self.offsets[range.end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_chars_of_valid_utf8() {
+        let chars = ByteChars::new("aé!".as_bytes()).collect::<String>();
+        assert_eq!(chars, "aé!");
+    }
+
+    #[test]
+    fn byte_chars_of_invalid_bytes() {
+        let chars = ByteChars::new(b"caf\xE9 \xE3\x81").collect::<Vec<_>>();
+        assert_eq!(
+            chars,
+            [
+                'c',
+                'a',
+                'f',
+                byte_char(0xE9),
+                ' ',
+                byte_char(0xE3),
+                byte_char(0x81)
+            ]
+        );
+    }
+
+    #[test]
+    fn decoded_ranges() {
+        let decoded = Decoded::new(b"\xFFa\xC3\xA9\xFE");
+        let ff = byte_char(0xFF).len_utf8();
+        assert_eq!(decoded.text.len(), ff + 1 + 2 + ff);
+        assert_eq!(decoded.original_range(0..0), 0..0);
+        assert_eq!(decoded.original_range(0..ff), 0..1);
+        assert_eq!(decoded.original_range(ff..ff + 3), 1..4);
+        assert_eq!(decoded.original_range(ff + 3..decoded.text.len()), 4..5);
+    }
+}
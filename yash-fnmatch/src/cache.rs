@@ -0,0 +1,172 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Cache of compiled patterns
+
+use crate::Config;
+use crate::Error;
+use crate::Pattern;
+use crate::PatternChar;
+use std::collections::HashMap;
+
+/// Bounded cache of compiled patterns
+///
+/// Compiling a pattern can be much more expensive than matching a text against
+/// it. A `PatternCache` remembers recently compiled patterns so that the same
+/// pattern used repeatedly (say, in a loop) is compiled only once.
+///
+/// The cache holds at most [`capacity`](Self::capacity) patterns. When a new
+/// pattern is added to a full cache, the least recently used pattern is
+/// removed. Patterns that fail to compile are not cached.
+///
+/// ```
+/// # use yash_fnmatch::{without_escape, Config, PatternCache};
+/// let mut cache = PatternCache::new(16);
+/// let p = cache.get_or_parse(without_escape("*.txt"), Config::default());
+/// assert_eq!(p.unwrap().find("a.txt"), Some(0..5));
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PatternCache {
+    capacity: usize,
+    clock: u64,
+    entries: HashMap<(Vec<PatternChar>, Config), (Pattern, u64)>,
+}
+
+impl PatternCache {
+    /// Creates an empty cache that holds up to `capacity` patterns.
+    ///
+    /// A capacity of zero is treated as one.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        PatternCache {
+            capacity,
+            clock: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the maximum number of patterns the cache holds.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of patterns in the cache.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Tests whether the cache is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all patterns from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+
+    /// Returns a compiled pattern, compiling it if not cached.
+    ///
+    /// This function returns the same result as
+    /// [`Pattern::parse_with_config`], but reuses the pattern compiled
+    /// previously with the same pattern characters and configuration.
+    pub fn get_or_parse<I>(&mut self, pattern: I, config: Config) -> Result<&Pattern, Error>
+    where
+        I: IntoIterator<Item = PatternChar>,
+    {
+        self.clock += 1;
+        let key = (pattern.into_iter().collect::<Vec<_>>(), config);
+
+        if !self.entries.contains_key(&key) {
+            let pattern = Pattern::parse_with_config(key.0.iter().copied(), config)?;
+            if self.entries.len() >= self.capacity {
+                self.evict();
+            }
+            self.entries.insert(key.clone(), (pattern, self.clock));
+        }
+
+        let (pattern, last_used) = self.entries.get_mut(&key).unwrap();
+        *last_used = self.clock;
+        Ok(pattern)
+    }
+
+    /// Removes the least recently used pattern.
+    fn evict(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::without_escape;
+
+    #[test]
+    fn same_pattern_is_compiled_once() {
+        let mut cache = PatternCache::new(4);
+        let config = Config::default();
+        assert!(cache.is_empty());
+
+        let p = cache.get_or_parse(without_escape("a*"), config).unwrap();
+        assert_eq!(p.find("ba"), Some(1..2));
+        let p = cache.get_or_parse(without_escape("a*"), config).unwrap();
+        assert_eq!(p.find("ab"), Some(0..2));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn patterns_are_distinguished_by_config() {
+        let mut cache = PatternCache::new(4);
+        let mut config = Config::default();
+        let p = cache.get_or_parse(without_escape("a*"), config).unwrap();
+        assert_eq!(p.find("ba"), Some(1..2));
+
+        config.anchor_begin = true;
+        let p = cache.get_or_parse(without_escape("a*"), config).unwrap();
+        assert_eq!(p.find("ba"), None);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used_pattern_is_evicted() {
+        let mut cache = PatternCache::new(2);
+        let config = Config::default();
+        cache.get_or_parse(without_escape("a"), config).unwrap();
+        cache.get_or_parse(without_escape("b"), config).unwrap();
+        cache.get_or_parse(without_escape("a"), config).unwrap();
+        cache.get_or_parse(without_escape("c"), config).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        let key = |c| (vec![PatternChar::Normal(c)], config);
+        assert!(cache.entries.contains_key(&key('a')));
+        assert!(!cache.entries.contains_key(&key('b')));
+        assert!(cache.entries.contains_key(&key('c')));
+    }
+
+    #[test]
+    fn broken_pattern_is_not_cached() {
+        let mut cache = PatternCache::new(2);
+        let config = Config::default();
+        let result = cache.get_or_parse(without_escape("[[:nothing:]]"), config);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::UndefinedCharClass("nothing".to_string())
+        );
+        assert!(cache.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 // This file is part of yash, an extended POSIX shell.
 // Copyright (C) 2022 WATANABE Yuki
 
+use crate::bytes::ByteChars;
 #[cfg(doc)]
 use crate::Pattern;
 use std::str::Chars;
@@ -110,9 +111,77 @@ pub fn without_escape(pattern: &str) -> WithoutEscape {
     WithoutEscape { chars }
 }
 
+/// Iterator returned by [`with_escape_bytes`]
+#[derive(Clone, Debug)]
+pub struct WithEscapeBytes<'a> {
+    chars: ByteChars<'a>,
+}
+
+impl Iterator for WithEscapeBytes<'_> {
+    type Item = PatternChar;
+    fn next(&mut self) -> Option<PatternChar> {
+        match self.chars.next() {
+            None => None,
+            Some('\\') => self.chars.next().map(Literal),
+            Some(c) => Some(Normal(c)),
+        }
+    }
+}
+
+/// Adapts an escaped byte string for input to a parser.
+///
+/// This function is similar to [`with_escape`], but accepts a byte string that
+/// may not be valid UTF-8. Bytes that are not part of a valid UTF-8 sequence
+/// are converted as described in the [crate documentation](crate#byte-strings).
+///
+/// ```
+/// # use yash_fnmatch::{with_escape_bytes, Pattern};
+/// let p = Pattern::parse(with_escape_bytes(b"caf\xE9\\?")).unwrap();
+/// assert!(p.is_match_bytes(b"caf\xE9?"));
+/// assert!(!p.is_match_bytes(b"caf\xE9!"));
+/// ```
+#[must_use]
+pub fn with_escape_bytes(pattern: &[u8]) -> WithEscapeBytes {
+    let chars = ByteChars::new(pattern);
+    WithEscapeBytes { chars }
+}
+
+/// Iterator returned by [`without_escape_bytes`]
+#[derive(Clone, Debug)]
+pub struct WithoutEscapeBytes<'a> {
+    chars: ByteChars<'a>,
+}
+
+impl Iterator for WithoutEscapeBytes<'_> {
+    type Item = PatternChar;
+    fn next(&mut self) -> Option<PatternChar> {
+        self.chars.next().map(Normal)
+    }
+}
+
+/// Adapts a literal byte string for input to a parser.
+///
+/// This function is similar to [`without_escape`], but accepts a byte string
+/// that may not be valid UTF-8. Bytes that are not part of a valid UTF-8
+/// sequence are converted as described in the [crate
+/// documentation](crate#byte-strings).
+///
+/// ```
+/// # use yash_fnmatch::{without_escape_bytes, Pattern};
+/// let p = Pattern::parse(without_escape_bytes(b"\xE9*")).unwrap();
+/// assert!(p.is_match_bytes(b"\xE9t\xE9"));
+/// assert!(!p.is_match_bytes("été".as_bytes()));
+/// ```
+#[must_use]
+pub fn without_escape_bytes(pattern: &[u8]) -> WithoutEscapeBytes {
+    let chars = ByteChars::new(pattern);
+    WithoutEscapeBytes { chars }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bytes::byte_char;
 
     #[test]
     fn with_escape_as_iterator() {
@@ -128,4 +197,23 @@ mod tests {
             [Normal('a'), Normal('\\'), Normal('b'), Normal('c')]
         );
     }
+
+    #[test]
+    fn with_escape_bytes_as_iterator() {
+        let v: Vec<_> = with_escape_bytes(b"a\\\xFF\xFE").collect();
+        assert_eq!(
+            v.as_slice(),
+            [
+                Normal('a'),
+                Literal(byte_char(0xFF)),
+                Normal(byte_char(0xFE))
+            ]
+        );
+    }
+
+    #[test]
+    fn without_escape_bytes_as_iterator() {
+        let v: Vec<_> = without_escape_bytes(b"\\\xFF").collect();
+        assert_eq!(v.as_slice(), [Normal('\\'), Normal(byte_char(0xFF))]);
+    }
 }
@@ -15,17 +15,47 @@
 //!     - Collating symbols (e.g. `[.ch.]`)
 //!     - Equivalence classes (e.g. `[=a=]`)
 //!     - Character classes (e.g. `[:alpha:]`)
+//! - Extended patterns (e.g. `@(foo|bar)`), if enabled by
+//!   [`Config::extended`]
 //!
-//! The current implementation does not support any locale-specific
+//! By default, the implementation does not support any locale-specific
 //! characteristics. Especially, collating symbols and equivalent classes only
 //! match the specified character sequence itself, and character classes only
-//! match ASCII characters.
+//! match ASCII characters. If [`Config::unicode`] is enabled, character classes
+//! and equivalence classes follow Unicode character properties instead.
+//! Collating symbols always match the specified character sequence only.
+//!
+//! The [`Config::pathname`], [`Config::literal_period`], and
+//! [`Config::leading_dir`] options correspond to the `FNM_PATHNAME`,
+//! `FNM_PERIOD`, and `FNM_LEADING_DIR` flags of `fnmatch(3)`, respectively.
+//! Enabling [`Config::anchor_begin`] and [`Config::anchor_end`] as well makes
+//! a pattern match the whole text as `fnmatch(3)` does.
 //!
 //! This crate is very similar to the [`fnmatch-regex`] crate in that both
 //! perform matching by converting the pattern to a regular expression. The
 //! `yash-fnmatch` crate tries to support the POSIX specification as much as
 //! possible rather than introducing unique (non-portable) functionalities.
 //!
+//! Since compiling a regular expression is relatively expensive, a pattern
+//! made up only of literal characters, `?`, `*`, and bracket expressions
+//! matching a single character is matched directly without a regular
+//! expression. To avoid compiling the same pattern repeatedly, you can use a
+//! [`PatternCache`].
+//!
+//! # Byte strings
+//!
+//! Patterns and texts are usually given as `str`, but [`with_escape_bytes`],
+//! [`without_escape_bytes`], and the `*_bytes` methods of [`Pattern`] accept
+//! byte strings that may not be valid UTF-8, such as filenames in a legacy
+//! encoding. Valid UTF-8 sequences in a byte string are decoded as usual, and
+//! each of the other bytes is treated as a single character that only matches
+//! the same byte. A `?` in a pattern matches such a byte, and `*` matches a
+//! sequence containing such bytes.
+//!
+//! Internally, the bytes are mapped to the characters in the range from
+//! U+10FF80 to U+10FFFF. A text that contains those private-use characters may
+//! therefore match a byte that is not part of a valid UTF-8 sequence.
+//!
 //! # Example
 //!
 //! ```
@@ -37,13 +67,27 @@
 //! [`fnmatch-regex`]: https://crates.io/crates/fnmatch-regex
 
 pub mod ast;
+mod bytes;
+mod cache;
 mod char_iter;
+mod negation;
+mod pathname;
+mod simple;
 
 use self::ast::Ast;
+use self::bytes::Decoded;
+pub use self::cache::PatternCache;
 pub use self::char_iter::*;
+use self::negation::Matcher;
+use self::pathname::Components;
+use self::simple::SimpleMatcher;
 use regex::Regex;
 use regex::RegexBuilder;
+#[cfg(unix)]
+use std::ffi::OsStr;
 use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
 use thiserror::Error;
 
 /// Configuration for a pattern
@@ -71,6 +115,11 @@ pub struct Config {
     /// the filename `.foo.txt`.
     ///
     /// When `literal_period` is `false`, the above restriction does not apply.
+    ///
+    /// If [`pathname`](Self::pathname) is also `true`, the restriction applies
+    /// to a period at the beginning of every pathname component, that is, a
+    /// period following a slash as well. This is equivalent to specifying
+    /// both `FNM_PATHNAME` and `FNM_PERIOD` to `fnmatch(3)`.
     pub literal_period: bool,
 
     /// Whether the pattern matches shortest part of text
@@ -86,6 +135,56 @@ pub struct Config {
     /// For non-literal patterns, the "simple" case folding rules defined by
     /// Unicode are applied to allow case-insensitive matches.
     pub case_insensitive: bool,
+
+    /// Whether the pattern may contain extended patterns
+    ///
+    /// When `extended` is `true`, the following ksh-style extended patterns
+    /// are recognized, where `...` is one or more patterns separated by `|`:
+    ///
+    /// - `?(...)`: zero or one occurrence of any of the patterns
+    /// - `*(...)`: any number of occurrences of any of the patterns
+    /// - `+(...)`: one or more occurrences of any of the patterns
+    /// - `@(...)`: exactly one occurrence of any of the patterns
+    /// - `!(...)`: anything that does not match any of the patterns
+    ///
+    /// When `extended` is `false`, the characters are treated as in POSIX.
+    pub extended: bool,
+
+    /// Whether bracket expressions follow Unicode character properties
+    ///
+    /// When `unicode` is `true`, character classes in bracket expressions are
+    /// defined by Unicode properties. For example, `[[:alpha:]]` matches any
+    /// alphabetic character including `é` and `あ`, and `[[:upper:]]` matches
+    /// `É`. An equivalence class of a single character matches any character
+    /// whose canonical decomposition starts with the same base character. For
+    /// example, `[[=e=]]` matches `e`, `é`, `è`, and `ê`.
+    ///
+    /// When `unicode` is `false`, character classes only match ASCII
+    /// characters and equivalence classes only match the specified character
+    /// itself, which is the behavior in the POSIX locale.
+    pub unicode: bool,
+
+    /// Whether slashes in text are matched only by slashes in the pattern
+    ///
+    /// When `pathname` is `true`, a slash in text must be matched by a slash in
+    /// the pattern. Wildcard patterns (`?` and `*`) and bracket expressions
+    /// never match a slash, nor do extended patterns even if they contain a
+    /// slash. This is equivalent to `FNM_PATHNAME` of `fnmatch(3)`.
+    ///
+    /// When `pathname` is `false`, a slash is treated like any other
+    /// character.
+    pub pathname: bool,
+
+    /// Whether the pattern may match a leading part of a pathname
+    ///
+    /// When `leading_dir` and [`anchor_end`](Self::anchor_end) are both
+    /// `true`, a match may end just before a slash in the text as well as at
+    /// the end of the text. For example, the pattern `src/*` matches the text
+    /// `src/main/lib.rs` in addition to `src/main`. This is equivalent to
+    /// `FNM_LEADING_DIR` of `fnmatch(3)`.
+    ///
+    /// This option has no effect if `anchor_end` is `false`.
+    pub leading_dir: bool,
 }
 
 /// Error that may happen in building a pattern.
@@ -120,6 +219,16 @@ pub enum Error {
     #[error("character class [:{0}:] used as range bound")]
     CharClassInRange(String),
 
+    /// Negation in an extended pattern converted to a regular expression
+    ///
+    /// This error may happen only when converting an AST containing an
+    /// [`ExtendedOp::Not`](ast::ExtendedOp::Not) to a regular expression with
+    /// [`Ast::to_regex`]. Compiling such an AST into a [`Pattern`] does not
+    /// fail because a pattern containing a negation is matched without a
+    /// regular expression.
+    #[error("negation cannot be converted to a regular expression")]
+    NegationInRegex,
+
     /// Error in underlying regular expression processing
     #[error(transparent)]
     RegexError(#[from] regex::Error),
@@ -130,11 +239,23 @@ pub enum Error {
 enum Body {
     /// Literal string pattern
     Literal(String),
+    /// Direct matcher for patterns without complex elements
+    Simple {
+        matcher: SimpleMatcher,
+        starts_with_literal_dot: bool,
+    },
     /// Compiled regular expression
     Regex {
         regex: Regex,
         starts_with_literal_dot: bool,
     },
+    /// Backtracking matcher for patterns containing negation
+    Matcher {
+        matcher: Matcher,
+        starts_with_literal_dot: bool,
+    },
+    /// Patterns for pathname components
+    Pathname(Components),
 }
 
 /// Compiled globbing pattern
@@ -162,7 +283,7 @@ impl Pattern {
         I: IntoIterator<Item = PatternChar>,
         <I as IntoIterator>::IntoIter: Clone,
     {
-        Self::from_ast_and_config(&Ast::new(pattern), config)
+        Self::from_ast_and_config(&Ast::new_with_config(pattern, &config), config)
     }
 
     /// Compiles a pattern from the given AST with defaulted configuration.
@@ -174,6 +295,18 @@ impl Pattern {
     pub fn from_ast_and_config(ast: &Ast, config: Config) -> Result<Self, Error> {
         let body = if let Some(literal) = ast.to_literal() {
             Body::Literal(literal)
+        } else if config.pathname {
+            Body::Pathname(Components::new(ast, &config)?)
+        } else if ast.contains_negation() {
+            Body::Matcher {
+                matcher: Matcher::new(&ast.atoms, &config)?,
+                starts_with_literal_dot: ast.starts_with_literal_dot(),
+            }
+        } else if let Some(matcher) = SimpleMatcher::new(ast, &config) {
+            Body::Simple {
+                matcher,
+                starts_with_literal_dot: ast.starts_with_literal_dot(),
+            }
         } else {
             Body::Regex {
                 regex: RegexBuilder::new(&ast.to_regex(&config)?)
@@ -203,7 +336,9 @@ impl Pattern {
     pub fn as_literal(&self) -> Option<&str> {
         match &self.body {
             Body::Literal(s) => Some(s),
-            Body::Regex { .. } => None,
+            Body::Simple { .. } | Body::Regex { .. } | Body::Matcher { .. } | Body::Pathname(_) => {
+                None
+            }
         }
     }
 
@@ -215,13 +350,18 @@ impl Pattern {
     pub fn into_literal(self) -> Result<String, Self> {
         match self.body {
             Body::Literal(s) => Ok(s),
-            Body::Regex { .. } => Err(self),
+            Body::Simple { .. } | Body::Regex { .. } | Body::Matcher { .. } | Body::Pathname(_) => {
+                Err(self)
+            }
         }
     }
 
     /// Tests whether this pattern matches the given text.
     #[must_use]
     pub fn is_match(&self, text: &str) -> bool {
+        if self.matches_leading_dir() {
+            return self.find(text).is_some();
+        }
         match &self.body {
             Body::Literal(s) => match (self.config.anchor_begin, self.config.anchor_end) {
                 (false, false) => text.contains(s),
@@ -239,15 +379,85 @@ impl Pattern {
                 let at_index = if reject_initial_dot { 1 } else { 0 };
                 regex.is_match_at(text, at_index)
             }
+            Body::Simple { .. } | Body::Matcher { .. } | Body::Pathname(_) => {
+                self.find(text).is_some()
+            }
+        }
+    }
+
+    /// Returns the positions in `text` where a match by a backtracking or
+    /// simple matcher may start, in ascending order.
+    fn matcher_starts(&self, text: &str, starts_with_literal_dot: bool) -> Vec<usize> {
+        let reject_initial_dot =
+            self.config.literal_period && !starts_with_literal_dot && text.starts_with('.');
+        if self.config.anchor_begin {
+            if reject_initial_dot {
+                vec![]
+            } else {
+                vec![0]
+            }
+        } else {
+            #[allow(clippy::bool_to_int_with_if)]
+            let at_index = if reject_initial_dot { 1 } else { 0 };
+            negation::boundaries(text, at_index).collect()
         }
     }
 
+    /// Tests whether a match may end before a slash in the text.
+    fn matches_leading_dir(&self) -> bool {
+        self.config.leading_dir && self.config.anchor_end
+    }
+
+    /// Applies `find` to `text` and each leading part of `text` followed by a
+    /// slash, and returns the preferred match.
+    ///
+    /// A match starting earlier is preferred if `prefer_later_start` is false,
+    /// and later otherwise. Among matches starting at the same position, the
+    /// longest one is preferred unless `config.shortest_match` is true.
+    fn find_leading_dir<F>(
+        &self,
+        text: &str,
+        find: F,
+        prefer_later_start: bool,
+    ) -> Option<Range<usize>>
+    where
+        F: Fn(&Self, &str) -> Option<Range<usize>>,
+    {
+        let ends = text.match_indices('/').map(|(index, _)| index);
+        let ends = ends.chain(std::iter::once(text.len()));
+        ends.filter_map(|end| find(self, &text[..end]))
+            .reduce(|best, range| {
+                let is_better = if range.start != best.start {
+                    (range.start > best.start) == prefer_later_start
+                } else if self.config.shortest_match {
+                    range.end < best.end
+                } else {
+                    range.end > best.end
+                };
+                if is_better {
+                    range
+                } else {
+                    best
+                }
+            })
+    }
+
     /// Returns the index range where this pattern matches in the given text.
     ///
     /// If `self` matches (part of) `text`, this function returns the index
     /// range of the first match. Otherwise, the result is `None`.
     #[must_use]
     pub fn find(&self, text: &str) -> Option<Range<usize>> {
+        if self.matches_leading_dir() {
+            self.find_leading_dir(text, Self::find_whole, false)
+        } else {
+            self.find_whole(text)
+        }
+    }
+
+    /// Returns the index range of the first match that may end only at the end
+    /// of `text` if `config.anchor_end` is true.
+    fn find_whole(&self, text: &str) -> Option<Range<usize>> {
         match &self.body {
             Body::Literal(s) => match (self.config.anchor_begin, self.config.anchor_end) {
                 (false, false) => text.find(s).map(|pos| pos..pos + s.len()),
@@ -255,6 +465,18 @@ impl Pattern {
                 (false, true) => text.ends_with(s).then(|| text.len() - s.len()..text.len()),
                 (true, true) => (text == s).then(|| 0..s.len()),
             },
+            Body::Simple {
+                matcher,
+                starts_with_literal_dot,
+            } => {
+                let reject_initial_dot =
+                    self.config.literal_period && !starts_with_literal_dot && text.starts_with('.');
+                if reject_initial_dot && self.config.anchor_begin {
+                    return None;
+                }
+                let at_index = usize::from(reject_initial_dot);
+                matcher.find_at(text, at_index, self.config.anchor_begin, &self.config)
+            }
             Body::Regex {
                 regex,
                 starts_with_literal_dot,
@@ -265,6 +487,14 @@ impl Pattern {
                 let at_index = if reject_initial_dot { 1 } else { 0 };
                 regex.find_at(text, at_index).map(|m| m.range())
             }
+            Body::Matcher {
+                matcher,
+                starts_with_literal_dot,
+            } => {
+                let starts = self.matcher_starts(text, *starts_with_literal_dot);
+                matcher.find_from(text, starts, &self.config)
+            }
+            Body::Pathname(components) => components.find(text, &self.config),
         }
     }
 
@@ -274,6 +504,16 @@ impl Pattern {
     /// range of the last match. Otherwise, the result is `None`.
     #[must_use]
     pub fn rfind(&self, text: &str) -> Option<Range<usize>> {
+        if self.matches_leading_dir() {
+            self.find_leading_dir(text, Self::rfind_whole, true)
+        } else {
+            self.rfind_whole(text)
+        }
+    }
+
+    /// Returns the index range of the last match that may end only at the end
+    /// of `text` if `config.anchor_end` is true.
+    fn rfind_whole(&self, text: &str) -> Option<Range<usize>> {
         match &self.body {
             Body::Literal(s) => match (self.config.anchor_begin, self.config.anchor_end) {
                 (false, false) => text.rfind(s).map(|pos| pos..pos + s.len()),
@@ -282,11 +522,20 @@ impl Pattern {
                 (true, true) => (text == s).then(|| 0..s.len()),
             },
 
+            Body::Simple {
+                matcher,
+                starts_with_literal_dot,
+            } => self
+                .matcher_starts(text, *starts_with_literal_dot)
+                .into_iter()
+                .rev()
+                .find_map(|start| matcher.find_at(text, start, true, &self.config)),
+
             Body::Regex {
                 regex,
                 starts_with_literal_dot: _,
             } => {
-                let mut range = self.find(text)?;
+                let mut range = self.find_whole(text)?;
 
                 while let Some(next_range) = (range.start + 1..=text.len())
                     .find(|&index| text.is_char_boundary(index))
@@ -297,8 +546,99 @@ impl Pattern {
 
                 Some(range)
             }
+
+            Body::Matcher {
+                matcher,
+                starts_with_literal_dot,
+            } => {
+                let starts = self.matcher_starts(text, *starts_with_literal_dot);
+                matcher.find_from(text, starts.into_iter().rev(), &self.config)
+            }
+
+            Body::Pathname(components) => components.rfind(text, &self.config),
+        }
+    }
+
+    /// Tests whether this pattern matches the given byte string.
+    ///
+    /// See the [crate documentation](crate#byte-strings) for how bytes that
+    /// are not part of a valid UTF-8 sequence are matched.
+    #[must_use]
+    pub fn is_match_bytes(&self, text: &[u8]) -> bool {
+        match std::str::from_utf8(text) {
+            Ok(text) => self.is_match(text),
+            Err(_) => self.is_match(&Decoded::new(text).text),
+        }
+    }
+
+    /// Returns the index range where this pattern matches in the given byte
+    /// string.
+    ///
+    /// This function is the byte string version of [`find`](Self::find). The
+    /// result is a range of byte indices in `text`.
+    #[must_use]
+    pub fn find_bytes(&self, text: &[u8]) -> Option<Range<usize>> {
+        match std::str::from_utf8(text) {
+            Ok(text) => self.find(text),
+            Err(_) => {
+                let decoded = Decoded::new(text);
+                let range = self.find(&decoded.text)?;
+                Some(decoded.original_range(range))
+            }
         }
     }
+
+    /// Returns the index range where this pattern matches in the given byte
+    /// string.
+    ///
+    /// This function is the byte string version of [`rfind`](Self::rfind).
+    /// The result is a range of byte indices in `text`.
+    #[must_use]
+    pub fn rfind_bytes(&self, text: &[u8]) -> Option<Range<usize>> {
+        match std::str::from_utf8(text) {
+            Ok(text) => self.rfind(text),
+            Err(_) => {
+                let decoded = Decoded::new(text);
+                let range = self.rfind(&decoded.text)?;
+                Some(decoded.original_range(range))
+            }
+        }
+    }
+
+    /// Tests whether this pattern matches the given OS string.
+    ///
+    /// This is equivalent to [`is_match_bytes`](Self::is_match_bytes) on the
+    /// bytes of the OS string.
+    #[cfg(unix)]
+    #[inline]
+    #[must_use]
+    pub fn is_match_os_str(&self, text: &OsStr) -> bool {
+        self.is_match_bytes(text.as_bytes())
+    }
+
+    /// Returns the index range where this pattern matches in the given OS
+    /// string.
+    ///
+    /// This is equivalent to [`find_bytes`](Self::find_bytes) on the bytes of
+    /// the OS string.
+    #[cfg(unix)]
+    #[inline]
+    #[must_use]
+    pub fn find_os_str(&self, text: &OsStr) -> Option<Range<usize>> {
+        self.find_bytes(text.as_bytes())
+    }
+
+    /// Returns the index range where this pattern matches in the given OS
+    /// string.
+    ///
+    /// This is equivalent to [`rfind_bytes`](Self::rfind_bytes) on the bytes
+    /// of the OS string.
+    #[cfg(unix)]
+    #[inline]
+    #[must_use]
+    pub fn rfind_os_str(&self, text: &OsStr) -> Option<Range<usize>> {
+        self.rfind_bytes(text.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -812,6 +1152,55 @@ mod tests {
         assert_eq!(p.find("[ij]"), Some(1..3));
     }
 
+    #[test]
+    fn non_ascii_equivalence_class() {
+        let p = Pattern::parse(without_escape("[![=é=]]")).unwrap();
+        assert_eq!(p.find("é"), None);
+        assert_eq!(p.find("e"), Some(0..1));
+        assert_eq!(p.find("è"), Some(0..2));
+    }
+
+    #[test]
+    fn unicode_equivalence_class() {
+        let config = Config {
+            unicode: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("[[=e=]]"), config).unwrap();
+        assert_eq!(p.find("e"), Some(0..1));
+        assert_eq!(p.find("é"), Some(0..2));
+        assert_eq!(p.find("ệ"), Some(0..3));
+        assert_eq!(p.find("E"), None);
+        assert_eq!(p.find("a"), None);
+
+        let p = Pattern::parse_with_config(without_escape("[![=é=]]"), config).unwrap();
+        assert_eq!(p.find("e"), None);
+        assert_eq!(p.find("è"), None);
+        assert_eq!(p.find("a"), Some(0..1));
+    }
+
+    #[test]
+    fn unicode_character_classes() {
+        let config = Config {
+            unicode: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("[[:alpha:]]"), config).unwrap();
+        assert_eq!(p.find("é"), Some(0..2));
+        assert_eq!(p.find("あ"), Some(0..3));
+        assert_eq!(p.find("-"), None);
+
+        let p = Pattern::parse_with_config(without_escape("[[:upper:]]"), config).unwrap();
+        assert_eq!(p.find("É"), Some(0..2));
+        assert_eq!(p.find("é"), None);
+
+        let p = Pattern::parse_with_config(without_escape("[![:punct:]]"), config).unwrap();
+        assert_eq!(p.find("「a"), Some(3..4));
+
+        let p = Pattern::parse(without_escape("[[:alpha:]]")).unwrap();
+        assert_eq!(p.find("é"), None);
+    }
+
     #[test]
     fn character_class_alnum() {
         let p = Pattern::parse(without_escape("[[:alnum:]]")).unwrap();
@@ -1319,4 +1708,273 @@ mod tests {
         assert_eq!(p.rfind("A-Z"), Some(0..3));
         assert_eq!(p.rfind("b&b"), None);
     }
+
+    #[test]
+    fn extended_pattern() {
+        let config = Config {
+            extended: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("x+(ab|c)"), config).unwrap();
+        assert_eq!(p.as_literal(), None);
+
+        assert!(!p.is_match("x"));
+        assert!(p.is_match("xab"));
+        assert!(p.is_match("-xcabc-"));
+
+        assert_eq!(p.find("xabcx"), Some(0..4));
+        assert_eq!(p.rfind("xabcx"), Some(0..4));
+    }
+
+    #[test]
+    fn negated_extended_pattern() {
+        let config = Config {
+            extended: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("a!(b)"), config).unwrap();
+        assert_eq!(p.as_literal(), None);
+
+        assert!(p.is_match("a"));
+        assert!(p.is_match("ab"));
+        assert_eq!(p.find("ab"), Some(0..1));
+        assert_eq!(p.find("xacx"), Some(1..4));
+        assert_eq!(p.rfind("aab"), Some(1..2));
+    }
+
+    #[test]
+    fn negated_extended_pattern_with_anchors() {
+        let config = Config {
+            extended: true,
+            anchor_begin: true,
+            anchor_end: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("!(*.txt)"), config).unwrap();
+        assert!(p.is_match("foo"));
+        assert!(!p.is_match("foo.txt"));
+        assert!(p.is_match("foo.txt.bak"));
+        assert_eq!(p.find("foo"), Some(0..3));
+        assert_eq!(p.find("foo.txt"), None);
+    }
+
+    #[test]
+    fn negated_extended_pattern_with_shortest_match() {
+        let config = Config {
+            extended: true,
+            anchor_begin: true,
+            shortest_match: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("!(a)"), config).unwrap();
+        assert_eq!(p.find("abc"), Some(0..0));
+
+        let p = Pattern::parse_with_config(without_escape("a!(b*)"), config).unwrap();
+        assert_eq!(p.find("abc"), Some(0..1));
+    }
+
+    #[test]
+    fn negated_extended_pattern_with_literal_period() {
+        let config = Config {
+            extended: true,
+            anchor_begin: true,
+            anchor_end: true,
+            literal_period: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("!(foo)"), config).unwrap();
+        assert!(p.is_match("bar"));
+        assert!(!p.is_match(".bar"));
+    }
+
+    #[test]
+    fn extended_pattern_starting_with_literal_period() {
+        let config = Config {
+            extended: true,
+            anchor_begin: true,
+            anchor_end: true,
+            literal_period: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("@(.a)"), config).unwrap();
+        assert!(p.is_match(".a"));
+
+        let p = Pattern::parse_with_config(without_escape("@(.a|b)"), config).unwrap();
+        assert!(p.is_match(".a"));
+        assert!(p.is_match("b"));
+
+        let p = Pattern::parse_with_config(without_escape("@(?a)"), config).unwrap();
+        assert!(!p.is_match(".a"));
+    }
+
+    #[test]
+    fn byte_string_texts() {
+        let p = Pattern::parse(without_escape("*.txt")).unwrap();
+        assert!(p.is_match_bytes(b"caf\xE9.txt"));
+        assert!(!p.is_match_bytes(b"caf\xE9.rs"));
+        assert_eq!(p.find_bytes(b"\xE9.txt!"), Some(0..5));
+        assert_eq!(p.find_bytes("é.txt!".as_bytes()), Some(0..6));
+
+        let p = Pattern::parse(without_escape("a?b")).unwrap();
+        assert_eq!(p.find_bytes(b"\xFFa\xFFb\xFF"), Some(1..4));
+        assert_eq!(p.rfind_bytes(b"a\xFFba\xFEb"), Some(3..6));
+        assert_eq!(p.find_bytes(b"a\xFF\xFFb"), None);
+
+        let p = Pattern::parse(without_escape("[!a]")).unwrap();
+        assert_eq!(p.find_bytes(b"a\x80"), Some(1..2));
+    }
+
+    #[test]
+    fn byte_string_patterns() {
+        let p = Pattern::parse(with_escape_bytes(b"\xE9*")).unwrap();
+        assert!(p.is_match_bytes(b"\xE9t\xE9"));
+        assert!(!p.is_match_bytes("été".as_bytes()));
+        assert_eq!(p.find_bytes(b"t\xE9t"), Some(1..3));
+
+        let p = Pattern::parse(with_escape_bytes(b"[\xE8-\xEA]")).unwrap();
+        assert_eq!(p.find_bytes(b"\xE7\xE9"), Some(1..2));
+        assert_eq!(p.find_bytes(b"\xE7\xEB"), None);
+    }
+
+    #[test]
+    fn byte_string_with_literal_period() {
+        let config = Config {
+            anchor_begin: true,
+            anchor_end: true,
+            literal_period: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("*"), config).unwrap();
+        assert!(p.is_match_bytes(b"\xE9"));
+        assert!(!p.is_match_bytes(b".\xE9"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn os_string_texts() {
+        use std::os::unix::ffi::OsStrExt as _;
+        let p = Pattern::parse(without_escape("?*")).unwrap();
+        let text = OsStr::from_bytes(b"\xFFabc");
+        assert!(p.is_match_os_str(text));
+        assert_eq!(p.find_os_str(text), Some(0..4));
+        assert_eq!(p.rfind_os_str(text), Some(3..4));
+    }
+
+    fn fnmatch_config() -> Config {
+        Config {
+            anchor_begin: true,
+            anchor_end: true,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn pathname_wildcards_do_not_match_slash() {
+        let config = Config {
+            pathname: true,
+            ..fnmatch_config()
+        };
+        let p = Pattern::parse_with_config(without_escape("*"), config).unwrap();
+        assert!(p.is_match("abc"));
+        assert!(!p.is_match("a/c"));
+
+        let p = Pattern::parse_with_config(without_escape("*/*"), config).unwrap();
+        assert!(p.is_match("a/c"));
+        assert!(p.is_match("/"));
+        assert!(!p.is_match("a/b/c"));
+
+        let p = Pattern::parse_with_config(without_escape("a?c"), config).unwrap();
+        assert!(!p.is_match("a/c"));
+        let p = Pattern::parse_with_config(without_escape("a[/]c"), config).unwrap();
+        assert!(!p.is_match("a/c"));
+        let p = Pattern::parse_with_config(without_escape("a[!x]c"), config).unwrap();
+        assert!(!p.is_match("a/c"));
+
+        let p = Pattern::parse_with_config(without_escape("*"), fnmatch_config()).unwrap();
+        assert!(p.is_match("a/c"));
+    }
+
+    #[test]
+    fn pathname_unanchored_find() {
+        let config = Config {
+            pathname: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("b*"), config).unwrap();
+        assert_eq!(p.find("ab/bc"), Some(1..2));
+        assert_eq!(p.rfind("ab/bc"), Some(3..5));
+
+        let p = Pattern::parse_with_config(without_escape("?/b"), config).unwrap();
+        assert_eq!(p.find("xa/bc"), Some(1..4));
+        assert_eq!(p.find("xa/cb"), None);
+    }
+
+    #[test]
+    fn pathname_with_literal_period() {
+        let config = Config {
+            pathname: true,
+            literal_period: true,
+            ..fnmatch_config()
+        };
+        let p = Pattern::parse_with_config(without_escape("*/*"), config).unwrap();
+        assert!(p.is_match("a/b"));
+        assert!(!p.is_match("a/.b"));
+        assert!(!p.is_match(".a/b"));
+
+        let p = Pattern::parse_with_config(without_escape(".*/.*"), config).unwrap();
+        assert!(p.is_match(".a/.b"));
+
+        let p = Pattern::parse_with_config(without_escape("a/[.]b"), config).unwrap();
+        assert!(!p.is_match("a/.b"));
+
+        // Without the pathname option, only the first period is special.
+        let config = Config {
+            literal_period: true,
+            ..fnmatch_config()
+        };
+        let p = Pattern::parse_with_config(without_escape("*/*"), config).unwrap();
+        assert!(p.is_match("a/.b"));
+        assert!(!p.is_match(".a/b"));
+    }
+
+    #[test]
+    fn leading_dir() {
+        let config = Config {
+            leading_dir: true,
+            ..fnmatch_config()
+        };
+        let p = Pattern::parse_with_config(without_escape("src"), config).unwrap();
+        assert!(p.is_match("src"));
+        assert!(p.is_match("src/main.rs"));
+        assert!(!p.is_match("srcx"));
+        assert_eq!(p.find("src/a/b"), Some(0..3));
+
+        let config = Config {
+            pathname: true,
+            ..config
+        };
+        let p = Pattern::parse_with_config(without_escape("src/*"), config).unwrap();
+        assert!(p.is_match("src/main/lib.rs"));
+        assert!(!p.is_match("src"));
+        assert_eq!(p.find("src/main/lib.rs"), Some(0..8));
+    }
+
+    #[test]
+    fn leading_dir_preferences() {
+        let config = Config {
+            anchor_end: true,
+            leading_dir: true,
+            ..Config::default()
+        };
+        let p = Pattern::parse_with_config(without_escape("b*"), config).unwrap();
+        assert_eq!(p.find("ab/cb/d"), Some(1..7));
+        assert_eq!(p.rfind("ab/cb/d"), Some(4..7));
+
+        let config = Config {
+            shortest_match: true,
+            ..config
+        };
+        let p = Pattern::parse_with_config(without_escape("b*"), config).unwrap();
+        assert_eq!(p.find("ab/cb/d"), Some(1..2));
+    }
 }
@@ -0,0 +1,250 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Matching of patterns containing negation
+//!
+//! A negation `!(...)` in an extended pattern cannot be expressed by a regular
+//! expression the [`regex`] crate supports. A pattern containing a negation is
+//! instead compiled into a tree of [`Matcher`]s that is evaluated by
+//! backtracking. Parts of the pattern that do not contain any negation are
+//! still compiled into regular expressions.
+
+use crate::ast::Ast;
+use crate::ast::Atom;
+use crate::ast::ExtendedOp;
+use crate::Config;
+use crate::Error;
+use regex::Regex;
+use regex::RegexBuilder;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Compiled part of a pattern that is matched against a whole string
+#[derive(Clone, Debug)]
+pub(crate) enum Matcher {
+    /// Regular expression anchored at both ends
+    Regex(Regex),
+    /// Matchers that match consecutive substrings
+    Sequence(Vec<Matcher>),
+    /// Extended pattern
+    Extended {
+        op: ExtendedOp,
+        alternatives: Vec<Matcher>,
+    },
+}
+
+/// Returns the indices of character boundaries in `text` not less than `start`.
+pub(crate) fn boundaries(text: &str, start: usize) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    (start..=text.len()).filter(|&index| text.is_char_boundary(index))
+}
+
+impl Matcher {
+    /// Compiles a sequence of atoms.
+    pub fn new(atoms: &[Atom], config: &Config) -> Result<Self, Error> {
+        let mut matchers = Vec::new();
+        let mut run = Vec::new();
+        for atom in atoms {
+            match atom {
+                Atom::Extended(extended) if extended.contains_negation() => {
+                    if !run.is_empty() {
+                        let run = std::mem::take(&mut run);
+                        matchers.push(Self::regex(run, config)?);
+                    }
+                    let alternatives = extended
+                        .alternatives
+                        .iter()
+                        .map(|alternative| Self::new(&alternative.atoms, config))
+                        .collect::<Result<_, _>>()?;
+                    let op = extended.op;
+                    matchers.push(Matcher::Extended { op, alternatives });
+                }
+                _ => run.push(atom.clone()),
+            }
+        }
+        if !run.is_empty() || matchers.is_empty() {
+            matchers.push(Self::regex(run, config)?);
+        }
+
+        if matchers.len() == 1 {
+            Ok(matchers.pop().unwrap())
+        } else {
+            Ok(Matcher::Sequence(matchers))
+        }
+    }
+
+    /// Compiles atoms that do not contain any negation into a regular
+    /// expression.
+    fn regex(atoms: Vec<Atom>, config: &Config) -> Result<Self, Error> {
+        let mut regex_config = *config;
+        regex_config.anchor_begin = true;
+        regex_config.anchor_end = true;
+        let regex = Ast { atoms }.to_regex(&regex_config)?;
+        let regex = RegexBuilder::new(&regex)
+            .case_insensitive(config.case_insensitive)
+            .dot_matches_new_line(true)
+            .build()?;
+        Ok(Matcher::Regex(regex))
+    }
+
+    /// Tests whether this matcher matches the whole `text`.
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Matcher::Regex(regex) => regex.is_match(text),
+
+            Matcher::Sequence(matchers) => {
+                let mut positions = BTreeSet::from([0]);
+                for (i, matcher) in matchers.iter().enumerate() {
+                    let is_last = i == matchers.len() - 1;
+                    let mut next_positions = BTreeSet::new();
+                    for &start in &positions {
+                        if is_last {
+                            if matcher.matches(&text[start..]) {
+                                return true;
+                            }
+                            continue;
+                        }
+                        for end in boundaries(text, start) {
+                            if matcher.matches(&text[start..end]) {
+                                next_positions.insert(end);
+                            }
+                        }
+                    }
+                    positions = next_positions;
+                }
+                false
+            }
+
+            Matcher::Extended { op, alternatives } => {
+                let any = |text: &str| alternatives.iter().any(|m| m.matches(text));
+                match op {
+                    ExtendedOp::ExactlyOne => any(text),
+                    ExtendedOp::ZeroOrOne => text.is_empty() || any(text),
+                    ExtendedOp::Not => !any(text),
+                    ExtendedOp::ZeroOrMore | ExtendedOp::OneOrMore => {
+                        if text.is_empty() {
+                            return *op == ExtendedOp::ZeroOrMore || any(text);
+                        }
+                        // Split the text into non-empty substrings each
+                        // matching one of the alternatives.
+                        let mut reachable = vec![false; text.len() + 1];
+                        reachable[0] = true;
+                        for start in boundaries(text, 0) {
+                            if !reachable[start] {
+                                continue;
+                            }
+                            for end in boundaries(text, start).skip(1) {
+                                if !reachable[end] && any(&text[start..end]) {
+                                    reachable[end] = true;
+                                }
+                            }
+                        }
+                        reachable[text.len()]
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the index range where this matcher matches in `text`, starting
+    /// at one of the given positions.
+    ///
+    /// Candidate start positions are tried in the order given by `starts`.
+    /// For each start position, the longest match is preferred unless
+    /// `config.shortest_match` is true. If `config.anchor_end` is true, only
+    /// matches ending at the end of `text` are considered.
+    pub fn find_from<S>(&self, text: &str, starts: S, config: &Config) -> Option<Range<usize>>
+    where
+        S: IntoIterator<Item = usize>,
+    {
+        for start in starts {
+            let found = if config.anchor_end {
+                self.matches(&text[start..]).then_some(text.len())
+            } else if config.shortest_match {
+                boundaries(text, start).find(|&end| self.matches(&text[start..end]))
+            } else {
+                boundaries(text, start)
+                    .rev()
+                    .find(|&end| self.matches(&text[start..end]))
+            };
+            if let Some(end) = found {
+                return Some(start..end);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::without_escape;
+
+    fn matcher(pattern: &str) -> Matcher {
+        let config = Config {
+            extended: true,
+            ..Config::default()
+        };
+        let ast = Ast::new_with_config(without_escape(pattern), &config);
+        Matcher::new(&ast.atoms, &config).unwrap()
+    }
+
+    #[test]
+    fn negation_alone() {
+        let m = matcher("!(foo|bar)");
+        assert!(m.matches(""));
+        assert!(m.matches("fo"));
+        assert!(!m.matches("foo"));
+        assert!(!m.matches("bar"));
+        assert!(m.matches("foobar"));
+    }
+
+    #[test]
+    fn negation_in_sequence() {
+        let m = matcher("a!(b)c");
+        assert!(m.matches("ac"));
+        assert!(!m.matches("abc"));
+        assert!(m.matches("abbc"));
+        assert!(m.matches("axc"));
+        assert!(!m.matches("ab"));
+    }
+
+    #[test]
+    fn negation_followed_by_wildcard() {
+        // "!(*.txt)*" matches anything because the negation can match an
+        // empty string.
+        let m = matcher("!(*.txt)*");
+        assert!(m.matches("a.txt"));
+
+        let m = matcher("!(*.txt)");
+        assert!(!m.matches("a.txt"));
+        assert!(m.matches("a.txt.bak"));
+    }
+
+    #[test]
+    fn repeated_negation() {
+        let m = matcher("+(!(a))");
+        assert!(m.matches(""));
+        assert!(!m.matches("a"));
+        assert!(m.matches("b"));
+        assert!(m.matches("ab"));
+
+        let m = matcher("*(!(x)|x)");
+        assert!(m.matches(""));
+        assert!(m.matches("xyz"));
+    }
+
+    #[test]
+    fn nested_negation() {
+        let m = matcher("!(!(abc))");
+        assert!(m.matches("abc"));
+        assert!(!m.matches("ab"));
+    }
+
+    #[test]
+    fn multibyte_characters() {
+        let m = matcher("!(あ)い");
+        assert!(m.matches("い"));
+        assert!(!m.matches("あい"));
+        assert!(m.matches("ああい"));
+    }
+}
@@ -0,0 +1,171 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Matching in the pathname mode
+//!
+//! When [`Config::pathname`] is true, a pattern is split at slashes into
+//! components, each of which is compiled into a separate [`Pattern`]. The text
+//! is also split at slashes, and the components of the pattern are matched
+//! against consecutive components of the text. Since no text component
+//! contains a slash, wildcards and bracket expressions never match a slash.
+
+use crate::ast::Ast;
+use crate::ast::Atom;
+use crate::Config;
+use crate::Error;
+use crate::Pattern;
+use std::ops::Range;
+
+/// Pattern compiled in the pathname mode
+#[derive(Clone, Debug)]
+pub(crate) struct Components {
+    patterns: Vec<Pattern>,
+}
+
+/// Returns the index ranges of the components of `text` separated by slashes.
+fn text_components(text: &str) -> Vec<Range<usize>> {
+    let mut components = Vec::new();
+    let mut start = 0;
+    for (index, _) in text.match_indices('/') {
+        components.push(start..index);
+        start = index + 1;
+    }
+    components.push(start..text.len());
+    components
+}
+
+impl Components {
+    /// Compiles the AST split at slashes.
+    ///
+    /// Only slashes that are not part of a bracket expression or extended
+    /// pattern separate components.
+    pub fn new(ast: &Ast, config: &Config) -> Result<Self, Error> {
+        let components = ast.atoms.split(|atom| *atom == Atom::Char('/'));
+        let count = components.clone().count();
+        let patterns = components
+            .enumerate()
+            .map(|(index, atoms)| {
+                let mut config = *config;
+                config.pathname = false;
+                config.leading_dir = false;
+                config.anchor_begin |= index > 0;
+                config.anchor_end |= index < count - 1;
+                let ast = Ast {
+                    atoms: atoms.to_vec(),
+                };
+                Pattern::from_ast_and_config(&ast, config)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Components { patterns })
+    }
+
+    /// Matches the patterns against the text components starting at
+    /// `components[first]`.
+    ///
+    /// The first pattern is matched by `find_first`, and the other patterns
+    /// by [`Pattern::find`].
+    fn find_from<F>(
+        &self,
+        text: &str,
+        components: &[Range<usize>],
+        first: usize,
+        find_first: F,
+    ) -> Option<Range<usize>>
+    where
+        F: FnOnce(&Pattern, &str) -> Option<Range<usize>>,
+    {
+        let (first_pattern, rest_patterns) = self.patterns.split_first().unwrap();
+        let first_component = components[first].clone();
+        let range = find_first(first_pattern, &text[first_component.clone()])?;
+        let start = first_component.start + range.start;
+        let mut end = first_component.start + range.end;
+
+        for (pattern, component) in rest_patterns.iter().zip(&components[first + 1..]) {
+            let range = pattern.find(&text[component.clone()])?;
+            end = component.start + range.end;
+        }
+        Some(start..end)
+    }
+
+    /// Returns the indices of the text components where a match may start, in
+    /// ascending order.
+    fn first_components(&self, components: &[Range<usize>], config: &Config) -> Range<usize> {
+        let count = (components.len() + 1).saturating_sub(self.patterns.len());
+        let first = if config.anchor_end {
+            count.saturating_sub(1)
+        } else {
+            0
+        };
+        let end = if config.anchor_begin {
+            count.min(1)
+        } else {
+            count
+        };
+        first..end
+    }
+
+    /// Returns the index range of the first match in `text`.
+    pub fn find(&self, text: &str, config: &Config) -> Option<Range<usize>> {
+        let components = text_components(text);
+        self.first_components(&components, config)
+            .find_map(|first| self.find_from(text, &components, first, Pattern::find))
+    }
+
+    /// Returns the index range of the last match in `text`.
+    pub fn rfind(&self, text: &str, config: &Config) -> Option<Range<usize>> {
+        let components = text_components(text);
+        self.first_components(&components, config)
+            .rev()
+            .find_map(|first| self.find_from(text, &components, first, Pattern::rfind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::without_escape;
+
+    fn components(pattern: &str, config: &Config) -> Components {
+        Components::new(&Ast::new(without_escape(pattern)), config).unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn text_components_of_paths() {
+        assert_eq!(text_components(""), [0..0]);
+        assert_eq!(text_components("a"), [0..1]);
+        assert_eq!(text_components("/a/bc"), [0..0, 1..2, 3..5]);
+        assert_eq!(text_components("a//"), [0..1, 2..2, 3..3]);
+    }
+
+    #[test]
+    fn single_component() {
+        let config = Config::default();
+        let p = components("b*", &config);
+        assert_eq!(p.find("ab/bc/b", &config), Some(1..2));
+        assert_eq!(p.rfind("ab/bc/b", &config), Some(6..7));
+        assert_eq!(p.find("a/c", &config), None);
+    }
+
+    #[test]
+    fn multiple_components() {
+        let config = Config::default();
+        let p = components("*/b?", &config);
+        assert_eq!(p.find("xa/bc/bd", &config), Some(0..5));
+        assert_eq!(p.rfind("xa/bc/bd", &config), Some(5..8));
+        assert_eq!(p.find("a/b", &config), None);
+    }
+
+    #[test]
+    fn anchored_components() {
+        let config = Config {
+            anchor_begin: true,
+            anchor_end: true,
+            ..Config::default()
+        };
+        let p = components("*/b?", &config);
+        assert_eq!(p.find("a/bc", &config), Some(0..4));
+        assert_eq!(p.find("a/bc/bd", &config), None);
+        assert_eq!(p.find("bc", &config), None);
+    }
+}
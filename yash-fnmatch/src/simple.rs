@@ -0,0 +1,396 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+
+//! Direct matching of simple patterns
+//!
+//! Compiling a regular expression is much more expensive than matching a short
+//! text against it. A pattern made up only of literal characters, `?`, `*`,
+//! and bracket expressions that match a single character is instead compiled
+//! into a [`SimpleMatcher`], which simulates a small nondeterministic automaton
+//! built directly from the AST.
+//!
+//! The matcher finds the same match as the regular expression that would be
+//! compiled from the pattern: the leftmost match is preferred, and among
+//! matches starting at the same position, the longest one is preferred unless
+//! [`Config::shortest_match`] is true.
+
+use crate::ast::Ast;
+use crate::ast::Atom;
+use crate::ast::Bracket;
+use crate::ast::BracketAtom;
+use crate::ast::BracketItem;
+use crate::Config;
+use regex_syntax::ast::ClassAsciiKind;
+use std::ops::Range;
+
+/// Item of a bracket expression that matches a single character
+#[derive(Clone, Debug)]
+enum Item {
+    Char(char),
+    Range(char, char),
+    Class(ClassAsciiKind),
+}
+
+/// Element of a simple pattern
+#[derive(Clone, Debug)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyString,
+    Bracket { complement: bool, items: Vec<Item> },
+}
+
+/// Tests whether the character belongs to the ASCII character class.
+fn class_matches(kind: &ClassAsciiKind, c: char) -> bool {
+    use ClassAsciiKind::*;
+    match kind {
+        Alnum => c.is_ascii_alphanumeric(),
+        Alpha => c.is_ascii_alphabetic(),
+        Ascii => c.is_ascii(),
+        Blank => c == ' ' || c == '\t',
+        Cntrl => c.is_ascii_control(),
+        Digit => c.is_ascii_digit(),
+        Graph => c.is_ascii_graphic(),
+        Lower => c.is_ascii_lowercase(),
+        Print => c == ' ' || c.is_ascii_graphic(),
+        Punct => c.is_ascii_punctuation(),
+        Space => matches!(c, '\t' | '\n' | '\x0B' | '\x0C' | '\r' | ' '),
+        Upper => c.is_ascii_uppercase(),
+        Word => c.is_ascii_alphanumeric() || c == '_',
+        Xdigit => c.is_ascii_hexdigit(),
+    }
+}
+
+impl Item {
+    /// Converts a bracket atom that is not a range bound.
+    fn from_atom(atom: &BracketAtom, config: &Config) -> Option<Self> {
+        match atom {
+            BracketAtom::Char(c) => Some(Item::Char(*c)),
+            BracketAtom::CollatingSymbol(value) => single_char(value).map(Item::Char),
+            BracketAtom::EquivalenceClass(value) if !config.unicode => {
+                single_char(value).map(Item::Char)
+            }
+            BracketAtom::EquivalenceClass(_) => None,
+            BracketAtom::CharClass(_) if config.unicode => None,
+            BracketAtom::CharClass(name) => ClassAsciiKind::from_name(name).map(Item::Class),
+        }
+    }
+
+    /// Converts a bracket item.
+    fn from_bracket_item(item: &BracketItem, config: &Config) -> Option<Self> {
+        match item {
+            BracketItem::Atom(atom) => Self::from_atom(atom, config),
+            BracketItem::Range(range) => {
+                let start = range_bound(range.start())?;
+                let end = range_bound(range.end())?;
+                (start <= end).then_some(Item::Range(start, end))
+            }
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Item::Char(d) => c == *d,
+            Item::Range(start, end) => (*start..=*end).contains(&c),
+            Item::Class(kind) => class_matches(kind, c),
+        }
+    }
+}
+
+/// Returns the only character in the string, if any.
+fn single_char(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// Returns the character that bounds a range.
+///
+/// A collating symbol or equivalence class bounds the range with its first
+/// character, as in the conversion to a regular expression.
+fn range_bound(atom: &BracketAtom) -> Option<char> {
+    match atom {
+        BracketAtom::Char(c) => Some(*c),
+        BracketAtom::CollatingSymbol(value) | BracketAtom::EquivalenceClass(value) => {
+            value.chars().next()
+        }
+        BracketAtom::CharClass(_) => None,
+    }
+}
+
+impl Token {
+    fn from_atom(atom: &Atom, config: &Config) -> Option<Self> {
+        match atom {
+            Atom::Char(c) => Some(Token::Char(*c)),
+            Atom::AnyChar => Some(Token::AnyChar),
+            Atom::AnyString => Some(Token::AnyString),
+            Atom::Bracket(Bracket { complement, items }) if !items.is_empty() => {
+                let items = items
+                    .iter()
+                    .map(|item| Item::from_bracket_item(item, config))
+                    .collect::<Option<_>>()?;
+                let complement = *complement;
+                Some(Token::Bracket { complement, items })
+            }
+            Atom::Bracket(_) | Atom::Extended(_) => None,
+        }
+    }
+
+    /// Tests whether this token consumes the character.
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Char(d) => c == *d,
+            Token::AnyChar | Token::AnyString => true,
+            Token::Bracket { complement, items } => {
+                items.iter().any(|item| item.matches(c)) != *complement
+            }
+        }
+    }
+}
+
+/// Compiled simple pattern
+#[derive(Clone, Debug)]
+pub(crate) struct SimpleMatcher {
+    tokens: Vec<Token>,
+}
+
+impl SimpleMatcher {
+    /// Compiles the AST if it is simple enough.
+    ///
+    /// Returns `None` if the AST contains an element that this matcher does not
+    /// support or that would be an error in the regular expression, or if
+    /// `config.case_insensitive` is true. Such a pattern should be compiled
+    /// into a regular expression instead.
+    pub fn new(ast: &Ast, config: &Config) -> Option<Self> {
+        if config.case_insensitive {
+            return None;
+        }
+        let tokens = ast
+            .atoms
+            .iter()
+            .map(|atom| Token::from_atom(atom, config))
+            .collect::<Option<_>>()?;
+        Some(SimpleMatcher { tokens })
+    }
+
+    /// Adds a thread at the given state to the set of active states.
+    ///
+    /// Each state remembers the start position of the match in progress. If
+    /// the state is already active with a start position not after `start`,
+    /// the state is left intact since the existing thread is preferred.
+    fn add_thread(&self, states: &mut [Option<usize>], mut index: usize, start: usize) {
+        loop {
+            match states[index] {
+                Some(old_start) if old_start <= start => return,
+                _ => states[index] = Some(start),
+            }
+            match self.tokens.get(index) {
+                // `*` may match an empty string, so the next state is also active.
+                Some(Token::AnyString) => index += 1,
+                _ => return,
+            }
+        }
+    }
+
+    /// Returns the index range of the first match in `text[from..]`.
+    ///
+    /// If `anchored` is true, only a match starting at `from` is considered.
+    /// If `config.anchor_end` is true, only a match ending at the end of `text`
+    /// is considered.
+    pub fn find_at(
+        &self,
+        text: &str,
+        from: usize,
+        anchored: bool,
+        config: &Config,
+    ) -> Option<Range<usize>> {
+        let final_state = self.tokens.len();
+        let mut current = vec![None; final_state + 1];
+        let mut next = vec![None; final_state + 1];
+        let mut found: Option<Range<usize>> = None;
+        let mut chars = text[from..].chars();
+        let mut position = from;
+
+        loop {
+            if found.is_none() && (!anchored || position == from) {
+                self.add_thread(&mut current, 0, position);
+            }
+
+            if let Some(start) = current[final_state] {
+                if !config.anchor_end || position == text.len() {
+                    let is_better = match &found {
+                        None => true,
+                        Some(range) => {
+                            start < range.start || start == range.start && !config.shortest_match
+                        }
+                    };
+                    if is_better {
+                        found = Some(start..position);
+                    }
+                }
+            }
+
+            if let Some(range) = &found {
+                // Threads that started after the match cannot produce a
+                // better match.
+                for state in &mut current {
+                    if let Some(start) = *state {
+                        if start > range.start || start == range.start && config.shortest_match {
+                            *state = None;
+                        }
+                    }
+                }
+            }
+
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break,
+            };
+            if (anchored || found.is_some()) && current.iter().all(Option::is_none) {
+                break;
+            }
+
+            for (index, token) in self.tokens.iter().enumerate() {
+                if let Some(start) = current[index] {
+                    match token {
+                        Token::AnyString => self.add_thread(&mut next, index, start),
+                        _ if token.matches(c) => self.add_thread(&mut next, index + 1, start),
+                        _ => (),
+                    }
+                }
+            }
+
+            position += c.len_utf8();
+            std::mem::swap(&mut current, &mut next);
+            next.fill(None);
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::without_escape;
+
+    fn matcher(pattern: &str) -> SimpleMatcher {
+        let ast = Ast::new(without_escape(pattern));
+        SimpleMatcher::new(&ast, &Config::default()).unwrap()
+    }
+
+    #[test]
+    fn unsupported_patterns() {
+        let config = Config::default();
+        let ast = Ast::new(without_escape("[[.ch.]]"));
+        assert!(SimpleMatcher::new(&ast, &config).is_none());
+        let ast = Ast::new(without_escape("[z-a]"));
+        assert!(SimpleMatcher::new(&ast, &config).is_none());
+        let ast = Ast::new(without_escape("[[:nothing:]]"));
+        assert!(SimpleMatcher::new(&ast, &config).is_none());
+
+        let config = Config {
+            case_insensitive: true,
+            ..Config::default()
+        };
+        let ast = Ast::new(without_escape("a*"));
+        assert!(SimpleMatcher::new(&ast, &config).is_none());
+
+        let config = Config {
+            unicode: true,
+            ..Config::default()
+        };
+        let ast = Ast::new(without_escape("[[:alpha:]]"));
+        assert!(SimpleMatcher::new(&ast, &config).is_none());
+    }
+
+    #[test]
+    fn leftmost_longest_match() {
+        let m = matcher("a*a");
+        let config = Config::default();
+        assert_eq!(m.find_at("banana", 0, false, &config), Some(1..6));
+        assert_eq!(m.find_at("banana", 2, false, &config), Some(3..6));
+        assert_eq!(m.find_at("banana", 0, true, &config), None);
+        assert_eq!(m.find_at("banana", 5, false, &config), None);
+    }
+
+    #[test]
+    fn leftmost_shortest_match() {
+        let m = matcher("a*a");
+        let config = Config {
+            shortest_match: true,
+            ..Config::default()
+        };
+        assert_eq!(m.find_at("banana", 0, false, &config), Some(1..4));
+        assert_eq!(m.find_at("banana", 1, true, &config), Some(1..4));
+    }
+
+    #[test]
+    fn match_anchored_at_end() {
+        let m = matcher("?a");
+        let config = Config {
+            anchor_end: true,
+            ..Config::default()
+        };
+        assert_eq!(m.find_at("banana", 0, false, &config), Some(4..6));
+        assert_eq!(m.find_at("banana", 0, true, &config), None);
+        assert_eq!(m.find_at("bananas", 0, false, &config), None);
+    }
+
+    #[test]
+    fn earlier_start_wins_over_shorter_match() {
+        let m = matcher("*c");
+        let config = Config {
+            shortest_match: true,
+            ..Config::default()
+        };
+        assert_eq!(m.find_at("abc", 0, false, &config), Some(0..3));
+    }
+
+    #[test]
+    fn bracket_expressions() {
+        let m = matcher("[!a-c[:digit:]]");
+        let config = Config::default();
+        assert_eq!(m.find_at("ab1cdx", 0, false, &config), Some(4..5));
+
+        let m = matcher("[[=x=][.y.]]");
+        assert_eq!(m.find_at("abyz", 0, false, &config), Some(2..3));
+    }
+
+    #[test]
+    fn same_results_as_regex() {
+        let patterns = ["a*", "*a", "a?b", "*a*b*", "[ab]*[!b]", "?*", "**a"];
+        let texts = ["", "a", "ab", "ba", "aab", "abab", "bbaab", "abba"];
+        for pattern in patterns {
+            let ast = Ast::new(without_escape(pattern));
+            for flags in 0..8 {
+                let config = Config {
+                    anchor_begin: flags & 1 != 0,
+                    anchor_end: flags & 2 != 0,
+                    shortest_match: flags & 4 != 0,
+                    ..Config::default()
+                };
+                let m = SimpleMatcher::new(&ast, &config).unwrap();
+                let regex = regex::RegexBuilder::new(&ast.to_regex(&config).unwrap())
+                    .swap_greed(config.shortest_match)
+                    .build()
+                    .unwrap();
+                for text in texts {
+                    let expected = regex.find(text).map(|m| m.range());
+                    let actual = m.find_at(text, 0, config.anchor_begin, &config);
+                    assert_eq!(actual, expected, "{pattern:?} {text:?} {config:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multibyte_characters() {
+        let m = matcher("?*い");
+        let config = Config::default();
+        assert_eq!(m.find_at("あいうい", 0, false, &config), Some(0..12));
+        assert_eq!(m.find_at("あいうい", 3, false, &config), Some(3..12));
+    }
+}
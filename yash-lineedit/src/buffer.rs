@@ -0,0 +1,280 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Edit buffer
+
+use std::ops::Range;
+
+/// Kind of word used in word motions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WordKind {
+    /// Sequence of alphanumeric and underscore characters, or sequence of
+    /// other non-blank characters
+    Viword,
+    /// Sequence of non-blank characters
+    Bigword,
+    /// Sequence of alphanumeric characters
+    Emacsword,
+}
+
+impl WordKind {
+    /// Classifies a character.
+    ///
+    /// Blank characters are class 0. Characters in the same word have the
+    /// same non-zero class. For emacs words, all non-alphanumeric characters
+    /// are treated as blanks.
+    fn class(self, c: char) -> u8 {
+        if self == WordKind::Emacsword {
+            u8::from(c.is_alphanumeric())
+        } else if c.is_whitespace() {
+            0
+        } else if self == WordKind::Bigword || c.is_alphanumeric() || c == '_' {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// Line being edited
+///
+/// The buffer is a sequence of characters with a cursor position. The cursor
+/// is an index into the sequence and may be equal to the length of the
+/// sequence.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Buffer {
+    /// Characters in the buffer
+    pub chars: Vec<char>,
+    /// Cursor position
+    pub cursor: usize,
+}
+
+/// Creates a buffer containing the string with the cursor at the end.
+impl From<&str> for Buffer {
+    fn from(s: &str) -> Self {
+        let chars: Vec<char> = s.chars().collect();
+        let cursor = chars.len();
+        Self { chars, cursor }
+    }
+}
+
+impl Buffer {
+    /// Returns the number of characters in the buffer.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Tests whether the buffer is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Returns the content of the buffer as a string.
+    #[must_use]
+    pub fn as_string(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Returns the content of the range as a string.
+    #[must_use]
+    pub fn substring(&self, range: Range<usize>) -> String {
+        self.chars[range].iter().collect()
+    }
+
+    /// Inserts characters at the cursor and moves the cursor after them.
+    pub fn insert(&mut self, s: &[char]) {
+        self.chars
+            .splice(self.cursor..self.cursor, s.iter().copied());
+        self.cursor += s.len();
+    }
+
+    /// Removes the range and returns the removed characters.
+    ///
+    /// The cursor is moved to the start of the range if it was after the
+    /// start.
+    pub fn remove(&mut self, range: Range<usize>) -> Vec<char> {
+        let start = range.start;
+        let removed = self.chars.drain(range).collect();
+        self.cursor = self.cursor.min(start);
+        removed
+    }
+
+    /// Limits the cursor to the last character.
+    ///
+    /// In the vi command mode, the cursor cannot be after the last character.
+    pub fn clamp_cursor(&mut self) {
+        self.cursor = self.cursor.min(self.len().saturating_sub(1));
+    }
+
+    /// Returns the index of the first non-blank character.
+    #[must_use]
+    pub fn first_nonblank(&self) -> usize {
+        self.chars
+            .iter()
+            .position(|c| !c.is_whitespace())
+            .unwrap_or(self.len())
+    }
+
+    /// Returns the index of the beginning of the next word.
+    ///
+    /// If there is no next word, the result is the length of the buffer.
+    #[must_use]
+    pub fn next_word_start(&self, from: usize, kind: WordKind) -> usize {
+        let mut i = from;
+        if let Some(&c) = self.chars.get(i) {
+            let class = kind.class(c);
+            if class != 0 {
+                while self.chars.get(i).is_some_and(|&c| kind.class(c) == class) {
+                    i += 1;
+                }
+            }
+        }
+        while self.chars.get(i).is_some_and(|&c| kind.class(c) == 0) {
+            i += 1;
+        }
+        i
+    }
+
+    /// Returns the index of the beginning of the previous word.
+    ///
+    /// If there is no previous word, the result is 0.
+    #[must_use]
+    pub fn prev_word_start(&self, from: usize, kind: WordKind) -> usize {
+        let mut i = from.min(self.len());
+        while i > 0 && kind.class(self.chars[i - 1]) == 0 {
+            i -= 1;
+        }
+        if i > 0 {
+            let class = kind.class(self.chars[i - 1]);
+            while i > 0 && kind.class(self.chars[i - 1]) == class {
+                i -= 1;
+            }
+        }
+        i
+    }
+
+    /// Returns the index just after the end of the current or next word.
+    ///
+    /// If there is no such word, the result is the length of the buffer.
+    #[must_use]
+    pub fn next_word_end(&self, from: usize, kind: WordKind) -> usize {
+        let mut i = from;
+        while self.chars.get(i).is_some_and(|&c| kind.class(c) == 0) {
+            i += 1;
+        }
+        if let Some(&c) = self.chars.get(i) {
+            let class = kind.class(c);
+            while self.chars.get(i).is_some_and(|&c| kind.class(c) == class) {
+                i += 1;
+            }
+        }
+        i
+    }
+
+    /// Returns the index of the last character of the current or next word.
+    ///
+    /// If there is no such word, the result is the index of the last
+    /// character.
+    #[must_use]
+    pub fn word_end(&self, from: usize, kind: WordKind) -> usize {
+        let mut i = from + 1;
+        while self.chars.get(i).is_some_and(|&c| kind.class(c) == 0) {
+            i += 1;
+        }
+        let Some(&c) = self.chars.get(i) else {
+            return self.len().saturating_sub(1);
+        };
+        let class = kind.class(c);
+        while self
+            .chars
+            .get(i + 1)
+            .is_some_and(|&c| kind.class(c) == class)
+        {
+            i += 1;
+        }
+        i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove() {
+        let mut buffer = Buffer::from("ac");
+        buffer.cursor = 1;
+        buffer.insert(&['b']);
+        assert_eq!(buffer.as_string(), "abc");
+        assert_eq!(buffer.cursor, 2);
+
+        assert_eq!(buffer.remove(0..2), ['a', 'b']);
+        assert_eq!(buffer.as_string(), "c");
+        assert_eq!(buffer.cursor, 0);
+    }
+
+    #[test]
+    fn next_word_start() {
+        let buffer = Buffer::from("foo.bar  baz");
+        assert_eq!(buffer.next_word_start(0, WordKind::Viword), 3);
+        assert_eq!(buffer.next_word_start(3, WordKind::Viword), 4);
+        assert_eq!(buffer.next_word_start(4, WordKind::Viword), 9);
+        assert_eq!(buffer.next_word_start(9, WordKind::Viword), 12);
+        assert_eq!(buffer.next_word_start(0, WordKind::Bigword), 9);
+        assert_eq!(buffer.next_word_start(7, WordKind::Bigword), 9);
+    }
+
+    #[test]
+    fn prev_word_start() {
+        let buffer = Buffer::from("foo.bar  baz");
+        assert_eq!(buffer.prev_word_start(12, WordKind::Viword), 9);
+        assert_eq!(buffer.prev_word_start(9, WordKind::Viword), 4);
+        assert_eq!(buffer.prev_word_start(4, WordKind::Viword), 3);
+        assert_eq!(buffer.prev_word_start(3, WordKind::Viword), 0);
+        assert_eq!(buffer.prev_word_start(0, WordKind::Viword), 0);
+        assert_eq!(buffer.prev_word_start(9, WordKind::Bigword), 0);
+    }
+
+    #[test]
+    fn word_end() {
+        let buffer = Buffer::from("foo.bar  baz");
+        assert_eq!(buffer.word_end(0, WordKind::Viword), 2);
+        assert_eq!(buffer.word_end(2, WordKind::Viword), 3);
+        assert_eq!(buffer.word_end(3, WordKind::Viword), 6);
+        assert_eq!(buffer.word_end(6, WordKind::Viword), 11);
+        assert_eq!(buffer.word_end(0, WordKind::Bigword), 6);
+        assert_eq!(buffer.word_end(11, WordKind::Bigword), 11);
+    }
+
+    #[test]
+    fn next_word_end() {
+        let buffer = Buffer::from("foo.bar  baz");
+        assert_eq!(buffer.next_word_end(0, WordKind::Emacsword), 3);
+        assert_eq!(buffer.next_word_end(3, WordKind::Emacsword), 7);
+        assert_eq!(buffer.next_word_end(7, WordKind::Emacsword), 12);
+        assert_eq!(buffer.next_word_end(12, WordKind::Emacsword), 12);
+        assert_eq!(buffer.next_word_end(0, WordKind::Bigword), 7);
+        assert_eq!(buffer.prev_word_start(7, WordKind::Emacsword), 4);
+    }
+
+    #[test]
+    fn first_nonblank() {
+        assert_eq!(Buffer::from("  a b").first_nonblank(), 2);
+        assert_eq!(Buffer::from("  ").first_nonblank(), 2);
+    }
+}
@@ -0,0 +1,366 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rendering the edit line on the terminal
+//!
+//! The [`Renderer`] composes the escape sequences that redraw the prompt and
+//! the edit line. The layout is computed from the display width of each
+//! character, so that lines containing wide characters wrap at the same
+//! positions as the terminal wraps them. Newlines in the edit line start a new
+//! row so that a command spanning more than one line can be edited at once.
+
+use std::fmt::Write as _;
+use unicode_width::UnicodeWidthChar;
+
+/// Number of columns assumed if the terminal width is unknown
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Position on the terminal relative to the beginning of the prompt
+///
+/// `column` may be equal to the terminal width, in which case the terminal
+/// is waiting to wrap the line before printing the next character.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Position {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Returns the position at which a character of the given width is
+    /// printed.
+    ///
+    /// If the character does not fit in the current row, the terminal prints
+    /// it at the beginning of the next row.
+    #[must_use]
+    fn wrap_for(self, char_width: usize, width: usize) -> Self {
+        if char_width > 0 && self.column + char_width > width {
+            Position {
+                row: self.row + 1,
+                column: 0,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Returns the position after printing a character of the given width.
+    #[must_use]
+    fn advance(self, char_width: usize, width: usize) -> Self {
+        let start = self.wrap_for(char_width, width);
+        Position {
+            column: start.column + char_width,
+            ..start
+        }
+    }
+}
+
+/// Returns the form in which the character is shown in the edit line.
+///
+/// Control characters are shown in the caret notation like `^A`.
+fn visible_form(c: char) -> (Option<char>, char) {
+    if c.is_ascii_control() {
+        (Some('^'), ((c as u8) ^ 0x40) as char)
+    } else {
+        (None, c)
+    }
+}
+
+/// Returns the display width of a character shown in the edit line.
+#[must_use]
+pub fn char_width(c: char) -> usize {
+    match visible_form(c) {
+        (Some(_), _) => 2,
+        (None, c) => c.width().unwrap_or(0),
+    }
+}
+
+/// Computes the position after printing the prompt.
+///
+/// Escape sequences in the prompt are assumed to occupy no space on the
+/// terminal.
+#[must_use]
+pub fn prompt_end(prompt: &str, width: usize) -> Position {
+    let mut position = Position::default();
+    let mut chars = prompt.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                position.row += 1;
+                position.column = 0;
+            }
+            '\r' => position.column = 0,
+            '\t' => {
+                let start = position.wrap_for(1, width);
+                let column = (start.column / 8 + 1) * 8;
+                position = Position {
+                    column: column.min(width),
+                    ..start
+                };
+            }
+            '\x1B' => skip_escape_sequence(&mut chars),
+            c if c.is_control() => (),
+            c => position = position.advance(c.width().unwrap_or(0), width),
+        }
+    }
+    position
+}
+
+/// Skips the rest of an escape sequence following an escape character.
+fn skip_escape_sequence(chars: &mut std::str::Chars) {
+    match chars.next() {
+        // Control sequence, terminated by a final byte
+        Some('[') => {
+            for c in chars.by_ref() {
+                if ('\x40'..='\x7E').contains(&c) {
+                    break;
+                }
+            }
+        }
+        // Operating system command, terminated by BEL or ST
+        Some(']') => {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\x07' => break,
+                    '\x1B' => {
+                        chars.next();
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Composer of the escape sequences that draw the edit line
+///
+/// The renderer remembers the row of the cursor relative to the beginning of
+/// the prompt so that the next redraw can start at the beginning of the
+/// prompt.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Renderer {
+    cursor_row: usize,
+}
+
+impl Renderer {
+    /// Creates a renderer for a prompt that has not yet been printed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the escape sequences that redraw the prompt and the line.
+    ///
+    /// The output moves the cursor to the beginning of the previously drawn
+    /// prompt, clears the rest of the screen, prints the prompt and the line,
+    /// and moves the cursor to the `cursor` index in the line.
+    pub fn redraw(&mut self, prompt: &str, line: &[char], cursor: usize, width: usize) -> String {
+        let width = if width == 0 { DEFAULT_WIDTH } else { width };
+        let mut output = String::new();
+        if self.cursor_row > 0 {
+            write!(output, "\x1B[{}A", self.cursor_row).unwrap();
+        }
+        output.push_str("\r\x1B[J");
+        output.push_str(prompt);
+
+        let mut position = prompt_end(prompt, width);
+        let mut cursor_position = None;
+        for (index, &c) in line.iter().enumerate() {
+            if c == '\n' {
+                if index == cursor {
+                    cursor_position = Some(position);
+                }
+                output.push_str("\r\n");
+                position = Position {
+                    row: position.row + 1,
+                    column: 0,
+                };
+                continue;
+            }
+            let char_width = char_width(c);
+            if index == cursor {
+                cursor_position = Some(position.wrap_for(char_width, width));
+            }
+            let (caret, c) = visible_form(c);
+            output.extend(caret);
+            output.push(c);
+            position = position.advance(char_width, width);
+        }
+
+        if position.column >= width {
+            // Leave the pending wrap state so that the cursor position is
+            // definite.
+            output.push('\n');
+            position.row += 1;
+            position.column = 0;
+        }
+        let cursor_position = cursor_position.unwrap_or(position);
+
+        let up = position.row - cursor_position.row;
+        if up > 0 {
+            write!(output, "\x1B[{up}A").unwrap();
+        }
+        output.push('\r');
+        if cursor_position.column > 0 {
+            write!(output, "\x1B[{}C", cursor_position.column).unwrap();
+        }
+        self.cursor_row = cursor_position.row;
+        output
+    }
+
+    /// Returns the escape sequences that clear the screen.
+    ///
+    /// The next [`redraw`](Self::redraw) will print the prompt at the top of
+    /// the screen.
+    pub fn clear_screen(&mut self) -> &'static str {
+        self.cursor_row = 0;
+        "\x1B[H\x1B[2J"
+    }
+
+    /// Forgets the position of the prompt.
+    ///
+    /// This function should be called when something other than the edit line
+    /// has been printed, so that the next [`redraw`](Self::redraw) prints the
+    /// prompt at the current cursor position.
+    pub fn reset(&mut self) {
+        self.cursor_row = 0;
+    }
+}
+
+/// Arranges the items in columns that fit in the terminal width.
+///
+/// The items are ordered from top to bottom in each column, like the output
+/// of `ls`. Each row ends with a newline.
+#[must_use]
+pub fn columns(items: &[&str], width: usize) -> String {
+    let width = if width == 0 { DEFAULT_WIDTH } else { width };
+    let widths: Vec<usize> = items
+        .iter()
+        .map(|item| item.chars().map(char_width).sum())
+        .collect();
+    let column_width = widths.iter().max().map_or(0, |max| max + 2);
+    let column_count = (width / column_width.max(1)).max(1);
+    let row_count = items.len().div_ceil(column_count);
+
+    let mut result = String::new();
+    for row in 0..row_count {
+        for index in (row..items.len()).step_by(row_count) {
+            result.push_str(items[index]);
+            if index + row_count < items.len() {
+                let padding = column_width - widths[index];
+                result.extend(std::iter::repeat(' ').take(padding));
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_end_plain() {
+        assert_eq!(prompt_end("$ ", 80), Position { row: 0, column: 2 });
+        assert_eq!(prompt_end("a\nbc", 80), Position { row: 1, column: 2 });
+        assert_eq!(prompt_end("abcd", 4), Position { row: 0, column: 4 });
+        assert_eq!(prompt_end("abcde", 4), Position { row: 1, column: 1 });
+    }
+
+    #[test]
+    fn prompt_end_with_escape_sequences() {
+        let prompt = "\x1B[1;32mgreen\x1B[m\x1B]0;title\x07> ";
+        assert_eq!(prompt_end(prompt, 80), Position { row: 0, column: 7 });
+    }
+
+    #[test]
+    fn prompt_end_with_wide_characters() {
+        assert_eq!(prompt_end("あい", 80), Position { row: 0, column: 4 });
+        assert_eq!(prompt_end("aあ", 2), Position { row: 1, column: 2 });
+    }
+
+    #[test]
+    fn redraw_single_row() {
+        let mut renderer = Renderer::new();
+        let line: Vec<char> = "echo".chars().collect();
+        let output = renderer.redraw("$ ", &line, 4, 80);
+        assert_eq!(output, "\r\x1B[J$ echo\r\x1B[6C");
+        let output = renderer.redraw("$ ", &line, 0, 80);
+        assert_eq!(output, "\r\x1B[J$ echo\r\x1B[2C");
+    }
+
+    #[test]
+    fn redraw_wrapping_line() {
+        let mut renderer = Renderer::new();
+        let line: Vec<char> = "abcdefg".chars().collect();
+        let output = renderer.redraw("$ ", &line, 1, 4);
+        assert_eq!(output, "\r\x1B[J$ abcdefg\x1B[2A\r\x1B[3C");
+
+        // The next redraw starts from the row of the prompt.
+        let output = renderer.redraw("$ ", &line, 7, 4);
+        assert_eq!(output, "\r\x1B[J$ abcdefg\r\x1B[1C");
+        let output = renderer.redraw("$ ", &line, 7, 4);
+        assert_eq!(output, "\x1B[2A\r\x1B[J$ abcdefg\r\x1B[1C");
+    }
+
+    #[test]
+    fn redraw_line_filling_last_row() {
+        let mut renderer = Renderer::new();
+        let line: Vec<char> = "ab".chars().collect();
+        let output = renderer.redraw("$ ", &line, 2, 4);
+        assert_eq!(output, "\r\x1B[J$ ab\n\r");
+    }
+
+    #[test]
+    fn redraw_wide_characters() {
+        let mut renderer = Renderer::new();
+        // The second wide character does not fit in the first row.
+        let line: Vec<char> = "あい".chars().collect();
+        let output = renderer.redraw("$ ", &line, 1, 5);
+        assert_eq!(output, "\r\x1B[J$ あい\r");
+        let output = renderer.redraw("$ ", &line, 0, 5);
+        assert_eq!(output, "\x1B[1A\r\x1B[J$ あい\x1B[1A\r\x1B[2C");
+    }
+
+    #[test]
+    fn redraw_multiple_lines() {
+        let mut renderer = Renderer::new();
+        let line: Vec<char> = "ab\ncd".chars().collect();
+        let output = renderer.redraw("$ ", &line, 1, 80);
+        assert_eq!(output, "\r\x1B[J$ ab\r\ncd\x1B[1A\r\x1B[3C");
+        let output = renderer.redraw("$ ", &line, 5, 80);
+        assert_eq!(output, "\r\x1B[J$ ab\r\ncd\r\x1B[2C");
+        let output = renderer.redraw("$ ", &line, 2, 80);
+        assert_eq!(output, "\x1B[1A\r\x1B[J$ ab\r\ncd\x1B[1A\r\x1B[4C");
+    }
+
+    #[test]
+    fn redraw_control_characters() {
+        let mut renderer = Renderer::new();
+        let output = renderer.redraw("", &['a', '\x01'], 2, 80);
+        assert_eq!(output, "\r\x1B[Ja^A\r\x1B[3C");
+    }
+
+    #[test]
+    fn columns_in_width() {
+        let items = ["a", "bb", "ccc", "d", "e"];
+        assert_eq!(columns(&items, 12), "a    d\nbb   e\nccc\n");
+        assert_eq!(columns(&items, 4), "a\nbb\nccc\nd\ne\n");
+        assert_eq!(columns(&[], 80), "");
+    }
+}
@@ -0,0 +1,707 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Input decorator that edits lines on the terminal
+
+use crate::display::columns;
+use crate::display::Renderer;
+use crate::engine::Engine;
+use crate::engine::Outcome;
+use crate::terminal::RawMode;
+use async_trait::async_trait;
+use futures_util::future::select;
+use futures_util::future::Either;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::pin;
+use std::rc::Rc;
+use yash_env::history;
+use yash_env::input::Context;
+use yash_env::input::Input;
+use yash_env::input::Result;
+use yash_env::io::Fd;
+use yash_env::keymap::Mode;
+use yash_env::option::Option::{Emacs, Vi};
+use yash_env::option::State::On;
+use yash_env::trap::Action;
+use yash_env::trap::Signal;
+use yash_env::Env;
+use yash_env::SharedSystem;
+use yash_env::System;
+use yash_prompt::prepare_prompt;
+use yash_prompt::read_with_notify;
+use yash_semantics::completion;
+
+/// Input decorator that provides command line editing
+///
+/// A `LineEditor` reads lines from the terminal with the editing engine if
+/// the [`Vi`] or [`Emacs`] option is on and both the input file descriptor
+/// and the standard error are terminals. Otherwise, it delegates each
+/// [`next_line`](Input::next_line) call to the inner input, which is usually a
+/// [`Prompter`](yash_prompt::Prompter).
+///
+/// When editing, the `LineEditor` prints the prompt by itself and puts the
+/// terminal in the [raw mode](RawMode) until the line is accepted. The
+/// original terminal mode is restored while the prompt is expanded, while
+/// completion functions run, and while the shell is suspended by `SIGTSTP`;
+/// the raw mode is set again when the shell is resumed. Accepted
+/// lines, as well as lines read from the inner input, are
+/// [recorded](yash_env::history::record) in the history in the environment.
+/// Continuation lines read with the `PS2` prompt are appended to the history
+/// entry of the first line, so that the whole command can be recalled and
+/// edited as a multi-line buffer.
+///
+/// The environment is borrowed only while the prompt or the edit line is being
+/// updated, so the read-eval loop can share the same `RefCell` to execute
+/// commands. See the [`Prompter`](yash_prompt::Prompter#borrowing-the-environment)
+/// docs for why the borrow may be held across await points.
+#[derive(Debug)]
+pub struct LineEditor<'a, 'b, T> {
+    inner: T,
+    fd: Fd,
+    env: &'a RefCell<&'b mut Env>,
+    engine: Engine,
+}
+
+impl<'a, 'b, T> LineEditor<'a, 'b, T> {
+    /// Creates a new `LineEditor` decorating the given input.
+    ///
+    /// The `fd` is the file descriptor from which the line editor reads keys.
+    pub fn new(inner: T, fd: Fd, env: &'a RefCell<&'b mut Env>) -> Self {
+        let engine = Engine::new();
+        Self {
+            inner,
+            fd,
+            env,
+            engine,
+        }
+    }
+
+    /// Returns the editing engine.
+    #[must_use]
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Returns the editing engine.
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Returns the editing mode to start with, or `None` if line editing
+    /// should not be performed.
+    fn initial_mode(&self) -> Option<Mode> {
+        let env = self.env.borrow();
+        let mode = if env.options.get(Emacs) == On {
+            Mode::Emacs
+        } else if env.options.get(Vi) == On {
+            Mode::ViInsert
+        } else {
+            return None;
+        };
+        let is_terminal = env.system.isatty(self.fd).unwrap_or(false)
+            && env.system.isatty(Fd::STDERR).unwrap_or(false);
+        is_terminal.then_some(mode)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> Input for LineEditor<'_, '_, T>
+where
+    T: Input,
+{
+    // See the "Borrowing the environment" section of the `Prompter` docs.
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn next_line(&mut self, context: &Context) -> Result {
+        let Some(mode) = self.initial_mode() else {
+            return self.read_inner(context).await;
+        };
+
+        let system = self.env.borrow().system.clone();
+        let Ok(mut raw_mode) = RawMode::new(system.clone(), self.fd) else {
+            return self.read_inner(context).await;
+        };
+
+        // The prompt may contain command substitutions, which must run in
+        // the original terminal mode.
+        let prompt = prepare_prompt(&mut self.env.borrow_mut(), context).await;
+        raw_mode
+            .enter()
+            .map_err(|errno| std::io::Error::from_raw_os_error(errno as i32))?;
+
+        {
+            let env = &mut **self.env.borrow_mut();
+            _ = env.traps.enable_suspension_handlers(&mut env.system);
+        }
+        let result = self
+            .edit(context, mode, &prompt, &mut raw_mode, system)
+            .await;
+        {
+            let env = &mut **self.env.borrow_mut();
+            _ = env.traps.disable_suspension_handlers(&mut env.system);
+        }
+        drop(raw_mode);
+        result
+    }
+}
+
+impl<T: Input> LineEditor<'_, '_, T> {
+    /// Reads a line from the inner input without editing.
+    async fn read_inner(&mut self, context: &Context) -> Result {
+        let line = self.inner.next_line(context).await?;
+        record(self.env, context, &line);
+        Ok(line)
+    }
+}
+
+impl<T> LineEditor<'_, '_, T> {
+    /// Reads a line by editing.
+    // See the "Borrowing the environment" section of the `Prompter` docs.
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn edit(
+        &mut self,
+        context: &Context,
+        mode: Mode,
+        prompt: &str,
+        raw_mode: &mut RawMode,
+        mut system: SharedSystem,
+    ) -> Result {
+        self.engine.start_line(mode, &self.env.borrow());
+        let mut renderer = Renderer::new();
+        self.redraw(&mut renderer, prompt, false).await;
+
+        let mut bytes = Vec::new();
+        let mut chars = VecDeque::new();
+        loop {
+            if chars.is_empty() {
+                let mut buffer = [0; 64];
+                let result = {
+                    let suspension = pin!(wait_for_suspension(system.clone()));
+                    let read = pin!(system.read_async(self.fd, &mut buffer));
+                    let read = pin!(read_with_notify(self.env, read));
+                    match select(read, suspension).await {
+                        Either::Left((result, _)) => result,
+                        Either::Right((signals, _)) => {
+                            if !self.suspend_or_resume(&signals, raw_mode).await {
+                                continue;
+                            }
+                            None
+                        }
+                    }
+                };
+                let count = match result {
+                    Some(Ok(count)) => count,
+                    None => {
+                        // Job status was reported or the shell was stopped,
+                        // so the prompt must be printed again.
+                        renderer.reset();
+                        self.redraw(&mut renderer, prompt, false).await;
+                        continue;
+                    }
+                    Some(Err(errno)) => {
+                        return Err(std::io::Error::from_raw_os_error(errno as i32))
+                    }
+                };
+                if count == 0 {
+                    self.finish(&mut renderer, prompt).await;
+                    return Ok(String::new());
+                }
+                bytes.extend_from_slice(&buffer[..count]);
+                chars.extend(decode(&mut bytes));
+            }
+
+            // Keys after a completion request are kept in `chars` so that
+            // they are processed after the completion is applied.
+            let outcome = {
+                let env = self.env.borrow();
+                let mut outcome = Outcome::Continue;
+                while let Some(c) = chars.pop_front() {
+                    outcome = self.engine.input(c, &env);
+                    if outcome != Outcome::Continue {
+                        break;
+                    }
+                }
+                if outcome == Outcome::Continue && bytes.is_empty() {
+                    outcome = self.engine.flush(&env);
+                }
+                outcome
+            };
+
+            if self.engine.take_alert() {
+                _ = system.write_all(Fd::STDERR, b"\x07").await;
+            }
+            if self.engine.take_clear() {
+                let clear = renderer.clear_screen();
+                _ = system.write_all(Fd::STDERR, clear.as_bytes()).await;
+            }
+
+            match outcome {
+                Outcome::Continue => self.redraw(&mut renderer, prompt, false).await,
+                Outcome::Complete => {
+                    self.complete(&mut renderer, prompt, raw_mode).await;
+                    self.redraw(&mut renderer, prompt, false).await;
+                }
+                Outcome::Accept(mut line) => {
+                    self.finish(&mut renderer, prompt).await;
+                    record(self.env, context, &line);
+                    line.push('\n');
+                    return Ok(line);
+                }
+                Outcome::Eof => {
+                    self.finish(&mut renderer, prompt).await;
+                    return Ok(String::new());
+                }
+            }
+        }
+    }
+
+    /// Handles `SIGTSTP` and `SIGCONT` caught while reading keys.
+    ///
+    /// On `SIGTSTP`, the original terminal mode is restored and the shell
+    /// stops itself with `SIGSTOP`, unless the signal is trapped by the user.
+    /// On `SIGCONT`, the terminal is put into the raw mode again because the
+    /// terminal mode may have been changed while the shell was stopped.
+    ///
+    /// Returns true if the shell stopped itself, in which case the edit line
+    /// must be redrawn on a new line.
+    async fn suspend_or_resume(&self, signals: &[Signal], raw_mode: &mut RawMode) -> bool {
+        let mut system = self.env.borrow().system.clone();
+        let mut stopped = false;
+
+        if signals.contains(&Signal::SIGTSTP) {
+            let is_trapped = {
+                let mut env = self.env.borrow_mut();
+                env.traps.catch_signal(Signal::SIGTSTP);
+                let (current, _) = env.traps.get_state(Signal::SIGTSTP);
+                current.is_some_and(|state| state.action != Action::Default)
+            };
+            if !is_trapped {
+                raw_mode.leave();
+                _ = system.write_all(Fd::STDERR, b"\n").await;
+                let pid = system.getpid();
+                _ = system.kill(pid, Some(Signal::SIGSTOP)).await;
+                stopped = true;
+            }
+        }
+
+        if stopped || signals.contains(&Signal::SIGCONT) {
+            _ = raw_mode.enter();
+        }
+        stopped
+    }
+
+    /// Redraws the prompt and the edit line.
+    ///
+    /// If `at_end` is true, the cursor is moved to the end of the line
+    /// regardless of the cursor position in the buffer.
+    async fn redraw(&self, renderer: &mut Renderer, prompt: &str, at_end: bool) {
+        let mut system = self.env.borrow().system.clone();
+        let width = system.tcgetwinsize(self.fd).map_or(0, |size| size.columns);
+        let (text, cursor) = self.engine.display();
+        let cursor = if at_end { text.len() } else { cursor };
+        let output = renderer.redraw(prompt, &text, cursor, width.into());
+        _ = system.write_all(Fd::STDERR, output.as_bytes()).await;
+    }
+
+    /// Completes the word before the cursor.
+    ///
+    /// A single candidate is inserted into the buffer. If there are more
+    /// candidates, their longest common prefix is inserted, or the candidates
+    /// are listed below the edit line if the prefix is no longer than the
+    /// word being completed.
+    // See the "Borrowing the environment" section of the `Prompter` docs.
+    #[allow(clippy::await_holding_refcell_ref)]
+    ///
+    /// Completion functions are run in the original terminal mode.
+    async fn complete(&mut self, renderer: &mut Renderer, prompt: &str, raw_mode: &mut RawMode) {
+        let buffer = self.engine.buffer();
+        let line: String = buffer.chars[..buffer.cursor].iter().collect();
+        raw_mode.leave();
+        let completed = completion::complete(&mut self.env.borrow_mut(), &line).await;
+        _ = raw_mode.enter();
+        let mut system = self.env.borrow().system.clone();
+
+        match &completed.candidates[..] {
+            [] => _ = system.write_all(Fd::STDERR, b"\x07").await,
+            [candidate] => {
+                let text = completed.replacement(&candidate.value, candidate.ends_word);
+                self.engine.insert_completion(completed.start, &text);
+            }
+            candidates => {
+                let common = common_prefix(candidates.iter().map(|c| c.value.as_str()));
+                if common.len() > completed.target.len() {
+                    let text = completed.replacement(common, false);
+                    self.engine.insert_completion(completed.start, &text);
+                    return;
+                }
+
+                self.finish(renderer, prompt).await;
+                let width = system.tcgetwinsize(self.fd).map_or(0, |size| size.columns);
+                let items: Vec<&str> = candidates.iter().map(|c| c.value.as_str()).collect();
+                let list = columns(&items, width.into());
+                _ = system.write_all(Fd::STDERR, list.as_bytes()).await;
+                renderer.reset();
+            }
+        }
+    }
+
+    /// Moves the cursor to the end of the edit line and to the next line.
+    async fn finish(&self, renderer: &mut Renderer, prompt: &str) {
+        self.redraw(renderer, prompt, true).await;
+        let mut system = self.env.borrow().system.clone();
+        _ = system.write_all(Fd::STDERR, b"\n").await;
+    }
+}
+
+/// Returns the longest common prefix of the strings.
+fn common_prefix<'a, I: IntoIterator<Item = &'a str>>(strings: I) -> &'a str {
+    let mut strings = strings.into_iter();
+    let Some(mut prefix) = strings.next() else {
+        return "";
+    };
+    for s in strings {
+        let len = prefix
+            .char_indices()
+            .zip(s.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(s.len()), |((i, _), _)| i);
+        prefix = &prefix[..len];
+    }
+    prefix
+}
+
+/// Waits for `SIGTSTP` or `SIGCONT` to be caught.
+///
+/// Returns the signals caught at once.
+async fn wait_for_suspension(system: SharedSystem) -> Rc<[Signal]> {
+    loop {
+        let signals = system.wait_for_signals().await;
+        if signals.contains(&Signal::SIGTSTP) || signals.contains(&Signal::SIGCONT) {
+            return signals;
+        }
+    }
+}
+
+/// Records a line in the history.
+fn record(env: &RefCell<&mut Env>, context: &Context, line: &str) {
+    let env = &mut **env.borrow_mut();
+    if context.is_first_line() {
+        history::record(env, line);
+    } else {
+        history::record_continuation(env, line);
+    }
+}
+
+/// Decodes complete UTF-8 sequences from the bytes.
+///
+/// The decoded bytes are removed from the vector, leaving an incomplete
+/// sequence at the end, if any. Invalid bytes are decoded as the replacement
+/// character.
+fn decode(bytes: &mut Vec<u8>) -> Vec<char> {
+    let mut chars = Vec::new();
+    let mut rest = &bytes[..];
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(s) => {
+                chars.extend(s.chars());
+                rest = &[];
+                break;
+            }
+            Err(error) => {
+                let (valid, after) = rest.split_at(error.valid_up_to());
+                chars.extend(std::str::from_utf8(valid).unwrap().chars());
+                match error.error_len() {
+                    Some(len) => {
+                        chars.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[len..];
+                    }
+                    None => {
+                        rest = after;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let consumed = bytes.len() - rest.len();
+    bytes.drain(..consumed);
+    chars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use std::collections::VecDeque;
+    use yash_env::input::Memory;
+    use yash_env::job::ProcessState;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::VirtualSystem;
+    use yash_env::variable::Scope;
+    use yash_syntax::source::Location;
+
+    fn set_terminal(system: &VirtualSystem, path: &str, content: &[u8]) {
+        let state = system.state.borrow();
+        let file = state.file_system.get(path).unwrap();
+        file.borrow_mut().body = FileBody::Terminal {
+            content: VecDeque::from(content.to_vec()),
+        };
+    }
+
+    fn virtual_terminal(input: &[u8]) -> (VirtualSystem, Env) {
+        let system = VirtualSystem::new();
+        set_terminal(&system, "/dev/stdin", input);
+        set_terminal(&system, "/dev/stderr", b"");
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(Vi, On);
+        env.variables
+            .get_or_new("PS1", Scope::Global)
+            .assign("$ ", None)
+            .unwrap();
+        (system, env)
+    }
+
+    #[test]
+    fn editing_a_line() {
+        let (_system, mut env) = virtual_terminal(b"echo foo\x1Bbcwbar\r");
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new("fallback\n"), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "echo bar\n");
+        assert_eq!(env.borrow().history.get(0), Some("echo bar"));
+    }
+
+    #[test]
+    fn history_recall() {
+        let (_system, mut env) = virtual_terminal(b"\x1Bkx\r");
+        env.history.add("echo");
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "cho\n");
+    }
+
+    #[test]
+    fn eof_on_empty_line() {
+        let (_system, mut env) = virtual_terminal(b"\x04");
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "");
+    }
+
+    #[test]
+    fn terminal_mode_restored_after_editing() {
+        let (system, mut env) = virtual_terminal(b"ls\r");
+        let original = env.system.tcgetattr(Fd::STDIN).unwrap();
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let state = system.state.borrow();
+        assert_eq!(state.terminal_attributes, Some(original));
+    }
+
+    #[test]
+    fn delegation_without_vi_option() {
+        let (_system, mut env) = virtual_terminal(b"ls\r");
+        env.options.set(Vi, yash_env::option::State::Off);
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new("fallback\n"), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "fallback\n");
+        assert_eq!(env.borrow().history.get(0), Some("fallback"));
+    }
+
+    #[test]
+    fn delegation_on_non_terminal() {
+        let (system, mut env) = virtual_terminal(b"ls\r");
+        {
+            let state = system.state.borrow();
+            let file = state.file_system.get("/dev/stdin").unwrap();
+            file.borrow_mut().body = FileBody::new(*b"ls\n");
+        }
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new("fallback\n"), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "fallback\n");
+    }
+
+    #[test]
+    fn editing_in_emacs_mode() {
+        let (_system, mut env) = virtual_terminal(b"echo foo\x17bar\x01\x06\x06\x14\r");
+        env.options.set(Emacs, On);
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new("fallback\n"), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "ehco bar\n");
+    }
+
+    #[test]
+    fn continuation_lines_join_history_entry() {
+        let (system, mut env) = virtual_terminal(b"echo \\\r");
+        env.options.set(Emacs, On);
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+        let mut context = Context::default();
+        let line = editor.next_line(&context).now_or_never().unwrap().unwrap();
+        assert_eq!(line, "echo \\\n");
+
+        set_terminal(&system, "/dev/stdin", b"foo\r");
+        context.set_is_first_line(false);
+        let line = editor.next_line(&context).now_or_never().unwrap().unwrap();
+        assert_eq!(line, "foo\n");
+
+        let env = env.borrow();
+        assert_eq!(env.history.len(), 1);
+        assert_eq!(env.history.get(0), Some("echo \\\nfoo"));
+    }
+
+    #[test]
+    fn completing_a_word() {
+        let (_system, mut env) = virtual_terminal(b"cat /dev/stdi\tx\r");
+        env.options.set(Emacs, On);
+        let env = RefCell::new(&mut env);
+        let mut editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        let line = editor
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "cat /dev/stdin x\n");
+    }
+
+    #[test]
+    fn raw_mode_is_set_again_on_sigcont() {
+        let (system, mut env) = virtual_terminal(b"");
+        let original = env.system.tcgetattr(Fd::STDIN).unwrap();
+        let mut raw_mode = RawMode::new(env.system.clone(), Fd::STDIN).unwrap();
+        raw_mode.enter().unwrap();
+        let raw = env.system.tcgetattr(Fd::STDIN).unwrap();
+        system.state.borrow_mut().terminal_attributes = Some(original);
+        let env = RefCell::new(&mut env);
+        let editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        let stopped = editor
+            .suspend_or_resume(&[Signal::SIGCONT], &mut raw_mode)
+            .now_or_never()
+            .unwrap();
+        assert!(!stopped);
+        assert_eq!(system.state.borrow().terminal_attributes, Some(raw));
+    }
+
+    #[test]
+    fn terminal_mode_restored_before_stopping_on_sigtstp() {
+        let (system, mut env) = virtual_terminal(b"");
+        let original = env.system.tcgetattr(Fd::STDIN).unwrap();
+        let mut raw_mode = RawMode::new(env.system.clone(), Fd::STDIN).unwrap();
+        raw_mode.enter().unwrap();
+        let env = RefCell::new(&mut env);
+        let editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        // The future does not complete because the process is stopped.
+        let result = editor
+            .suspend_or_resume(&[Signal::SIGTSTP], &mut raw_mode)
+            .now_or_never();
+        assert_eq!(result, None);
+        assert_eq!(system.state.borrow().terminal_attributes, Some(original));
+        assert_eq!(
+            system.current_process().state(),
+            ProcessState::Stopped(Signal::SIGSTOP)
+        );
+    }
+
+    #[test]
+    fn trapped_sigtstp_does_not_stop_shell() {
+        let (system, mut env) = virtual_terminal(b"");
+        let origin = Location::dummy("");
+        env.traps
+            .set_action(
+                &mut env.system,
+                Signal::SIGTSTP,
+                Action::Command("echo".into()),
+                origin,
+                false,
+            )
+            .unwrap();
+        let mut raw_mode = RawMode::new(env.system.clone(), Fd::STDIN).unwrap();
+        raw_mode.enter().unwrap();
+        let raw = env.system.tcgetattr(Fd::STDIN).unwrap();
+        let env = RefCell::new(&mut env);
+        let editor = LineEditor::new(Memory::new(""), Fd::STDIN, &env);
+
+        let stopped = editor
+            .suspend_or_resume(&[Signal::SIGTSTP], &mut raw_mode)
+            .now_or_never()
+            .unwrap();
+        assert!(!stopped);
+        assert_eq!(system.state.borrow().terminal_attributes, Some(raw));
+        assert_eq!(system.current_process().state(), ProcessState::Running);
+    }
+
+    #[test]
+    fn longest_common_prefix() {
+        assert_eq!(common_prefix(["foobar", "foobaz", "foo"]), "foo");
+        assert_eq!(common_prefix(["あいう", "あいえ"]), "あい");
+        assert_eq!(common_prefix([]), "");
+    }
+
+    #[test]
+    fn decoding_utf8() {
+        let mut bytes = "aあ".as_bytes().to_vec();
+        bytes.push(0xE3);
+        assert_eq!(decode(&mut bytes), ['a', 'あ']);
+        assert_eq!(bytes, [0xE3]);
+        bytes.extend_from_slice(&[0x81, 0x84, 0xFF, b'b']);
+        assert_eq!(decode(&mut bytes), ['い', char::REPLACEMENT_CHARACTER, 'b']);
+        assert!(bytes.is_empty());
+    }
+}
@@ -0,0 +1,2116 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Editing engine
+//!
+//! The [`Engine`] interprets the characters typed by the user as editing
+//! commands and applies them to the [`Buffer`]. The engine does not perform
+//! any I/O so that it can be tested without a terminal.
+
+use crate::buffer::Buffer;
+use crate::buffer::WordKind;
+use std::collections::VecDeque;
+use std::mem::take;
+use yash_env::keymap::Command;
+use yash_env::keymap::Lookup;
+use yash_env::keymap::Mode;
+use yash_env::Env;
+
+/// Result of processing input
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// Editing continues.
+    Continue,
+    /// The user accepted the line.
+    ///
+    /// The string does not contain a trailing newline.
+    Accept(String),
+    /// The user requested end-of-file.
+    Eof,
+    /// The user requested completion of the word before the cursor.
+    ///
+    /// The caller should generate candidates and apply the result with
+    /// [`insert_completion`](Engine::insert_completion).
+    Complete,
+}
+
+/// Direction of character search and history search
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+impl Direction {
+    fn reversed(self) -> Self {
+        match self {
+            Direction::Forward => Direction::Backward,
+            Direction::Backward => Direction::Forward,
+        }
+    }
+}
+
+/// Character search performed by `f`, `F`, `t`, and `T`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Find {
+    direction: Direction,
+    /// Whether the cursor stops just before the found character
+    till: bool,
+    target: char,
+}
+
+/// Command waiting for the next typed character
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Awaiting {
+    Verbatim,
+    Find { direction: Direction, till: bool },
+    Replace,
+}
+
+/// Motion that determines the operand of an operator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Motion {
+    Command(Command),
+    Find(Find),
+}
+
+/// Operator waiting for a motion
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Operator {
+    command: Command,
+    count: Option<usize>,
+}
+
+/// History search being typed
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Search {
+    direction: Direction,
+    pattern: String,
+}
+
+/// Incremental history search being typed
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct IncrementalSearch {
+    direction: Direction,
+    pattern: String,
+    /// Whether the pattern was not found
+    failed: bool,
+    /// Buffer before the search started
+    start: Buffer,
+    /// History index before the search started
+    start_index: usize,
+}
+
+/// Text inserted by the last `yank` or `yank-pop` command
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Yanked {
+    start: usize,
+    end: usize,
+    /// Index of the inserted text in the kill ring
+    index: usize,
+}
+
+/// Maximum number of entries in the kill ring
+const KILL_RING_SIZE: usize = 32;
+
+/// Change that can be repeated by the `redo` command
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Change {
+    count: Option<usize>,
+    keys: String,
+}
+
+/// Editing engine
+///
+/// The engine holds the state of the line being edited as well as the state
+/// that persists across lines, such as the text saved by delete and copy
+/// commands and the last change repeated by the `redo` command. Key sequences
+/// are looked up in the [key bindings](yash_env::keymap::KeyBindings) of the
+/// environment passed to [`input`](Self::input).
+#[derive(Clone, Debug)]
+pub struct Engine {
+    // State of the line being edited
+    mode: Mode,
+    buffer: Buffer,
+    pending_keys: String,
+    count: Option<usize>,
+    operator: Option<Operator>,
+    awaiting: Option<Awaiting>,
+    search: Option<Search>,
+    undo: Vec<Buffer>,
+    redo: Vec<Buffer>,
+    original: Buffer,
+    history_index: usize,
+    new_line: Option<Buffer>,
+    insert_count: usize,
+    insert_start: usize,
+    isearch: Option<IncrementalSearch>,
+    last_command: Option<Command>,
+    yanked: Option<Yanked>,
+
+    // State for the redo command
+    command_keys: String,
+    change_count: Option<usize>,
+    recording: Option<String>,
+    replaying: bool,
+
+    // State persisting across lines
+    yank: Vec<char>,
+    last_find: Option<Find>,
+    last_search: Option<Search>,
+    last_change: Option<Change>,
+    kill_ring: VecDeque<Vec<char>>,
+    last_isearch: String,
+
+    // Requests to the terminal
+    alert: bool,
+    clear: bool,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    /// Creates a new engine.
+    #[must_use]
+    pub fn new() -> Self {
+        Engine {
+            mode: Mode::ViInsert,
+            buffer: Buffer::default(),
+            pending_keys: String::new(),
+            count: None,
+            operator: None,
+            awaiting: None,
+            search: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            original: Buffer::default(),
+            history_index: 0,
+            new_line: None,
+            insert_count: 1,
+            insert_start: 0,
+            isearch: None,
+            last_command: None,
+            yanked: None,
+            command_keys: String::new(),
+            change_count: None,
+            recording: None,
+            replaying: false,
+            yank: Vec::new(),
+            last_find: None,
+            last_search: None,
+            last_change: None,
+            kill_ring: VecDeque::new(),
+            last_isearch: String::new(),
+            alert: false,
+            clear: false,
+        }
+    }
+
+    /// Starts editing a new line.
+    ///
+    /// The engine starts in the given mode with an empty buffer. If the mode
+    /// is a vi mode, the engine starts in the vi insert mode. The history in
+    /// the environment is used to navigate to previous lines.
+    pub fn start_line(&mut self, mode: Mode, env: &Env) {
+        self.buffer = Buffer::default();
+        self.pending_keys.clear();
+        self.count = None;
+        self.operator = None;
+        self.awaiting = None;
+        self.search = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.original = Buffer::default();
+        self.history_index = env.history.len();
+        self.new_line = None;
+        self.command_keys.clear();
+        self.recording = None;
+        self.isearch = None;
+        self.last_command = None;
+        self.yanked = None;
+        if mode == Mode::Emacs {
+            self.mode = mode;
+        } else {
+            self.begin_insert();
+        }
+    }
+
+    /// Returns the current mode.
+    #[must_use]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns the line being edited.
+    #[must_use]
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Returns the text to be shown in the edit line and the cursor position
+    /// in it.
+    ///
+    /// The text is the buffer content, or the search pattern while the user is
+    /// typing one. During an incremental search, the buffer content is
+    /// preceded by the search pattern.
+    #[must_use]
+    pub fn display(&self) -> (Vec<char>, usize) {
+        if let Some(isearch) = &self.isearch {
+            let failed = if isearch.failed { "failed " } else { "" };
+            let reverse = match isearch.direction {
+                Direction::Forward => "",
+                Direction::Backward => "reverse-",
+            };
+            let prefix = format!("({failed}{reverse}i-search)`{}': ", isearch.pattern);
+            let mut text: Vec<char> = prefix.chars().collect();
+            let cursor = text.len() + self.buffer.cursor;
+            text.extend(&self.buffer.chars);
+            return (text, cursor);
+        }
+        match &self.search {
+            None => (self.buffer.chars.clone(), self.buffer.cursor),
+            Some(search) => {
+                let prefix = match search.direction {
+                    Direction::Forward => '?',
+                    Direction::Backward => '/',
+                };
+                let text: Vec<char> = std::iter::once(prefix)
+                    .chain(search.pattern.chars())
+                    .collect();
+                let cursor = text.len();
+                (text, cursor)
+            }
+        }
+    }
+
+    /// Returns and resets the flag that requests ringing the bell.
+    pub fn take_alert(&mut self) -> bool {
+        take(&mut self.alert)
+    }
+
+    /// Returns and resets the flag that requests clearing the screen.
+    pub fn take_clear(&mut self) -> bool {
+        take(&mut self.clear)
+    }
+
+    /// Replaces the text between `start` and the cursor with the completed
+    /// text.
+    ///
+    /// `start` is a character index in the buffer, which is clamped to the
+    /// cursor position.
+    pub fn insert_completion(&mut self, start: usize, text: &str) {
+        if self.mode == Mode::Emacs {
+            self.begin_change();
+        }
+        let cursor = self.buffer.cursor;
+        self.buffer.remove(start.min(cursor)..cursor);
+        let chars: Vec<char> = text.chars().collect();
+        self.buffer.insert(&chars);
+    }
+
+    /// Processes a character typed by the user.
+    ///
+    /// If the character starts or continues a key sequence that is a prefix of
+    /// a longer bound sequence, the command is not executed until the sequence
+    /// is complete or [`flush`](Self::flush) is called.
+    pub fn input(&mut self, c: char, env: &Env) -> Outcome {
+        if let Some(awaiting) = self.awaiting.take() {
+            return self.input_awaited(awaiting, c);
+        }
+        if self.search.is_some() {
+            return self.input_search(c, env);
+        }
+        if self.isearch.is_some() {
+            return self.input_isearch(c, env);
+        }
+
+        self.pending_keys.push(c);
+        match env.key_bindings.get(self.mode).lookup(&self.pending_keys) {
+            Lookup::Prefix | Lookup::Command(_, true) => Outcome::Continue,
+            Lookup::Command(command, false) => {
+                let keys = take(&mut self.pending_keys);
+                self.execute(command, &keys, env)
+            }
+            Lookup::Default(_) => self.resolve_pending(env),
+        }
+    }
+
+    /// Executes the command for the pending key sequence.
+    ///
+    /// This function should be called when no more characters are
+    /// immediately available, so that a key sequence that is a prefix of
+    /// another (like a lone escape) is executed.
+    pub fn flush(&mut self, env: &Env) -> Outcome {
+        if self.pending_keys.is_empty() {
+            Outcome::Continue
+        } else {
+            self.resolve_pending(env)
+        }
+    }
+
+    /// Executes the command for the longest bound prefix of the pending keys
+    /// and re-processes the remaining characters.
+    fn resolve_pending(&mut self, env: &Env) -> Outcome {
+        let pending = take(&mut self.pending_keys);
+        let keymap = env.key_bindings.get(self.mode);
+        let first_len = pending.chars().next().map_or(0, char::len_utf8);
+        let (split, command) = pending
+            .char_indices()
+            .map(|(index, c)| index + c.len_utf8())
+            .rev()
+            .find_map(|end| match keymap.lookup(&pending[..end]) {
+                Lookup::Command(command, _) => Some((end, command)),
+                _ => None,
+            })
+            .unwrap_or((first_len, keymap.default_command()));
+
+        let (keys, rest) = pending.split_at(split);
+        let outcome = self.execute(command, keys, env);
+        if outcome != Outcome::Continue {
+            return outcome;
+        }
+        for c in rest.chars() {
+            let outcome = self.input(c, env);
+            if outcome != Outcome::Continue {
+                return outcome;
+            }
+        }
+        Outcome::Continue
+    }
+
+    /// Executes a command.
+    fn execute(&mut self, command: Command, keys: &str, env: &Env) -> Outcome {
+        if self.mode == Mode::Emacs {
+            let outcome = self.execute_emacs(command, keys, env);
+            self.last_command = Some(command);
+            return outcome;
+        }
+        if self.mode == Mode::ViInsert {
+            if let Some(recording) = &mut self.recording {
+                if command != Command::SetmodeVicommand {
+                    recording.push_str(keys);
+                }
+            }
+            return self.execute_insert(command, keys, env);
+        }
+
+        // A count typed before the command is not part of the change to be
+        // repeated since the redo command may be given a different count.
+        if self.operator.is_some() || command != Command::DigitArgument {
+            self.command_keys.push_str(keys);
+        }
+        let outcome = self.execute_command(command, keys, env);
+        if self.awaiting.is_none() && self.operator.is_none() && self.count.is_none() {
+            self.command_keys.clear();
+        }
+        if self.mode == Mode::ViCommand && self.search.is_none() {
+            self.buffer.clamp_cursor();
+        }
+        outcome
+    }
+
+    /// Executes a command in the vi insert mode.
+    fn execute_insert(&mut self, command: Command, keys: &str, env: &Env) -> Outcome {
+        use Command::*;
+        match command {
+            SelfInsert => {
+                let chars: Vec<char> = keys.chars().collect();
+                self.buffer.insert(&chars);
+            }
+            ExpectVerbatim => self.awaiting = Some(Awaiting::Verbatim),
+            BackwardDeleteChar if self.buffer.cursor > 0 => {
+                let cursor = self.buffer.cursor;
+                self.buffer.remove(cursor - 1..cursor);
+            }
+            BackwardDeleteViword if self.buffer.cursor > 0 => {
+                let cursor = self.buffer.cursor;
+                let start = self.buffer.prev_word_start(cursor, WordKind::Viword);
+                self.buffer.remove(start..cursor);
+            }
+            BackwardDeleteLine if self.buffer.cursor > 0 => {
+                let cursor = self.buffer.cursor;
+                self.buffer.remove(0..cursor);
+            }
+            DeleteChar if self.buffer.cursor < self.buffer.len() => {
+                let cursor = self.buffer.cursor;
+                self.buffer.remove(cursor..cursor + 1);
+            }
+            SetmodeVicommand => self.end_insert(keys),
+            _ => return self.execute_common(command, env),
+        }
+        Outcome::Continue
+    }
+
+    /// Executes a command in the vi command mode.
+    fn execute_command(&mut self, command: Command, keys: &str, env: &Env) -> Outcome {
+        use Command::*;
+
+        if command == DigitArgument {
+            let digit = keys.chars().last().and_then(|c| c.to_digit(10));
+            match (digit, self.count) {
+                (Some(0), None) => return self.execute_command(BeginningOfLine, keys, env),
+                (Some(digit), count) => {
+                    let count = count.unwrap_or(0).saturating_mul(10);
+                    self.count = Some(count.saturating_add(digit as usize));
+                }
+                (None, _) => self.cancel(),
+            }
+            return Outcome::Continue;
+        }
+
+        if let Some(operator) = self.operator {
+            if command == operator.command {
+                self.operator = None;
+                self.count = None;
+                self.apply_operator(operator, 0..self.buffer.len());
+                if operator.command == ViDeleteTo {
+                    self.finish_change(operator.count);
+                }
+            } else if command.is_motion() {
+                self.start_motion(command);
+            } else {
+                self.cancel();
+            }
+            return Outcome::Continue;
+        }
+
+        if command.is_motion() {
+            self.start_motion(command);
+            return Outcome::Continue;
+        }
+
+        let count = self.count.take();
+        let n = count.unwrap_or(1);
+        let cursor = self.buffer.cursor;
+        let len = self.buffer.len();
+        match command {
+            ViDeleteTo | ViChangeTo | ViCopyTo => {
+                self.operator = Some(Operator { command, count });
+                return Outcome::Continue;
+            }
+            ViFindChar | ViFindCharRev | ViTillChar | ViTillCharRev => unreachable!(),
+
+            SetmodeViinsert => self.start_insert(count, cursor),
+            ViAppend => self.start_insert(count, (cursor + 1).min(len)),
+            ViInsertBeginning => self.start_insert(count, 0),
+            ViAppendToEol => self.start_insert(count, len),
+
+            KillChar | DeleteChar if cursor < len => {
+                self.begin_change();
+                self.yank = self.buffer.remove(cursor..(cursor + n).min(len));
+                self.finish_change(count);
+            }
+            BackwardKillChar if cursor > 0 => {
+                self.begin_change();
+                self.yank = self.buffer.remove(cursor.saturating_sub(n)..cursor);
+                self.finish_change(count);
+            }
+            ViDeleteToEol if cursor < len => {
+                self.begin_change();
+                self.yank = self.buffer.remove(cursor..len);
+                self.finish_change(count);
+            }
+            ViChangeToEol => {
+                self.begin_change();
+                self.yank = self.buffer.remove(cursor..len);
+                self.start_insert_session(count, cursor);
+            }
+            ViChangeLine => {
+                self.begin_change();
+                self.yank = self.buffer.remove(0..len);
+                self.start_insert_session(count, 0);
+            }
+            ViSubstitute => {
+                self.begin_change();
+                if cursor < len {
+                    self.yank = self.buffer.remove(cursor..(cursor + n).min(len));
+                }
+                self.start_insert_session(None, cursor);
+            }
+            ViPutAfter | ViPutBefore if !self.yank.is_empty() => {
+                self.begin_change();
+                let position = if command == ViPutAfter && len > 0 {
+                    cursor + 1
+                } else {
+                    cursor
+                };
+                self.buffer.cursor = position;
+                let yank = self.yank.repeat(n);
+                self.buffer.insert(&yank);
+                self.buffer.cursor -= 1;
+                self.finish_change(count);
+            }
+            ViReplaceChar if cursor + n <= len => {
+                self.count = count;
+                self.awaiting = Some(Awaiting::Replace);
+            }
+            ViChangeCase if cursor < len => {
+                self.begin_change();
+                let end = (cursor + n).min(len);
+                for c in &mut self.buffer.chars[cursor..end] {
+                    *c = switch_case(*c);
+                }
+                self.buffer.cursor = end;
+                self.finish_change(count);
+            }
+
+            UndoAll => {
+                self.begin_change();
+                self.buffer = self.original.clone();
+                self.buffer.cursor = 0;
+            }
+            Redo => self.redo(count, env),
+
+            ViSearchBackward | ViSearchForward => {
+                let direction = if command == ViSearchBackward {
+                    Direction::Backward
+                } else {
+                    Direction::Forward
+                };
+                self.search = Some(Search {
+                    direction,
+                    pattern: String::new(),
+                });
+            }
+            SearchAgain | SearchAgainRev => match self.last_search.clone() {
+                Some(mut search) => {
+                    if command == SearchAgainRev {
+                        search.direction = search.direction.reversed();
+                    }
+                    self.search_history(&search, env);
+                }
+                None => self.alert = true,
+            },
+
+            PrevHistory | NextHistory => {
+                self.count = count;
+                return self.execute_common(command, env);
+            }
+            _ => return self.execute_common(command, env),
+        }
+        Outcome::Continue
+    }
+
+    /// Executes a command in the emacs mode.
+    fn execute_emacs(&mut self, command: Command, keys: &str, env: &Env) -> Outcome {
+        use Command::*;
+        let cursor = self.buffer.cursor;
+        let len = self.buffer.len();
+        match command {
+            SelfInsert => {
+                // Consecutive insertions are undone at once.
+                if self.last_command != Some(SelfInsert) {
+                    self.begin_change();
+                }
+                let chars: Vec<char> = keys.chars().collect();
+                self.buffer.insert(&chars);
+            }
+            ExpectVerbatim => {
+                self.begin_change();
+                self.awaiting = Some(Awaiting::Verbatim);
+            }
+            EofOrDelete if self.buffer.is_empty() => return Outcome::Eof,
+            DeleteChar | EofOrDelete if cursor < len => {
+                self.begin_change();
+                self.buffer.remove(cursor..cursor + 1);
+            }
+            BackwardDeleteChar if cursor > 0 => {
+                self.begin_change();
+                self.buffer.remove(cursor - 1..cursor);
+            }
+
+            KillLine => self.kill(cursor..len, false),
+            BackwardKillLine => self.kill(0..cursor, true),
+            KillEmacsword => {
+                let end = self.buffer.next_word_end(cursor, WordKind::Emacsword);
+                self.kill(cursor..end, false);
+            }
+            BackwardKillEmacsword => {
+                let start = self.buffer.prev_word_start(cursor, WordKind::Emacsword);
+                self.kill(start..cursor, true);
+            }
+            BackwardKillBigword => {
+                let start = self.buffer.prev_word_start(cursor, WordKind::Bigword);
+                self.kill(start..cursor, true);
+            }
+            Yank => match self.kill_ring.front() {
+                Some(text) => {
+                    let text = text.clone();
+                    self.begin_change();
+                    self.buffer.insert(&text);
+                    let end = self.buffer.cursor;
+                    self.yanked = Some(Yanked {
+                        start: cursor,
+                        end,
+                        index: 0,
+                    });
+                }
+                None => self.alert = true,
+            },
+            YankPop => match self.yanked {
+                Some(yanked) if matches!(self.last_command, Some(Yank | YankPop)) => {
+                    let index = (yanked.index + 1) % self.kill_ring.len();
+                    let text = self.kill_ring[index].clone();
+                    self.begin_change();
+                    self.buffer.remove(yanked.start..yanked.end);
+                    self.buffer.cursor = yanked.start;
+                    self.buffer.insert(&text);
+                    let end = self.buffer.cursor;
+                    self.yanked = Some(Yanked {
+                        end,
+                        index,
+                        ..yanked
+                    });
+                }
+                _ => self.alert = true,
+            },
+
+            TransposeChars if cursor > 0 && len >= 2 => {
+                let index = cursor.min(len - 1);
+                self.begin_change();
+                self.buffer.chars.swap(index - 1, index);
+                self.buffer.cursor = index + 1;
+            }
+            TransposeWords => self.transpose_words(),
+            UpcaseEmacsword | DowncaseEmacsword | CapitalizeEmacsword => {
+                let end = self.buffer.next_word_end(cursor, WordKind::Emacsword);
+                self.begin_change();
+                let mut first = true;
+                for c in &mut self.buffer.chars[cursor..end] {
+                    let upper = match command {
+                        UpcaseEmacsword => true,
+                        DowncaseEmacsword => false,
+                        _ => first && c.is_alphanumeric(),
+                    };
+                    if c.is_alphanumeric() {
+                        first = false;
+                    }
+                    *c = if upper {
+                        c.to_uppercase().next().unwrap_or(*c)
+                    } else {
+                        c.to_lowercase().next().unwrap_or(*c)
+                    };
+                }
+                self.buffer.cursor = end;
+            }
+            UndoAll => {
+                self.begin_change();
+                self.buffer = self.original.clone();
+            }
+
+            EmacsSearchBackward | EmacsSearchForward => {
+                let direction = if command == EmacsSearchBackward {
+                    Direction::Backward
+                } else {
+                    Direction::Forward
+                };
+                self.isearch = Some(IncrementalSearch {
+                    direction,
+                    pattern: String::new(),
+                    failed: false,
+                    start: self.buffer.clone(),
+                    start_index: self.history_index,
+                });
+            }
+
+            _ => return self.execute_common(command, env),
+        }
+        Outcome::Continue
+    }
+
+    /// Deletes the range, saving the text in the kill ring.
+    ///
+    /// If the last command was also a kill, the text is appended to the last
+    /// entry of the kill ring so that the entries are yanked at once.
+    /// Otherwise, the text is added as a new entry.
+    fn kill(&mut self, range: std::ops::Range<usize>, backward: bool) {
+        if range.is_empty() {
+            return;
+        }
+        self.begin_change();
+        let killed = self.buffer.remove(range);
+        match self.kill_ring.front_mut() {
+            Some(last) if self.last_command.is_some_and(is_kill) => {
+                if backward {
+                    last.splice(0..0, killed);
+                } else {
+                    last.extend(killed);
+                }
+            }
+            _ => {
+                self.kill_ring.push_front(killed);
+                self.kill_ring.truncate(KILL_RING_SIZE);
+            }
+        }
+    }
+
+    /// Swaps the emacs word before the cursor with the next one.
+    ///
+    /// If the cursor is in a word, the word is swapped with the previous one.
+    fn transpose_words(&mut self) {
+        let kind = WordKind::Emacsword;
+        let buffer = &self.buffer;
+        let end2 = buffer.next_word_end(buffer.cursor, kind);
+        let start2 = buffer.prev_word_start(end2, kind);
+        let start1 = buffer.prev_word_start(start2, kind);
+        let end1 = buffer.next_word_end(start1, kind);
+        if start1 == start2 || start2 == end2 || end1 > start2 {
+            self.alert = true;
+            return;
+        }
+
+        self.begin_change();
+        let chars = &self.buffer.chars;
+        let swapped = [
+            &chars[start2..end2],
+            &chars[end1..start2],
+            &chars[start1..end1],
+        ]
+        .concat();
+        self.buffer.chars.splice(start1..end2, swapped);
+        self.buffer.cursor = end2;
+    }
+
+    /// Executes a command that works the same in any mode.
+    fn execute_common(&mut self, command: Command, env: &Env) -> Outcome {
+        use Command::*;
+        match command {
+            Noop => (),
+            AcceptLine => {
+                self.cancel_pending();
+                let line = self.buffer.as_string();
+                return Outcome::Accept(line);
+            }
+            EofIfEmpty if self.buffer.is_empty() => return Outcome::Eof,
+            ClearAndRedraw => self.clear = true,
+            Complete => return Outcome::Complete,
+            PrevHistory => {
+                let n = self.count.take().unwrap_or(1);
+                match self.history_index.checked_sub(n) {
+                    Some(index) => self.go_to_history(index, env),
+                    None => self.alert = true,
+                }
+            }
+            NextHistory => {
+                let n = self.count.take().unwrap_or(1);
+                let index = self.history_index.saturating_add(n);
+                if index <= env.history.len() {
+                    self.go_to_history(index, env);
+                } else {
+                    self.alert = true;
+                }
+            }
+            BeginningOfHistory if !env.history.is_empty() => self.go_to_history(0, env),
+            EndOfHistory => self.go_to_history(env.history.len(), env),
+            Undo => match self.undo.pop() {
+                Some(buffer) => {
+                    let current = std::mem::replace(&mut self.buffer, buffer);
+                    self.redo.push(current);
+                }
+                None => self.alert = true,
+            },
+            CancelUndo => match self.redo.pop() {
+                Some(buffer) => {
+                    let current = std::mem::replace(&mut self.buffer, buffer);
+                    self.undo.push(current);
+                }
+                None => self.alert = true,
+            },
+            command if command.is_motion() => self.start_motion(command),
+            _ => self.alert = true,
+        }
+        Outcome::Continue
+    }
+
+    /// Starts a motion command.
+    ///
+    /// Find commands wait for the target character. Other motions are
+    /// performed immediately.
+    fn start_motion(&mut self, command: Command) {
+        use Command::*;
+        let (direction, till) = match command {
+            ViFindChar => (Direction::Forward, false),
+            ViFindCharRev => (Direction::Backward, false),
+            ViTillChar => (Direction::Forward, true),
+            ViTillCharRev => (Direction::Backward, true),
+            _ => return self.perform_motion(Motion::Command(command)),
+        };
+        self.awaiting = Some(Awaiting::Find { direction, till });
+    }
+
+    /// Moves the cursor or applies the pending operator.
+    fn perform_motion(&mut self, motion: Motion) {
+        let operator = self.operator.take();
+        let count = match (operator.and_then(|o| o.count), self.count.take()) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(1).saturating_mul(b.unwrap_or(1))),
+        };
+
+        let Some((target, inclusive)) = self.motion_target(motion, count, operator) else {
+            self.cancel();
+            return;
+        };
+
+        match operator {
+            None => {
+                self.buffer.cursor = target;
+            }
+            Some(operator) => {
+                let cursor = self.buffer.cursor;
+                let start = cursor.min(target);
+                let end = (cursor.max(target) + usize::from(inclusive)).min(self.buffer.len());
+                self.apply_operator(operator, start..end);
+                if operator.command == Command::ViDeleteTo {
+                    self.finish_change(operator.count);
+                }
+            }
+        }
+    }
+
+    /// Computes the target of a motion.
+    ///
+    /// Returns the target index and whether the character at the target is
+    /// included in the operand of an operator. Returns `None` if the motion
+    /// fails.
+    fn motion_target(
+        &mut self,
+        motion: Motion,
+        count: Option<usize>,
+        operator: Option<Operator>,
+    ) -> Option<(usize, bool)> {
+        use Command::*;
+        let n = count.unwrap_or(1);
+        let buffer = &self.buffer;
+        let cursor = buffer.cursor;
+        let len = buffer.len();
+        let command_mode = self.mode == Mode::ViCommand && operator.is_none();
+        let last = if command_mode {
+            len.saturating_sub(1)
+        } else {
+            len
+        };
+
+        let command = match motion {
+            Motion::Find(find) => {
+                self.last_find = Some(find);
+                return self.find_target(find, n);
+            }
+            Motion::Command(command) => command,
+        };
+
+        let result = match command {
+            ForwardChar => {
+                if cursor >= last {
+                    return None;
+                }
+                ((cursor + n).min(last), false)
+            }
+            BackwardChar => {
+                if cursor == 0 {
+                    return None;
+                }
+                (cursor.saturating_sub(n), false)
+            }
+            BeginningOfLine => (0, false),
+            FirstNonblank => (buffer.first_nonblank().min(last), false),
+            EndOfLine => {
+                if self.mode != Mode::ViCommand && operator.is_none() {
+                    (len, false)
+                } else {
+                    (len.saturating_sub(1), true)
+                }
+            }
+            GoToColumn => (n.saturating_sub(1).min(last), false),
+            ForwardViword | ForwardBigword => {
+                let kind = word_kind(command == ForwardViword);
+                let is_change = operator.is_some_and(|o| o.command == ViChangeTo);
+                if is_change && buffer.chars.get(cursor).is_some_and(|c| !c.is_whitespace()) {
+                    // "cw" changes to the end of the word like "ce".
+                    let mut i = cursor;
+                    for _ in 1..n {
+                        i = buffer.word_end(i, kind);
+                    }
+                    let mut end = i;
+                    if n > 1 || !is_word_end(buffer, i, kind) {
+                        end = buffer.word_end(i, kind);
+                    }
+                    (end, true)
+                } else {
+                    let mut i = cursor;
+                    for _ in 0..n {
+                        i = buffer.next_word_start(i, kind);
+                    }
+                    (i.min(last), false)
+                }
+            }
+            ForwardEmacsword => {
+                let mut i = cursor;
+                for _ in 0..n {
+                    i = buffer.next_word_end(i, WordKind::Emacsword);
+                }
+                (i.min(last), false)
+            }
+            BackwardEmacsword => {
+                let mut i = cursor;
+                for _ in 0..n {
+                    i = buffer.prev_word_start(i, WordKind::Emacsword);
+                }
+                (i, false)
+            }
+            BackwardViword | BackwardBigword => {
+                let kind = word_kind(command == BackwardViword);
+                let mut i = cursor;
+                for _ in 0..n {
+                    i = buffer.prev_word_start(i, kind);
+                }
+                (i, false)
+            }
+            EndOfViword | EndOfBigword => {
+                let kind = word_kind(command == EndOfViword);
+                let mut i = cursor;
+                for _ in 0..n {
+                    i = buffer.word_end(i, kind);
+                }
+                (i, true)
+            }
+            ViRefindChar | ViRefindCharRev => {
+                let mut find = self.last_find?;
+                if command == ViRefindCharRev {
+                    find.direction = find.direction.reversed();
+                }
+                return self.find_target(find, n);
+            }
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Computes the target of a character search.
+    fn find_target(&self, find: Find, n: usize) -> Option<(usize, bool)> {
+        let chars = &self.buffer.chars;
+        let cursor = self.buffer.cursor;
+        match find.direction {
+            Direction::Forward => {
+                let start = cursor + 1 + usize::from(find.till);
+                let index = chars
+                    .iter()
+                    .enumerate()
+                    .skip(start)
+                    .filter(|&(_, &c)| c == find.target)
+                    .nth(n - 1)?
+                    .0;
+                Some((index - usize::from(find.till), true))
+            }
+            Direction::Backward => {
+                let end = cursor.saturating_sub(usize::from(find.till));
+                let index = chars[..end]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|&(_, &c)| c == find.target)
+                    .nth(n - 1)?
+                    .0;
+                Some((index + usize::from(find.till), false))
+            }
+        }
+    }
+
+    /// Applies an operator to the range.
+    fn apply_operator(&mut self, operator: Operator, range: std::ops::Range<usize>) {
+        match operator.command {
+            Command::ViCopyTo => {
+                self.yank = self.buffer.chars[range.clone()].to_vec();
+                self.buffer.cursor = range.start;
+            }
+            Command::ViDeleteTo => {
+                self.begin_change();
+                self.yank = self.buffer.remove(range.clone());
+                self.buffer.cursor = range.start;
+            }
+            Command::ViChangeTo => {
+                self.begin_change();
+                self.yank = self.buffer.remove(range.clone());
+                self.start_insert_session(None, range.start);
+            }
+            _ => unreachable!("not an operator: {:?}", operator.command),
+        }
+    }
+
+    /// Processes a character for a command waiting for it.
+    fn input_awaited(&mut self, awaiting: Awaiting, c: char) -> Outcome {
+        match awaiting {
+            Awaiting::Verbatim => {
+                if let Some(recording) = &mut self.recording {
+                    recording.push(c);
+                }
+                self.buffer.insert(&[c]);
+            }
+            Awaiting::Find { direction, till } => {
+                if c == '\x1B' {
+                    self.cancel();
+                } else {
+                    self.command_keys.push(c);
+                    let find = Find {
+                        direction,
+                        till,
+                        target: c,
+                    };
+                    self.perform_motion(Motion::Find(find));
+                }
+            }
+            Awaiting::Replace => {
+                let count = self.count.take();
+                let n = count.unwrap_or(1);
+                if c == '\x1B' {
+                    self.cancel();
+                } else {
+                    self.command_keys.push(c);
+                    self.begin_change();
+                    let cursor = self.buffer.cursor;
+                    for target in &mut self.buffer.chars[cursor..cursor + n] {
+                        *target = c;
+                    }
+                    self.buffer.cursor = cursor + n - 1;
+                    self.finish_change(count);
+                }
+            }
+        }
+        if self.mode == Mode::ViCommand {
+            if self.awaiting.is_none() && self.operator.is_none() {
+                self.command_keys.clear();
+            }
+            self.buffer.clamp_cursor();
+        }
+        Outcome::Continue
+    }
+
+    /// Processes a character while the user is typing a search pattern.
+    fn input_search(&mut self, c: char, env: &Env) -> Outcome {
+        let search = self.search.as_mut().unwrap();
+        match c {
+            '\n' | '\r' => {
+                let mut search = self.search.take().unwrap();
+                if search.pattern.is_empty() {
+                    match &self.last_search {
+                        Some(last) => search.pattern.clone_from(&last.pattern),
+                        None => {
+                            self.alert = true;
+                            return Outcome::Continue;
+                        }
+                    }
+                }
+                self.search_history(&search, env);
+                self.last_search = Some(search);
+                self.buffer.clamp_cursor();
+            }
+            '\x1B' => self.search = None,
+            '\x08' | '\x7F' => {
+                if search.pattern.pop().is_none() {
+                    self.search = None;
+                }
+            }
+            '\x15' => search.pattern.clear(),
+            c if c.is_control() => self.alert = true,
+            c => search.pattern.push(c),
+        }
+        Outcome::Continue
+    }
+
+    /// Processes a character during an incremental search.
+    ///
+    /// Typed characters are appended to the pattern and the history is
+    /// searched as the pattern is updated. A control character other than
+    /// those used for editing the search ends the search and is processed as
+    /// a normal key.
+    fn input_isearch(&mut self, c: char, env: &Env) -> Outcome {
+        let isearch = self.isearch.as_mut().unwrap();
+        match c {
+            '\x12' | '\x13' => {
+                isearch.direction = if c == '\x12' {
+                    Direction::Backward
+                } else {
+                    Direction::Forward
+                };
+                if isearch.pattern.is_empty() {
+                    isearch.pattern.clone_from(&self.last_isearch);
+                }
+                self.isearch_find(false, env);
+            }
+            '\x08' | '\x7F' => {
+                isearch.pattern.pop();
+                self.isearch_restart(env);
+            }
+            '\x07' => {
+                let isearch = self.isearch.take().unwrap();
+                self.go_to_history(isearch.start_index, env);
+                self.buffer = isearch.start;
+            }
+            '\x1B' => self.end_isearch(),
+            c if c.is_control() => {
+                self.end_isearch();
+                return self.input(c, env);
+            }
+            c => {
+                isearch.pattern.push(c);
+                self.isearch_find(true, env);
+            }
+        }
+        Outcome::Continue
+    }
+
+    /// Searches again from where the incremental search started.
+    fn isearch_restart(&mut self, env: &Env) {
+        let isearch = self.isearch.as_ref().unwrap();
+        let (start, start_index) = (isearch.start.clone(), isearch.start_index);
+        if self.history_index != start_index {
+            self.go_to_history(start_index, env);
+        }
+        self.buffer = start;
+        self.isearch_find(true, env);
+    }
+
+    /// Finds the next history entry that contains the search pattern.
+    ///
+    /// If `include_current` is true, the current entry is also searched. The
+    /// current entry is searched in the buffer being edited rather than in the
+    /// history because the user may have modified it.
+    fn isearch_find(&mut self, include_current: bool, env: &Env) {
+        let isearch = self.isearch.as_ref().unwrap();
+        if isearch.pattern.is_empty() {
+            self.isearch.as_mut().unwrap().failed = false;
+            return;
+        }
+        let pattern = isearch.pattern.clone();
+        let direction = isearch.direction;
+        let current = self.history_index;
+        let len = env.history.len();
+        let line = self.buffer.as_string();
+        let matches = |index: &usize| {
+            let entry = if *index == current {
+                &line
+            } else {
+                env.history.get(*index).unwrap_or_default()
+            };
+            entry.contains(pattern.as_str())
+        };
+        let found = match direction {
+            Direction::Backward => {
+                let end = if include_current {
+                    current + 1
+                } else {
+                    current
+                };
+                (0..end.min(len)).rev().find(matches)
+            }
+            Direction::Forward => {
+                let start = if include_current {
+                    current
+                } else {
+                    current + 1
+                };
+                (start..len).find(matches)
+            }
+        };
+
+        let Some(index) = found else {
+            self.isearch.as_mut().unwrap().failed = true;
+            self.alert = true;
+            return;
+        };
+        if index != current {
+            self.go_to_history(index, env);
+        }
+        let line = self.buffer.as_string();
+        let position = match direction {
+            Direction::Backward => line.rfind(pattern.as_str()),
+            Direction::Forward => line.find(pattern.as_str()),
+        };
+        let cursor = line[..position.unwrap_or(0)].chars().count();
+        self.buffer.cursor = cursor.min(self.buffer.len());
+        self.isearch.as_mut().unwrap().failed = false;
+    }
+
+    /// Ends the incremental search, leaving the found entry in the buffer.
+    fn end_isearch(&mut self) {
+        if let Some(isearch) = self.isearch.take() {
+            if !isearch.pattern.is_empty() {
+                self.last_isearch = isearch.pattern;
+            }
+        }
+    }
+
+    /// Searches the history for the pattern.
+    ///
+    /// A pattern starting with `^` matches only at the beginning of lines.
+    fn search_history(&mut self, search: &Search, env: &Env) {
+        let (anchored, pattern) = match search.pattern.strip_prefix('^') {
+            Some(pattern) => (true, pattern),
+            None => (false, search.pattern.as_str()),
+        };
+        let matches = |index: &usize| {
+            let entry = env.history.get(*index).unwrap_or_default();
+            if anchored {
+                entry.starts_with(pattern)
+            } else {
+                entry.contains(pattern)
+            }
+        };
+        let found = match search.direction {
+            Direction::Backward => (0..self.history_index).rev().find(matches),
+            Direction::Forward => (self.history_index + 1..env.history.len()).find(matches),
+        };
+        match found {
+            Some(index) => self.go_to_history(index, env),
+            None => self.alert = true,
+        }
+    }
+
+    /// Replaces the buffer with the history entry at the index.
+    ///
+    /// The index equal to the history length denotes the new line.
+    fn go_to_history(&mut self, index: usize, env: &Env) {
+        if self.history_index == env.history.len() {
+            self.new_line = Some(self.buffer.clone());
+        }
+        self.history_index = index;
+        self.buffer = match env.history.get(index) {
+            Some(entry) => Buffer::from(entry),
+            None => self.new_line.take().unwrap_or_default(),
+        };
+        if self.mode == Mode::ViCommand {
+            self.buffer.cursor = 0;
+        }
+        self.original = self.buffer.clone();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Saves the current buffer for the `undo` command.
+    fn begin_change(&mut self) {
+        self.undo.push(self.buffer.clone());
+        self.redo.clear();
+    }
+
+    /// Enters the vi insert mode at the position.
+    fn start_insert(&mut self, count: Option<usize>, position: usize) {
+        self.begin_change();
+        self.start_insert_session(count, position);
+    }
+
+    /// Enters the vi insert mode without saving the buffer for undo.
+    ///
+    /// The keys typed in the insert mode are recorded for the `redo` command.
+    fn start_insert_session(&mut self, count: Option<usize>, position: usize) {
+        self.buffer.cursor = position;
+        self.mode = Mode::ViInsert;
+        if !self.replaying {
+            self.recording = Some(take(&mut self.command_keys));
+            self.change_count = count;
+        }
+        self.command_keys.clear();
+        self.insert_count = count.unwrap_or(1);
+        self.insert_start = position;
+    }
+
+    /// Starts the vi insert mode at the beginning of a line.
+    fn begin_insert(&mut self) {
+        self.mode = Mode::ViInsert;
+        self.begin_change();
+        self.insert_count = 1;
+        self.insert_start = self.buffer.cursor;
+    }
+
+    /// Leaves the vi insert mode.
+    fn end_insert(&mut self, keys: &str) {
+        let cursor = self.buffer.cursor;
+        if self.insert_count > 1 && cursor >= self.insert_start {
+            let inserted = self.buffer.chars[self.insert_start..cursor].to_vec();
+            for _ in 1..self.insert_count {
+                self.buffer.insert(&inserted);
+            }
+        }
+        self.insert_count = 1;
+
+        if self
+            .undo
+            .last()
+            .is_some_and(|b| b.chars == self.buffer.chars)
+        {
+            self.undo.pop();
+        }
+        if let Some(mut keys_typed) = self.recording.take() {
+            keys_typed.push_str(keys);
+            self.last_change = Some(Change {
+                count: self.change_count,
+                keys: keys_typed,
+            });
+        }
+
+        self.mode = Mode::ViCommand;
+        self.buffer.cursor = self.buffer.cursor.saturating_sub(1);
+    }
+
+    /// Records the completed change for the `redo` command.
+    fn finish_change(&mut self, count: Option<usize>) {
+        if !self.replaying {
+            let keys = take(&mut self.command_keys);
+            self.last_change = Some(Change { count, keys });
+        }
+    }
+
+    /// Repeats the last change.
+    fn redo(&mut self, count: Option<usize>, env: &Env) {
+        let Some(change) = self.last_change.clone() else {
+            self.alert = true;
+            return;
+        };
+        self.command_keys.clear();
+        self.count = count.or(change.count);
+        self.replaying = true;
+        for c in change.keys.chars() {
+            self.input(c, env);
+        }
+        _ = self.flush(env);
+        self.replaying = false;
+    }
+
+    /// Cancels the pending count, operator, and awaited character.
+    fn cancel(&mut self) {
+        self.cancel_pending();
+        self.alert = true;
+    }
+
+    fn cancel_pending(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.awaiting = None;
+        self.command_keys.clear();
+    }
+}
+
+/// Tests whether the command saves the deleted text in the kill ring.
+fn is_kill(command: Command) -> bool {
+    use Command::*;
+    matches!(
+        command,
+        KillLine | BackwardKillLine | KillEmacsword | BackwardKillEmacsword | BackwardKillBigword
+    )
+}
+
+fn word_kind(is_viword: bool) -> WordKind {
+    if is_viword {
+        WordKind::Viword
+    } else {
+        WordKind::Bigword
+    }
+}
+
+/// Tests whether the index is at the last character of a word.
+fn is_word_end(buffer: &Buffer, index: usize, kind: WordKind) -> bool {
+    match buffer.chars.get(index + 1) {
+        None => true,
+        Some(&next) => {
+            let current = buffer.chars[index];
+            next.is_whitespace()
+                || (kind == WordKind::Viword && is_word_char(current) != is_word_char(next))
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Switches the case of a character.
+fn switch_case(c: char) -> char {
+    if c.is_lowercase() {
+        c.to_uppercase().next().unwrap_or(c)
+    } else if c.is_uppercase() {
+        c.to_lowercase().next().unwrap_or(c)
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types the keys and returns the last outcome.
+    fn type_keys(engine: &mut Engine, env: &Env, keys: &str) -> Outcome {
+        for c in keys.chars() {
+            let outcome = engine.input(c, env);
+            if outcome != Outcome::Continue {
+                return outcome;
+            }
+        }
+        engine.flush(env)
+    }
+
+    fn engine_with(text: &str, cursor: usize) -> (Engine, Env) {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, text);
+        type_keys(&mut engine, &env, "\x1B0");
+        for _ in 0..cursor {
+            type_keys(&mut engine, &env, "l");
+        }
+        (engine, env)
+    }
+
+    fn line(engine: &Engine) -> String {
+        engine.buffer().as_string()
+    }
+
+    #[test]
+    fn insert_and_accept() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        assert_eq!(engine.mode(), Mode::ViInsert);
+        let outcome = type_keys(&mut engine, &env, "echo hello\r");
+        assert_eq!(outcome, Outcome::Accept("echo hello".to_string()));
+    }
+
+    #[test]
+    fn eof_on_empty_line() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        assert_eq!(type_keys(&mut engine, &env, "a\x04"), Outcome::Continue);
+        assert!(engine.take_alert());
+        assert_eq!(type_keys(&mut engine, &env, "\x7F\x04"), Outcome::Eof);
+    }
+
+    #[test]
+    fn insert_mode_deletion() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "ls foo.bar\x17");
+        assert_eq!(line(&engine), "ls foo.");
+        type_keys(&mut engine, &env, "\x08\x7F");
+        assert_eq!(line(&engine), "ls fo");
+        type_keys(&mut engine, &env, "\x15");
+        assert_eq!(line(&engine), "");
+    }
+
+    #[test]
+    fn escape_enters_command_mode() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "abc\x1B");
+        assert_eq!(engine.mode(), Mode::ViCommand);
+        assert_eq!(engine.buffer().cursor, 2);
+    }
+
+    #[test]
+    fn escape_followed_by_command_in_same_chunk() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        for c in "abc\x1Bx".chars() {
+            engine.input(c, &env);
+        }
+        assert_eq!(line(&engine), "ab");
+    }
+
+    #[test]
+    fn arrow_keys_in_insert_mode() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "ac\x1B[Db\x1B[3~");
+        assert_eq!(line(&engine), "ab");
+        type_keys(&mut engine, &env, "\x1B[H_\x1B[F!");
+        assert_eq!(line(&engine), "_ab!");
+    }
+
+    #[test]
+    fn verbatim_insert() {
+        let env = Env::new_virtual();
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "a\x16\x1Bb");
+        assert_eq!(line(&engine), "a\x1Bb");
+    }
+
+    #[test]
+    fn motions() {
+        let (mut engine, env) = engine_with("foo.bar  baz", 0);
+        type_keys(&mut engine, &env, "w");
+        assert_eq!(engine.buffer().cursor, 3);
+        type_keys(&mut engine, &env, "2w");
+        assert_eq!(engine.buffer().cursor, 9);
+        type_keys(&mut engine, &env, "b");
+        assert_eq!(engine.buffer().cursor, 4);
+        type_keys(&mut engine, &env, "B");
+        assert_eq!(engine.buffer().cursor, 0);
+        type_keys(&mut engine, &env, "W");
+        assert_eq!(engine.buffer().cursor, 9);
+        type_keys(&mut engine, &env, "0e");
+        assert_eq!(engine.buffer().cursor, 2);
+        type_keys(&mut engine, &env, "E");
+        assert_eq!(engine.buffer().cursor, 6);
+        type_keys(&mut engine, &env, "$");
+        assert_eq!(engine.buffer().cursor, 11);
+        type_keys(&mut engine, &env, "0");
+        assert_eq!(engine.buffer().cursor, 0);
+        type_keys(&mut engine, &env, "5|");
+        assert_eq!(engine.buffer().cursor, 4);
+        type_keys(&mut engine, &env, "3h");
+        assert_eq!(engine.buffer().cursor, 1);
+        type_keys(&mut engine, &env, "100l");
+        assert_eq!(engine.buffer().cursor, 11);
+    }
+
+    #[test]
+    fn first_nonblank() {
+        let (mut engine, env) = engine_with("   ls", 4);
+        type_keys(&mut engine, &env, "^");
+        assert_eq!(engine.buffer().cursor, 3);
+    }
+
+    #[test]
+    fn find_motions() {
+        let (mut engine, env) = engine_with("a,b,c,d", 0);
+        type_keys(&mut engine, &env, "f,");
+        assert_eq!(engine.buffer().cursor, 1);
+        type_keys(&mut engine, &env, ";");
+        assert_eq!(engine.buffer().cursor, 3);
+        type_keys(&mut engine, &env, ",");
+        assert_eq!(engine.buffer().cursor, 1);
+        type_keys(&mut engine, &env, "2tc");
+        assert_eq!(engine.buffer().cursor, 1);
+        assert!(engine.take_alert());
+        type_keys(&mut engine, &env, "td");
+        assert_eq!(engine.buffer().cursor, 5);
+        type_keys(&mut engine, &env, "Fa");
+        assert_eq!(engine.buffer().cursor, 0);
+        type_keys(&mut engine, &env, "$Tb");
+        assert_eq!(engine.buffer().cursor, 3);
+    }
+
+    #[test]
+    fn delete_operator() {
+        let (mut engine, env) = engine_with("one two three", 0);
+        type_keys(&mut engine, &env, "dw");
+        assert_eq!(line(&engine), "two three");
+        assert_eq!(engine.buffer().cursor, 0);
+        type_keys(&mut engine, &env, "de");
+        assert_eq!(line(&engine), " three");
+        type_keys(&mut engine, &env, "$d0");
+        assert_eq!(line(&engine), "e");
+        type_keys(&mut engine, &env, "dd");
+        assert_eq!(line(&engine), "");
+    }
+
+    #[test]
+    fn delete_with_counts() {
+        let (mut engine, env) = engine_with("a b c d e f g", 0);
+        type_keys(&mut engine, &env, "2d2w");
+        assert_eq!(line(&engine), "e f g");
+        type_keys(&mut engine, &env, "d2w");
+        assert_eq!(line(&engine), "g");
+    }
+
+    #[test]
+    fn delete_to_find() {
+        let (mut engine, env) = engine_with("echo (foo) bar", 0);
+        type_keys(&mut engine, &env, "df)");
+        assert_eq!(line(&engine), " bar");
+        let (mut engine, env) = engine_with("echo (foo) bar", 0);
+        type_keys(&mut engine, &env, "dt)");
+        assert_eq!(line(&engine), ") bar");
+    }
+
+    #[test]
+    fn failed_motion_cancels_operator() {
+        let (mut engine, env) = engine_with("abc", 0);
+        type_keys(&mut engine, &env, "dfz");
+        assert_eq!(line(&engine), "abc");
+        assert!(engine.take_alert());
+        type_keys(&mut engine, &env, "x");
+        assert_eq!(line(&engine), "bc");
+    }
+
+    #[test]
+    fn change_operator() {
+        let (mut engine, env) = engine_with("one two three", 4);
+        type_keys(&mut engine, &env, "cwTWO\x1B");
+        assert_eq!(line(&engine), "one TWO three");
+        assert_eq!(engine.mode(), Mode::ViCommand);
+        type_keys(&mut engine, &env, "ccnew\x1B");
+        assert_eq!(line(&engine), "new");
+    }
+
+    #[test]
+    fn copy_and_put() {
+        let (mut engine, env) = engine_with("ab cd", 0);
+        type_keys(&mut engine, &env, "yw$p");
+        assert_eq!(line(&engine), "ab cdab ");
+        assert_eq!(engine.buffer().cursor, 7);
+        type_keys(&mut engine, &env, "0xP");
+        assert_eq!(line(&engine), "ab cdab ");
+        type_keys(&mut engine, &env, "yy$2p");
+        assert_eq!(line(&engine), "ab cdab ab cdab ab cdab ");
+    }
+
+    #[test]
+    fn simple_editing_commands() {
+        let (mut engine, env) = engine_with("hello world", 0);
+        type_keys(&mut engine, &env, "3x");
+        assert_eq!(line(&engine), "lo world");
+        type_keys(&mut engine, &env, "$X");
+        assert_eq!(line(&engine), "lo word");
+        type_keys(&mut engine, &env, "0rL");
+        assert_eq!(line(&engine), "Lo word");
+        type_keys(&mut engine, &env, "3~");
+        assert_eq!(line(&engine), "lO word");
+        assert_eq!(engine.buffer().cursor, 3);
+        type_keys(&mut engine, &env, "D");
+        assert_eq!(line(&engine), "lO ");
+        type_keys(&mut engine, &env, "0sX\x1B");
+        assert_eq!(line(&engine), "XO ");
+        type_keys(&mut engine, &env, "Cy\x1B");
+        assert_eq!(line(&engine), "y");
+        type_keys(&mut engine, &env, "Snew\x1B");
+        assert_eq!(line(&engine), "new");
+    }
+
+    #[test]
+    fn insert_commands() {
+        let (mut engine, env) = engine_with("bc", 0);
+        type_keys(&mut engine, &env, "ia\x1B");
+        assert_eq!(line(&engine), "abc");
+        type_keys(&mut engine, &env, "A!\x1B");
+        assert_eq!(line(&engine), "abc!");
+        type_keys(&mut engine, &env, "I>\x1B");
+        assert_eq!(line(&engine), ">abc!");
+        type_keys(&mut engine, &env, "a-\x1B");
+        assert_eq!(line(&engine), ">-abc!");
+        type_keys(&mut engine, &env, "3a.\x1B");
+        assert_eq!(line(&engine), ">-...abc!");
+    }
+
+    #[test]
+    fn undo_and_cancel_undo() {
+        let (mut engine, env) = engine_with("abc", 0);
+        type_keys(&mut engine, &env, "x");
+        type_keys(&mut engine, &env, "x");
+        assert_eq!(line(&engine), "c");
+        type_keys(&mut engine, &env, "u");
+        assert_eq!(line(&engine), "bc");
+        type_keys(&mut engine, &env, "u");
+        assert_eq!(line(&engine), "abc");
+        type_keys(&mut engine, &env, "u");
+        assert_eq!(line(&engine), "");
+        type_keys(&mut engine, &env, "u");
+        assert!(engine.take_alert());
+        type_keys(&mut engine, &env, "\x12\x12");
+        assert_eq!(line(&engine), "bc");
+    }
+
+    #[test]
+    fn undo_insertion_as_single_change() {
+        let (mut engine, env) = engine_with("a", 0);
+        type_keys(&mut engine, &env, "Abcd\x1B");
+        assert_eq!(line(&engine), "abcd");
+        type_keys(&mut engine, &env, "u");
+        assert_eq!(line(&engine), "a");
+    }
+
+    #[test]
+    fn undo_all() {
+        let (mut engine, env) = engine_with("abc", 0);
+        type_keys(&mut engine, &env, "xxU");
+        assert_eq!(line(&engine), "");
+        type_keys(&mut engine, &env, "u");
+        assert_eq!(line(&engine), "c");
+    }
+
+    #[test]
+    fn redo_repeats_last_change() {
+        let (mut engine, env) = engine_with("a b c d e f", 0);
+        type_keys(&mut engine, &env, "dw");
+        type_keys(&mut engine, &env, ".");
+        assert_eq!(line(&engine), "c d e f");
+        type_keys(&mut engine, &env, "2.");
+        assert_eq!(line(&engine), "e f");
+
+        let (mut engine, env) = engine_with("abcdef", 0);
+        type_keys(&mut engine, &env, "2x.");
+        assert_eq!(line(&engine), "ef");
+    }
+
+    #[test]
+    fn redo_repeats_insertion() {
+        let (mut engine, env) = engine_with("x", 0);
+        type_keys(&mut engine, &env, "A-y\x1B");
+        type_keys(&mut engine, &env, ".");
+        assert_eq!(line(&engine), "x-y-y");
+        type_keys(&mut engine, &env, "0cwz\x1B");
+        assert_eq!(line(&engine), "z-y-y");
+        type_keys(&mut engine, &env, "w.");
+        assert_eq!(line(&engine), "zzy-y");
+    }
+
+    #[test]
+    fn motions_are_not_redone() {
+        let (mut engine, env) = engine_with("abcd", 0);
+        type_keys(&mut engine, &env, "xl.");
+        assert_eq!(line(&engine), "bd");
+    }
+
+    fn env_with_history(entries: &[&str]) -> Env {
+        let mut env = Env::new_virtual();
+        for entry in entries {
+            env.history.add(entry);
+        }
+        env
+    }
+
+    #[test]
+    fn history_navigation() {
+        let env = env_with_history(&["first", "second"]);
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "new\x1Bk");
+        assert_eq!(line(&engine), "second");
+        assert_eq!(engine.buffer().cursor, 0);
+        type_keys(&mut engine, &env, "k");
+        assert_eq!(line(&engine), "first");
+        type_keys(&mut engine, &env, "k");
+        assert!(engine.take_alert());
+        type_keys(&mut engine, &env, "2j");
+        assert_eq!(line(&engine), "new");
+        type_keys(&mut engine, &env, "j");
+        assert!(engine.take_alert());
+    }
+
+    #[test]
+    fn history_navigation_with_arrows_in_insert_mode() {
+        let env = env_with_history(&["first", "second"]);
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "\x1B[A\x1B[A!");
+        assert_eq!(line(&engine), "first!");
+    }
+
+    #[test]
+    fn history_search() {
+        let env = env_with_history(&["echo 1", "ls", "echo 2", "cat"]);
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "\x1B/ech");
+        assert_eq!(engine.display(), ("/ech".chars().collect(), 4));
+        type_keys(&mut engine, &env, "o\r");
+        assert_eq!(line(&engine), "echo 2");
+        type_keys(&mut engine, &env, "n");
+        assert_eq!(line(&engine), "echo 1");
+        type_keys(&mut engine, &env, "n");
+        assert!(engine.take_alert());
+        type_keys(&mut engine, &env, "N");
+        assert_eq!(line(&engine), "echo 2");
+        type_keys(&mut engine, &env, "?^c\r");
+        assert_eq!(line(&engine), "cat");
+        type_keys(&mut engine, &env, "/^e\r");
+        assert_eq!(line(&engine), "echo 2");
+        type_keys(&mut engine, &env, "/\r");
+        assert_eq!(line(&engine), "echo 1");
+    }
+
+    #[test]
+    fn search_abort() {
+        let env = env_with_history(&["echo"]);
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        type_keys(&mut engine, &env, "\x1B/e\x7F\x7F");
+        assert_eq!(engine.display(), (vec![], 0));
+        type_keys(&mut engine, &env, "/e\x1B");
+        assert_eq!(line(&engine), "");
+    }
+
+    #[test]
+    fn accept_in_command_mode() {
+        let (mut engine, env) = engine_with("ls", 0);
+        let outcome = type_keys(&mut engine, &env, "\r");
+        assert_eq!(outcome, Outcome::Accept("ls".to_string()));
+    }
+
+    #[test]
+    fn unbound_key_in_command_mode_alerts() {
+        let (mut engine, env) = engine_with("ls", 0);
+        type_keys(&mut engine, &env, "Q");
+        assert!(engine.take_alert());
+        assert_eq!(line(&engine), "ls");
+    }
+
+    #[test]
+    fn wide_characters() {
+        let (mut engine, env) = engine_with("あいう", 0);
+        type_keys(&mut engine, &env, "lx");
+        assert_eq!(line(&engine), "あう");
+        type_keys(&mut engine, &env, "iい\x1B");
+        assert_eq!(line(&engine), "あいう");
+    }
+
+    fn emacs_engine(env: &Env, text: &str) -> Engine {
+        let mut engine = Engine::new();
+        engine.start_line(Mode::Emacs, env);
+        type_keys(&mut engine, env, text);
+        engine
+    }
+
+    #[test]
+    fn emacs_insert_and_accept() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "");
+        assert_eq!(engine.mode(), Mode::Emacs);
+        let outcome = type_keys(&mut engine, &env, "echo\x02\x02\x7F\x06x\r");
+        assert_eq!(outcome, Outcome::Accept("ehxo".to_string()));
+    }
+
+    #[test]
+    fn emacs_eof_or_delete() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "ab\x01\x04");
+        assert_eq!(line(&engine), "b");
+        assert_eq!(type_keys(&mut engine, &env, "\x04"), Outcome::Continue);
+        assert_eq!(type_keys(&mut engine, &env, "\x04"), Outcome::Eof);
+    }
+
+    #[test]
+    fn emacs_word_motions() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "foo.bar  baz\x01");
+        type_keys(&mut engine, &env, "\x1Bf");
+        assert_eq!(engine.buffer().cursor, 3);
+        type_keys(&mut engine, &env, "\x1Bf");
+        assert_eq!(engine.buffer().cursor, 7);
+        type_keys(&mut engine, &env, "\x05\x1Bb");
+        assert_eq!(engine.buffer().cursor, 9);
+        type_keys(&mut engine, &env, "\x1Bb\x1Bb");
+        assert_eq!(engine.buffer().cursor, 0);
+    }
+
+    #[test]
+    fn emacs_kill_and_yank() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "echo foo bar\x02\x02\x02\x0B");
+        assert_eq!(line(&engine), "echo foo ");
+        type_keys(&mut engine, &env, "\x01\x19");
+        assert_eq!(line(&engine), "barecho foo ");
+        assert_eq!(engine.buffer().cursor, 3);
+    }
+
+    #[test]
+    fn emacs_consecutive_kills_are_joined() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "one two three\x17\x17");
+        assert_eq!(line(&engine), "one ");
+        type_keys(&mut engine, &env, "\x19");
+        assert_eq!(line(&engine), "one two three");
+    }
+
+    #[test]
+    fn emacs_yank_pop() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "one two\x17\x02\x17\x19");
+        assert_eq!(line(&engine), "one ");
+        type_keys(&mut engine, &env, "\x1By");
+        assert_eq!(line(&engine), "two ");
+        type_keys(&mut engine, &env, "\x1By");
+        assert_eq!(line(&engine), "one ");
+
+        // yank-pop is effective only just after yank
+        type_keys(&mut engine, &env, "\x02\x1By");
+        assert!(engine.take_alert());
+    }
+
+    #[test]
+    fn emacs_kill_words() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "ls foo.bar\x1B\x7F");
+        assert_eq!(line(&engine), "ls foo.");
+        type_keys(&mut engine, &env, "\x01\x1Bd");
+        assert_eq!(line(&engine), " foo.");
+    }
+
+    #[test]
+    fn emacs_transpose_chars() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "abc\x14");
+        assert_eq!(line(&engine), "acb");
+        type_keys(&mut engine, &env, "\x01\x06\x14");
+        assert_eq!(line(&engine), "cab");
+        assert_eq!(engine.buffer().cursor, 2);
+        type_keys(&mut engine, &env, "\x01\x14");
+        assert!(engine.take_alert());
+    }
+
+    #[test]
+    fn emacs_transpose_words() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "cp foo bar\x1Bt");
+        assert_eq!(line(&engine), "cp bar foo");
+        assert_eq!(engine.buffer().cursor, 10);
+        type_keys(&mut engine, &env, "\x01\x1Bt");
+        assert_eq!(line(&engine), "cp bar foo");
+        assert!(engine.take_alert());
+    }
+
+    #[test]
+    fn emacs_case_commands() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "foo bAR baz\x01\x1Bu");
+        assert_eq!(line(&engine), "FOO bAR baz");
+        type_keys(&mut engine, &env, "\x1Bc");
+        assert_eq!(line(&engine), "FOO Bar baz");
+        type_keys(&mut engine, &env, "\x01\x1Bl");
+        assert_eq!(line(&engine), "foo Bar baz");
+    }
+
+    #[test]
+    fn emacs_undo() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "echo foo\x17");
+        assert_eq!(line(&engine), "echo ");
+        type_keys(&mut engine, &env, "\x1F");
+        assert_eq!(line(&engine), "echo foo");
+        type_keys(&mut engine, &env, "\x18\x15");
+        assert_eq!(line(&engine), "");
+    }
+
+    #[test]
+    fn emacs_history_navigation() {
+        let env = env_with_history(&["first", "second"]);
+        let mut engine = emacs_engine(&env, "new\x10");
+        assert_eq!(line(&engine), "second");
+        assert_eq!(engine.buffer().cursor, 6);
+        type_keys(&mut engine, &env, "\x1B<");
+        assert_eq!(line(&engine), "first");
+        type_keys(&mut engine, &env, "\x1B>");
+        assert_eq!(line(&engine), "new");
+    }
+
+    #[test]
+    fn emacs_incremental_search() {
+        let env = env_with_history(&["echo 1", "ls", "echo 2", "cat"]);
+        let mut engine = emacs_engine(&env, "\x12e");
+        assert_eq!(line(&engine), "echo 2");
+        assert_eq!(engine.buffer().cursor, 0);
+        let (text, cursor) = engine.display();
+        let prefix = "(reverse-i-search)`e': ";
+        assert_eq!(text.iter().collect::<String>(), format!("{prefix}echo 2"));
+        assert_eq!(cursor, prefix.len());
+
+        type_keys(&mut engine, &env, "cho 1");
+        assert_eq!(line(&engine), "echo 1");
+        type_keys(&mut engine, &env, "\x7F\x7F\x12");
+        assert_eq!(line(&engine), "echo 1");
+
+        type_keys(&mut engine, &env, "\x12");
+        assert!(engine.take_alert());
+        let (text, _) = engine.display();
+        assert!(text.iter().collect::<String>().starts_with("(failed "));
+
+        let outcome = type_keys(&mut engine, &env, "\x13\r");
+        assert_eq!(outcome, Outcome::Accept("echo 2".to_string()));
+    }
+
+    #[test]
+    fn emacs_incremental_search_abort() {
+        let env = env_with_history(&["echo 1", "ls"]);
+        let engine = emacs_engine(&env, "new\x12ech\x07");
+        assert_eq!(line(&engine), "new");
+        assert_eq!(engine.display().0.len(), 3);
+    }
+
+    #[test]
+    fn emacs_incremental_search_ended_by_other_key() {
+        let env = env_with_history(&["ls -l", "echo 1", "ls"]);
+        let mut engine = emacs_engine(&env, "\x12ls\x05x");
+        assert_eq!(line(&engine), "lsx");
+
+        // The last pattern is reused if the search starts with an empty one.
+        type_keys(&mut engine, &env, "\x15\x12\x12");
+        assert_eq!(line(&engine), "ls -l");
+    }
+
+    #[test]
+    fn emacs_incremental_search_after_editing_history_entry() {
+        let env = env_with_history(&["echo 1", "echo 2"]);
+        let mut engine = emacs_engine(&env, "\x10\x15\x12o");
+        assert_eq!(line(&engine), "echo 1");
+        assert_eq!(engine.buffer().cursor, 3);
+        type_keys(&mut engine, &env, "\x17");
+        assert_eq!(line(&engine), "o 1");
+        type_keys(&mut engine, &env, "\x19");
+        assert_eq!(line(&engine), "echo 1");
+
+        // The edited current entry is searched instead of the history.
+        let env = env_with_history(&["ls"]);
+        let mut engine = emacs_engine(&env, "\x10\x15foo\x01\x12o");
+        assert_eq!(line(&engine), "foo");
+        assert_eq!(engine.buffer().cursor, 2);
+        type_keys(&mut engine, &env, "\x17\x19");
+        assert_eq!(line(&engine), "foo");
+    }
+
+    #[test]
+    fn emacs_custom_binding() {
+        let mut env = Env::new_virtual();
+        let keymap = env.key_bindings.get_mut(Mode::Emacs);
+        keymap.bind("\x1Bq", Command::KillLine);
+        let engine = emacs_engine(&env, "foo\x01\x1Bq");
+        assert_eq!(line(&engine), "");
+    }
+
+    #[test]
+    fn completion_request() {
+        let env = Env::new_virtual();
+        let mut engine = emacs_engine(&env, "echo fo");
+        assert_eq!(type_keys(&mut engine, &env, "\t"), Outcome::Complete);
+        engine.insert_completion(5, "foo ");
+        assert_eq!(line(&engine), "echo foo ");
+        assert_eq!(engine.buffer().cursor, 9);
+        type_keys(&mut engine, &env, "\x1F");
+        assert_eq!(line(&engine), "echo fo");
+
+        let mut engine = Engine::new();
+        engine.start_line(Mode::ViInsert, &env);
+        assert_eq!(type_keys(&mut engine, &env, "ls\t"), Outcome::Complete);
+    }
+
+    #[test]
+    fn multi_line_buffer() {
+        let env = env_with_history(&["for i in 1 2\ndo echo $i\ndone"]);
+        let mut engine = emacs_engine(&env, "\x10");
+        assert_eq!(line(&engine), "for i in 1 2\ndo echo $i\ndone");
+        let outcome = type_keys(&mut engine, &env, "\x16\n;\r");
+        assert_eq!(
+            outcome,
+            Outcome::Accept("for i in 1 2\ndo echo $i\ndone\n;".to_string())
+        );
+    }
+}
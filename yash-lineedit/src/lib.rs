@@ -0,0 +1,54 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line editor for the shell.
+//!
+//! An interactive shell allows the user to edit the command line before
+//! executing it if the standard input is a terminal and line editing is
+//! enabled. The [`LineEditor`] is an input decorator that reads keys from the
+//! terminal and edits the line in the vi-like or emacs-like editing mode.
+//!
+//! In the vi insert mode, typed characters are inserted into the line. In the
+//! vi command mode, keys are interpreted as [commands](Command) that move the
+//! cursor, modify the line, and recall lines from the history. In the emacs
+//! mode, typed characters are inserted and control keys and meta keys
+//! (escape-prefixed keys) are interpreted as commands. The mapping from keys
+//! to commands is defined by a [`KeyMap`] for each [`Mode`] in the
+//! [`KeyBindings`] of the environment.
+//!
+//! The [`Complete`](Command::Complete) command completes the word before the
+//! cursor with candidates generated by
+//! [`yash_semantics::completion`]. If there are multiple candidates that
+//! have no common prefix longer than the word, they are listed below the edit
+//! line.
+//!
+//! The [`Engine`] implements the editing commands independently of the
+//! terminal. The terminal is put in the raw mode while the line is being
+//! edited and restored before the line is returned to the shell.
+
+mod buffer;
+mod display;
+mod editor;
+mod engine;
+mod terminal;
+
+pub use self::buffer::Buffer;
+pub use self::editor::LineEditor;
+pub use self::engine::Engine;
+pub use self::engine::Outcome;
+pub use self::terminal::RawMode;
+#[doc(no_inline)]
+pub use yash_env::keymap::*;
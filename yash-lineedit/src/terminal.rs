@@ -0,0 +1,154 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Terminal mode control
+
+use yash_env::io::Fd;
+use yash_env::system::OFlag;
+use yash_env::system::SetArg;
+use yash_env::system::Termios;
+use yash_env::SharedSystem;
+use yash_env::System;
+
+/// Guard that keeps the terminal in the raw mode
+///
+/// The raw mode disables the canonical input processing and echoing by the
+/// terminal so that the line editor can process each key as it is typed.
+/// Signal-generating characters remain effective so that the user can
+/// interrupt the shell while editing.
+///
+/// The guard saves the original terminal mode when created, and the terminal
+/// is put into the raw mode by [`enter`](Self::enter). The editor
+/// [leaves](Self::leave) the raw mode before running any commands, such as
+/// completion functions, and before the shell is suspended.
+///
+/// The original terminal mode is restored when the guard is dropped, which
+/// happens when the editor returns a line to the shell, when reading is
+/// interrupted by a signal, and when the shell exits. The file status flags
+/// of the file descriptor are restored as well, because an interrupted read
+/// may leave the file descriptor in the non-blocking mode.
+#[derive(Debug)]
+#[must_use = "the terminal mode is restored when the guard is dropped"]
+pub struct RawMode {
+    system: SharedSystem,
+    fd: Fd,
+    original: Termios,
+    flags: OFlag,
+    is_raw: bool,
+}
+
+impl RawMode {
+    /// Saves the current terminal mode.
+    ///
+    /// The terminal mode is not changed until [`enter`](Self::enter) is
+    /// called. This function fails if the file descriptor is not a terminal.
+    pub fn new(system: SharedSystem, fd: Fd) -> nix::Result<Self> {
+        let original = system.tcgetattr(fd)?;
+        let flags = system.fcntl_getfl(fd)?;
+        Ok(RawMode {
+            system,
+            fd,
+            original,
+            flags,
+            is_raw: false,
+        })
+    }
+
+    /// Puts the terminal into the raw mode.
+    ///
+    /// The raw mode is applied even if the guard has already entered it, since
+    /// the terminal mode may have been changed while the shell was suspended.
+    pub fn enter(&mut self) -> nix::Result<()> {
+        use nix::sys::termios::InputFlags;
+        use nix::sys::termios::LocalFlags;
+        use nix::sys::termios::SpecialCharacterIndices::{VMIN, VTIME};
+
+        let mut raw = self.original.clone();
+        raw.local_flags
+            .remove(LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::IEXTEN);
+        raw.input_flags.remove(InputFlags::IXON);
+        raw.control_chars[VMIN as usize] = 1;
+        raw.control_chars[VTIME as usize] = 0;
+        self.system.tcsetattr(self.fd, SetArg::TCSADRAIN, &raw)?;
+        self.is_raw = true;
+        Ok(())
+    }
+
+    /// Restores the original terminal mode.
+    ///
+    /// This function does nothing if the terminal is not in the raw mode.
+    pub fn leave(&mut self) {
+        if self.is_raw {
+            _ = self.system.fcntl_setfl(self.fd, self.flags);
+            _ = self
+                .system
+                .tcsetattr(self.fd, SetArg::TCSADRAIN, &self.original);
+            self.is_raw = false;
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        self.leave()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::sys::termios::LocalFlags;
+    use std::collections::VecDeque;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::VirtualSystem;
+
+    #[test]
+    fn raw_mode_is_restored_on_drop() {
+        let system = VirtualSystem::new();
+        {
+            let state = system.state.borrow();
+            let stdin = state.file_system.get("/dev/stdin").unwrap();
+            stdin.borrow_mut().body = FileBody::Terminal {
+                content: VecDeque::new(),
+            };
+        }
+        let mut system = SharedSystem::new(Box::new(system));
+        let mut termios = system.tcgetattr(Fd::STDIN).unwrap();
+        termios.local_flags = LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::ISIG;
+        system
+            .tcsetattr(Fd::STDIN, SetArg::TCSANOW, &termios)
+            .unwrap();
+
+        let mut guard = RawMode::new(system.clone(), Fd::STDIN).unwrap();
+        assert_eq!(system.tcgetattr(Fd::STDIN), Ok(termios.clone()));
+        guard.enter().unwrap();
+        let raw = system.tcgetattr(Fd::STDIN).unwrap();
+        assert_eq!(raw.local_flags, LocalFlags::ISIG);
+
+        guard.leave();
+        assert_eq!(system.tcgetattr(Fd::STDIN), Ok(termios.clone()));
+        guard.enter().unwrap();
+        drop(guard);
+        assert_eq!(system.tcgetattr(Fd::STDIN), Ok(termios));
+    }
+
+    #[test]
+    fn raw_mode_fails_on_non_terminal() {
+        let system = SharedSystem::new(Box::new(VirtualSystem::new()));
+        let result = RawMode::new(system, Fd::STDIN);
+        assert_eq!(result.unwrap_err(), nix::errno::Errno::ENOTTY);
+    }
+}
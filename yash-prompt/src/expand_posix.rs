@@ -0,0 +1,295 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Expansion of prompt strings
+
+use yash_env::variable::Value::Scalar;
+use yash_env::variable::Variable;
+use yash_env::Env;
+use yash_env::System;
+use yash_semantics::expansion::expand_text;
+use yash_semantics::Handle;
+use yash_syntax::parser::lex::Lexer;
+use yash_syntax::source::Source;
+use yash_syntax::syntax::Text;
+use yash_syntax::syntax::TextUnit::{self, Backslashed, Literal};
+
+/// Returns the value of a scalar variable, or an empty string.
+fn scalar<'a>(env: &'a Env, name: &str) -> &'a str {
+    match env.variables.get(name) {
+        Some(Variable {
+            value: Some(Scalar(value)),
+            ..
+        }) => value,
+        _ => "",
+    }
+}
+
+/// Returns the host name up to the first dot.
+fn host_name(env: &Env) -> String {
+    match env.system.gethostname() {
+        Ok(name) => {
+            let name = name.to_string_lossy();
+            name.split('.').next().unwrap_or_default().to_owned()
+        }
+        Err(_) => String::new(),
+    }
+}
+
+/// Returns the name of the effective user.
+fn user_name(env: &Env) -> String {
+    let uid = env.system.geteuid();
+    env.system
+        .getpwuid_name(uid)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Returns `$PWD` with the `$HOME` prefix replaced with a tilde.
+fn working_directory(env: &Env) -> String {
+    let pwd = scalar(env, "PWD");
+    let home = scalar(env, "HOME");
+    if !home.is_empty() {
+        if let Some(rest) = pwd.strip_prefix(home) {
+            if rest.is_empty() || rest.starts_with('/') {
+                return format!("~{rest}");
+            }
+        }
+    }
+    pwd.to_owned()
+}
+
+/// Returns the replacement for a backslash escape.
+///
+/// Returns `None` if the character does not form a valid escape.
+fn escape(env: &Env, c: char) -> Option<String> {
+    match c {
+        'a' => Some("\x07".to_owned()),
+        'e' => Some("\x1B".to_owned()),
+        'h' => Some(host_name(env)),
+        'j' => Some(env.jobs.len().to_string()),
+        'n' => Some("\n".to_owned()),
+        'r' => Some("\r".to_owned()),
+        'u' => Some(user_name(env)),
+        'w' => Some(working_directory(env)),
+        '[' | ']' => Some(String::new()),
+        _ => None,
+    }
+}
+
+/// Replaces backslash escapes in the text with literals.
+fn apply_escapes(env: &Env, units: Vec<TextUnit>) -> Vec<TextUnit> {
+    let mut result = Vec::with_capacity(units.len());
+    let mut units = units.into_iter().peekable();
+    while let Some(unit) = units.next() {
+        match unit {
+            Backslashed('$') => {
+                let is_root = env.system.geteuid().is_root();
+                result.push(Literal(if is_root { '#' } else { '$' }));
+            }
+            Backslashed(c) => result.push(Literal(c)),
+            Literal('\\') => {
+                let replacement = match units.peek() {
+                    Some(Literal(c)) => escape(env, *c),
+                    _ => None,
+                };
+                match replacement {
+                    Some(replacement) => {
+                        units.next();
+                        result.extend(replacement.chars().map(Literal));
+                    }
+                    None => result.push(Literal('\\')),
+                }
+            }
+            unit => result.push(unit),
+        }
+    }
+    result
+}
+
+/// Expands a prompt string.
+///
+/// This function parses the prompt string as a text and performs parameter
+/// expansion, command substitution, and arithmetic expansion. The `name`
+/// argument is the name of the variable from which the prompt string was
+/// obtained; it is used in error messages.
+///
+/// Additionally, the following backslash escapes are recognized:
+///
+/// - `\a` – bell (`\x07`)
+/// - `\e` – escape (`\x1B`)
+/// - `\h` – host name up to the first dot
+/// - `\j` – number of jobs in the job list
+/// - `\n` – newline
+/// - `\r` – carriage return
+/// - `\u` – name of the effective user
+/// - `\w` – value of `$PWD`, with a leading `$HOME` replaced with `~`
+/// - `\$` – `#` if the effective user ID is 0, `$` otherwise
+/// - `\\` – backslash
+/// - `` \` `` – backquote
+/// - `\[` and `\]` – start and end of non-printing characters (removed)
+///
+/// Other backslashes are left intact. Results of the escapes are not subject
+/// to further expansion.
+///
+/// If the prompt string cannot be parsed or expanded, the error is printed
+/// and the original string is returned.
+pub async fn expand_posix(env: &mut Env, name: &str, prompt: &str) -> String {
+    let source = Source::VariableValue {
+        name: name.to_owned(),
+    };
+    let mut lexer = Lexer::from_memory(prompt, source);
+    let text_result = lexer
+        .text(|_| false, |c| matches!(c, '$' | '`' | '\\'))
+        .await;
+    let text = match text_result {
+        Ok(text) => text,
+        Err(error) => {
+            _ = error.handle(env).await;
+            return prompt.to_owned();
+        }
+    };
+
+    let text = Text(apply_escapes(env, text.0));
+    match expand_text(env, &text).await {
+        Ok((expansion, _exit_status)) => expansion,
+        Err(error) => {
+            _ = error.handle(env).await;
+            prompt.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use yash_env::job::Job;
+    use yash_env::job::Pid;
+    use yash_env::system::r#virtual::VirtualSystem;
+    use yash_env::system::Uid;
+    use yash_env::variable::Scope;
+
+    fn expand(env: &mut Env, prompt: &str) -> String {
+        expand_posix(env, "PS1", prompt).now_or_never().unwrap()
+    }
+
+    fn assign(env: &mut Env, name: &str, value: &str) {
+        env.variables
+            .get_or_new(name, Scope::Global)
+            .assign(value, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn plain_prompt() {
+        let mut env = Env::new_virtual();
+        assert_eq!(expand(&mut env, "$ "), "$ ");
+        assert_eq!(expand(&mut env, "foo> "), "foo> ");
+    }
+
+    #[test]
+    fn parameter_expansion() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "foo", "bar");
+        assert_eq!(expand(&mut env, "${foo}$foo> "), "barbar> ");
+    }
+
+    #[test]
+    fn arithmetic_expansion() {
+        let mut env = Env::new_virtual();
+        assert_eq!(expand(&mut env, "$((1+2))"), "3");
+    }
+
+    #[test]
+    fn simple_escapes() {
+        let mut env = Env::new_virtual();
+        assert_eq!(expand(&mut env, r"\a\e\n\r"), "\x07\x1B\n\r");
+        assert_eq!(expand(&mut env, r"\\\`"), r"\`");
+        assert_eq!(expand(&mut env, r"\[x\]"), "x");
+    }
+
+    #[test]
+    fn unknown_escapes_are_left_intact() {
+        let mut env = Env::new_virtual();
+        assert_eq!(expand(&mut env, r"\z\"), r"\z\");
+    }
+
+    #[test]
+    fn dollar_escape() {
+        let mut system = VirtualSystem::new();
+        system.current_process_mut().set_euid(Uid::from_raw(1000));
+        let mut env = Env::with_system(Box::new(system.clone()));
+        assert_eq!(expand(&mut env, r"\$ "), "$ ");
+
+        system.current_process_mut().set_euid(Uid::from_raw(0));
+        assert_eq!(expand(&mut env, r"\$ "), "# ");
+    }
+
+    #[test]
+    fn user_and_host_escapes() {
+        let system = VirtualSystem::new();
+        {
+            let mut state = system.state.borrow_mut();
+            state.hostname = "myhost.example.com".into();
+            state.user_names.insert(Uid::from_raw(0), "root".to_owned());
+        }
+        let mut env = Env::with_system(Box::new(system));
+        assert_eq!(expand(&mut env, r"\u@\h"), "root@myhost");
+    }
+
+    #[test]
+    fn working_directory_escape() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "PWD", "/home/user/src");
+        assert_eq!(expand(&mut env, r"\w"), "/home/user/src");
+
+        assign(&mut env, "HOME", "/home/user");
+        assert_eq!(expand(&mut env, r"\w"), "~/src");
+
+        assign(&mut env, "PWD", "/home/user");
+        assert_eq!(expand(&mut env, r"\w"), "~");
+
+        assign(&mut env, "PWD", "/home/username");
+        assert_eq!(expand(&mut env, r"\w"), "/home/username");
+    }
+
+    #[test]
+    fn escape_results_are_not_expanded() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "HOME", "/home");
+        assign(&mut env, "PWD", "/home/$x");
+        assign(&mut env, "x", "X");
+        assert_eq!(expand(&mut env, r"\w"), "~/$x");
+    }
+
+    #[test]
+    fn jobs_escape() {
+        let mut env = Env::new_virtual();
+        assert_eq!(expand(&mut env, r"\j"), "0");
+        env.jobs.add(Job::new(Pid(10)));
+        env.jobs.add(Job::new(Pid(20)));
+        assert_eq!(expand(&mut env, r"\j"), "2");
+    }
+
+    #[test]
+    fn escaped_parameter_is_literal() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "foo", "bar");
+        assert_eq!(expand(&mut env, r"\$foo"), "#foo");
+    }
+}
@@ -0,0 +1,39 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command prompt for the shell.
+//!
+//! An interactive shell prints a prompt before reading each line of input.
+//! The prompt for the first line of a command is the value of the `PS1`
+//! variable, and the prompt for the following lines is the value of `PS2`.
+//!
+//! [`fetch_posix`] obtains the value of the prompt variable for the current
+//! input [`Context`](yash_env::input::Context), and [`expand_posix`] expands
+//! it. The [`Prompter`] is an input decorator that performs these steps and
+//! prints the prompt before reading a line.
+//!
+//! Other inputs that print their own prompt, such as a line editor, can use
+//! [`prepare_prompt`] and [`read_with_notify`] to behave in the same way as
+//! the `Prompter`.
+
+mod expand_posix;
+mod prompter;
+
+pub use self::expand_posix::expand_posix;
+pub use self::prompter::fetch_posix;
+pub use self::prompter::prepare_prompt;
+pub use self::prompter::read_with_notify;
+pub use self::prompter::Prompter;
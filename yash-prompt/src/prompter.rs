@@ -0,0 +1,379 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Input decorator that prints a prompt
+
+use super::expand_posix;
+use async_trait::async_trait;
+use futures_util::future::select;
+use futures_util::future::Either;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::pin::Pin;
+use yash_env::input::Context;
+use yash_env::input::Input;
+use yash_env::input::Result;
+use yash_env::job::ProcessState;
+use yash_env::option::Option::Notify;
+use yash_env::option::State::On;
+use yash_env::trap::Signal::SIGCHLD;
+use yash_env::variable::Value::Scalar;
+use yash_env::variable::Variable;
+use yash_env::variable::VariableSet;
+use yash_env::Env;
+
+/// Returns the name of the prompt variable for the context.
+fn variable_name(context: &Context) -> &'static str {
+    if context.is_first_line() {
+        "PS1"
+    } else {
+        "PS2"
+    }
+}
+
+/// Returns the value of the prompt variable for the context.
+///
+/// This function returns the value of `PS1` if the context is for the first
+/// line of a command, and `PS2` otherwise. If the variable is not set or is
+/// not a scalar, the result is an empty string.
+#[must_use]
+pub fn fetch_posix(variables: &VariableSet, context: &Context) -> String {
+    match variables.get(variable_name(context)) {
+        Some(Variable {
+            value: Some(Scalar(value)),
+            ..
+        }) => value.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Input decorator that prints a prompt before reading a line
+///
+/// A `Prompter` wraps an inner [`Input`] and prints a prompt to the standard
+/// error before delegating each [`next_line`](Input::next_line) call to the
+/// inner input. The prompt is [fetched](fetch_posix) and
+/// [expanded](expand_posix) in the environment shared through the `RefCell`.
+///
+/// Before printing the prompt for the first line of a command, the `Prompter`
+/// [reports job status changes](Env::report_job_status_changes). While the
+/// inner input is waiting for a line, if the [`Notify`] option is on, the
+/// `Prompter` also reports job status changes each time `SIGCHLD` is caught.
+///
+/// # Borrowing the environment
+///
+/// The environment is borrowed only while the prompt or a report is being
+/// printed, so the [`ReadEvalLoop`](yash_semantics::ReadEvalLoop) can share
+/// the same `RefCell` to execute commands. The borrow may be held across await
+/// points because the read-eval loop does not borrow the environment while
+/// reading input. Other inputs that share the environment with the read-eval
+/// loop, such as a line editor, rely on the same invariant.
+#[derive(Clone, Debug)]
+pub struct Prompter<'a, 'b, T> {
+    inner: T,
+    env: &'a RefCell<&'b mut Env>,
+}
+
+impl<'a, 'b, T> Prompter<'a, 'b, T> {
+    /// Creates a new `Prompter` decorating the given input.
+    pub fn new(inner: T, env: &'a RefCell<&'b mut Env>) -> Self {
+        Self { inner, env }
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> Input for Prompter<'_, '_, T>
+where
+    T: Input,
+{
+    // See the "Borrowing the environment" section of the `Prompter` docs.
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn next_line(&mut self, context: &Context) -> Result {
+        let env = self.env;
+        print_prompt(&mut env.borrow_mut(), context).await;
+
+        let mut read = pin!(self.inner.next_line(context));
+        loop {
+            match read_with_notify(env, read.as_mut()).await {
+                Some(result) => return result,
+                None => print_prompt(&mut env.borrow_mut(), context).await,
+            }
+        }
+    }
+}
+
+/// Returns the prompt to print before reading a line.
+///
+/// If the context is for the first line of a command, this function first
+/// [reports job status changes](Env::report_job_status_changes). The prompt is
+/// then [fetched](fetch_posix) and [expanded](expand_posix).
+pub async fn prepare_prompt(env: &mut Env, context: &Context) -> String {
+    if context.is_first_line() {
+        env.report_job_status_changes().await;
+    }
+
+    let prompt = fetch_posix(&env.variables, context);
+    expand_posix(env, variable_name(context), &prompt).await
+}
+
+async fn print_prompt(env: &mut Env, context: &Context) {
+    let prompt = prepare_prompt(env, context).await;
+    env.system.print_error(&prompt).await;
+}
+
+/// Awaits a read from the input, reporting job status changes on `SIGCHLD`.
+///
+/// If the [`Notify`] option is on, this function reports job status changes
+/// each time `SIGCHLD` is caught while `read` is pending. When a report is
+/// printed, this function returns `None` without completing `read`, so that
+/// the caller can print the prompt again below the report and then call this
+/// function again to resume reading. Otherwise, this function returns the
+/// result of `read`.
+// See the "Borrowing the environment" section of the `Prompter` docs.
+#[allow(clippy::await_holding_refcell_ref)]
+pub async fn read_with_notify<F: Future>(
+    env: &RefCell<&mut Env>,
+    mut read: Pin<&mut F>,
+) -> Option<F::Output> {
+    let system = env.borrow().system.clone();
+    loop {
+        if !enable_notify(&mut env.borrow_mut()) {
+            return Some(read.await);
+        }
+        let sigchld = pin!(system.wait_for_signal(SIGCHLD));
+        match select(read.as_mut(), sigchld).await {
+            Either::Left((result, _)) => return Some(result),
+            Either::Right(((), _)) => {
+                if notify(&mut env.borrow_mut()).await {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Prepares for asynchronous job status notification.
+///
+/// Returns true if the [`Notify`] option is on and the `SIGCHLD` handler is
+/// installed.
+fn enable_notify(env: &mut Env) -> bool {
+    env.options.get(Notify) == On && env.traps.enable_sigchld_handler(&mut env.system).is_ok()
+}
+
+/// Reports job status changes while waiting for input.
+///
+/// If there is any job to report, the report is printed on a new line and
+/// this function returns true.
+async fn notify(env: &mut Env) -> bool {
+    env.update_all_subshell_statuses();
+    let has_report = env
+        .jobs
+        .iter()
+        .any(|(_, job)| job.state_changed && job.state != ProcessState::Running);
+    if has_report {
+        env.system.print_error("\n").await;
+        env.report_job_status_changes().await;
+    }
+    has_report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use futures_util::future::poll_fn;
+    use futures_util::task::noop_waker_ref;
+    use futures_util::FutureExt as _;
+    use std::rc::Rc;
+    use std::task::Poll;
+    use yash_env::input::FdReader;
+    use yash_env::input::Memory;
+    use yash_env::io::Fd;
+    use yash_env::job::Job;
+    use yash_env::job::Pid;
+    use yash_env::semantics::ExitStatus;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::VirtualSystem;
+    use yash_env::variable::Scope;
+
+    fn stderr(system: &VirtualSystem) -> String {
+        let state = system.state.borrow();
+        let file = state.file_system.get("/dev/stderr").unwrap();
+        let file = file.borrow();
+        match &file.body {
+            FileBody::Regular { content, .. } => String::from_utf8(content.clone()).unwrap(),
+            body => panic!("unexpected file body: {body:?}"),
+        }
+    }
+
+    #[test]
+    fn fetch_posix_ps1_and_ps2() {
+        let mut variables = VariableSet::new();
+        variables
+            .get_or_new("PS1", Scope::Global)
+            .assign("one", None)
+            .unwrap();
+        variables
+            .get_or_new("PS2", Scope::Global)
+            .assign("two", None)
+            .unwrap();
+
+        let mut context = Context::default();
+        assert_eq!(fetch_posix(&variables, &context), "one");
+        context.set_is_first_line(false);
+        assert_eq!(fetch_posix(&variables, &context), "two");
+    }
+
+    #[test]
+    fn fetch_posix_unset() {
+        let variables = VariableSet::new();
+        assert_eq!(fetch_posix(&variables, &Context::default()), "");
+    }
+
+    #[test]
+    fn prompter_prints_prompt_before_reading() {
+        let system = VirtualSystem::new();
+        {
+            let state = system.state.borrow();
+            let file = state.file_system.get("/dev/stdin").unwrap();
+            file.borrow_mut().body = FileBody::new(*b"echo \\\nok\n");
+        }
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.variables.init();
+        env.variables
+            .get_or_new("x", Scope::Global)
+            .assign("X", None)
+            .unwrap();
+        env.variables
+            .get_or_new("PS1", Scope::Global)
+            .assign("[$x] ", None)
+            .unwrap();
+        let reader = FdReader::new(Fd::STDIN, env.system.clone());
+        let ref_env = RefCell::new(&mut env);
+        let mut prompter = Prompter::new(reader, &ref_env);
+
+        let line = prompter
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "echo \\\n");
+        assert_eq!(stderr(&system), "[X] ");
+
+        let mut context = Context::default();
+        context.set_is_first_line(false);
+        let line = prompter
+            .next_line(&context)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "ok\n");
+        assert_eq!(stderr(&system), "[X] > ");
+    }
+
+    #[test]
+    fn job_status_reported_before_first_line_prompt() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        let mut job = Job::new(Pid(123));
+        job.name = "sleep 1".to_string();
+        job.state = ProcessState::Exited(ExitStatus::SUCCESS);
+        env.jobs.add(job);
+        env.variables
+            .get_or_new("PS1", Scope::Global)
+            .assign("$ ", None)
+            .unwrap();
+        env.variables
+            .get_or_new("PS2", Scope::Global)
+            .assign("> ", None)
+            .unwrap();
+        let ref_env = RefCell::new(&mut env);
+        let mut prompter = Prompter::new(Memory::new("\n\n"), &ref_env);
+
+        let mut context = Context::default();
+        context.set_is_first_line(false);
+        prompter
+            .next_line(&context)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(stderr(&system), "> ");
+
+        prompter
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(stderr(&system), "> [1] + Done                 sleep 1\n$ ");
+        assert_eq!(env.jobs.get(0), None);
+    }
+
+    /// Input that is pending until a line is set in the shared cell
+    struct PendingInput(Rc<RefCell<Option<String>>>);
+
+    #[async_trait(?Send)]
+    impl Input for PendingInput {
+        async fn next_line(&mut self, _: &Context) -> Result {
+            poll_fn(|_| match self.0.borrow_mut().take() {
+                Some(line) => Poll::Ready(Ok(line)),
+                None => Poll::Pending,
+            })
+            .await
+        }
+    }
+
+    #[test]
+    fn job_status_reported_on_sigchld_with_notify() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(Notify, On);
+        env.variables
+            .get_or_new("PS1", Scope::Global)
+            .assign("$ ", None)
+            .unwrap();
+        let mut job = Job::new(Pid(123));
+        job.name = "sleep 1".to_string();
+        env.jobs.add(job);
+        let ref_env = RefCell::new(&mut env);
+        let line = Rc::new(RefCell::new(None));
+        let mut prompter = Prompter::new(PendingInput(Rc::clone(&line)), &ref_env);
+        let context = Context::default();
+        let mut future = prompter.next_line(&context);
+        let mut task_context = std::task::Context::from_waker(noop_waker_ref());
+
+        assert!(future.as_mut().poll(&mut task_context).is_pending());
+        assert_eq!(stderr(&system), "$ ");
+
+        {
+            let mut env = ref_env.borrow_mut();
+            env.jobs
+                .update_status(Pid(123), ProcessState::Exited(ExitStatus(1)));
+            let mut state = system.state.borrow_mut();
+            let process = state.processes.get_mut(&system.process_id).unwrap();
+            let _ = process.raise_signal(SIGCHLD);
+        }
+        ref_env.borrow().system.select(true).unwrap();
+        assert!(future.as_mut().poll(&mut task_context).is_pending());
+        assert_eq!(
+            stderr(&system),
+            "$ \n[1] + Done(1)              sleep 1\n$ "
+        );
+
+        *line.borrow_mut() = Some("echo\n".to_string());
+        let result = future.as_mut().poll(&mut task_context);
+        assert_matches!(result, Poll::Ready(Ok(line)) if line == "echo\n");
+    }
+}
@@ -17,11 +17,17 @@
 //! Assignment.
 
 use crate::expansion::expand_value;
+use crate::expansion::initial::evaluate_arith;
+use crate::expansion::AssignElementError;
 use crate::expansion::AssignReadOnlyError;
 use crate::xtrace::XTrace;
 use std::fmt::Write;
 use yash_env::semantics::ExitStatus;
+use yash_env::variable::Value;
+use yash_env::variable::Variable;
 use yash_env::Env;
+use yash_syntax::source::Location;
+use yash_syntax::syntax::Text;
 
 #[doc(no_inline)]
 pub use crate::expansion::{Error, ErrorCause, Result};
@@ -30,6 +36,58 @@ pub use yash_env::variable::Scope;
 #[doc(no_inline)]
 pub use yash_syntax::syntax::Assign;
 
+/// Expands and evaluates the index of an element assignment.
+async fn evaluate_index(
+    env: &mut Env,
+    index: &Text,
+    location: &Location,
+) -> Result<(i64, Option<ExitStatus>)> {
+    let mut env = crate::expansion::initial::Env::new(env);
+    let yash_arith::Value::Integer(index) = evaluate_arith(index, location, &mut env).await?;
+    Ok((index, env.last_command_subst_exit_status))
+}
+
+/// Returns the value of an array with an element replaced.
+///
+/// The variable named `assign.name` must be an existing array, and `index`
+/// must specify one of its elements. A negative index counts from the end.
+fn replace_element(env: &Env, assign: &Assign, index: i64, value: Value) -> Result<Value> {
+    let name = assign.name.clone();
+    let cause = match (value, env.variables.get(&assign.name)) {
+        (Value::Array(_), _) => AssignElementError::ArrayValue { name },
+        (
+            Value::Scalar(element),
+            Some(Variable {
+                value: Some(Value::Array(values)),
+                ..
+            }),
+        ) => {
+            let len = values.len();
+            let position = if index < 0 {
+                usize::try_from(index.unsigned_abs())
+                    .ok()
+                    .and_then(|i| len.checked_sub(i))
+            } else {
+                usize::try_from(index).ok().and_then(|i| i.checked_sub(1))
+            };
+            match position {
+                Some(position) if position < len => {
+                    let mut values = values.clone();
+                    values[position] = element;
+                    return Ok(Value::Array(values));
+                }
+                _ => AssignElementError::IndexOutOfRange { name, index, len },
+            }
+        }
+        (Value::Scalar(_), _) => AssignElementError::NotArray { name },
+    };
+    let location = assign.location.clone();
+    Err(Error {
+        cause: cause.into(),
+        location,
+    })
+}
+
 /// Performs an assignment.
 ///
 /// This function [expands the value](expand_value) and then
@@ -37,6 +95,11 @@ pub use yash_syntax::syntax::Assign;
 /// The return value is the exit status of the last command substitution
 /// performed during the expansion of the assigned value, if any
 ///
+/// If the assignment has an index, the index is evaluated as an arithmetic
+/// expression before the value is expanded, and the value replaces the
+/// element of the existing array at the index. It is an error if the variable
+/// is not an array or the index is out of range.
+///
 /// If `xtrace` is `Some` instance of `XTrace`, the expanded assignment word is
 /// written to its assignments buffer.
 pub async fn perform_assignment(
@@ -47,18 +110,30 @@ pub async fn perform_assignment(
     xtrace: Option<&mut XTrace>,
 ) -> Result<Option<ExitStatus>> {
     let name = assign.name.clone();
+    let (index, index_exit_status) = match &assign.index {
+        None => (None, None),
+        Some(index) => {
+            let (index, exit_status) = evaluate_index(env, index, &assign.location).await?;
+            (Some(index), exit_status)
+        }
+    };
     let (value, exit_status) = expand_value(env, &assign.value).await?;
+    let exit_status = exit_status.or(index_exit_status);
 
     if let Some(xtrace) = xtrace {
-        write!(
-            xtrace.assigns(),
-            "{}={} ",
-            yash_quote::quoted(&name),
-            value.quote()
-        )
-        .unwrap();
+        let assigns = xtrace.assigns();
+        write!(assigns, "{}", yash_quote::quoted(&name)).unwrap();
+        if let Some(index) = index {
+            write!(assigns, "[{index}]").unwrap();
+        }
+        write!(assigns, "={} ", value.quote()).unwrap();
     }
 
+    let value = match index {
+        None => value,
+        Some(index) => replace_element(env, assign, index, value)?,
+    };
+
     let mut variable = env.get_or_create_variable(name, scope);
     variable
         .assign(value, assign.location.clone())
@@ -107,9 +182,6 @@ mod tests {
     use crate::tests::return_builtin;
     use assert_matches::assert_matches;
     use futures_util::FutureExt;
-    use yash_env::variable::Value;
-    use yash_env::variable::Variable;
-    use yash_syntax::source::Location;
 
     #[test]
     fn perform_assignment_new_value() {
@@ -183,6 +255,102 @@ mod tests {
         assert_eq!(e.location, Location::dummy("v=new"));
     }
 
+    fn env_with_array() -> Env {
+        let mut env = Env::new_virtual();
+        env.variables
+            .get_or_new("a", Scope::Global)
+            .assign(Value::array(["x", "y", "z"]), None)
+            .unwrap();
+        env
+    }
+
+    #[test]
+    fn perform_assignment_to_element() {
+        let mut env = env_with_array();
+        let a: Assign = "a[2]=Y".parse().unwrap();
+        let exit_status = perform_assignment(&mut env, &a, Scope::Global, false, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit_status, None);
+        let a_var = env.variables.get("a").unwrap();
+        assert_eq!(a_var.value, Some(Value::array(["x", "Y", "z"])));
+        assert_eq!(a_var.last_assigned_location, Some(a.location));
+
+        let a: Assign = "a[-1]=Z".parse().unwrap();
+        perform_assignment(&mut env, &a, Scope::Global, false, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let a_var = env.variables.get("a").unwrap();
+        assert_eq!(a_var.value, Some(Value::array(["x", "Y", "Z"])));
+    }
+
+    #[test]
+    fn perform_assignment_to_element_out_of_range() {
+        let mut env = env_with_array();
+        for index in ["0", "4", "-4"] {
+            let a: Assign = format!("a[{index}]=new").parse().unwrap();
+            let e = perform_assignment(&mut env, &a, Scope::Global, false, None)
+                .now_or_never()
+                .unwrap()
+                .unwrap_err();
+            assert_eq!(
+                e.cause,
+                ErrorCause::AssignElement(AssignElementError::IndexOutOfRange {
+                    name: "a".to_string(),
+                    index: index.parse().unwrap(),
+                    len: 3,
+                })
+            );
+            assert_eq!(e.location, a.location);
+        }
+        let a_var = env.variables.get("a").unwrap();
+        assert_eq!(a_var.value, Some(Value::array(["x", "y", "z"])));
+    }
+
+    #[test]
+    fn perform_assignment_to_element_of_non_array() {
+        let mut env = Env::new_virtual();
+        env.variables
+            .get_or_new("s", Scope::Global)
+            .assign("scalar", None)
+            .unwrap();
+        for name in ["s", "unset"] {
+            let a: Assign = format!("{name}[1]=new").parse().unwrap();
+            let e = perform_assignment(&mut env, &a, Scope::Global, false, None)
+                .now_or_never()
+                .unwrap()
+                .unwrap_err();
+            assert_eq!(
+                e.cause,
+                ErrorCause::AssignElement(AssignElementError::NotArray {
+                    name: name.to_string()
+                })
+            );
+        }
+        assert_eq!(env.variables.get("unset"), None);
+    }
+
+    #[test]
+    fn perform_assignment_to_element_with_xtrace() {
+        let mut xtrace = XTrace::new();
+        let mut env = env_with_array();
+        env.variables
+            .get_or_new("i", Scope::Global)
+            .assign("1", None)
+            .unwrap();
+        let a: Assign = "a[i+1]=foo".parse().unwrap();
+        perform_assignment(&mut env, &a, Scope::Global, false, Some(&mut xtrace))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            xtrace.finish(&mut env).now_or_never().unwrap(),
+            "a[2]=foo\n"
+        );
+    }
+
     #[test]
     fn perform_assignment_with_xtrace() {
         let mut xtrace = XTrace::new();
@@ -41,17 +41,20 @@ pub trait Command {
 
 /// Executes the command.
 ///
-/// After executing the command body, the `execute` function [runs
+/// After executing the command body, the `execute` function [finishes process
+/// substitutions](crate::process_subst::finish) started in the command, [runs
 /// traps](run_traps_for_caught_signals) if any caught signals are pending, and
 /// [updates subshell statuses](Env::update_all_subshell_statuses).
 impl Command for syntax::Command {
     async fn execute(&self, env: &mut Env) -> Result {
         use syntax::Command::*;
+        let process_substs = env.process_substs.len();
         let main_result = match self {
             Simple(command) => command.execute(env).await,
             Compound(command) => command.execute(env).await,
             Function(definition) => definition.execute(env).await,
         };
+        crate::process_subst::finish(env, process_substs).await;
 
         let trap_result = run_traps_for_caught_signals(env).await;
         env.update_all_subshell_statuses();
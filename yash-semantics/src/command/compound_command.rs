@@ -51,6 +51,7 @@ async fn evaluate_condition(env: &mut Env, condition: &syntax::List) -> Result<b
 }
 
 mod case;
+mod double_bracket;
 mod for_loop;
 mod r#if;
 mod subshell;
@@ -124,6 +125,15 @@ impl Command for syntax::FullCompoundCommand {
 ///
 /// POSIX does not specify the order in which the shell tests multiple patterns
 /// in an item. This implementation tries them in the order of appearance.
+///
+/// # Double-bracket command
+///
+/// The double-bracket command evaluates the conditional expression and exits
+/// with zero if it is true and one if it is false. The operands are expanded
+/// without field splitting or pathname expansion. The right-hand side of `==`
+/// and `!=` is a pattern and that of `=~` is an extended regular expression,
+/// where quoted characters match literally. If the expression cannot be
+/// evaluated, an error message is printed and the exit status is two.
 impl Command for syntax::CompoundCommand {
     async fn execute(&self, env: &mut Env) -> Result {
         use syntax::CompoundCommand::*;
@@ -140,6 +150,7 @@ impl Command for syntax::CompoundCommand {
                 r#else,
             } => r#if::execute(env, condition, body, elifs, r#else).await,
             Case { subject, items } => case::execute(env, subject, items).await,
+            DoubleBracket(expr) => double_bracket::execute(env, expr).await,
         }
     }
 }
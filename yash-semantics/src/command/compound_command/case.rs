@@ -18,21 +18,24 @@
 
 use crate::command::Command;
 use crate::expansion::attr::fnmatch::apply_escapes;
-use crate::expansion::attr::fnmatch::to_pattern_chars;
+use crate::expansion::attr::fnmatch::with_pattern;
 use crate::expansion::expand_word;
 use crate::expansion::expand_word_attr;
+use crate::expansion::glob::is_unicode_locale;
 use crate::xtrace::print;
 use crate::xtrace::XTrace;
 use crate::Handle;
 use std::fmt::Write;
 use std::ops::ControlFlow::Continue;
+use yash_env::option::Option::ExtendedGlob;
+use yash_env::option::State::On;
 use yash_env::semantics::apply_errexit;
 use yash_env::semantics::ExitStatus;
 use yash_env::semantics::Result;
 use yash_env::Env;
 use yash_fnmatch::Config;
-use yash_fnmatch::Pattern;
 use yash_quote::quoted;
+use yash_syntax::syntax::CaseContinuation;
 use yash_syntax::syntax::CaseItem;
 use yash_syntax::syntax::Word;
 
@@ -45,14 +48,22 @@ async fn trace_subject(env: &mut Env, value: &str) {
 // We don't trace expanded patterns since they need a quoting method different
 // from yash_quote::quote.
 
-fn config() -> Config {
+fn config(env: &Env) -> Config {
     let mut config = Config::default();
     config.anchor_begin = true;
     config.anchor_end = true;
+    config.extended = env.options.get(ExtendedGlob) == On;
+    config.unicode = is_unicode_locale(&env.variables);
     config
 }
 
 /// Executes the case command.
+///
+/// The items are tested in order until one of them has a pattern that matches
+/// the subject. After the body of the matched item is executed, the
+/// [continuation](CaseContinuation) of the item determines what happens next:
+/// `;;` finishes the command, `;&` executes the body of the next item without
+/// testing its patterns, and `;|` or `;;&` resumes testing with the next item.
 pub async fn execute(env: &mut Env, subject: &Word, items: &[CaseItem]) -> Result {
     let subject = match expand_word(env, subject).await {
         Ok((expansion, _exit_status)) => expansion,
@@ -60,35 +71,51 @@ pub async fn execute(env: &mut Env, subject: &Word, items: &[CaseItem]) -> Resul
     };
     trace_subject(env, &subject.value).await;
 
-    'outer: for item in items {
-        for pattern in &item.patterns {
-            let mut pattern = match expand_word_attr(env, pattern).await {
-                Ok((expansion, _exit_status)) => expansion,
-                Err(error) => return apply_errexit(error.handle(env).await, env),
-            };
-
-            // Unquoted backslashes should act as quoting, as required by POSIX XCU 2.13.1
-            apply_escapes(&mut pattern.chars);
-
-            let pattern =
-                match Pattern::parse_with_config(to_pattern_chars(&pattern.chars), config()) {
-                    Ok(parse) => parse,
-                    Err(_error) => {
-                        // Treat the broken pattern as a valid pattern that does not match anything
-                        continue;
-                    }
+    let mut executed = false;
+    let mut falling_through = false;
+    for item in items {
+        if !falling_through {
+            let mut matched = false;
+            for pattern in &item.patterns {
+                let mut pattern = match expand_word_attr(env, pattern).await {
+                    Ok((expansion, _exit_status)) => expansion,
+                    Err(error) => return apply_errexit(error.handle(env).await, env),
                 };
 
-            if pattern.is_match(&subject.value) {
-                if item.body.0.is_empty() {
-                    break 'outer;
-                };
-                return item.body.execute(env).await;
+                // Unquoted backslashes should act as quoting, as required by POSIX XCU 2.13.1
+                apply_escapes(&mut pattern.chars);
+
+                // Treat a broken pattern as a valid pattern that does not match anything
+                let is_match = with_pattern(&pattern.chars, config(env), |pattern| {
+                    pattern.is_match(&subject.value)
+                });
+                if is_match == Ok(true) {
+                    matched = true;
+                    break;
+                }
+            }
+            if !matched {
+                continue;
             }
         }
+
+        if item.body.0.is_empty() {
+            env.exit_status = ExitStatus::SUCCESS;
+        } else {
+            item.body.execute(env).await?;
+        }
+        executed = true;
+
+        match item.continuation {
+            CaseContinuation::Break => return Continue(()),
+            CaseContinuation::FallThrough => falling_through = true,
+            CaseContinuation::Continue => falling_through = false,
+        }
     }
 
-    env.exit_status = ExitStatus::SUCCESS;
+    if !executed {
+        env.exit_status = ExitStatus::SUCCESS;
+    }
     Continue(())
 }
 
@@ -408,4 +435,109 @@ mod tests {
         assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
         assert_stderr(&state, |stderr| assert_ne!(stderr, ""));
     }
+
+    #[test]
+    fn fall_through_to_next_bodies() {
+        let (mut env, state) = fixture();
+        let command: CompoundCommand = "case foo in
+        (bar) echo bar;&
+        (foo) echo foo;&
+        (not_matched) echo A;&
+        (not_matched) ;&
+        (not_matched) echo B; return -n 5;;
+        (foo) echo C;;
+        esac"
+            .parse()
+            .unwrap();
+
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus(5));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "foo\nA\nB\n"));
+    }
+
+    #[test]
+    fn fall_through_past_last_item() {
+        let (mut env, state) = fixture();
+        let command: CompoundCommand = "case foo in (foo) return -n 3;& esac".parse().unwrap();
+
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus(3));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
+    }
+
+    #[test]
+    fn continue_testing_next_items() {
+        let (mut env, state) = fixture();
+        let command: CompoundCommand = "case foo in
+        (f*) echo A;|
+        (bar) echo B;;
+        (*o) echo C;;&
+        (foo) echo D; return -n 4;;
+        (foo) echo E;;
+        esac"
+            .parse()
+            .unwrap();
+
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus(4));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "A\nC\nD\n"));
+    }
+
+    #[test]
+    fn exit_status_of_continued_items() {
+        let (mut env, _state) = fixture();
+        let command: CompoundCommand = "case foo in
+        (foo) return -n 6;|
+        (bar) return -n 7;;
+        esac"
+            .parse()
+            .unwrap();
+
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus(6));
+
+        let command: CompoundCommand = "case foo in
+        (foo) return -n 6;|
+        (foo) ;;
+        esac"
+            .parse()
+            .unwrap();
+
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn extended_patterns_with_extendedglob_option() {
+        let (mut env, state) = fixture();
+        let var = &mut env.variables.get_or_new("p", Scope::Global);
+        var.assign("!(foo|bar)", None).unwrap();
+        let command: CompoundCommand = "case baz in ($p) echo X;; esac".parse().unwrap();
+
+        let _ = command.execute(&mut env).now_or_never().unwrap();
+        assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
+
+        env.options.set(yash_env::option::ExtendedGlob, On);
+        let _ = command.execute(&mut env).now_or_never().unwrap();
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "X\n"));
+    }
+
+    #[test]
+    fn unicode_character_classes_with_locale() {
+        let (mut env, state) = fixture();
+        let command: CompoundCommand = "case É in ([[:upper:]]) echo X;; esac".parse().unwrap();
+
+        let _ = command.execute(&mut env).now_or_never().unwrap();
+        assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
+
+        let var = &mut env.variables.get_or_new("LC_ALL", Scope::Global);
+        var.assign("en_US.UTF-8", None).unwrap();
+        let _ = command.execute(&mut env).now_or_never().unwrap();
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "X\n"));
+    }
 }
@@ -0,0 +1,541 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2022 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Execution of the double-bracket command
+
+use crate::expansion::attr::fnmatch::apply_escapes;
+use crate::expansion::attr::fnmatch::to_pattern_chars;
+use crate::expansion::attr::AttrField;
+use crate::expansion::expand_word;
+use crate::expansion::expand_word_attr;
+use crate::expansion::glob::is_unicode_locale;
+use crate::Handle;
+use regex::Regex;
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::ops::ControlFlow::Continue;
+use thiserror::Error;
+use yash_env::io::print_message;
+use yash_env::io::Fd;
+use yash_env::option::canonicalize;
+use yash_env::option::parse_long;
+use yash_env::option::Option::ExtendedGlob;
+use yash_env::option::State::On;
+use yash_env::semantics::ExitStatus;
+use yash_env::semantics::Result;
+use yash_env::system::AtFlags;
+use yash_env::system::FileStat;
+use yash_env::system::Mode;
+use yash_env::system::SFlag;
+use yash_env::system::AT_FDCWD;
+use yash_env::variable::Scope;
+use yash_env::variable::Value;
+use yash_env::Env;
+use yash_env::System;
+use yash_fnmatch::Config;
+use yash_fnmatch::Pattern;
+use yash_syntax::source::pretty::Annotation;
+use yash_syntax::source::pretty::AnnotationType;
+use yash_syntax::source::pretty::MessageBase;
+use yash_syntax::source::Location;
+use yash_syntax::syntax::BinaryCondOp;
+use yash_syntax::syntax::CondExpr;
+use yash_syntax::syntax::UnaryCondOp;
+use yash_syntax::syntax::Word;
+
+/// Cause of an error in evaluating a conditional expression
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+enum ErrorCause {
+    /// An operand of an integer comparison is not an integer.
+    #[error("not a valid integer")]
+    InvalidInteger(String),
+    /// The right-hand side of `=~` is not a valid regular expression.
+    #[error("invalid regular expression")]
+    InvalidRegex(String),
+}
+
+/// Error in evaluating a conditional expression
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct EvalError {
+    cause: ErrorCause,
+    location: Location,
+}
+
+impl MessageBase for EvalError {
+    fn message_title(&self) -> Cow<str> {
+        self.cause.to_string().into()
+    }
+
+    fn main_annotation(&self) -> Annotation<'_> {
+        let label = match &self.cause {
+            ErrorCause::InvalidInteger(value) => format!("{value:?} is not an integer"),
+            ErrorCause::InvalidRegex(message) => message.clone(),
+        };
+        Annotation::new(AnnotationType::Error, label.into(), &self.location)
+    }
+}
+
+/// Error that interrupts the evaluation
+enum Error {
+    Expansion(crate::expansion::Error),
+    Eval(EvalError),
+}
+
+impl From<crate::expansion::Error> for Error {
+    fn from(error: crate::expansion::Error) -> Self {
+        Error::Expansion(error)
+    }
+}
+
+impl From<EvalError> for Error {
+    fn from(error: EvalError) -> Self {
+        Error::Eval(error)
+    }
+}
+
+/// Expands an operand to a string.
+async fn expand(env: &mut Env, word: &Word) -> std::result::Result<String, Error> {
+    Ok(expand_word(env, word).await?.0.value)
+}
+
+/// Expands the pattern operand and matches it against the value.
+///
+/// A broken pattern is treated as a pattern that matches nothing.
+async fn match_pattern(
+    env: &mut Env,
+    value: &str,
+    pattern: &Word,
+) -> std::result::Result<bool, Error> {
+    let mut pattern = expand_word_attr(env, pattern).await?.0;
+    apply_escapes(&mut pattern.chars);
+    let mut config = Config::default();
+    config.anchor_begin = true;
+    config.anchor_end = true;
+    config.extended = env.options.get(ExtendedGlob) == On;
+    config.unicode = is_unicode_locale(&env.variables);
+    Ok(
+        Pattern::parse_with_config(to_pattern_chars(&pattern.chars), config)
+            .is_ok_and(|pattern| pattern.is_match(value)),
+    )
+}
+
+/// Converts the expanded regex operand to a regular expression.
+///
+/// Quoted characters match literally.
+fn to_regex(field: &AttrField) -> String {
+    let mut regex = String::new();
+    for c in &field.chars {
+        if c.is_quoting {
+            continue;
+        }
+        if c.is_quoted {
+            regex.push_str(&regex::escape(c.value.encode_utf8(&mut [0; 4])));
+        } else {
+            regex.push(c.value);
+        }
+    }
+    regex
+}
+
+/// Expands the regex operand and matches it against the value.
+///
+/// On a match, the matched substring and the substrings matched by the
+/// parenthesized subexpressions are assigned to the `BASH_REMATCH` array.
+async fn match_regex(env: &mut Env, value: &str, word: &Word) -> std::result::Result<bool, Error> {
+    let field = expand_word_attr(env, word).await?.0;
+    let regex = Regex::new(&to_regex(&field)).map_err(|e| EvalError {
+        cause: ErrorCause::InvalidRegex(e.to_string()),
+        location: field.origin.clone(),
+    })?;
+    let captures: Vec<String> = match regex.captures(value) {
+        Some(captures) => captures
+            .iter()
+            .map(|m| m.map_or("", |m| m.as_str()).to_owned())
+            .collect(),
+        None => Vec::new(),
+    };
+    let matched = !captures.is_empty();
+    let variable = &mut env.variables.get_or_new("BASH_REMATCH", Scope::Global);
+    _ = variable.assign(Value::Array(captures), None);
+    Ok(matched)
+}
+
+/// Expands an operand and parses it as an integer.
+async fn integer(env: &mut Env, word: &Word) -> std::result::Result<i64, Error> {
+    let value = expand(env, word).await?;
+    value.trim().parse().map_err(|_| {
+        let cause = ErrorCause::InvalidInteger(value);
+        let location = word.location.clone();
+        EvalError { cause, location }.into()
+    })
+}
+
+/// Returns the metadata of the file.
+fn stat(env: &Env, path: &str, flags: AtFlags) -> Option<FileStat> {
+    let path = CString::new(path).ok()?;
+    env.system.fstatat(AT_FDCWD, &path, flags).ok()
+}
+
+/// Returns the file type bits of the metadata.
+fn file_type(stat: &FileStat) -> SFlag {
+    SFlag::from_bits_truncate(stat.st_mode & SFlag::S_IFMT.bits())
+}
+
+/// Tests whether the file is accessible by the effective user.
+///
+/// The superuser can read and write any file and execute any file that has
+/// an executable bit. For other users, the owner permission bits are
+/// examined if the user owns the file, and the group and other bits
+/// otherwise.
+fn is_accessible(env: &Env, stat: &FileStat, owner: Mode, others: Mode) -> bool {
+    let uid = env.system.geteuid();
+    let mode = Mode::from_bits_truncate(stat.st_mode);
+    if uid.is_root() {
+        return owner != Mode::S_IXUSR || mode.intersects(Mode::S_IXUSR | others);
+    }
+    if stat.st_uid == uid.as_raw() {
+        mode.contains(owner)
+    } else {
+        mode.intersects(others)
+    }
+}
+
+/// Evaluates a unary operator.
+fn unary(env: &Env, op: UnaryCondOp, operand: &str) -> bool {
+    use UnaryCondOp::*;
+    let has_type = |flags, file_type_wanted| {
+        stat(env, operand, flags).is_some_and(|stat| file_type(&stat) == file_type_wanted)
+    };
+    let has_mode = |mode: Mode| {
+        stat(env, operand, AtFlags::empty())
+            .is_some_and(|stat| Mode::from_bits_truncate(stat.st_mode).contains(mode))
+    };
+    let is_accessible = |owner, others| {
+        stat(env, operand, AtFlags::empty())
+            .is_some_and(|stat| is_accessible(env, &stat, owner, others))
+    };
+    match op {
+        Exists => stat(env, operand, AtFlags::empty()).is_some(),
+        BlockSpecial => has_type(AtFlags::empty(), SFlag::S_IFBLK),
+        CharacterSpecial => has_type(AtFlags::empty(), SFlag::S_IFCHR),
+        Directory => has_type(AtFlags::empty(), SFlag::S_IFDIR),
+        RegularFile => has_type(AtFlags::empty(), SFlag::S_IFREG),
+        SymbolicLink => has_type(AtFlags::AT_SYMLINK_NOFOLLOW, SFlag::S_IFLNK),
+        Fifo => has_type(AtFlags::empty(), SFlag::S_IFIFO),
+        Socket => has_type(AtFlags::empty(), SFlag::S_IFSOCK),
+        SetGroupId => has_mode(Mode::S_ISGID),
+        SetUserId => has_mode(Mode::S_ISUID),
+        Sticky => has_mode(Mode::S_ISVTX),
+        Readable => is_accessible(Mode::S_IRUSR, Mode::S_IRGRP | Mode::S_IROTH),
+        Writable => is_accessible(Mode::S_IWUSR, Mode::S_IWGRP | Mode::S_IWOTH),
+        Executable => is_accessible(Mode::S_IXUSR, Mode::S_IXGRP | Mode::S_IXOTH),
+        NonEmptyFile => stat(env, operand, AtFlags::empty()).is_some_and(|stat| stat.st_size > 0),
+        OwnedByUser => stat(env, operand, AtFlags::empty())
+            .is_some_and(|stat| stat.st_uid == env.system.geteuid().as_raw()),
+        Terminal => operand
+            .trim()
+            .parse()
+            .is_ok_and(|fd| env.system.isatty(Fd(fd)).unwrap_or(false)),
+        NonEmptyString => !operand.is_empty(),
+        EmptyString => operand.is_empty(),
+        OptionOn => parse_long(&canonicalize(operand))
+            .is_ok_and(|(option, state)| env.options.get(option) == state),
+        VariableSet => env
+            .variables
+            .get(operand)
+            .is_some_and(|variable| variable.value.is_some()),
+    }
+}
+
+/// Compares the modification times or identities of two files.
+fn compare_files(env: &Env, op: BinaryCondOp, lhs: &str, rhs: &str) -> bool {
+    let lhs = stat(env, lhs, AtFlags::empty());
+    let rhs = stat(env, rhs, AtFlags::empty());
+    let mtime = |stat: &FileStat| (stat.st_mtime, stat.st_mtime_nsec);
+    match (op, lhs, rhs) {
+        (BinaryCondOp::NewerThan, Some(lhs), Some(rhs)) => mtime(&lhs) > mtime(&rhs),
+        (BinaryCondOp::NewerThan, Some(_), None) => true,
+        (BinaryCondOp::OlderThan, Some(lhs), Some(rhs)) => mtime(&lhs) < mtime(&rhs),
+        (BinaryCondOp::OlderThan, None, Some(_)) => true,
+        (BinaryCondOp::SameFile, Some(lhs), Some(rhs)) => {
+            (lhs.st_dev, lhs.st_ino) == (rhs.st_dev, rhs.st_ino)
+        }
+        _ => false,
+    }
+}
+
+/// Evaluates a binary operator.
+async fn binary(
+    env: &mut Env,
+    lhs: &Word,
+    op: BinaryCondOp,
+    rhs: &Word,
+) -> std::result::Result<bool, Error> {
+    use BinaryCondOp::*;
+    match op {
+        Match | NotMatch => {
+            let value = expand(env, lhs).await?;
+            let matched = match_pattern(env, &value, rhs).await?;
+            Ok(matched == (op == Match))
+        }
+        RegexMatch => {
+            let value = expand(env, lhs).await?;
+            match_regex(env, &value, rhs).await
+        }
+        Less | Greater => {
+            let lhs = expand(env, lhs).await?;
+            let rhs = expand(env, rhs).await?;
+            Ok(if op == Less { lhs < rhs } else { lhs > rhs })
+        }
+        Equal | NotEqual | LessThan | LessEqual | GreaterThan | GreaterEqual => {
+            let lhs = integer(env, lhs).await?;
+            let rhs = integer(env, rhs).await?;
+            Ok(match op {
+                Equal => lhs == rhs,
+                NotEqual => lhs != rhs,
+                LessThan => lhs < rhs,
+                LessEqual => lhs <= rhs,
+                GreaterThan => lhs > rhs,
+                _ => lhs >= rhs,
+            })
+        }
+        NewerThan | OlderThan | SameFile => {
+            let lhs = expand(env, lhs).await?;
+            let rhs = expand(env, rhs).await?;
+            Ok(compare_files(env, op, &lhs, &rhs))
+        }
+    }
+}
+
+/// Evaluates the conditional expression.
+///
+/// The `&&` and `||` operators do not evaluate the right-hand side if the
+/// result is determined by the left-hand side.
+async fn evaluate(env: &mut Env, expr: &CondExpr) -> std::result::Result<bool, Error> {
+    match expr {
+        CondExpr::Word(word) => Ok(!expand(env, word).await?.is_empty()),
+        CondExpr::Unary(op, operand) => {
+            let operand = expand(env, operand).await?;
+            Ok(unary(env, *op, &operand))
+        }
+        CondExpr::Binary(lhs, op, rhs) => binary(env, lhs, *op, rhs).await,
+        CondExpr::Not(expr) => Ok(!Box::pin(evaluate(env, expr)).await?),
+        CondExpr::And(lhs, rhs) => {
+            Ok(Box::pin(evaluate(env, lhs)).await? && Box::pin(evaluate(env, rhs)).await?)
+        }
+        CondExpr::Or(lhs, rhs) => {
+            Ok(Box::pin(evaluate(env, lhs)).await? || Box::pin(evaluate(env, rhs)).await?)
+        }
+        CondExpr::Paren(expr) => Box::pin(evaluate(env, expr)).await,
+    }
+}
+
+/// Executes the double-bracket command.
+pub async fn execute(env: &mut Env, expr: &CondExpr) -> Result {
+    match evaluate(env, expr).await {
+        Ok(true) => env.exit_status = ExitStatus::SUCCESS,
+        Ok(false) => env.exit_status = ExitStatus::FAILURE,
+        Err(Error::Expansion(error)) => return error.handle(env).await,
+        Err(Error::Eval(error)) => {
+            print_message(env, &error).await;
+            env.exit_status = ExitStatus::ERROR;
+        }
+    }
+    env.apply_errexit()?;
+    Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use crate::tests::assert_stderr;
+    use futures_util::FutureExt;
+    use std::cell::RefCell;
+    use std::ops::ControlFlow::Break;
+    use std::rc::Rc;
+    use yash_env::option::Option::ErrExit;
+    use yash_env::option::State::On;
+    use yash_env::semantics::Divert;
+    use yash_env::system::r#virtual::INode;
+    use yash_env::VirtualSystem;
+    use yash_syntax::syntax::CompoundCommand;
+
+    fn run(env: &mut Env, source: &str) -> ExitStatus {
+        let command: CompoundCommand = source.parse().unwrap();
+        let result = command.execute(env).now_or_never().unwrap();
+        assert_eq!(result, Continue(()), "{source}");
+        env.exit_status
+    }
+
+    fn assign(env: &mut Env, name: &str, value: &str) {
+        env.variables
+            .get_or_new(name, Scope::Global)
+            .assign(value, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn string_tests() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "empty", "");
+        assert_eq!(run(&mut env, "[[ foo ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ $empty ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ -z $empty ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -n $empty ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ a < b ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ a > b ]]"), ExitStatus::FAILURE);
+    }
+
+    #[test]
+    fn no_field_splitting_or_pathname_expansion() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "x", "a b *");
+        assert_eq!(run(&mut env, "[[ $x == 'a b *' ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -n $x ]]"), ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn pattern_matching() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "file", "main.rs");
+        assert_eq!(run(&mut env, "[[ $file == *.rs ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ $file = *.c ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ $file != m* ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ $file == '*.rs' ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ '*.rs' == \\*.rs ]]"), ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn regex_matching() {
+        let mut env = Env::new_virtual();
+        assign(&mut env, "x", "key=value");
+        assert_eq!(
+            run(&mut env, "[[ $x =~ ^([a-z]+)=(.*)$ ]]"),
+            ExitStatus::SUCCESS
+        );
+        let rematch = env.variables.get("BASH_REMATCH").unwrap();
+        assert_eq!(
+            rematch.value,
+            Some(Value::array(["key=value", "key", "value"]))
+        );
+
+        assert_eq!(run(&mut env, "[[ $x =~ 'y.v' ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ a.b =~ a'.'b ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ axb =~ a\\.b ]]"), ExitStatus::FAILURE);
+        let rematch = env.variables.get("BASH_REMATCH").unwrap();
+        assert_eq!(rematch.value, Some(Value::Array(vec![])));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        assert_eq!(run(&mut env, "[[ a =~ a{2,1} ]]"), ExitStatus::ERROR);
+        assert_stderr(&state, |stderr| {
+            assert!(stderr.contains("invalid regular expression"), "{stderr}")
+        });
+    }
+
+    #[test]
+    fn unclosed_group_in_regex() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        assign(&mut env, "p", "(");
+        assert_eq!(run(&mut env, "[[ a =~ $p ]]"), ExitStatus::ERROR);
+        assert_stderr(&state, |stderr| {
+            assert!(stderr.contains("invalid regular expression"), "{stderr}")
+        });
+
+        // A quoted parenthesis is matched literally.
+        assert_eq!(run(&mut env, "[[ a =~ \"(\" ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ '(a' =~ \"(\"a ]]"), ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn integer_comparison() {
+        let mut env = Env::new_virtual();
+        assert_eq!(run(&mut env, "[[ 10 -gt 9 ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -3 -lt -4 ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ ' 7' -eq 7 ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ 1 -ne 1 ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ x -le 1 ]]"), ExitStatus::ERROR);
+    }
+
+    #[test]
+    fn logical_operators() {
+        let mut env = Env::new_virtual();
+        assert_eq!(run(&mut env, "[[ ! a ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ a && '' ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ '' || a ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ ! ( a && '' ) ]]"), ExitStatus::SUCCESS);
+        // The right-hand side is not evaluated.
+        assert_eq!(run(&mut env, "[[ a || x -eq 0 ]]"), ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn file_tests() {
+        let system = VirtualSystem::new();
+        {
+            let mut state = system.state.borrow_mut();
+            let mut file = INode::new([1, 2, 3]);
+            file.permissions.0 = 0o755;
+            let file = Rc::new(RefCell::new(file));
+            state.file_system.save("/dir/file", file).unwrap();
+            let empty = Rc::new(RefCell::new(INode::default()));
+            state.file_system.save("/dir/empty", empty).unwrap();
+        }
+        let mut env = Env::with_system(Box::new(system));
+        assert_eq!(run(&mut env, "[[ -e /dir/file ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -f /dir/file ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -d /dir ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -d /dir/file ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ -s /dir/file ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -s /dir/empty ]]"), ExitStatus::FAILURE);
+        assert_eq!(run(&mut env, "[[ -x /dir/file ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -e /no/such ]]"), ExitStatus::FAILURE);
+        let same = "[[ /dir/file -ef /dir/../dir/file ]]";
+        assert_eq!(run(&mut env, same), ExitStatus::SUCCESS);
+    }
+
+    #[test]
+    fn option_and_variable_tests() {
+        let mut env = Env::new_virtual();
+        env.options.set(ErrExit, On);
+        assign(&mut env, "v", "");
+        assert_eq!(run(&mut env, "[[ -o errexit ]]"), ExitStatus::SUCCESS);
+        assert_eq!(run(&mut env, "[[ -v v ]]"), ExitStatus::SUCCESS);
+        env.options.set(ErrExit, yash_env::option::State::Off);
+        assert_eq!(run(&mut env, "[[ -o errexit ]]"), ExitStatus::FAILURE);
+        assert_eq!(
+            run(&mut env, "[[ -o no-such-option ]]"),
+            ExitStatus::FAILURE
+        );
+        assert_eq!(run(&mut env, "[[ -v undefined ]]"), ExitStatus::FAILURE);
+    }
+
+    #[test]
+    fn errexit_on_false() {
+        let mut env = Env::new_virtual();
+        env.options.set(ErrExit, On);
+        let command: CompoundCommand = "[[ '' ]]".parse().unwrap();
+        let result = command.execute(&mut env).now_or_never().unwrap();
+        assert_eq!(result, Break(Divert::Exit(None)));
+        assert_eq!(env.exit_status, ExitStatus::FAILURE);
+    }
+}
@@ -50,6 +50,7 @@ pub async fn execute_builtin(
     let env = &mut env.push_frame(FrameBuiltin { name, is_special }.into());
 
     let env = &mut RedirGuard::new(env);
+    let process_substs = env.process_substs.len();
     if let Err(e) = env.perform_redirs(redirs, xtrace.as_mut()).await {
         e.handle(env).await?;
         return match builtin.r#type {
@@ -75,6 +76,7 @@ pub async fn execute_builtin(
 
     if result.should_retain_redirs() {
         env.preserve_redirs();
+        crate::process_subst::release(env, process_substs);
     }
     env.exit_status = result.exit_status();
     result.divert()
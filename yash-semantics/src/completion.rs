@@ -0,0 +1,650 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command line completion
+//!
+//! The [`complete`] function finds the word being completed in a partially
+//! typed command line and generates [candidates](Candidate) for it. The line
+//! is analyzed with the [lexer](Lexer) so that quotes in the line are
+//! understood. The candidates depend on the context of the word:
+//!
+//! - After `$` or `${`, [variable names](variable_names) are generated.
+//! - For a word starting with `%`, [job IDs](job_ids) are generated.
+//! - For a command name, [command names](command_names) are generated.
+//! - For an argument of a command that has a completion function registered
+//!   in [`env.completion`](yash_env::completion::Completion), the function
+//!   is called and the candidates it adds to the
+//!   [request](yash_env::completion::Request) are used.
+//! - Otherwise, [filenames] are generated.
+//!
+//! The completion function is called with the words preceding the word being
+//! completed in the command, starting with the command name, followed by the
+//! word being completed as the positional parameters.
+//!
+//! If the cursor is in an unclosed command substitution, the command in the
+//! substitution is completed.
+
+use crate::command::simple_command::execute_function_body;
+use crate::command_search::PathEnv;
+use crate::expansion::attr::AttrChar;
+use crate::expansion::attr::AttrField;
+use crate::expansion::attr::Origin;
+use crate::expansion::glob::glob;
+use std::collections::BTreeSet;
+use std::ffi::CString;
+use yash_env::completion::Candidate;
+use yash_env::completion::Request;
+use yash_env::option::Option::Glob;
+use yash_env::option::State::On;
+use yash_env::semantics::Field;
+use yash_env::system::AtFlags;
+use yash_env::system::AT_FDCWD;
+use yash_env::variable::Scope;
+use yash_env::Env;
+use yash_env::System;
+use yash_syntax::parser::lex::is_name_char;
+use yash_syntax::parser::lex::is_token_delimiter_char;
+use yash_syntax::parser::lex::Lexer;
+use yash_syntax::parser::lex::Operator;
+use yash_syntax::parser::lex::TokenId;
+use yash_syntax::source::Location;
+use yash_syntax::source::Source;
+use yash_syntax::syntax::RedirOp;
+use yash_syntax::syntax::Unquote;
+
+/// Generates the names of built-ins, functions, aliases, and executable
+/// files in `$PATH` that start with the prefix.
+///
+/// If the prefix contains a slash, pathnames of directories and executable
+/// files are generated instead.
+pub fn command_names(env: &mut Env, prefix: &str) -> Vec<Candidate> {
+    if prefix.contains('/') {
+        let mut candidates = filenames(env, prefix);
+        candidates.retain(|candidate| {
+            !candidate.ends_word
+                || CString::new(candidate.value.as_str())
+                    .is_ok_and(|path| env.system.is_executable_file(&path))
+        });
+        return candidates;
+    }
+
+    let mut names = BTreeSet::new();
+    names.extend(env.builtins.keys().map(|name| name.to_string()));
+    names.extend(env.functions.iter().map(|function| function.name.clone()));
+    names.extend(env.aliases.iter().map(|alias| alias.0.name.clone()));
+    names.retain(|name| name.starts_with(prefix));
+
+    let dirs: Vec<String> = env.path().split().map(str::to_owned).collect();
+    for dir in dirs {
+        let dir = if dir.is_empty() { ".".to_owned() } else { dir };
+        let Ok(dir_path) = CString::new(dir.as_str()) else {
+            continue;
+        };
+        let Ok(mut entries) = env.system.opendir(&dir_path) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        while let Ok(Some(entry)) = entries.next() {
+            if let Some(name) = entry.name.to_str() {
+                if name.starts_with(prefix) && !names.contains(name) {
+                    found.push(name.to_owned());
+                }
+            }
+        }
+        drop(entries);
+        for name in found {
+            let path = format!("{dir}/{name}");
+            if CString::new(path).is_ok_and(|path| env.system.is_executable_file(&path)) {
+                names.insert(name);
+            }
+        }
+    }
+
+    names.into_iter().map(Candidate::new).collect()
+}
+
+/// Generates pathnames that start with the prefix.
+///
+/// The pathnames are searched for with [pathname expansion](glob) of the
+/// prefix followed by `*`, regardless of the `glob` option. Directory names
+/// are followed by a slash and do not [end the word](Candidate::ends_word).
+/// Files whose name starts with a period are generated only if the last
+/// component of the prefix starts with a period.
+pub fn filenames(env: &mut Env, prefix: &str) -> Vec<Candidate> {
+    let literal = |value| AttrChar {
+        value,
+        origin: Origin::Literal,
+        is_quoted: true,
+        is_quoting: false,
+    };
+    let mut chars: Vec<AttrChar> = prefix.chars().map(literal).collect();
+    chars.push(AttrChar {
+        is_quoted: false,
+        ..literal('*')
+    });
+    let field = AttrField {
+        chars,
+        origin: Location::dummy(prefix),
+    };
+
+    let glob_option = env.options.get(Glob);
+    env.options.set(Glob, On);
+    let fields: Vec<Field> = glob(env, field).collect();
+    env.options.set(Glob, glob_option);
+
+    fields
+        .into_iter()
+        .filter(|field| {
+            let name = field.value.rsplit('/').next().unwrap_or_default();
+            name != "." && name != ".."
+        })
+        .filter_map(|field| {
+            let path = CString::new(field.value.as_str()).ok()?;
+            env.system.fstatat(AT_FDCWD, &path, AtFlags::empty()).ok()?;
+            let mut value = field.value;
+            let is_dir = env.system.is_directory(&path);
+            if is_dir {
+                value.push('/');
+            }
+            Some(Candidate {
+                value,
+                ends_word: !is_dir,
+            })
+        })
+        .collect()
+}
+
+/// Generates the names of variables that start with the prefix.
+pub fn variable_names(env: &Env, prefix: &str) -> Vec<Candidate> {
+    let names: BTreeSet<&str> = env
+        .variables
+        .iter(Scope::Global)
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.into_iter().map(Candidate::new).collect()
+}
+
+/// Generates the job IDs that start with the prefix.
+///
+/// A job ID is `%` followed by the job number.
+pub fn job_ids(env: &Env, prefix: &str) -> Vec<Candidate> {
+    env.jobs
+        .iter()
+        .map(|(index, _)| format!("%{}", index + 1))
+        .filter(|id| id.starts_with(prefix))
+        .map(Candidate::new)
+        .collect()
+}
+
+/// Kind of the text being completed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    /// Word, possibly in an unclosed quote
+    Word { quote: Option<char> },
+    /// Variable name after `$` or `${`
+    Variable { braced: bool },
+}
+
+/// Result of [`complete`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Completed {
+    /// Character index in the line where the text being completed starts
+    ///
+    /// The text from this index to the cursor is replaced with a
+    /// [replacement](Self::replacement).
+    pub start: usize,
+    /// Text being completed, with quotes removed
+    pub target: String,
+    /// Generated candidates
+    pub candidates: Vec<Candidate>,
+    kind: Kind,
+}
+
+impl Completed {
+    /// Returns the text that replaces the text being completed.
+    ///
+    /// The value is quoted in the same way as the word being completed. If
+    /// `ends_word` is true, the quotation is closed and the word is
+    /// terminated with a space.
+    #[must_use]
+    pub fn replacement(&self, value: &str, ends_word: bool) -> String {
+        let mut result = String::new();
+        match self.kind {
+            Kind::Variable { braced } => {
+                result.push_str(value);
+                if ends_word && braced {
+                    result.push('}');
+                }
+                return result;
+            }
+            Kind::Word { quote: Some('\'') } => {
+                result.push('\'');
+                result.push_str(&value.replace('\'', "'\\''"));
+            }
+            Kind::Word { quote: Some(quote) } => {
+                result.push(quote);
+                for c in value.chars() {
+                    if matches!(c, '"' | '\\' | '$' | '`') {
+                        result.push('\\');
+                    }
+                    result.push(c);
+                }
+            }
+            Kind::Word { quote: None } => {
+                for c in value.chars() {
+                    if needs_escape(c) {
+                        result.push('\\');
+                    }
+                    result.push(c);
+                }
+            }
+        }
+        if ends_word {
+            if let Kind::Word { quote: Some(quote) } = self.kind {
+                result.push(quote);
+            }
+            result.push(' ');
+        }
+        result
+    }
+}
+
+/// Tests whether the character must be escaped in an unquoted word.
+fn needs_escape(c: char) -> bool {
+    is_token_delimiter_char(c)
+        || matches!(
+            c,
+            '\\' | '\'' | '"' | '$' | '`' | '*' | '?' | '[' | ']' | '#' | '~' | '{' | '}'
+        )
+}
+
+/// Construct that is not closed in the line
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Open {
+    /// Character that closes the construct
+    closer: char,
+    /// Character index just after the opening
+    end: usize,
+}
+
+/// Result of scanning quotes and expansions in the line
+#[derive(Debug, Default)]
+struct Scan {
+    open: Vec<Open>,
+    /// Start of the variable name at the end of the line and whether it is
+    /// braced
+    variable: Option<(usize, bool)>,
+}
+
+/// Finds quotes and expansions that are not closed at the end of the line.
+fn scan(line: &[char]) -> Scan {
+    let mut scan = Scan::default();
+    let mut index = 0;
+    while let Some(&c) = line.get(index) {
+        index += 1;
+        let top = scan.open.last().map(|open| open.closer);
+        if top == Some('\'') {
+            if c == '\'' {
+                scan.open.pop();
+            }
+            continue;
+        }
+        if scan.variable.is_some() && !is_name_char(c) {
+            scan.variable = None;
+        }
+
+        let mut push = |closer, end| scan.open.push(Open { closer, end });
+        match c {
+            '\\' => index += 1,
+            '\'' if top != Some('"') => push('\'', index),
+            '"' | '`' if top == Some(c) => _ = scan.open.pop(),
+            '"' | '`' => push(c, index),
+            '$' if line.get(index) == Some(&'{') => {
+                index += 1;
+                push('}', index);
+                scan.variable = Some((index, true));
+            }
+            '$' if line.get(index) == Some(&'(') => {
+                index += 1;
+                push(')', index);
+            }
+            '$' => scan.variable = Some((index, false)),
+            '(' if top == Some(')') => push(')', index),
+            '}' | ')' if top == Some(c) => _ = scan.open.pop(),
+            _ => (),
+        }
+    }
+    scan
+}
+
+/// Tests whether the operator introduces a redirection operand.
+fn is_redirection(operator: Operator) -> bool {
+    RedirOp::try_from(operator).is_ok()
+        || matches!(operator, Operator::LessLess | Operator::LessLessDash)
+}
+
+/// Tests whether the word is an assignment.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=')
+        .is_some_and(|(name, _)| !name.is_empty() && name.chars().all(is_name_char))
+}
+
+/// Calls the completion function and returns the candidates it added.
+async fn call_function(
+    env: &mut Env,
+    name: &str,
+    words: Vec<String>,
+    target: String,
+) -> Vec<Candidate> {
+    let Some(function) = env.functions.get(name).cloned() else {
+        return filenames(env, &target);
+    };
+    let fields = std::iter::once(name.to_owned())
+        .chain(words.iter().cloned())
+        .chain(std::iter::once(target.clone()))
+        .map(Field::dummy)
+        .collect();
+    let request = Request {
+        words,
+        target,
+        candidates: Vec::new(),
+    };
+
+    let previous = env.completion.request.replace(request);
+    let exit_status = env.exit_status;
+    _ = execute_function_body(env, function, fields, |_| ()).await;
+    env.exit_status = exit_status;
+    let request = std::mem::replace(&mut env.completion.request, previous);
+    request
+        .map(|request| request.candidates)
+        .unwrap_or_default()
+}
+
+/// Generates candidates for the word being completed.
+///
+/// The `line` is the part of the command line before the cursor. The
+/// returned `Completed` has no candidates if the line cannot be analyzed.
+pub async fn complete(env: &mut Env, line: &str) -> Completed {
+    let line: Vec<char> = line.chars().collect();
+    let scan = scan(&line);
+
+    if let Some((start, braced)) = scan.variable {
+        let target: String = line[start..].iter().collect();
+        let candidates = variable_names(env, &target);
+        let kind = Kind::Variable { braced };
+        return Completed {
+            start,
+            target,
+            candidates,
+            kind,
+        };
+    }
+
+    // Complete the command in the innermost unclosed command substitution
+    let substitution = scan
+        .open
+        .iter()
+        .rposition(|open| open.closer == ')' || open.closer == '`');
+    let (offset, open) = match substitution {
+        Some(index) => (scan.open[index].end, &scan.open[index + 1..]),
+        None => (0, &scan.open[..]),
+    };
+    let quote = open
+        .last()
+        .map(|open| open.closer)
+        .filter(|&c| c == '\'' || c == '"');
+    let kind = Kind::Word { quote };
+
+    let code: String = line[offset..]
+        .iter()
+        .copied()
+        .chain(open.iter().rev().map(|open| open.closer))
+        .collect();
+    let code_len = line.len() - offset;
+    let mut completed = Completed {
+        start: line.len(),
+        target: String::new(),
+        candidates: Vec::new(),
+        kind,
+    };
+
+    let mut lexer = Lexer::from_memory(&code, Source::Unknown);
+    let mut words = Vec::new();
+    let mut redirection = false;
+    let (start, target) = loop {
+        if lexer.skip_blanks_and_comment().await.is_err() {
+            return completed;
+        }
+        let Ok(token) = lexer.token().await else {
+            return completed;
+        };
+        let is_target = token.word.location.range.end >= code_len;
+        match token.id {
+            TokenId::EndOfInput => break (code_len, String::new()),
+            TokenId::Operator(operator) => {
+                if is_redirection(operator) {
+                    redirection = true;
+                } else {
+                    words.clear();
+                    redirection = false;
+                }
+                if is_target {
+                    break (code_len, String::new());
+                }
+            }
+            TokenId::IoNumber if !is_target => (),
+            TokenId::Token(_) | TokenId::IoNumber => {
+                let (word, _) = token.word.unquote();
+                if is_target {
+                    break (token.index, word);
+                }
+                if redirection {
+                    redirection = false;
+                } else if !(words.is_empty()
+                    && (matches!(token.id, TokenId::Token(Some(_))) || is_assignment(&word)))
+                {
+                    words.push(word);
+                }
+            }
+        }
+    };
+    completed.start = offset + start;
+
+    completed.candidates = if redirection {
+        filenames(env, &target)
+    } else if target.starts_with('%') {
+        job_ids(env, &target)
+    } else if let Some(command) = words.first() {
+        match env.completion.functions.get(command).cloned() {
+            Some(function) => call_function(env, &function, words, target.clone()).await,
+            None => filenames(env, &target),
+        }
+    } else {
+        command_names(env, &target)
+    };
+    completed.target = target;
+    completed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::echo_builtin;
+    use futures_util::FutureExt as _;
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use yash_env::builtin::Builtin;
+    use yash_env::builtin::Type::Mandatory;
+    use yash_env::function::Function;
+    use yash_env::job::Job;
+    use yash_env::job::Pid;
+    use yash_env::system::r#virtual::INode;
+    use yash_env::VirtualSystem;
+    use yash_syntax::syntax::CompoundCommand;
+    use yash_syntax::syntax::FullCompoundCommand;
+
+    fn env_with_files(paths: &[&str]) -> Env {
+        let system = VirtualSystem::new();
+        {
+            let mut state = system.state.borrow_mut();
+            for path in paths {
+                let file = Rc::new(RefCell::new(INode::default()));
+                state.file_system.save(path, file).unwrap();
+            }
+        }
+        Env::with_system(Box::new(system))
+    }
+
+    fn values(completed: &Completed) -> Vec<&str> {
+        completed
+            .candidates
+            .iter()
+            .map(|c| c.value.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn command_name_completion() {
+        let mut env = Env::new_virtual();
+        env.builtins.insert("echo", echo_builtin());
+        env.builtins.insert("eval", echo_builtin());
+        env.builtins.insert("exit", echo_builtin());
+        let completed = complete(&mut env, "ls; e").now_or_never().unwrap();
+        assert_eq!(completed.start, 4);
+        assert_eq!(completed.target, "e");
+        assert_eq!(values(&completed), ["echo", "eval", "exit"]);
+
+        let completed = complete(&mut env, "if ev").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["eval"]);
+    }
+
+    #[test]
+    fn filename_completion() {
+        let mut env = env_with_files(&["/foo/bar", "/foo/baz", "/foo/.hidden", "/foo/dir/file"]);
+        let completed = complete(&mut env, "cat /foo/").now_or_never().unwrap();
+        assert_eq!(completed.start, 4);
+        assert_eq!(values(&completed), ["/foo/bar", "/foo/baz", "/foo/dir/"]);
+        assert!(!completed.candidates[2].ends_word);
+
+        let completed = complete(&mut env, "cat /foo/.").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["/foo/.hidden"]);
+    }
+
+    #[test]
+    fn filename_completion_in_quotes() {
+        let mut env = env_with_files(&["/a b/file"]);
+        let completed = complete(&mut env, "cat '/a b/f").now_or_never().unwrap();
+        assert_eq!(completed.start, 4);
+        assert_eq!(completed.target, "/a b/f");
+        assert_eq!(values(&completed), ["/a b/file"]);
+        assert_eq!(completed.replacement("/a b/file", true), "'/a b/file' ");
+
+        let completed = complete(&mut env, "cat /a\\ b/").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["/a b/file"]);
+        assert_eq!(completed.replacement("/a b/f", false), "/a\\ b/f");
+    }
+
+    #[test]
+    fn redirection_operand_is_filename() {
+        let mut env = env_with_files(&["/tmp/out"]);
+        let completed = complete(&mut env, "echo >/tmp/o").now_or_never().unwrap();
+        assert_eq!(completed.start, 6);
+        assert_eq!(values(&completed), ["/tmp/out"]);
+    }
+
+    #[test]
+    fn variable_name_completion() {
+        let mut env = Env::new_virtual();
+        for name in ["HOME", "HOST", "PATH"] {
+            env.variables
+                .get_or_new(name, Scope::Global)
+                .assign("", None)
+                .unwrap();
+        }
+        let completed = complete(&mut env, "echo \"$HO").now_or_never().unwrap();
+        assert_eq!(completed.start, 7);
+        assert_eq!(values(&completed), ["HOME", "HOST"]);
+        assert_eq!(completed.replacement("HOME", true), "HOME");
+
+        let completed = complete(&mut env, "echo ${PA").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["PATH"]);
+        assert_eq!(completed.replacement("PATH", true), "PATH}");
+    }
+
+    #[test]
+    fn job_id_completion() {
+        let mut env = Env::new_virtual();
+        env.jobs.add(Job::new(Pid(10)));
+        env.jobs.add(Job::new(Pid(11)));
+        let completed = complete(&mut env, "fg %").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["%1", "%2"]);
+    }
+
+    #[test]
+    fn command_substitution() {
+        let mut env = Env::new_virtual();
+        env.builtins.insert("echo", echo_builtin());
+        let completed = complete(&mut env, "ls \"$(ec").now_or_never().unwrap();
+        assert_eq!(completed.start, 6);
+        assert_eq!(values(&completed), ["echo"]);
+    }
+
+    fn add_builtin_main(
+        env: &mut Env,
+        args: Vec<Field>,
+    ) -> Pin<Box<dyn Future<Output = yash_env::builtin::Result> + '_>> {
+        Box::pin(async move {
+            let request = env.completion.request.as_mut().unwrap();
+            for arg in args {
+                request.add(Candidate::new(arg.value));
+            }
+            Default::default()
+        })
+    }
+
+    #[test]
+    fn completion_function() {
+        let mut env = Env::new_virtual();
+        env.builtins.insert(
+            "add",
+            Builtin {
+                r#type: Mandatory,
+                execute: add_builtin_main,
+            },
+        );
+        let body = FullCompoundCommand {
+            command: CompoundCommand::Grouping(
+                "add \"$1\" \"$2\"mit \"$#\" \"$3\"x".parse().unwrap(),
+            ),
+            redirs: vec![],
+        };
+        let function = Function::new("comp", body, Location::dummy("comp"));
+        env.functions.define(function).unwrap();
+        env.completion
+            .functions
+            .insert("git".to_owned(), "comp".to_owned());
+
+        let completed = complete(&mut env, "git 'com").now_or_never().unwrap();
+        assert_eq!(completed.target, "com");
+        assert_eq!(values(&completed), ["commit"]);
+        let completed = complete(&mut env, "git ").now_or_never().unwrap();
+        assert_eq!(values(&completed), ["git", "mit", "2", "x"]);
+        assert_eq!(env.completion.request, None);
+    }
+}
@@ -42,7 +42,9 @@
 //!
 //! ## Brace expansion
 //!
-//! The brace expansion produces copies of a field containing a pair of braces.
+//! The [brace expansion](brace) produces copies of a field containing a pair of
+//! braces. This step is performed only if the [`BraceExpand`] option is
+//! enabled.
 //!
 //! ## Field splitting
 //!
@@ -67,6 +69,7 @@
 
 pub mod attr;
 pub mod attr_strip;
+pub mod brace;
 pub mod glob;
 pub mod initial;
 pub mod phrase;
@@ -88,6 +91,8 @@ use self::quote_removal::skip_quotes;
 use self::split::Ifs;
 use std::borrow::Cow;
 use thiserror::Error;
+use yash_env::option::Option::BraceExpand;
+use yash_env::option::State::On;
 use yash_env::semantics::ExitStatus;
 use yash_env::system::Errno;
 use yash_env::variable::Value;
@@ -114,6 +119,33 @@ pub struct AssignReadOnlyError {
     pub read_only_location: Location,
 }
 
+/// Error returned on assigning to an array element
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum AssignElementError {
+    /// The variable is not an array.
+    #[error("variable {name:?} is not an array")]
+    NotArray {
+        /// Name of the variable
+        name: String,
+    },
+    /// The index does not specify an existing element.
+    #[error("index {index} is out of range for array {name:?} of length {len}")]
+    IndexOutOfRange {
+        /// Name of the array
+        name: String,
+        /// Index of the element that was being assigned
+        index: i64,
+        /// Number of elements in the array
+        len: usize,
+    },
+    /// The assigned value is an array.
+    #[error("cannot assign an array to an element of {name:?}")]
+    ArrayValue {
+        /// Name of the array
+        name: String,
+    },
+}
+
 /// Types of errors that may occur in the word expansion.
 #[derive(Clone, Debug, Eq, Error, PartialEq)]
 pub enum ErrorCause {
@@ -121,6 +153,10 @@ pub enum ErrorCause {
     #[error("error in command substitution: {0}")]
     CommandSubstError(Errno),
 
+    /// System error while performing a process substitution.
+    #[error("error in process substitution: {0}")]
+    ProcessSubstError(Errno),
+
     /// Error while evaluating an arithmetic expansion.
     #[error(transparent)]
     ArithError(#[from] ArithError),
@@ -129,6 +165,10 @@ pub enum ErrorCause {
     #[error(transparent)]
     AssignReadOnly(#[from] AssignReadOnlyError),
 
+    /// Invalid assignment to an array element
+    #[error(transparent)]
+    AssignElement(#[from] AssignElementError),
+
     /// Expansion of an unset parameter with the `nounset` option
     #[error("unset parameter")]
     UnsetParameter,
@@ -150,8 +190,10 @@ impl ErrorCause {
         use ErrorCause::*;
         match self {
             CommandSubstError(_) => "error performing the command substitution",
+            ProcessSubstError(_) => "error performing the process substitution",
             ArithError(_) => "error evaluating the arithmetic expansion",
             AssignReadOnly(_) => "error assigning to variable",
+            AssignElement(_) => "error assigning to array element",
             UnsetParameter => "unset parameter",
             EmptyExpansion(error) => error.message_or_default(),
             NonassignableParameter(_) => "cannot assign to parameter",
@@ -164,9 +206,10 @@ impl ErrorCause {
         // TODO Localize
         use ErrorCause::*;
         match self {
-            CommandSubstError(e) => e.desc().into(),
+            CommandSubstError(e) | ProcessSubstError(e) => e.desc().into(),
             ArithError(e) => e.to_string().into(),
             AssignReadOnly(e) => e.to_string().into(),
+            AssignElement(e) => e.to_string().into(),
             UnsetParameter => "unset parameter disallowed by the nounset option".into(),
             EmptyExpansion(e) => e.state.description().into(),
             NonassignableParameter(e) => e.to_string().into(),
@@ -180,12 +223,13 @@ impl ErrorCause {
         // TODO Localize
         use ErrorCause::*;
         match self {
-            CommandSubstError(_) => None,
+            CommandSubstError(_) | ProcessSubstError(_) => None,
             ArithError(e) => e.related_location(),
             AssignReadOnly(e) => Some((
                 &e.read_only_location,
                 "the variable was made read-only here",
             )),
+            AssignElement(_) => None,
             UnsetParameter => None,
             EmptyExpansion(_) => None,
             NonassignableParameter(_) => None,
@@ -312,7 +356,14 @@ pub async fn expand_words<'a, I: IntoIterator<Item = &'a Word>>(
         }));
     }
 
-    // TODO brace expansion
+    // brace expansion //
+    if env.inner.options.get(BraceExpand) == On {
+        let mut brace_fields = Vec::with_capacity(fields.len());
+        for field in fields {
+            brace::expand_into(field, &mut brace_fields);
+        }
+        fields = brace_fields;
+    }
 
     // field splitting //
     use yash_env::variable::Value::Scalar;
@@ -416,6 +467,38 @@ mod tests {
         })
     }
 
+    #[test]
+    fn expand_words_performs_brace_expansion_if_enabled() {
+        let mut env = yash_env::Env::new_virtual();
+        let words = &["a{1..2}'{x,y}'{b,c}".parse().unwrap()];
+        let result = expand_words(&mut env, words).now_or_never().unwrap();
+        let (fields, _) = result.unwrap();
+        assert_matches!(fields.as_slice(), [f] => {
+            assert_eq!(f.value, "a{1..2}{x,y}{b,c}");
+        });
+
+        env.options.set(BraceExpand, On);
+        let result = expand_words(&mut env, words).now_or_never().unwrap();
+        let (fields, _) = result.unwrap();
+        let values: Vec<_> = fields.iter().map(|f| f.value.as_str()).collect();
+        assert_eq!(values, ["a1{x,y}b", "a1{x,y}c", "a2{x,y}b", "a2{x,y}c"]);
+    }
+
+    #[test]
+    fn expand_words_performs_brace_expansion_before_field_splitting() {
+        let mut env = yash_env::Env::new_virtual();
+        env.options.set(BraceExpand, On);
+        env.variables
+            .get_or_new("v", Scope::Global)
+            .assign("1 2", None)
+            .unwrap();
+        let words = &["{$v,x}".parse().unwrap()];
+        let result = expand_words(&mut env, words).now_or_never().unwrap();
+        let (fields, _) = result.unwrap();
+        let values: Vec<_> = fields.iter().map(|f| f.value.as_str()).collect();
+        assert_eq!(values, ["1", "2", "x"]);
+    }
+
     #[test]
     fn expand_words_performs_field_splitting_possibly_with_default_ifs() {
         let mut env = yash_env::Env::new_virtual();
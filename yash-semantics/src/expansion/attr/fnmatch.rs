@@ -17,6 +17,11 @@
 //! Utility for parsing `AttrChar` strings as a fnmatch pattern
 
 use super::AttrChar;
+use std::cell::RefCell;
+use yash_fnmatch::Config;
+use yash_fnmatch::Error;
+use yash_fnmatch::Pattern;
+use yash_fnmatch::PatternCache;
 use yash_fnmatch::PatternChar;
 
 /// Converts unquoted backslashes to quoting characters.
@@ -45,3 +50,28 @@ pub fn to_pattern_chars(chars: &[AttrChar]) -> impl Iterator<Item = PatternChar>
         }
     })
 }
+
+/// Maximum number of patterns cached by [`with_pattern`]
+const CACHE_CAPACITY: usize = 64;
+
+thread_local! {
+    /// Patterns compiled by [`with_pattern`]
+    static CACHE: RefCell<PatternCache> = RefCell::new(PatternCache::new(CACHE_CAPACITY));
+}
+
+/// Compiles an `AttrChar` slice as a pattern and applies a function to it.
+///
+/// The characters are converted with [`to_pattern_chars`]. Compiled patterns
+/// are cached so that a pattern used repeatedly, say, in a loop is not
+/// recompiled every time. If the pattern cannot be compiled, the function is
+/// not called and the error is returned.
+pub fn with_pattern<F, R>(chars: &[AttrChar], config: Config, f: F) -> Result<R, Error>
+where
+    F: FnOnce(&Pattern) -> R,
+{
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let pattern = cache.get_or_parse(to_pattern_chars(chars), config)?;
+        Ok(f(pattern))
+    })
+}
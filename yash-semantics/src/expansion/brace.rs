@@ -0,0 +1,471 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Brace expansion
+//!
+//! The brace expansion produces copies of a field containing a pair of braces.
+//! It is enabled by the [`BraceExpand`](yash_env::option::BraceExpand) option.
+//!
+//! # Comma-separated lists
+//!
+//! If the braces contain one or more commas, the field is copied for each
+//! comma-separated part, replacing the braces with the part. For example,
+//! `a{1,2,3}b` expands to `a1b`, `a2b`, and `a3b`. Braces may be nested:
+//! `{a,b{1,2}}` expands to `a`, `b1`, and `b2`.
+//!
+//! # Sequences
+//!
+//! If the braces contain two integers separated by `..`, the field is copied
+//! for each integer from the first to the second. For example, `{1..3}`
+//! expands to `1`, `2`, and `3`, and `{3..1}` to `3`, `2`, and `1`. If either
+//! integer has a leading zero, the results are padded with zeros to the same
+//! width, so `{08..10}` expands to `08`, `09`, and `10`.
+//!
+//! Similarly, two letters separated by `..` produce the characters between
+//! them. For example, `{a..c}` expands to `a`, `b`, and `c`.
+//!
+//! A sequence may have a step after another `..`, as in `{1..10..3}`, which
+//! expands to `1`, `4`, `7`, and `10`. The sign of the step is ignored.
+//!
+//! The characters produced by a sequence have the
+//! [`HardExpansion`](Origin::HardExpansion) origin.
+//!
+//! # Quoting
+//!
+//! Only unquoted braces and commas that appeared literally in the original
+//! word are recognized. Characters resulting from the initial expansion are
+//! not subject to the brace expansion. If the braces contain no valid comma
+//! or sequence, they remain intact.
+//!
+//! # Example
+//!
+//! ```
+//! use yash_syntax::source::Location;
+//! use yash_semantics::expansion::attr::{AttrChar, AttrField, Origin};
+//! use yash_semantics::expansion::brace::expand;
+//!
+//! fn field(s: &str) -> AttrField {
+//!     let chars = s.chars()
+//!         .map(|c| AttrChar {
+//!             value: c,
+//!             origin: Origin::Literal,
+//!             is_quoted: false,
+//!             is_quoting: false,
+//!         })
+//!         .collect();
+//!     let origin = Location::dummy("");
+//!     AttrField { chars, origin }
+//! }
+//! fn values(fields: Vec<AttrField>) -> Vec<String> {
+//!     fields.iter().map(|f| f.chars.iter().map(|c| c.value).collect()).collect()
+//! }
+//!
+//! let fields: Vec<AttrField> = expand(field("a{b,c}d"));
+//! assert_eq!(values(fields), ["abd", "acd"]);
+//!
+//! let fields: Vec<AttrField> = expand(field("x{1..3}"));
+//! assert_eq!(values(fields), ["x1", "x2", "x3"]);
+//!
+//! let fields: Vec<AttrField> = expand(field("{a}"));
+//! assert_eq!(values(fields), ["{a}"]);
+//! ```
+//!
+//! # Limitations
+//!
+//! The brace expansion is performed on each field produced by the initial
+//! expansion. Braces are not paired across fields resulting from a parameter
+//! expansion of `$@` or an array.
+
+use super::attr::AttrChar;
+use super::attr::AttrField;
+use super::attr::Origin;
+
+/// Tests if the character is an active (unquoted and literal) `c`.
+fn is_active(ch: &AttrChar, c: char) -> bool {
+    ch.value == c && !ch.is_quoted && !ch.is_quoting && ch.origin == Origin::Literal
+}
+
+/// Brace pair found in a field
+#[derive(Debug)]
+struct Braces {
+    /// Index of the opening brace
+    open: usize,
+    /// Indices of the commas at the top level of the braces
+    commas: Vec<usize>,
+    /// Index of the closing brace
+    close: usize,
+}
+
+/// Finds the closing brace that matches the opening brace at `open`.
+fn match_braces(chars: &[AttrChar], open: usize) -> Option<Braces> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    for (index, ch) in chars.iter().enumerate().skip(open + 1) {
+        if is_active(ch, '{') {
+            depth += 1;
+        } else if is_active(ch, '}') {
+            if depth == 0 {
+                return Some(Braces {
+                    open,
+                    commas,
+                    close: index,
+                });
+            }
+            depth -= 1;
+        } else if depth == 0 && is_active(ch, ',') {
+            commas.push(index);
+        }
+    }
+    None
+}
+
+/// Parses a decimal integer in a sequence.
+///
+/// Returns the value and whether the integer has a leading zero.
+fn parse_integer(s: &str) -> Option<(i64, bool)> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value = s.parse().ok()?;
+    let zero_padded = digits.len() > 1 && digits.starts_with('0');
+    Some((value, zero_padded))
+}
+
+/// Parses a single alphabetic character in a sequence.
+fn parse_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    (c.is_alphabetic() && chars.next().is_none()).then_some(c)
+}
+
+/// Generates the values of a sequence.
+///
+/// `content` is the text between the braces. Returns `None` if the text is
+/// not a valid sequence.
+fn sequence(content: &str) -> Option<Vec<String>> {
+    let mut parts = content.split("..");
+    let start = parts.next()?;
+    let end = parts.next()?;
+    let step = match parts.next() {
+        None => 1,
+        Some(step) => parse_integer(step)?.0.unsigned_abs().max(1),
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if let (Some((start_value, start_padded)), Some((end_value, end_padded))) =
+        (parse_integer(start), parse_integer(end))
+    {
+        let width = if start_padded || end_padded {
+            start.len().max(end.len())
+        } else {
+            0
+        };
+        let format = |n: i64| {
+            if n < 0 {
+                format!(
+                    "-{:0width$}",
+                    n.unsigned_abs(),
+                    width = width.saturating_sub(1)
+                )
+            } else {
+                format!("{n:0width$}")
+            }
+        };
+        return Some(
+            steps(start_value.into(), end_value.into(), step)
+                .map(|n| format(n as i64))
+                .collect(),
+        );
+    }
+
+    let start = parse_char(start)?;
+    let end = parse_char(end)?;
+    Some(
+        steps(u32::from(start).into(), u32::from(end).into(), step)
+            .filter_map(|n| char::from_u32(n as u32))
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Returns an iterator from `start` to `end` (inclusive) by `step`.
+///
+/// The iteration goes backward if `start` is greater than `end`.
+fn steps(start: i128, end: i128, step: u64) -> impl Iterator<Item = i128> {
+    let step = i128::from(step);
+    let (step, count) = if start <= end {
+        (step, (end - start) / step)
+    } else {
+        (-step, (start - end) / step)
+    };
+    (0..=count).map(move |i| start + i * step)
+}
+
+/// Converts the result of a sequence into a field value.
+fn sequence_chars(value: &str) -> impl Iterator<Item = AttrChar> + '_ {
+    value.chars().map(|c| AttrChar {
+        value: c,
+        origin: Origin::HardExpansion,
+        is_quoted: false,
+        is_quoting: false,
+    })
+}
+
+/// Returns the copies of the field for the first expandable brace pair.
+///
+/// Returns `None` if the field contains no expandable braces.
+fn expand_first(field: &AttrField) -> Option<Vec<Vec<AttrChar>>> {
+    let chars = &field.chars;
+    let mut start = 0;
+    while let Some(offset) = chars[start..].iter().position(|c| is_active(c, '{')) {
+        let open = start + offset;
+        start = open + 1;
+        let Some(braces) = match_braces(chars, open) else {
+            continue;
+        };
+
+        let prefix = &chars[..braces.open];
+        let suffix = &chars[braces.close + 1..];
+        let copy = |middle: &mut dyn Iterator<Item = AttrChar>| {
+            let mut chars = prefix.to_vec();
+            chars.extend(middle);
+            chars.extend_from_slice(suffix);
+            chars
+        };
+
+        if !braces.commas.is_empty() {
+            let bounds = std::iter::once(braces.open)
+                .chain(braces.commas.iter().copied())
+                .chain(std::iter::once(braces.close));
+            let mut results = Vec::with_capacity(braces.commas.len() + 1);
+            let mut bounds = bounds.peekable();
+            while let (Some(from), Some(&to)) = (bounds.next(), bounds.peek()) {
+                results.push(copy(&mut chars[from + 1..to].iter().copied()));
+            }
+            return Some(results);
+        }
+
+        let content = &chars[braces.open + 1..braces.close];
+        if content
+            .iter()
+            .all(|c| !c.is_quoted && !c.is_quoting && c.origin == Origin::Literal)
+        {
+            let content = content.iter().map(|c| c.value).collect::<String>();
+            if let Some(values) = sequence(&content) {
+                let results = values
+                    .iter()
+                    .map(|value| copy(&mut sequence_chars(value)))
+                    .collect();
+                return Some(results);
+            }
+        }
+    }
+    None
+}
+
+/// Performs brace expansion and appends the results to a collection.
+///
+/// This function applies brace expansion to the given field and extends the
+/// given collection with the results. The resultant fields share the same
+/// origin as the input field. If the field contains no expandable braces,
+/// the field is added intact.
+///
+/// See also [`expand`], which returns the results in a new collection rather
+/// than extending an existing one.
+pub fn expand_into<R>(field: AttrField, results: &mut R)
+where
+    R: Extend<AttrField>,
+{
+    match expand_first(&field) {
+        // TODO Use Extend::extend_one when stabilized (rust#72631)
+        None => results.extend(std::iter::once(field)),
+        Some(copies) => {
+            for chars in copies {
+                let origin = field.origin.clone();
+                expand_into(AttrField { chars, origin }, results);
+            }
+        }
+    }
+}
+
+/// Performs brace expansion and returns the results in a new collection.
+///
+/// This function works similarly to [`expand_into`], but returns the results
+/// in a new collection.
+pub fn expand<R>(field: AttrField) -> R
+where
+    R: Default + Extend<AttrField>,
+{
+    let mut results = R::default();
+    expand_into(field, &mut results);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yash_syntax::source::Location;
+
+    fn literal_field(s: &str) -> AttrField {
+        let chars = s
+            .chars()
+            .map(|c| AttrChar {
+                value: c,
+                origin: Origin::Literal,
+                is_quoted: false,
+                is_quoting: false,
+            })
+            .collect();
+        let origin = Location::dummy("");
+        AttrField { chars, origin }
+    }
+
+    fn expand_values(field: AttrField) -> Vec<String> {
+        let fields: Vec<AttrField> = expand(field);
+        fields
+            .iter()
+            .map(|field| field.chars.iter().map(|c| c.value).collect())
+            .collect()
+    }
+
+    #[test]
+    fn no_braces() {
+        assert_eq!(expand_values(literal_field("")), [""]);
+        assert_eq!(expand_values(literal_field("abc")), ["abc"]);
+    }
+
+    #[test]
+    fn comma_list() {
+        let values = expand_values(literal_field("a{b,c,d}e"));
+        assert_eq!(values, ["abe", "ace", "ade"]);
+    }
+
+    #[test]
+    fn comma_list_with_empty_parts() {
+        assert_eq!(expand_values(literal_field("a{,}")), ["a", "a"]);
+        assert_eq!(expand_values(literal_field("{x,,y}")), ["x", "", "y"]);
+    }
+
+    #[test]
+    fn nested_comma_lists() {
+        let values = expand_values(literal_field("{a,b{1,2},c}"));
+        assert_eq!(values, ["a", "b1", "b2", "c"]);
+    }
+
+    #[test]
+    fn multiple_comma_lists() {
+        let values = expand_values(literal_field("{a,b}{1,2}"));
+        assert_eq!(values, ["a1", "a2", "b1", "b2"]);
+    }
+
+    #[test]
+    fn unexpandable_braces() {
+        assert_eq!(expand_values(literal_field("{}")), ["{}"]);
+        assert_eq!(expand_values(literal_field("{a}")), ["{a}"]);
+        assert_eq!(expand_values(literal_field("{a,b")), ["{a,b"]);
+        assert_eq!(expand_values(literal_field("a,b}")), ["a,b}"]);
+        assert_eq!(expand_values(literal_field("{1..}")), ["{1..}"]);
+        assert_eq!(expand_values(literal_field("{1..a}")), ["{1..a}"]);
+        assert_eq!(expand_values(literal_field("{ab..c}")), ["{ab..c}"]);
+    }
+
+    #[test]
+    fn braces_inside_unexpandable_braces() {
+        let values = expand_values(literal_field("{a{b,c}}"));
+        assert_eq!(values, ["{ab}", "{ac}"]);
+        let values = expand_values(literal_field("{x{1,2}"));
+        assert_eq!(values, ["{x1", "{x2"]);
+    }
+
+    #[test]
+    fn quoted_braces_and_commas() {
+        let mut field = literal_field("{a,b}");
+        field.chars[0].is_quoted = true;
+        assert_eq!(expand_values(field), ["{a,b}"]);
+
+        let mut field = literal_field("{a,b}");
+        field.chars[2].is_quoted = true;
+        assert_eq!(expand_values(field), ["{a,b}"]);
+
+        let mut field = literal_field("{a,b}");
+        field.chars[2].origin = Origin::SoftExpansion;
+        assert_eq!(expand_values(field), ["{a,b}"]);
+    }
+
+    #[test]
+    fn attributes_are_kept_in_comma_lists() {
+        let mut field = literal_field("{a,b}");
+        field.chars[1].is_quoted = true;
+        let fields: Vec<AttrField> = expand(field);
+        assert_eq!(fields.len(), 2);
+        assert!(fields[0].chars[0].is_quoted);
+        assert!(!fields[1].chars[0].is_quoted);
+    }
+
+    #[test]
+    fn integer_sequences() {
+        assert_eq!(expand_values(literal_field("{1..3}")), ["1", "2", "3"]);
+        assert_eq!(expand_values(literal_field("{3..1}")), ["3", "2", "1"]);
+        assert_eq!(expand_values(literal_field("{-1..1}")), ["-1", "0", "1"]);
+        assert_eq!(expand_values(literal_field("{5..5}")), ["5"]);
+        let values = expand_values(literal_field("a{1..2}b"));
+        assert_eq!(values, ["a1b", "a2b"]);
+    }
+
+    #[test]
+    fn integer_sequences_with_step() {
+        let values = expand_values(literal_field("{1..10..3}"));
+        assert_eq!(values, ["1", "4", "7", "10"]);
+        let values = expand_values(literal_field("{10..1..-4}"));
+        assert_eq!(values, ["10", "6", "2"]);
+        let values = expand_values(literal_field("{1..3..0}"));
+        assert_eq!(values, ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn zero_padded_sequences() {
+        let values = expand_values(literal_field("{08..10}"));
+        assert_eq!(values, ["08", "09", "10"]);
+        let values = expand_values(literal_field("{1..003}"));
+        assert_eq!(values, ["001", "002", "003"]);
+        let values = expand_values(literal_field("{-02..1}"));
+        assert_eq!(values, ["-02", "-01", "000", "001"]);
+    }
+
+    #[test]
+    fn character_sequences() {
+        assert_eq!(expand_values(literal_field("{a..c}")), ["a", "b", "c"]);
+        assert_eq!(expand_values(literal_field("{c..a}")), ["c", "b", "a"]);
+        let values = expand_values(literal_field("{a..g..3}"));
+        assert_eq!(values, ["a", "d", "g"]);
+    }
+
+    #[test]
+    fn sequence_characters_are_hard_expansions() {
+        let fields: Vec<AttrField> = expand(literal_field("x{1..2}"));
+        assert_eq!(fields[0].chars[0].origin, Origin::Literal);
+        assert_eq!(fields[0].chars[1].origin, Origin::HardExpansion);
+    }
+
+    #[test]
+    fn sequence_in_comma_list() {
+        let values = expand_values(literal_field("{a,{1..2}}"));
+        assert_eq!(values, ["a", "1", "2"]);
+    }
+}
@@ -31,6 +31,17 @@
 //! Refer to the [`yash-fnmatch`](yash_fnmatch) crate for pattern syntax and
 //! semantics details.
 //!
+//! If the [`CaseGlob`] option is off, patterns match pathnames
+//! case-insensitively.
+//!
+//! If the [`ExtendedGlob`] option is on, the ksh-style extended patterns such
+//! as `@(foo|bar)` and `!(*.txt)` are also recognized, and a component
+//! consisting of `**` that is followed by a `/` matches any number of
+//! directory levels, including zero.
+//! Subdirectories are scanned recursively, but symbolic links to directories
+//! are not followed. A `***` component works the same way except that symbolic
+//! links are followed.
+//!
 //! # Directory scanning
 //!
 //! The expansion scans directories corresponding to components containing any
@@ -39,15 +50,36 @@
 //! performed. Search permissions for all ancestor directories are needed to
 //! check if the file exists referred to by the resulting pathname.
 //!
+//! A filename starting with a period is matched only by a pattern starting
+//! with a literal period unless the [`DotGlob`] option is on. Even if the
+//! option is on, the `.` and `..` entries are never matched by a pattern that
+//! does not start with a period, and recursive scanning never enters
+//! directories whose names start with a period.
+//!
 //! # Results
 //!
 //! Pathname expansion returns pathnames that have matched the input pattern,
 //! sorted alphabetically. Any errors are silently ignored. If directory
-//! scanning produces no pathnames, the input pattern is returned intact. (TODO:
-//! the null-glob option)
+//! scanning produces no pathnames, the input pattern is returned intact, or
+//! no field is produced if the [`NullGlob`] option is on.
+//!
+//! If the [`MarkDirs`] option is on, a slash is appended to each resulting
+//! pathname that names a directory.
 //!
 //! If the input field contains no non-literal elements subject to pattern
 //! matching at all, the result is the input intact.
+//!
+//! Directory entry names are matched as raw bytes, so names that are not valid
+//! UTF-8 (e.g., Latin-1 names) can be matched as described in
+//! [`yash_fnmatch`'s documentation](yash_fnmatch#byte-strings). However, a
+//! field value must be valid UTF-8 and cannot represent such a name without
+//! loss, so resulting pathnames that are not valid UTF-8 are skipped.
+//!
+//! [`CaseGlob`]: yash_env::option::Option::CaseGlob
+//! [`DotGlob`]: yash_env::option::Option::DotGlob
+//! [`ExtendedGlob`]: yash_env::option::Option::ExtendedGlob
+//! [`MarkDirs`]: yash_env::option::Option::MarkDirs
+//! [`NullGlob`]: yash_env::option::Option::NullGlob
 
 use super::attr::AttrChar;
 use super::attr::AttrField;
@@ -55,10 +87,17 @@ use super::attr::Origin;
 use std::ffi::CString;
 use std::iter::Once;
 use std::marker::PhantomData;
-use yash_env::option::State::Off;
+use std::os::unix::ffi::OsStrExt;
+use yash_env::option::Option::{CaseGlob, DotGlob, ExtendedGlob, Glob as GlobOption};
+use yash_env::option::Option::{MarkDirs, NullGlob};
+use yash_env::option::State::{Off, On};
 use yash_env::semantics::Field;
 use yash_env::system::AtFlags;
+use yash_env::system::SFlag;
 use yash_env::system::AT_FDCWD;
+use yash_env::variable::Value::Scalar;
+use yash_env::variable::Variable;
+use yash_env::variable::VariableSet;
 use yash_env::Env;
 use yash_env::System;
 use yash_fnmatch::Config;
@@ -68,6 +107,7 @@ use yash_syntax::source::Location;
 
 #[derive(Debug)]
 enum Inner {
+    Empty,
     One(Once<Field>),
     Many(std::vec::IntoIter<Field>),
 }
@@ -108,6 +148,7 @@ impl Iterator for Glob<'_> {
     type Item = Field;
     fn next(&mut self) -> Option<Field> {
         match &mut self.inner {
+            Inner::Empty => None,
             Inner::One(once) => once.next(),
             Inner::Many(many) => many.next(),
         }
@@ -115,7 +156,9 @@ impl Iterator for Glob<'_> {
 }
 
 /// Converts a field to a glob pattern.
-fn to_pattern(field: &[AttrChar]) -> Option<Pattern> {
+///
+/// The anchors are enabled in the given `config` before parsing.
+fn to_pattern(field: &[AttrChar], mut config: Config) -> Option<Pattern> {
     let chars = field.iter().filter_map(|c| {
         if c.is_quoting {
             None
@@ -125,13 +168,30 @@ fn to_pattern(field: &[AttrChar]) -> Option<Pattern> {
             Some(PatternChar::Normal(c.value))
         }
     });
-    let mut config = Config::default();
     config.anchor_begin = true;
     config.anchor_end = true;
-    config.literal_period = true;
     Pattern::parse_with_config(chars, config).ok()
 }
 
+/// Tests if the component is a `**` or `***` that scans directories
+/// recursively.
+///
+/// The result is `Some(follow_symlinks)` if the component is `**` (`false`) or
+/// `***` (`true`) consisting only of unquoted asterisks.
+fn recursion_kind(component: &[AttrChar]) -> Option<bool> {
+    let is_asterisk = |c: &AttrChar| {
+        c.value == '*' && !c.is_quoted && !c.is_quoting && c.origin != Origin::HardExpansion
+    };
+    if !component.iter().all(is_asterisk) {
+        return None;
+    }
+    match component.len() {
+        2 => Some(false),
+        3 => Some(true),
+        _ => None,
+    }
+}
+
 fn remove_quotes_and_strip(chars: &[AttrChar]) -> impl Iterator<Item = char> + '_ {
     use super::attr_strip::Strip;
     use super::quote_removal::skip_quotes;
@@ -141,12 +201,34 @@ fn remove_quotes_and_strip(chars: &[AttrChar]) -> impl Iterator<Item = char> + '
 #[derive(Debug)]
 struct SearchEnv<'e> {
     env: &'e mut Env,
-    prefix: String,
+    /// Pathname being built, which may not be valid UTF-8
+    prefix: Vec<u8>,
     origin: Location,
-    results: Vec<Field>,
+    /// Pathnames that have matched the pattern
+    results: Vec<Vec<u8>>,
+    /// Base configuration for parsing patterns
+    config: Config,
+    /// Whether `**` and `***` scan directories recursively
+    recursive: bool,
+    /// Whether to append a slash to directory names in the results
+    mark_dirs: bool,
+    /// Whether any component has been matched as a pattern
+    has_pattern: bool,
+    /// Directories being scanned recursively, identified by device and inode
+    /// numbers, to avoid infinitely following symbolic links
+    ancestors: Vec<(u64, u64)>,
 }
 
 impl SearchEnv<'_> {
+    /// Returns the directory path to scan, which is the current prefix.
+    fn dir_path(&self) -> Option<CString> {
+        if self.prefix.is_empty() {
+            Some(CString::new(".").unwrap())
+        } else {
+            CString::new(self.prefix.as_slice()).ok()
+        }
+    }
+
     /// Recursively searches directories for matching pathnames.
     fn search_dir(&mut self, suffix: &[AttrChar]) {
         let (this, new_suffix) = match suffix.iter().position(|c| c.value == '/') {
@@ -154,30 +236,45 @@ impl SearchEnv<'_> {
             Some(index) => (&suffix[..index], Some(&suffix[index + 1..])),
         };
 
-        match to_pattern(this).map(Pattern::into_literal) {
+        if self.recursive {
+            if let (Some(follow_symlinks), Some(new_suffix)) = (recursion_kind(this), new_suffix) {
+                self.has_pattern = true;
+                self.search_recursive(new_suffix, follow_symlinks);
+                return;
+            }
+        }
+
+        match to_pattern(this, self.config).map(Pattern::into_literal) {
             None => {
                 self.push_component(new_suffix, |prefix| {
-                    prefix.extend(remove_quotes_and_strip(this))
+                    let component = remove_quotes_and_strip(this).collect::<String>();
+                    prefix.extend_from_slice(component.as_bytes())
                 });
             }
             Some(Ok(literal)) => {
-                self.push_component(new_suffix, |prefix| prefix.push_str(&literal));
+                self.push_component(new_suffix, |prefix| {
+                    prefix.extend_from_slice(literal.as_bytes())
+                });
             }
             Some(Err(pattern)) => {
-                let dir_path = if self.prefix.is_empty() {
-                    CString::new(".").unwrap()
-                } else if let Ok(dir_path) = CString::new(self.prefix.as_str()) {
-                    dir_path
-                } else {
+                self.has_pattern = true;
+                let Some(dir_path) = self.dir_path() else {
                     return;
                 };
+                let starts_with_period =
+                    this.iter().find(|c| !c.is_quoting).map(|c| c.value) == Some('.');
+                let skip_dots = !self.config.literal_period && !starts_with_period;
 
                 if let Ok(mut dir) = self.env.system.opendir(&dir_path) {
                     while let Ok(Some(entry)) = dir.next() {
-                        if let Some(name) = entry.name.to_str() {
-                            if pattern.is_match(name) {
-                                self.push_component(new_suffix, |prefix| prefix.push_str(name));
-                            }
+                        let name = entry.name.as_bytes();
+                        if skip_dots && (name == b"." || name == b"..") {
+                            continue;
+                        }
+                        if pattern.is_match_bytes(name) {
+                            self.push_component(new_suffix, |prefix| {
+                                prefix.extend_from_slice(name)
+                            });
                         }
                     }
                 }
@@ -185,8 +282,62 @@ impl SearchEnv<'_> {
         }
     }
 
+    /// Searches the current directory and its subdirectories for matching
+    /// pathnames.
+    ///
+    /// The `suffix` is matched in the current directory and in every
+    /// subdirectory found by scanning directories recursively. Symbolic links to
+    /// directories are followed only if `follow_symlinks` is true.
+    fn search_recursive(&mut self, suffix: &[AttrChar], follow_symlinks: bool) {
+        self.search_dir(suffix);
+
+        let Some(dir_path) = self.dir_path() else {
+            return;
+        };
+        let Ok(mut dir) = self.env.system.opendir(&dir_path) else {
+            return;
+        };
+        let mut names = Vec::new();
+        while let Ok(Some(entry)) = dir.next() {
+            let name = entry.name.as_bytes();
+            if !name.starts_with(b".") {
+                names.push(name.to_owned());
+            }
+        }
+        drop(dir);
+
+        for name in names {
+            let old_prefix_len = self.prefix.len();
+            self.prefix.extend_from_slice(&name);
+            if let Some(id) = self.directory_id(follow_symlinks) {
+                if !self.ancestors.contains(&id) {
+                    self.ancestors.push(id);
+                    self.prefix.push(b'/');
+                    self.search_recursive(suffix, follow_symlinks);
+                    self.ancestors.pop();
+                }
+            }
+            self.prefix.truncate(old_prefix_len);
+        }
+    }
+
+    /// Returns the device and inode numbers of the file named by the current
+    /// prefix if it is a directory.
+    fn directory_id(&mut self, follow_symlinks: bool) -> Option<(u64, u64)> {
+        let path = CString::new(self.prefix.as_slice()).ok()?;
+        let flags = if follow_symlinks {
+            AtFlags::empty()
+        } else {
+            AtFlags::AT_SYMLINK_NOFOLLOW
+        };
+        let stat = self.env.system.fstatat(AT_FDCWD, &path, flags).ok()?;
+        let file_type = SFlag::from_bits_truncate(stat.st_mode & SFlag::S_IFMT.bits());
+        #[allow(clippy::unnecessary_cast)]
+        (file_type == SFlag::S_IFDIR).then_some((stat.st_dev as u64, stat.st_ino as u64))
+    }
+
     fn file_exists(&mut self) -> bool {
-        let path = match CString::new(self.prefix.as_str()) {
+        let path = match CString::new(self.prefix.as_slice()) {
             Ok(path) => path,
             Err(_) => return false,
         };
@@ -200,7 +351,7 @@ impl SearchEnv<'_> {
     /// component.
     fn push_component<F>(&mut self, suffix: Option<&[AttrChar]>, push: F)
     where
-        F: FnOnce(&mut String),
+        F: FnOnce(&mut Vec<u8>),
     {
         let old_prefix_len = self.prefix.len();
         push(&mut self.prefix);
@@ -209,15 +360,20 @@ impl SearchEnv<'_> {
             None => {
                 // TODO Don't need to check the file existence if the last path
                 // component comes from a directory entry
-                if self.file_exists() {
-                    self.results.push(Field {
-                        value: self.prefix.clone(),
-                        origin: self.origin.clone(),
-                    });
+                if !self.prefix.is_empty() && self.file_exists() {
+                    let mut value = self.prefix.clone();
+                    if self.mark_dirs && self.has_pattern && !value.ends_with(b"/") {
+                        if let Ok(path) = CString::new(value.as_slice()) {
+                            if self.env.system.is_directory(&path) {
+                                value.push(b'/');
+                            }
+                        }
+                    }
+                    self.results.push(value);
                 }
             }
             Some(suffix) => {
-                self.prefix.push('/');
+                self.prefix.push(b'/');
                 self.search_dir(suffix);
             }
         }
@@ -226,38 +382,88 @@ impl SearchEnv<'_> {
     }
 }
 
+/// Tests whether patterns should be matched with Unicode-aware character
+/// classes and equivalence classes.
+///
+/// The result is determined by the first non-empty scalar value of the
+/// `LC_ALL`, `LC_CTYPE`, and `LANG` variables. It is false if the value is `C`
+/// or `POSIX` or if none of the variables is set, and true otherwise.
+#[must_use]
+pub(crate) fn is_unicode_locale(variables: &VariableSet) -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| match variables.get(name) {
+            Some(Variable {
+                value: Some(Scalar(value)),
+                ..
+            }) if !value.is_empty() => Some(value.as_str()),
+            _ => None,
+        })
+        .is_some_and(|locale| locale != "C" && locale != "POSIX")
+}
+
 /// Performs parameter expansion.
 ///
 /// This function returns an iterator that yields fields resulting from the
 /// expansion.
 ///
 /// If the `Glob` option is `Off` in `env.options`, the expansion is skipped.
+/// The `CaseGlob`, `DotGlob`, `ExtendedGlob`, `MarkDirs`, and `NullGlob`
+/// options affect the expansion as described in the [module
+/// documentation](self).
 pub fn glob(env: &mut Env, field: AttrField) -> Glob {
-    if env.options.get(yash_env::option::Option::Glob) == Off {
+    if env.options.get(GlobOption) == Off {
         return Glob::from(Inner::from(field.remove_quotes_and_strip()));
     }
 
     // TODO Quick check for *, ?, [ containment
 
+    let mut config = Config::default();
+    config.case_insensitive = env.options.get(CaseGlob) == Off;
+    config.literal_period = env.options.get(DotGlob) == Off;
+    config.extended = env.options.get(ExtendedGlob) == On;
+    config.unicode = is_unicode_locale(&env.variables);
+    let recursive = config.extended;
+    let mark_dirs = env.options.get(MarkDirs) == On;
+    let null_glob = env.options.get(NullGlob) == On;
+
     let mut search_env = SearchEnv {
         env,
-        prefix: String::with_capacity(1024 /*nix::libc::PATH_MAX*/),
+        prefix: Vec::with_capacity(1024 /*nix::libc::PATH_MAX*/),
         origin: field.origin,
         results: Vec::new(),
+        config,
+        recursive,
+        mark_dirs,
+        has_pattern: false,
+        ancestors: Vec::new(),
     };
     search_env.search_dir(&field.chars);
 
-    let mut results = search_env.results;
-    Glob::from(if results.is_empty() {
-        let field = AttrField {
-            chars: field.chars,
-            origin: search_env.origin,
-        };
-        Inner::from(field.remove_quotes_and_strip())
-    } else {
-        results.sort_unstable_by(|a, b| a.value.cmp(&b.value));
-        Inner::Many(results.into_iter())
-    })
+    let mut results = search_env
+        .results
+        .into_iter()
+        .filter_map(|value| String::from_utf8(value).ok())
+        .collect::<Vec<_>>();
+    Glob::from(
+        if results.is_empty() && null_glob && search_env.has_pattern {
+            Inner::Empty
+        } else if results.is_empty() {
+            let field = AttrField {
+                chars: field.chars,
+                origin: search_env.origin,
+            };
+            Inner::from(field.remove_quotes_and_strip())
+        } else {
+            results.sort_unstable();
+            let origin = search_env.origin;
+            let fields = results.into_iter().map(|value| {
+                let origin = origin.clone();
+                Field { value, origin }
+            });
+            Inner::Many(fields.collect::<Vec<_>>().into_iter())
+        },
+    )
 }
 
 #[cfg(test)]
@@ -265,10 +471,16 @@ mod tests {
     use super::*;
     use crate::expansion::AttrChar;
     use crate::expansion::Origin;
+    use std::cell::RefCell;
     use std::ffi::OsStr;
     use std::os::unix::ffi::OsStrExt;
     use std::path::Path;
     use std::rc::Rc;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::INode;
+    use yash_env::system::Mode;
+    use yash_env::system::OFlag;
+    use yash_env::variable::Scope;
     use yash_env::VirtualSystem;
     use yash_syntax::source::Location;
 
@@ -472,6 +684,48 @@ mod tests {
         assert_eq!(i.next(), None);
     }
 
+    #[test]
+    fn latin1_directory_entry_names() {
+        let mut env = env_with_dummy_files([
+            OsStr::from_bytes(b"caf\xE9"),
+            OsStr::from_bytes(b"caf\xE9s"),
+            OsStr::from_bytes(b"d\xE9j\xE0/vu"),
+        ]);
+        // The names match, but they cannot be represented in the fields.
+        let f = dummy_attr_field("caf?");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "caf?");
+        assert_eq!(i.next(), None);
+
+        let f = dummy_attr_field("d*/vu");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "d*/vu");
+        assert_eq!(i.next(), None);
+
+        env.options.set(NullGlob, On);
+        let f = dummy_attr_field("caf[!e]s");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn expanded_pathnames_can_be_opened() {
+        let mut env = env_with_dummy_files([
+            OsStr::from_bytes(b"caf\xE9.txt"),
+            OsStr::from_bytes(b"cafe.txt"),
+            OsStr::from_bytes("café.txt".as_bytes()),
+        ]);
+        let f = dummy_attr_field("caf*.txt");
+        let values = glob(&mut env, f).map(|f| f.value).collect::<Vec<_>>();
+        assert_eq!(values, ["cafe.txt", "café.txt"]);
+
+        for value in values {
+            let path = CString::new(value).unwrap();
+            let fd = env.system.open(&path, OFlag::O_RDONLY, Mode::empty());
+            assert!(fd.is_ok(), "{path:?}: {fd:?}");
+        }
+    }
+
     #[test]
     fn noglob_option() {
         let mut env = env_with_dummy_files(["foo.exe"]);
@@ -481,4 +735,205 @@ mod tests {
         assert_eq!(i.next().unwrap().value, "foo.*");
         assert_eq!(i.next(), None);
     }
+
+    #[test]
+    fn nocaseglob_option() {
+        let mut env = env_with_dummy_files(["Foo.txt", "foo.TXT", "bar.txt"]);
+        env.options.set(CaseGlob, Off);
+        let f = dummy_attr_field("f*.txt");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "Foo.txt");
+        assert_eq!(i.next().unwrap().value, "foo.TXT");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn dotglob_option() {
+        let mut env = env_with_dummy_files(["dir/.foo", "dir/bar"]);
+        env.options.set(DotGlob, On);
+        let f = dummy_attr_field("dir/*");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "dir/.foo");
+        assert_eq!(i.next().unwrap().value, "dir/bar");
+        assert_eq!(i.next(), None);
+
+        let f = dummy_attr_field("dir/.*");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "dir/.");
+        assert_eq!(i.next().unwrap().value, "dir/..");
+        assert_eq!(i.next().unwrap().value, "dir/.foo");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn markdirs_option() {
+        let mut env = env_with_dummy_files(["a/foo", "b"]);
+        env.options.set(MarkDirs, On);
+        let f = dummy_attr_field("?");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "a/");
+        assert_eq!(i.next().unwrap().value, "b");
+        assert_eq!(i.next(), None);
+
+        // Literal fields are not subject to pathname expansion.
+        let f = dummy_attr_field("a");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "a");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn nullglob_option() {
+        let mut env = env_with_dummy_files(["foo.exe"]);
+        env.options.set(NullGlob, On);
+        let f = dummy_attr_field("*.txt");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next(), None);
+
+        let f = dummy_attr_field("no/*");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next(), None);
+
+        // Literal fields are not subject to pathname expansion.
+        let f = dummy_attr_field("foo.txt");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "foo.txt");
+        assert_eq!(i.next(), None);
+    }
+
+    fn set_variable(env: &mut Env, name: &str, value: &str) {
+        env.variables
+            .get_or_new(name, Scope::Global)
+            .assign(value, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn unicode_locale_by_variables() {
+        let mut env = Env::new_virtual();
+        assert!(!is_unicode_locale(&env.variables));
+
+        set_variable(&mut env, "LANG", "ja_JP.UTF-8");
+        assert!(is_unicode_locale(&env.variables));
+
+        set_variable(&mut env, "LC_CTYPE", "C");
+        assert!(!is_unicode_locale(&env.variables));
+
+        set_variable(&mut env, "LC_ALL", "");
+        assert!(!is_unicode_locale(&env.variables));
+
+        set_variable(&mut env, "LC_ALL", "en_US.UTF-8");
+        assert!(is_unicode_locale(&env.variables));
+
+        set_variable(&mut env, "LC_ALL", "POSIX");
+        assert!(!is_unicode_locale(&env.variables));
+    }
+
+    #[test]
+    fn character_class_in_unicode_locale() {
+        let mut env = env_with_dummy_files(["été", "Été", "eta"]);
+        let f = dummy_attr_field("[[:upper:]]*");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "[[:upper:]]*");
+        assert_eq!(i.next(), None);
+
+        set_variable(&mut env, "LANG", "fr_FR.UTF-8");
+        let f = dummy_attr_field("[[:upper:]]*");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "Été");
+        assert_eq!(i.next(), None);
+
+        let f = dummy_attr_field("[[=e=]]t?");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "eta");
+        assert_eq!(i.next().unwrap().value, "été");
+        assert_eq!(i.next(), None);
+    }
+
+    fn env_with_dummy_files_and_symlinks<'a, I>(paths: I, links: &[(&str, &str)]) -> Env
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let system = VirtualSystem::new();
+        let mut state = system.state.borrow_mut();
+        for path in paths {
+            state.file_system.save(path, Rc::default()).unwrap();
+        }
+        for &(path, target) in links {
+            let inode = INode {
+                body: FileBody::Symlink {
+                    target: target.into(),
+                },
+                permissions: Default::default(),
+            };
+            let inode = Rc::new(RefCell::new(inode));
+            state.file_system.save(path, inode).unwrap();
+        }
+        drop(state);
+        Env::with_system(Box::new(system))
+    }
+
+    #[test]
+    fn double_asterisk_without_extendedglob() {
+        let mut env = env_with_dummy_files(["a/x", "a/b/x", "x"]);
+        let f = dummy_attr_field("**/x");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "a/x");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn double_asterisk_recursion() {
+        let mut env = env_with_dummy_files_and_symlinks(
+            ["/x", "/a/x", "/a/b/x", "/a/b/c/y", "/a/.d/x", "/e/x"],
+            &[("/a/link", "/e")],
+        );
+        env.options.set(ExtendedGlob, On);
+        let f = dummy_attr_field("/**/x");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "/a/b/x");
+        assert_eq!(i.next().unwrap().value, "/a/x");
+        assert_eq!(i.next().unwrap().value, "/e/x");
+        assert_eq!(i.next().unwrap().value, "/x");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn double_asterisk_recursion_ending_with_slash() {
+        let mut env = env_with_dummy_files(["x/a/b/c", "x/d"]);
+        env.options.set(ExtendedGlob, On);
+        let f = dummy_attr_field("x/**/");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "x/");
+        assert_eq!(i.next().unwrap().value, "x/a/");
+        assert_eq!(i.next().unwrap().value, "x/a/b/");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn quoted_double_asterisk_does_not_recurse() {
+        let mut env = env_with_dummy_files(["a/x", "b*/x", "b*/c/x"]);
+        env.options.set(ExtendedGlob, On);
+        let mut f = dummy_attr_field("**/x");
+        f.chars[1].is_quoted = true;
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "b*/x");
+        assert_eq!(i.next(), None);
+    }
+
+    #[test]
+    fn triple_asterisk_recursion_follows_symlinks() {
+        let mut env = env_with_dummy_files_and_symlinks(
+            ["/a/x", "/e/x", "/e/f/x"],
+            &[("/a/link", "/e"), ("/e/loop", "/a")],
+        );
+        env.options.set(ExtendedGlob, On);
+        let f = dummy_attr_field("/a/***/x");
+        let mut i = glob(&mut env, f);
+        assert_eq!(i.next().unwrap().value, "/a/link/f/x");
+        assert_eq!(i.next().unwrap().value, "/a/link/loop/x");
+        assert_eq!(i.next().unwrap().value, "/a/link/x");
+        assert_eq!(i.next().unwrap().value, "/a/x");
+        assert_eq!(i.next(), None);
+    }
 }
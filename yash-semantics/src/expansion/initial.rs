@@ -73,11 +73,13 @@ pub trait Expand {
 mod arith;
 mod command_subst;
 mod param;
+mod process_subst;
 mod slice;
 mod text;
 mod tilde;
 mod word;
 
+pub use arith::evaluate as evaluate_arith;
 pub use arith::ArithError;
 pub use param::EmptyError;
 pub use param::NonassignableError;
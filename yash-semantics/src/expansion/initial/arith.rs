@@ -247,53 +247,72 @@ impl<'a> yash_arith::Env for VarEnv<'a> {
     }
 }
 
-pub async fn expand(text: &Text, location: &Location, env: &mut Env<'_>) -> Result<Phrase, Error> {
+/// Expands and evaluates an arithmetic expression.
+///
+/// The `location` should be the location of the expansion that contains the
+/// expression. It is used in the source of variables assigned in the
+/// expression and errors.
+pub async fn evaluate(
+    text: &Text,
+    location: &Location,
+    env: &mut Env<'_>,
+) -> Result<yash_arith::Value, Error> {
     let (expression, exit_status) = expand_text(env.inner, text).await?;
     if exit_status.is_some() {
         env.last_command_subst_exit_status = exit_status;
     }
+    evaluate_expanded(expression, location, env.inner)
+}
 
+/// Evaluates an arithmetic expression that has already been expanded.
+///
+/// This function is the same as [`evaluate`] except that it takes the result
+/// of expanding the expression instead of the text to be expanded.
+pub fn evaluate_expanded(
+    expression: String,
+    location: &Location,
+    env: &mut yash_env::Env,
+) -> Result<yash_arith::Value, Error> {
     let result = eval(
         &expression,
         &mut VarEnv {
-            env: env.inner,
+            env,
             expression: &expression,
             expansion_location: location,
         },
     );
 
-    match result {
-        Ok(value) => {
-            let value = value.to_string();
-            let chars = value
-                .chars()
-                .map(|c| AttrChar {
-                    value: c,
-                    origin: Origin::SoftExpansion,
-                    is_quoted: false,
-                    is_quoting: false,
-                })
-                .collect();
-            Ok(Phrase::Field(chars))
-        }
-        Err(error) => {
-            let code = Rc::new(Code {
-                value: expression.into(),
-                start_line_number: 1.try_into().unwrap(),
-                source: Source::Arith {
-                    original: location.clone(),
-                },
-            });
-            let cause = convert_error_cause(error.cause, &code);
-            Err(Error {
-                cause,
-                location: Location {
-                    code,
-                    range: error.location,
-                },
-            })
+    result.map_err(|error| {
+        let code = Rc::new(Code {
+            value: expression.into(),
+            start_line_number: 1.try_into().unwrap(),
+            source: Source::Arith {
+                original: location.clone(),
+            },
+        });
+        let cause = convert_error_cause(error.cause, &code);
+        Error {
+            cause,
+            location: Location {
+                code,
+                range: error.location,
+            },
         }
-    }
+    })
+}
+
+pub async fn expand(text: &Text, location: &Location, env: &mut Env<'_>) -> Result<Phrase, Error> {
+    let value = evaluate(text, location, env).await?.to_string();
+    let chars = value
+        .chars()
+        .map(|c| AttrChar {
+            value: c,
+            origin: Origin::SoftExpansion,
+            is_quoted: false,
+            is_quoting: false,
+        })
+        .collect();
+    Ok(Phrase::Field(chars))
 }
 
 #[cfg(test)]
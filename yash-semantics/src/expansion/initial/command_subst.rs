@@ -24,6 +24,7 @@ use super::Error;
 use crate::expansion::ErrorCause;
 use crate::Handle;
 use crate::ReadEvalLoop;
+use std::cell::RefCell;
 use yash_env::io::Fd;
 use yash_env::job::Pid;
 use yash_env::subshell::JobControl;
@@ -68,7 +69,9 @@ where
             }
 
             let mut lexer = Lexer::from_memory(command.as_ref(), Source::CommandSubst { original });
-            ReadEvalLoop::new(env, &mut lexer).run().await
+            ReadEvalLoop::new(&RefCell::new(env), &mut lexer)
+                .run()
+                .await
         })
     });
     let subshell_result = subshell.start(env.inner).await;
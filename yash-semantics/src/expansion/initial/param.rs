@@ -23,17 +23,40 @@ use super::super::ErrorCause;
 use super::super::Origin;
 use super::Env;
 use super::Expand;
+use crate::expansion::quote_removal::skip_quotes;
 use yash_env::option::Option::Unset;
 use yash_env::option::State::Off;
 use yash_env::variable::Expansion;
 use yash_env::variable::Value;
 use yash_syntax::source::Location;
+use yash_syntax::syntax::Index;
 use yash_syntax::syntax::Modifier;
 use yash_syntax::syntax::Param;
+use yash_syntax::syntax::ParamSubject;
+use yash_syntax::syntax::TextUnit;
+
+/// Subject of a [parameter expansion](ParamRef)
+#[derive(Clone, Copy, Debug)]
+pub enum Subject<'a> {
+    /// Parameter name
+    Name(&'a str),
+    /// Nested expansion whose result is the value to be modified
+    Nested(&'a TextUnit),
+}
+
+impl<'a> From<&'a ParamSubject> for Subject<'a> {
+    fn from(subject: &'a ParamSubject) -> Self {
+        match subject {
+            ParamSubject::Name(name) => Subject::Name(name),
+            ParamSubject::Nested(unit) => Subject::Nested(unit),
+        }
+    }
+}
 
 /// Reference to a parameter expansion
 pub struct ParamRef<'a> {
-    pub name: &'a str,
+    pub subject: Subject<'a>,
+    pub index: Option<&'a Index>,
     pub modifier: &'a Modifier,
     pub location: &'a Location,
 }
@@ -41,7 +64,8 @@ pub struct ParamRef<'a> {
 impl<'a> From<&'a Param> for ParamRef<'a> {
     fn from(param: &'a Param) -> Self {
         ParamRef {
-            name: &param.name,
+            subject: (&param.subject).into(),
+            index: param.index.as_ref(),
             modifier: &param.modifier,
             location: &param.location,
         }
@@ -49,11 +73,15 @@ impl<'a> From<&'a Param> for ParamRef<'a> {
 }
 
 // TODO Consider exporting these modules
+mod index;
 mod name;
 mod resolve;
+mod subst;
+mod substring;
 mod switch;
 mod trim;
 
+use index::Subscript;
 pub use switch::EmptyError;
 pub use switch::NonassignableError;
 pub use switch::ValueState;
@@ -61,23 +89,33 @@ pub use switch::ValueState;
 impl Expand for ParamRef<'_> {
     /// Performs parameter expansion.
     async fn expand(&self, env: &mut Env<'_>) -> Result<Phrase, Error> {
-        // TODO Expand and parse Index
-
         // Lookup //
-        let name = self.name.try_into().ok();
-        let resolve = match name {
-            Some(name) => resolve::resolve(name, env.inner, self.location),
-            None => Expansion::Unset,
+        let (name, mut value) = match self.subject {
+            Subject::Name(name) => {
+                let name = name.try_into().ok();
+                let resolve = match name {
+                    Some(name) => resolve::resolve(name, env.inner, self.location),
+                    None => Expansion::Unset,
+                };
+                (name, resolve.into_owned())
+            }
+            Subject::Nested(unit) => (None, Some(into_value(unit.expand(env).await?))),
         };
 
-        // TODO Apply Index
-
-        let mut value = resolve.into_owned();
+        // Index //
+        let subscript = match self.index {
+            None => None,
+            Some(index) => Some(index::evaluate_index(env, index, self.location).await?),
+        };
+        if let Some(subscript) = subscript {
+            value = resolve::subscript(value, subscript);
+        }
 
         // Switch //
         if let Modifier::Switch(switch) = self.modifier {
-            if let Some(result) = switch::apply(env, switch, name, &mut value, self.location).await
-            {
+            let indexed = subscript.is_some();
+            let result = switch::apply(env, switch, name, indexed, &mut value, self.location);
+            if let Some(result) = result.await {
                 return result;
             }
         }
@@ -98,10 +136,11 @@ impl Expand for ParamRef<'_> {
 
             Modifier::Length => {
                 // TODO Reject ${#*} and ${#@} in POSIX mode
-                match &mut value {
-                    None => (),
-                    Some(Value::Scalar(v)) => to_length(v),
-                    Some(Value::Array(vs)) => vs.iter_mut().for_each(to_length),
+                match (&mut value, subscript) {
+                    (None, _) => (),
+                    (Some(v), Some(Subscript::All | Subscript::Join)) => to_count(v),
+                    (Some(Value::Scalar(v)), _) => to_length(v),
+                    (Some(Value::Array(vs)), _) => vs.iter_mut().for_each(to_length),
                 }
             }
 
@@ -110,10 +149,24 @@ impl Expand for ParamRef<'_> {
                     trim::apply(env, trim, value).await?
                 }
             }
+
+            Modifier::Subst(subst) => {
+                if let Some(value) = &mut value {
+                    subst::apply(env, subst, value).await?
+                }
+            }
+
+            Modifier::Substring(substring) => {
+                if let Some(value) = &mut value {
+                    substring::apply(env, substring, value, self.location).await?
+                }
+            }
         }
 
         let mut phrase = into_phrase(value);
-        if !env.will_split && self.name == "*" {
+        let join = matches!(self.subject, Subject::Name("*"))
+            || matches!(subscript, Some(Subscript::Join));
+        if !env.will_split && join {
             phrase = Phrase::Field(phrase.ifs_join(&env.inner.variables));
         }
         Ok(phrase)
@@ -125,6 +178,32 @@ fn to_length(v: &mut String) {
     *v = v.chars().count().to_string()
 }
 
+/// Modifies a value to the number of its elements.
+///
+/// A scalar is regarded as having one element.
+fn to_count(v: &mut Value) {
+    let count = match v {
+        Value::Scalar(_) => 1,
+        Value::Array(vs) => vs.len(),
+    };
+    *v = Value::Scalar(count.to_string())
+}
+
+/// Converts the result of a nested expansion into a value.
+///
+/// Quoting characters are removed. A phrase with one field is converted into
+/// a scalar, and others into an array.
+fn into_value(phrase: Phrase) -> Value {
+    fn to_string(chars: Vec<AttrChar>) -> String {
+        skip_quotes(chars).map(|c| c.value).collect()
+    }
+    match phrase {
+        Phrase::Char(c) => Value::Scalar(to_string(vec![c])),
+        Phrase::Field(chars) => Value::Scalar(to_string(chars)),
+        Phrase::Full(fields) => Value::Array(fields.into_iter().map(to_string).collect()),
+    }
+}
+
 /// Converts a value into a phrase.
 fn into_phrase(value: Option<Value>) -> Phrase {
     match value {
@@ -152,6 +231,8 @@ fn to_field(value: &str) -> Vec<AttrChar> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::tests::echo_builtin;
+    use crate::tests::in_virtual_system;
     use futures_util::FutureExt;
     use yash_env::variable::Scope;
 
@@ -167,7 +248,8 @@ pub mod tests {
 
     pub fn param<N: ToString>(name: N) -> Param {
         Param {
-            name: name.to_string(),
+            subject: ParamSubject::Name(name.to_string()),
+            index: None,
             modifier: Modifier::None,
             location: Location::dummy(""),
         }
@@ -228,6 +310,88 @@ pub mod tests {
         );
     }
 
+    fn env_with_array() -> yash_env::Env {
+        let mut env = env_with_positional_params_and_ifs();
+        env.variables
+            .get_or_new("a", Scope::Global)
+            .assign(Value::array(["x", "y", "z"]), None)
+            .unwrap();
+        env
+    }
+
+    #[test]
+    fn array_element() {
+        let mut env = env_with_array();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${a[-2]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("y")));
+    }
+
+    #[test]
+    fn array_element_out_of_range_is_unset() {
+        let mut env = env_with_array();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${a[4]-unset}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("unset")));
+    }
+
+    #[test]
+    fn array_range() {
+        let mut env = env_with_array();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${a[2,3]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Full(vec![to_field("y"), to_field("z")]));
+    }
+
+    #[test]
+    fn array_all_elements_in_non_splitting_context() {
+        let mut env = env_with_array();
+        let mut env = Env::new(&mut env);
+        env.will_split = false;
+
+        let param: Param = "${a[@]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        let fields = vec![to_field("x"), to_field("y"), to_field("z")];
+        assert_eq!(phrase, Phrase::Full(fields));
+
+        let param: Param = "${a[*]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("x&y&z")));
+    }
+
+    #[test]
+    fn number_of_array_elements() {
+        let mut env = env_with_array();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${#a[*]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("3")));
+    }
+
+    #[test]
+    fn positional_params_range() {
+        let mut env = env_with_positional_params_and_ifs();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${@[-2,-1]}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Full(vec![to_field("a"), to_field("c")]));
+    }
+
     #[test]
     fn alter_empty() {
         use yash_syntax::syntax::{Switch, SwitchCondition, SwitchType};
@@ -290,6 +454,108 @@ pub mod tests {
         assert_eq!(phrase, Phrase::one_empty_field());
     }
 
+    #[test]
+    fn subst_positional_params() {
+        use yash_syntax::syntax::{Subst, SubstMode};
+
+        let mut env = env_with_positional_params_and_ifs();
+        let mut env = Env::new(&mut env);
+        let mut param = param("@");
+        param.modifier = Modifier::Subst(Subst {
+            mode: SubstMode::First,
+            pattern: "[ab]".parse().unwrap(),
+            replacement: "x".parse().unwrap(),
+        });
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Full(vec![to_field("x"), to_field("c")]));
+    }
+
+    #[test]
+    fn substring_of_positional_params() {
+        use yash_syntax::syntax::Substring;
+
+        let mut env = env_with_positional_params_and_ifs();
+        let mut env = Env::new(&mut env);
+        let mut param = param("@");
+        param.modifier = Modifier::Substring(Substring {
+            offset: "2".parse().unwrap(),
+            length: None,
+        });
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Full(vec![to_field("c")]));
+    }
+
+    #[test]
+    fn substring_of_unset_value() {
+        use yash_syntax::syntax::Substring;
+
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let mut param = param("foo");
+        param.modifier = Modifier::Substring(Substring {
+            offset: "$(echo)".parse().unwrap(),
+            length: None,
+        });
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::one_empty_field());
+    }
+
+    #[test]
+    fn nested_param() {
+        let mut env = yash_env::Env::new_virtual();
+        env.variables
+            .get_or_new("foo", Scope::Global)
+            .assign("abcabc", None)
+            .unwrap();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${${foo#a}%c}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("bcab")));
+    }
+
+    #[test]
+    fn nested_positional_params() {
+        let mut env = env_with_positional_params_and_ifs();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${${@}#?}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Full(vec![to_field(""), to_field("")]));
+    }
+
+    #[test]
+    fn nested_arith_with_length() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let param: Param = "${#$((100+23))}".parse().unwrap();
+        let param = ParamRef::from(&param);
+
+        let phrase = param.expand(&mut env).now_or_never().unwrap().unwrap();
+        assert_eq!(phrase, Phrase::Field(to_field("3")));
+    }
+
+    #[test]
+    fn nested_command_substitution() {
+        in_virtual_system(|mut env, _state| async move {
+            env.builtins.insert("echo", echo_builtin());
+            let mut env = Env::new(&mut env);
+            let param: Param = "${$(echo file.txt)%.*}".parse().unwrap();
+            let param = ParamRef::from(&param);
+
+            let phrase = param.expand(&mut env).await.unwrap();
+            assert_eq!(phrase, Phrase::Field(to_field("file")));
+        })
+    }
+
     #[test]
     fn unset_option() {
         let mut env = yash_env::Env::new_virtual();
@@ -0,0 +1,147 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Evaluating the index of a parameter expansion
+//!
+//! The start of an [`Index`] is first expanded. If there is no end and the
+//! result is `@` or `*`, the index selects all elements. Otherwise, the start
+//! and end are evaluated as arithmetic expressions. The evaluated index is
+//! applied to the value of the parameter by [`subscript`](super::resolve::subscript).
+
+use super::super::arith::evaluate;
+use super::super::arith::evaluate_expanded;
+use super::Env;
+use super::Error;
+use crate::expansion::expand_text;
+use yash_syntax::source::Location;
+use yash_syntax::syntax::Index;
+
+/// Result of evaluating an [`Index`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Subscript {
+    /// `[@]`: All elements
+    All,
+    /// `[*]`: All elements, which are joined in a context where field
+    /// splitting is not performed
+    Join,
+    /// Single element at the one-based index
+    Element(i64),
+    /// Elements in the one-based inclusive range
+    Range(i64, i64),
+}
+
+/// Evaluates an index.
+///
+/// The `location` should be that of the parameter expansion.
+pub async fn evaluate_index(
+    env: &mut Env<'_>,
+    index: &Index,
+    location: &Location,
+) -> Result<Subscript, Error> {
+    let (start, exit_status) = expand_text(env.inner, &index.start).await?;
+    if exit_status.is_some() {
+        env.last_command_subst_exit_status = exit_status;
+    }
+
+    let end = match &index.end {
+        None => match start.as_str() {
+            "@" => return Ok(Subscript::All),
+            "*" => return Ok(Subscript::Join),
+            _ => None,
+        },
+        Some(end) => Some(end),
+    };
+
+    let yash_arith::Value::Integer(start) = evaluate_expanded(start, location, env.inner)?;
+    match end {
+        None => Ok(Subscript::Element(start)),
+        Some(end) => {
+            let yash_arith::Value::Integer(end) = evaluate(end, location, env).await?;
+            Ok(Subscript::Range(start, end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion::ErrorCause;
+    use assert_matches::assert_matches;
+    use futures_util::FutureExt;
+    use yash_env::variable::Scope;
+
+    fn index(start: &str, end: Option<&str>) -> Index {
+        Index {
+            start: start.parse().unwrap(),
+            end: end.map(|end| end.parse().unwrap()),
+        }
+    }
+
+    fn evaluate_now(env: &mut yash_env::Env, index: &Index) -> Result<Subscript, Error> {
+        let mut env = Env::new(env);
+        let location = Location::dummy("");
+        evaluate_index(&mut env, index, &location)
+            .now_or_never()
+            .unwrap()
+    }
+
+    #[test]
+    fn all_elements() {
+        let mut env = yash_env::Env::new_virtual();
+        let result = evaluate_now(&mut env, &index("@", None));
+        assert_eq!(result, Ok(Subscript::All));
+        let result = evaluate_now(&mut env, &index("*", None));
+        assert_eq!(result, Ok(Subscript::Join));
+    }
+
+    #[test]
+    fn all_elements_from_expansion() {
+        let mut env = yash_env::Env::new_virtual();
+        env.variables
+            .get_or_new("i", Scope::Global)
+            .assign("@", None)
+            .unwrap();
+        let result = evaluate_now(&mut env, &index("$i", None));
+        assert_eq!(result, Ok(Subscript::All));
+    }
+
+    #[test]
+    fn single_element() {
+        let mut env = yash_env::Env::new_virtual();
+        env.variables
+            .get_or_new("i", Scope::Global)
+            .assign("2", None)
+            .unwrap();
+        let result = evaluate_now(&mut env, &index("i+1", None));
+        assert_eq!(result, Ok(Subscript::Element(3)));
+        let result = evaluate_now(&mut env, &index("-1", None));
+        assert_eq!(result, Ok(Subscript::Element(-1)));
+    }
+
+    #[test]
+    fn range() {
+        let mut env = yash_env::Env::new_virtual();
+        let result = evaluate_now(&mut env, &index("2", Some("-1")));
+        assert_eq!(result, Ok(Subscript::Range(2, -1)));
+    }
+
+    #[test]
+    fn asterisk_in_range_is_arithmetic() {
+        let mut env = yash_env::Env::new_virtual();
+        let error = evaluate_now(&mut env, &index("*", Some("2"))).unwrap_err();
+        assert_matches!(error.cause, ErrorCause::ArithError(_));
+    }
+}
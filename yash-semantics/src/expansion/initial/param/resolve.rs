@@ -14,10 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-//! Resolving parameter names to values
+//! Resolving parameter names and indices to values
 
+use super::index::Subscript;
 use super::name::Name;
+use std::ops::Range;
 use yash_env::variable::Expansion;
+use yash_env::variable::Value;
 use yash_env::Env;
 use yash_syntax::source::Location;
 
@@ -59,12 +62,79 @@ pub fn resolve<'a>(name: Name<'_>, env: &'a Env, location: &Location) -> Expansi
     }
 }
 
+/// Converts a one-based index into a zero-based position.
+///
+/// A negative index counts from the end. Returns `None` if the index is out of
+/// range.
+fn position(len: usize, index: i64) -> Option<usize> {
+    let position = if index < 0 {
+        len.checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+    } else {
+        usize::try_from(index).ok()?.checked_sub(1)?
+    };
+    (position < len).then_some(position)
+}
+
+/// Converts a one-based inclusive range into a zero-based range.
+///
+/// Negative indices count from the end. The result is clipped to the length.
+fn range(len: usize, start: i64, end: i64) -> Range<usize> {
+    let len_i64 = i64::try_from(len).unwrap_or(i64::MAX);
+    let normalize = |index: i64| {
+        if index < 0 {
+            len_i64.saturating_add(index).saturating_add(1)
+        } else {
+            index
+        }
+    };
+    let start = normalize(start).max(1);
+    let end = normalize(end).min(len_i64);
+    if start > end {
+        0..0
+    } else {
+        (start - 1) as usize..end as usize
+    }
+}
+
+/// Applies a subscript to the value of a parameter.
+///
+/// For an array, the subscript selects elements. A single element is
+/// returned as a scalar, or `None` if the index is out of range. For a scalar,
+/// the subscript selects characters, and the result is always a scalar.
+/// An unset value remains unset.
+pub fn subscript(value: Option<Value>, subscript: Subscript) -> Option<Value> {
+    match (subscript, value?) {
+        (Subscript::All | Subscript::Join, value) => Some(value),
+
+        (Subscript::Element(index), Value::Scalar(value)) => {
+            let len = value.chars().count();
+            let c = position(len, index).and_then(|i| value.chars().nth(i));
+            Some(Value::Scalar(c.map(String::from).unwrap_or_default()))
+        }
+        (Subscript::Element(index), Value::Array(mut values)) => {
+            let i = position(values.len(), index)?;
+            Some(Value::Scalar(values.swap_remove(i)))
+        }
+
+        (Subscript::Range(start, end), Value::Scalar(value)) => {
+            let range = range(value.chars().count(), start, end);
+            let value = value.chars().skip(range.start).take(range.len());
+            Some(Value::Scalar(value.collect()))
+        }
+        (Subscript::Range(start, end), Value::Array(mut values)) => {
+            let range = range(values.len(), start, end);
+            values.truncate(range.end);
+            values.drain(..range.start);
+            Some(Value::Array(values))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use yash_env::job::Pid;
     use yash_env::variable::Scope;
-    use yash_env::variable::Value;
     use yash_syntax::source::Location;
 
     #[test]
@@ -248,4 +318,72 @@ mod tests {
         let result = resolve(Name::Positional(2), &env, &loc);
         assert_eq!(result, Expansion::Scalar("b".into()));
     }
+
+    #[test]
+    fn position_in_range() {
+        assert_eq!(position(3, 1), Some(0));
+        assert_eq!(position(3, 3), Some(2));
+        assert_eq!(position(3, -1), Some(2));
+        assert_eq!(position(3, -3), Some(0));
+    }
+
+    #[test]
+    fn position_out_of_range() {
+        assert_eq!(position(3, 0), None);
+        assert_eq!(position(3, 4), None);
+        assert_eq!(position(3, -4), None);
+        assert_eq!(position(0, 1), None);
+        assert_eq!(position(3, i64::MIN), None);
+    }
+
+    #[test]
+    fn range_conversion() {
+        assert_eq!(range(5, 2, 4), 1..4);
+        assert_eq!(range(5, 1, -1), 0..5);
+        assert_eq!(range(5, -2, -1), 3..5);
+        assert_eq!(range(5, 0, 2), 0..2);
+        assert_eq!(range(5, -10, 10), 0..5);
+        assert_eq!(range(5, 4, 2), 0..0);
+        assert_eq!(range(5, 6, 7), 0..0);
+        assert_eq!(range(5, i64::MIN, i64::MAX), 0..5);
+    }
+
+    #[test]
+    fn subscript_all() {
+        let value = Some(Value::array(["a", "b"]));
+        assert_eq!(subscript(value.clone(), Subscript::All), value);
+        assert_eq!(subscript(value.clone(), Subscript::Join), value);
+        assert_eq!(subscript(None, Subscript::All), None);
+    }
+
+    #[test]
+    fn subscript_array_element() {
+        let value = Some(Value::array(["a", "b", "c"]));
+        let result = subscript(value.clone(), Subscript::Element(2));
+        assert_eq!(result, Some(Value::scalar("b")));
+        let result = subscript(value.clone(), Subscript::Element(-1));
+        assert_eq!(result, Some(Value::scalar("c")));
+        let result = subscript(value, Subscript::Element(4));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn subscript_array_range() {
+        let value = Some(Value::array(["a", "b", "c", "d"]));
+        let result = subscript(value.clone(), Subscript::Range(2, 3));
+        assert_eq!(result, Some(Value::array(["b", "c"])));
+        let result = subscript(value, Subscript::Range(3, 2));
+        assert_eq!(result, Some(Value::Array(vec![])));
+    }
+
+    #[test]
+    fn subscript_scalar() {
+        let value = Some(Value::scalar("hello"));
+        let result = subscript(value.clone(), Subscript::Element(-1));
+        assert_eq!(result, Some(Value::scalar("o")));
+        let result = subscript(value.clone(), Subscript::Element(9));
+        assert_eq!(result, Some(Value::scalar("")));
+        let result = subscript(value, Subscript::Range(2, 4));
+        assert_eq!(result, Some(Value::scalar("ell")));
+    }
 }
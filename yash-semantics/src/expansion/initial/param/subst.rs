@@ -0,0 +1,264 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parameter expansion substitution semantics
+
+use super::Env;
+use super::Error;
+use crate::expansion::attr::fnmatch::apply_escapes;
+use crate::expansion::attr::fnmatch::to_pattern_chars;
+use crate::expansion::glob::is_unicode_locale;
+use crate::expansion::initial::Expand as _;
+use crate::expansion::quote_removal::skip_quotes;
+use yash_env::option::Option::ExtendedGlob;
+use yash_env::option::State::On;
+use yash_env::variable::Value::{self, Array, Scalar};
+use yash_fnmatch::Config;
+use yash_fnmatch::Pattern;
+use yash_syntax::syntax::Subst;
+use yash_syntax::syntax::SubstMode::{All, First, Prefix, Suffix};
+
+fn subst_first(pattern: &Pattern, replacement: &str, value: &mut String) {
+    if let Some(range) = pattern.find(value) {
+        value.replace_range(range, replacement);
+    }
+}
+
+fn subst_all(pattern: &Pattern, replacement: &str, value: &mut String) {
+    let mut result = String::with_capacity(value.len());
+    let mut start = 0;
+    while let Some(range) = pattern.find(&value[start..]) {
+        let range = start + range.start..start + range.end;
+        result.push_str(&value[start..range.start]);
+        result.push_str(replacement);
+        if range.is_empty() {
+            // Skip a character to avoid matching the same empty string again
+            let Some(c) = value[range.end..].chars().next() else {
+                start = value.len();
+                break;
+            };
+            result.push(c);
+            start = range.end + c.len_utf8();
+        } else {
+            start = range.end;
+            if start == value.len() {
+                // Do not match the empty string at the end again
+                break;
+            }
+        }
+    }
+    result.push_str(&value[start..]);
+    *value = result;
+}
+
+/// Applies the substitution modifier to the value.
+pub async fn apply(env: &mut Env<'_>, subst: &Subst, value: &mut Value) -> Result<(), Error> {
+    let expansion = subst.pattern.expand(env).await?;
+    let mut pattern = expansion.ifs_join(&env.inner.variables);
+    apply_escapes(&mut pattern);
+
+    let expansion = subst.replacement.expand(env).await?;
+    let replacement = skip_quotes(expansion.ifs_join(&env.inner.variables))
+        .map(|c| c.value)
+        .collect::<String>();
+
+    // An empty pattern does not match anything unless anchored
+    if pattern.is_empty() && matches!(subst.mode, First | All) {
+        return Ok(());
+    }
+
+    let mut config = Config::default();
+    config.extended = env.inner.options.get(ExtendedGlob) == On;
+    config.unicode = is_unicode_locale(&env.inner.variables);
+    match subst.mode {
+        First | All => (),
+        Prefix => config.anchor_begin = true,
+        Suffix => config.anchor_end = true,
+    }
+    let pattern = match Pattern::parse_with_config(to_pattern_chars(&pattern), config) {
+        Ok(parse) => parse,
+        Err(_error) => {
+            // Treat the broken pattern as a valid pattern that does not match anything
+            return Ok(());
+        }
+    };
+
+    let subst_value = match subst.mode {
+        All => subst_all,
+        First | Prefix | Suffix => subst_first,
+    };
+    match value {
+        Scalar(value) => subst_value(&pattern, &replacement, value),
+        Array(array) => {
+            for value in array {
+                subst_value(&pattern, &replacement, value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    fn subst(mode: yash_syntax::syntax::SubstMode, pattern: &str, replacement: &str) -> Subst {
+        Subst {
+            mode,
+            pattern: pattern.parse().unwrap(),
+            replacement: replacement.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn first_with_scalar() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(First, "2*3", "X");
+        let mut value = Value::scalar("1231231");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("1X1"));
+    }
+
+    #[test]
+    fn first_with_array() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(First, "b", "[$x]");
+        let mut value = Value::array(["abcb", "", "bb"]);
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::array(["a[]cb", "", "[]b"]));
+    }
+
+    #[test]
+    fn first_unmatched() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(First, "x", "y");
+        let mut value = Value::scalar("abc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("abc"));
+    }
+
+    #[test]
+    fn all_with_scalar() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(All, "[ab]", "-");
+        let mut value = Value::scalar("abcabc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("--c--c"));
+    }
+
+    #[test]
+    fn all_with_array() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(All, "o", "0");
+        let mut value = Value::array(["foo", "bar", "boo"]);
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::array(["f00", "bar", "b00"]));
+    }
+
+    #[test]
+    fn all_with_match_to_end() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(All, "*", "X");
+        let mut value = Value::scalar("hello");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("X"));
+    }
+
+    #[test]
+    fn empty_pattern_does_not_match() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(All, "", "x");
+        let mut value = Value::scalar("abc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("abc"));
+    }
+
+    #[test]
+    fn prefix() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(Prefix, "?", "X");
+        let mut value = Value::scalar("aaa");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("Xaa"));
+
+        let subst = self::subst(Prefix, "", "X");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("XXaa"));
+    }
+
+    #[test]
+    fn prefix_unmatched() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(Prefix, "b", "X");
+        let mut value = Value::scalar("abc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("abc"));
+    }
+
+    #[test]
+    fn suffix() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(Suffix, "b*", "X");
+        let mut value = Value::scalar("abcabc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("aX"));
+    }
+
+    #[test]
+    fn quoted_replacement() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(First, "b", r#""*"\$"#);
+        let mut value = Value::scalar("abc");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("a*$c"));
+    }
+
+    #[test]
+    fn quoted_pattern() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let subst = subst(All, r"'*'\?", "!");
+        let mut value = Value::scalar("a*?b*?");
+        let result = apply(&mut env, &subst, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("a!b!"));
+    }
+}
@@ -0,0 +1,248 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parameter expansion substring semantics
+//!
+//! Like other modifiers, a substring is not applied to an unset parameter.
+//! If the `nounset` option is enabled, expanding an unset parameter is an
+//! error before the offset and length are evaluated. Otherwise, the result is
+//! empty and the offset and length are not evaluated at all. Unset variables
+//! in the offset and length are treated the same way as in arithmetic
+//! expansion.
+
+use super::super::arith::evaluate;
+use super::Env;
+use super::Error;
+use std::ops::Range;
+use yash_env::variable::Value::{self, Array, Scalar};
+use yash_syntax::source::Location;
+use yash_syntax::syntax::Substring;
+use yash_syntax::syntax::Text;
+
+/// Evaluates an offset or length of a substring.
+///
+/// An empty expression is regarded as zero.
+async fn evaluate_integer(
+    env: &mut Env<'_>,
+    text: &Text,
+    location: &Location,
+) -> Result<i64, Error> {
+    if text.0.is_empty() {
+        return Ok(0);
+    }
+    let yash_arith::Value::Integer(value) = evaluate(text, location, env).await?;
+    Ok(value)
+}
+
+/// Computes the index range of the extracted part.
+///
+/// `len` is the length of the whole value. `offset` is zero-based. A negative
+/// `offset` or `length` counts from the end of the value.
+fn range(len: usize, offset: i64, length: Option<i64>) -> Range<usize> {
+    let len = i64::try_from(len).unwrap_or(i64::MAX);
+    let start = if offset < 0 {
+        len.saturating_add(offset)
+    } else {
+        offset.min(len)
+    };
+    if start < 0 {
+        return 0..0;
+    }
+    let end = match length {
+        None => len,
+        Some(length) if length < 0 => len.saturating_add(length),
+        Some(length) => start.saturating_add(length).min(len),
+    };
+    let end = end.max(start);
+    start as usize..end as usize
+}
+
+/// Applies the substring modifier to the value.
+///
+/// For a scalar value, the offset is the zero-based index of the first
+/// character to extract. For an array (including the positional parameters),
+/// the offset is the one-based index of the first element to extract, where
+/// an offset of zero is the same as one. A negative offset counts from the
+/// end. If the length is negative, it is the number of characters or elements
+/// to leave at the end.
+///
+/// The `location` should be that of the parameter expansion.
+pub async fn apply(
+    env: &mut Env<'_>,
+    substring: &Substring,
+    value: &mut Value,
+    location: &Location,
+) -> Result<(), Error> {
+    let offset = evaluate_integer(env, &substring.offset, location).await?;
+    let length = match &substring.length {
+        None => None,
+        Some(length) => Some(evaluate_integer(env, length, location).await?),
+    };
+
+    match value {
+        Scalar(value) => {
+            let range = range(value.chars().count(), offset, length);
+            *value = value.chars().skip(range.start).take(range.len()).collect();
+        }
+        Array(values) => {
+            let offset = if offset > 0 { offset - 1 } else { offset };
+            let range = range(values.len(), offset, length);
+            values.truncate(range.end);
+            values.drain(..range.start);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expansion::ErrorCause;
+    use futures_util::FutureExt;
+    use yash_env::option::Option::Unset;
+    use yash_env::option::State::Off;
+
+    fn substring(offset: &str, length: Option<&str>) -> Substring {
+        Substring {
+            offset: offset.parse().unwrap(),
+            length: length.map(|length| length.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn range_with_positive_offset() {
+        assert_eq!(range(5, 0, None), 0..5);
+        assert_eq!(range(5, 2, None), 2..5);
+        assert_eq!(range(5, 5, None), 5..5);
+        assert_eq!(range(5, 7, None), 5..5);
+        assert_eq!(range(5, 1, Some(2)), 1..3);
+        assert_eq!(range(5, 1, Some(0)), 1..1);
+        assert_eq!(range(5, 4, Some(3)), 4..5);
+        assert_eq!(range(5, 1, Some(i64::MAX)), 1..5);
+    }
+
+    #[test]
+    fn range_with_negative_offset() {
+        assert_eq!(range(5, -1, None), 4..5);
+        assert_eq!(range(5, -5, None), 0..5);
+        assert_eq!(range(5, -6, None), 0..0);
+        assert_eq!(range(5, -3, Some(2)), 2..4);
+        assert_eq!(range(5, i64::MIN, None), 0..0);
+    }
+
+    #[test]
+    fn range_with_negative_length() {
+        assert_eq!(range(5, 1, Some(-1)), 1..4);
+        assert_eq!(range(5, -4, Some(-2)), 1..3);
+        assert_eq!(range(5, 3, Some(-3)), 3..3);
+    }
+
+    #[test]
+    fn scalar_with_offset() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let substring = substring("2", None);
+        let mut value = Value::scalar("a\u{30A4}cde");
+        let location = Location::dummy("");
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("cde"));
+    }
+
+    #[test]
+    fn scalar_with_offset_and_length() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let substring = substring(" -4", Some("1+1"));
+        let mut value = Value::scalar("abcde");
+        let location = Location::dummy("");
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("bc"));
+    }
+
+    #[test]
+    fn scalar_with_empty_offset() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let substring = substring("", Some("3"));
+        let mut value = Value::scalar("abcde");
+        let location = Location::dummy("");
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("abc"));
+    }
+
+    #[test]
+    fn array_with_offset_and_length() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let substring = substring("2", Some("3"));
+        let mut value = Value::array(["1", "2", "3", "4", "5", "6"]);
+        let location = Location::dummy("");
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::array(["2", "3", "4"]));
+    }
+
+    #[test]
+    fn array_with_zero_or_negative_offset() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let location = Location::dummy("");
+
+        let substring = self::substring("0", Some("2"));
+        let mut value = Value::array(["1", "2", "3"]);
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::array(["1", "2"]));
+
+        let substring = self::substring(" -2", None);
+        let mut value = Value::array(["1", "2", "3"]);
+        let result = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::array(["2", "3"]));
+    }
+
+    #[test]
+    fn unset_variable_in_expression_with_nounset() {
+        let mut env = yash_env::Env::new_virtual();
+        env.options.set(Unset, Off);
+        let mut env = Env::new(&mut env);
+        let substring = substring("x", None);
+        let mut value = Value::scalar("abc");
+        let location = Location::dummy("");
+        let e = apply(&mut env, &substring, &mut value, &location)
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(e.cause, ErrorCause::UnsetParameter);
+        assert_eq!(value, Value::scalar("abc"));
+    }
+}
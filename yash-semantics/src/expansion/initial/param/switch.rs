@@ -122,11 +122,10 @@ pub enum NonassignableError {
     /// The parameter is not a variable.
     #[error("not an assignable variable")]
     NotVariable,
-    // /// The parameter expansion refers to an array but does not index a single
-    // /// element.
-    // #[error("cannot assign to a non-scalar array range")]
-    // TODO ArrayIndex,
 
+    /// The parameter expansion has an index.
+    #[error("cannot assign to an indexed parameter")]
+    ArrayIndex,
     // /// The parameter expansion is nested.
     // #[error("cannot assign to a nested parameter expansion")]
     // TODO Nested,
@@ -169,13 +168,20 @@ fn attribute(mut phrase: Phrase) -> Phrase {
 }
 
 /// Assigns the expansion of `value` to variable `name`.
+///
+/// It is an error if the parameter expansion is `indexed`.
 async fn assign(
     env: &mut Env<'_>,
     name: Option<Name<'_>>,
+    indexed: bool,
     value: &Word,
     location: Location,
 ) -> Result<Phrase, Error> {
     // TODO Support assignment to an array element
+    if indexed {
+        let cause = ErrorCause::NonassignableParameter(NonassignableError::ArrayIndex);
+        return Err(Error { cause, location });
+    }
     let name = match name {
         Some(Name::Variable(name)) => name,
         _ => {
@@ -237,10 +243,14 @@ async fn empty_expansion_error(
 /// If this function returns `Some(_)`, that should be the result of the whole
 /// parameter expansion containing the switch. Otherwise, the parameter
 /// expansion should continue processing other modifiers.
+///
+/// `indexed` should be true if the parameter expansion has an index, in which
+/// case the assign switch cannot assign to the variable.
 pub async fn apply(
     env: &mut Env<'_>,
     switch: &Switch,
     name: Option<Name<'_>>,
+    indexed: bool,
     value: &mut Option<Value>,
     location: &Location,
 ) -> Option<Result<Phrase, Error>> {
@@ -250,7 +260,10 @@ pub async fn apply(
     match (switch.r#type, cond) {
         (Alter, Unset(_)) | (Default, Set) | (Assign, Set) | (Error, Set) => None,
         (Alter, Set) | (Default, Unset(_)) => Some(switch.word.expand(env).await.map(attribute)),
-        (Assign, Unset(_)) => Some(assign(env, name, &switch.word, location.clone()).await),
+        (Assign, Unset(_)) => {
+            let word = &switch.word;
+            Some(assign(env, name, indexed, word, location.clone()).await)
+        }
         (Error, Unset(state)) => Some(Err(empty_expansion_error(
             env,
             state,
@@ -351,7 +364,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = None;
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, None);
@@ -370,7 +383,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Scalar("bar".to_string()));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, Some(Ok(Phrase::Field(to_field("foo")))));
@@ -388,7 +401,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = None;
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, Some(Ok(Phrase::Field(to_field("foo")))));
@@ -406,7 +419,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Scalar("bar".to_string()));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, None);
@@ -426,7 +439,7 @@ mod tests {
         let mut value = None;
         let location = Location::dummy("somewhere");
 
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, Some(Ok(Phrase::Field(to_field("foo")))));
@@ -457,7 +470,7 @@ mod tests {
         let mut value = None;
         let location = Location::dummy("somewhere");
 
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
 
@@ -514,7 +527,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Scalar("bar".to_string()));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, None);
@@ -538,7 +551,7 @@ mod tests {
         let mut value = None;
         let location = Location::dummy("somewhere");
 
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_matches!(result, Some(Err(error)) => {
@@ -565,7 +578,7 @@ mod tests {
         let mut value = None;
         let location = Location::dummy("somewhere");
 
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         let error = result.unwrap().unwrap_err();
@@ -576,6 +589,31 @@ mod tests {
         assert_eq!(error.location, location);
     }
 
+    #[test]
+    fn assign_to_indexed_parameter() {
+        let mut env = yash_env::Env::new_virtual();
+        let mut env = Env::new(&mut env);
+        let switch = Switch {
+            r#type: Assign,
+            condition: Unset,
+            word: "foo".parse().unwrap(),
+        };
+        let name = Some(Name::Variable("var"));
+        let mut value = None;
+        let location = Location::dummy("somewhere");
+
+        let result = apply(&mut env, &switch, name, true, &mut value, &location)
+            .now_or_never()
+            .unwrap();
+        let error = result.unwrap().unwrap_err();
+        assert_eq!(
+            error.cause,
+            ErrorCause::NonassignableParameter(NonassignableError::ArrayIndex)
+        );
+        assert_eq!(error.location, location);
+        assert_eq!(env.inner.variables.get("var"), None);
+    }
+
     #[test]
     fn error_with_unset_value_and_non_empty_word() {
         let mut env = yash_env::Env::new_virtual();
@@ -588,7 +626,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = None;
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         let error = result.unwrap().unwrap_err();
@@ -610,7 +648,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Value::scalar(""));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         let error = result.unwrap().unwrap_err();
@@ -632,7 +670,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Value::Array(vec![]));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         let error = result.unwrap().unwrap_err();
@@ -655,7 +693,7 @@ mod tests {
         let name = Some(Name::Variable("var"));
         let mut value = Some(Value::scalar(""));
         let location = Location::dummy("somewhere");
-        let result = apply(&mut env, &switch, name, &mut value, &location)
+        let result = apply(&mut env, &switch, name, false, &mut value, &location)
             .now_or_never()
             .unwrap();
         assert_eq!(result, None);
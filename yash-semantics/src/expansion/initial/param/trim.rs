@@ -19,8 +19,11 @@
 use super::Env;
 use super::Error;
 use crate::expansion::attr::fnmatch::apply_escapes;
-use crate::expansion::attr::fnmatch::to_pattern_chars;
+use crate::expansion::attr::fnmatch::with_pattern;
+use crate::expansion::glob::is_unicode_locale;
 use crate::expansion::initial::Expand as _;
+use yash_env::option::Option::ExtendedGlob;
+use yash_env::option::State::On;
 use yash_env::variable::Value::{self, Array, Scalar};
 use yash_fnmatch::Config;
 use yash_fnmatch::Pattern;
@@ -47,6 +50,8 @@ pub async fn apply(env: &mut Env<'_>, trim: &Trim, value: &mut Value) -> Result<
     apply_escapes(&mut pattern);
 
     let mut config = Config::default();
+    config.extended = env.inner.options.get(ExtendedGlob) == On;
+    config.unicode = is_unicode_locale(&env.inner.variables);
     match trim.side {
         Prefix => config.anchor_begin = true,
         Suffix => config.anchor_end = true,
@@ -55,22 +60,15 @@ pub async fn apply(env: &mut Env<'_>, trim: &Trim, value: &mut Value) -> Result<
         Shortest => config.shortest_match = true,
         Longest => (),
     }
-    let pattern = match Pattern::parse_with_config(to_pattern_chars(&pattern), config) {
-        Ok(parse) => parse,
-        Err(_error) => {
-            // Treat the broken pattern as a valid pattern that does not match anything
-            return Ok(());
-        }
-    };
-
-    match value {
-        Scalar(value) => trim_value(&pattern, value),
+    // Treat a broken pattern as a valid pattern that does not match anything
+    let _ = with_pattern(&pattern, config, |pattern| match value {
+        Scalar(value) => trim_value(pattern, value),
         Array(array) => {
             for value in array {
-                trim_value(&pattern, value);
+                trim_value(pattern, value);
             }
         }
-    }
+    });
 
     Ok(())
 }
@@ -184,4 +182,21 @@ mod tests {
         assert_eq!(result, Ok(()));
         assert_eq!(value, Value::scalar("123123123"));
     }
+
+    #[test]
+    fn extended_pattern_with_extendedglob_option() {
+        let mut env = yash_env::Env::new_virtual();
+        env.options
+            .set(yash_env::option::ExtendedGlob, yash_env::option::On);
+        let mut env = Env::new(&mut env);
+        let trim = Trim {
+            side: Prefix,
+            length: Longest,
+            pattern: "+(12)".parse().unwrap(),
+        };
+        let mut value = Value::scalar("1212123");
+        let result = apply(&mut env, &trim, &mut value).now_or_never().unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, Value::scalar("3"));
+    }
 }
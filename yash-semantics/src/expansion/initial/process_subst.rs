@@ -0,0 +1,94 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Expansion of process substitution
+
+use super::super::attr::AttrChar;
+use super::super::attr::Origin;
+use super::super::phrase::Phrase;
+use super::Env;
+use super::Error;
+use crate::expansion::ErrorCause;
+use std::rc::Rc;
+use yash_syntax::source::Location;
+use yash_syntax::syntax::ProcessSubstDirection;
+
+/// Performs process substitution.
+///
+/// The result is the pathname `/dev/fd/N` where `N` is the file descriptor
+/// for the pipe end kept in the shell. See [`crate::process_subst`] for
+/// details.
+pub async fn expand(
+    direction: ProcessSubstDirection,
+    content: &Rc<str>,
+    location: &Location,
+    env: &mut Env<'_>,
+) -> Result<Phrase, Error> {
+    let content = Rc::clone(content);
+    let subst = match crate::process_subst::start(env.inner, direction, content, location).await {
+        Ok(subst) => subst,
+        Err(errno) => {
+            return Err(Error {
+                cause: ErrorCause::ProcessSubstError(errno),
+                location: location.clone(),
+            })
+        }
+    };
+    env.inner.process_substs.push(subst);
+
+    let path = format!("/dev/fd/{}", subst.fd.unwrap());
+    let chars = path
+        .chars()
+        .map(|value| AttrChar {
+            value,
+            origin: Origin::HardExpansion,
+            is_quoted: false,
+            is_quoting: false,
+        })
+        .collect();
+    Ok(Phrase::Field(chars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::echo_builtin;
+    use crate::tests::in_virtual_system;
+
+    #[test]
+    fn expanding_to_fd_path() {
+        in_virtual_system(|mut env, _state| async move {
+            env.builtins.insert("echo", echo_builtin());
+            let content = "echo ok".into();
+            let location = Location::dummy("");
+            let direction = ProcessSubstDirection::Input;
+            let mut env = Env::new(&mut env);
+            let result = expand(direction, &content, &location, &mut env).await;
+
+            let subst = env.inner.process_substs[0];
+            let fd = subst.fd.unwrap();
+            let path: Vec<_> = format!("/dev/fd/{fd}").chars().collect();
+            assert_matches::assert_matches!(result, Ok(Phrase::Field(chars)) => {
+                let values: Vec<_> = chars.iter().map(|c| c.value).collect();
+                assert_eq!(values, path);
+            });
+
+            let mut buffer = [0; 8];
+            let count = env.inner.system.read_async(fd, &mut buffer).await.unwrap();
+            assert_eq!(&buffer[..count], b"ok\n");
+        })
+    }
+}
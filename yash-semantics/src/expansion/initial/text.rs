@@ -20,6 +20,7 @@ use super::super::attr::AttrChar;
 use super::super::attr::Origin;
 use super::super::Error;
 use super::param::ParamRef;
+use super::param::Subject;
 use super::Env;
 use super::Expand;
 use super::Phrase;
@@ -107,7 +108,8 @@ impl Expand for TextUnit {
             RawParam { name, location } => {
                 let modifier = &yash_syntax::syntax::Modifier::None;
                 let param = ParamRef {
-                    name,
+                    subject: Subject::Name(name),
+                    index: None,
                     modifier,
                     location,
                 };
@@ -158,6 +160,7 @@ mod tests {
     use yash_syntax::source::Location;
     use yash_syntax::syntax::Modifier;
     use yash_syntax::syntax::Param;
+    use yash_syntax::syntax::ParamSubject;
 
     #[test]
     fn literal() {
@@ -226,7 +229,8 @@ mod tests {
             .unwrap();
         let mut env = Env::new(&mut env);
         let param = BracedParam(Param {
-            name: "foo".to_string(),
+            subject: ParamSubject::Name("foo".to_string()),
+            index: None,
             modifier: Modifier::None,
             location: Location::dummy(""),
         });
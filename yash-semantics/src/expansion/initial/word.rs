@@ -97,6 +97,11 @@ fn double_quote(phrase: &mut Phrase) {
 /// `Tilde(user)` expands to the `user`'s home directory.
 ///
 /// TODO: `~+`, `~-`, `~+n`, `~-n`
+///
+/// # Process substitution
+///
+/// `ProcessSubst { .. }` starts the process and expands to the pathname
+/// `/dev/fd/N` of the pipe end kept in the shell.
 impl Expand for WordUnit {
     async fn expand(&self, env: &mut Env<'_>) -> Result<Phrase, Error> {
         match self {
@@ -112,6 +117,11 @@ impl Expand for WordUnit {
                 Ok(phrase)
             }
             Tilde(name) => Ok(super::tilde::expand(name, env.inner).into()),
+            ProcessSubst {
+                direction,
+                content,
+                location,
+            } => super::process_subst::expand(*direction, content, location, env).await,
         }
     }
 }
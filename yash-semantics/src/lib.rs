@@ -29,7 +29,9 @@
 pub mod assign;
 pub mod command;
 pub mod command_search;
+pub mod completion;
 pub mod expansion;
+pub mod process_subst;
 pub mod redir;
 pub mod trap;
 pub mod xtrace;
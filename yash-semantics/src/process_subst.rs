@@ -0,0 +1,244 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Process substitution
+//!
+//! A [process substitution](yash_syntax::syntax::WordUnit::ProcessSubst) runs
+//! its commands asynchronously in a subshell connected to the shell with a
+//! pipe. For `<(...)`, the standard output of the subshell is the writing end
+//! of the pipe and the shell keeps the reading end. For `>(...)`, the standard
+//! input of the subshell is the reading end and the shell keeps the writing
+//! end.
+//!
+//! The [`start`] function starts the subshell. The subshell is not a job: it is
+//! not added to the [job list](yash_env::job::JobList) and does not change the
+//! value of the `$!` special parameter. Instead, the caller of `start` should
+//! record the result in [`Env::process_substs`]. When a command finishes,
+//! [`Command::execute`](crate::command::Command::execute) calls [`finish`] to
+//! close the pipe ends remaining in the shell and collect the exit status of
+//! the subshells started during the command.
+
+use crate::Handle;
+use crate::ReadEvalLoop;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yash_env::io::Fd;
+use yash_env::io::MIN_INTERNAL_FD;
+use yash_env::job::ProcessSubst;
+use yash_env::subshell::Subshell;
+use yash_env::system::Errno;
+use yash_env::system::FdFlag;
+use yash_env::Env;
+use yash_env::System;
+use yash_syntax::parser::lex::Lexer;
+use yash_syntax::source::Location;
+use yash_syntax::source::Source;
+use yash_syntax::syntax::ProcessSubstDirection;
+
+/// Starts a subshell for a process substitution.
+///
+/// This function opens a pipe and starts a subshell that runs `content`. The
+/// returned [`ProcessSubst`] contains the process ID of the subshell and the
+/// pipe end that the shell keeps. The file descriptor is not less than
+/// [`MIN_INTERNAL_FD`] and does not have the CLOEXEC flag so that it can be
+/// inherited by the command that uses it.
+///
+/// This function does not add the result to `env.process_substs`; that is up
+/// to the caller.
+pub async fn start(
+    env: &mut Env,
+    direction: ProcessSubstDirection,
+    content: Rc<str>,
+    location: &Location,
+) -> Result<ProcessSubst, Errno> {
+    let (reader, writer) = env.system.pipe()?;
+    let (kept, passed, target) = match direction {
+        ProcessSubstDirection::Input => (reader, writer, Fd::STDOUT),
+        ProcessSubstDirection::Output => (writer, reader, Fd::STDIN),
+    };
+
+    let kept = match env.system.dup(kept, MIN_INTERNAL_FD, FdFlag::empty()) {
+        Ok(fd) => {
+            env.system.close(kept).ok();
+            fd
+        }
+        Err(errno) => {
+            env.system.close(reader).ok();
+            env.system.close(writer).ok();
+            return Err(errno);
+        }
+    };
+
+    let original = location.clone();
+    let subshell = Subshell::new(move |env, _job_control| {
+        Box::pin(async move {
+            // The pipe ends of other process substitutions are not for us
+            for subst in std::mem::take(&mut env.process_substs) {
+                if let Some(fd) = subst.fd {
+                    env.system.close(fd).ok();
+                }
+            }
+            env.system.close(kept).ok();
+            if passed != target {
+                if let Err(errno) = env.system.dup2(passed, target) {
+                    let error = crate::expansion::Error {
+                        cause: crate::expansion::ErrorCause::ProcessSubstError(errno),
+                        location: original,
+                    };
+                    return error.handle(env).await;
+                }
+                env.system.close(passed).ok();
+            }
+
+            let source = Source::ProcessSubst { original };
+            let mut lexer = Lexer::from_memory(&content, source);
+            ReadEvalLoop::new(&RefCell::new(env), &mut lexer)
+                .run()
+                .await
+        })
+    });
+    let result = subshell.start(env).await;
+    env.system.close(passed).ok();
+
+    match result {
+        Ok((pid, job_control)) => {
+            debug_assert_eq!(job_control, None);
+            Ok(ProcessSubst {
+                pid,
+                fd: Some(kept),
+            })
+        }
+        Err(errno) => {
+            env.system.close(kept).ok();
+            Err(errno)
+        }
+    }
+}
+
+/// Finishes process substitutions.
+///
+/// This function removes the items of `env.process_substs` at and after
+/// `index`. For each removed item, the pipe end kept in the shell is closed
+/// and the subshell is awaited until it finishes. The exit status of the
+/// subshell is discarded.
+pub async fn finish(env: &mut Env, index: usize) {
+    if index >= env.process_substs.len() {
+        return;
+    }
+
+    let substs = env.process_substs.split_off(index);
+    for subst in &substs {
+        if let Some(fd) = subst.fd {
+            env.system.close(fd).ok();
+        }
+    }
+
+    for subst in substs {
+        _ = env.wait_for_subshell_to_finish(subst.pid).await;
+    }
+}
+
+/// Stops tracking process substitutions without waiting for them.
+///
+/// This function removes the items of `env.process_substs` at and after
+/// `index` and closes the pipe ends kept in the shell. The subshells are not
+/// awaited; they are reaped when the shell [updates subshell
+/// statuses](Env::update_all_subshell_statuses) later. This should be used
+/// instead of [`finish`]
+/// when redirections are made permanent, as by the `exec` built-in, since the
+/// subshells may keep running as long as the redirected file descriptors are
+/// open.
+pub fn release(env: &mut Env, index: usize) {
+    if index >= env.process_substs.len() {
+        return;
+    }
+
+    for subst in env.process_substs.split_off(index) {
+        if let Some(fd) = subst.fd {
+            env.system.close(fd).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::echo_builtin;
+    use crate::tests::in_virtual_system;
+    use crate::tests::return_builtin;
+    use yash_env::job::Pid;
+    use yash_env::job::ProcessState;
+    use yash_env::semantics::ExitStatus;
+
+    #[test]
+    fn input_process_substitution() {
+        in_virtual_system(|mut env, _state| async move {
+            env.builtins.insert("echo", echo_builtin());
+            let location = Location::dummy("");
+            let direction = ProcessSubstDirection::Input;
+            let subst = start(&mut env, direction, "echo ok".into(), &location)
+                .await
+                .unwrap();
+            let fd = subst.fd.unwrap();
+            assert!(fd >= MIN_INTERNAL_FD);
+            assert_eq!(env.jobs.len(), 0);
+            assert_eq!(env.jobs.last_async_pid(), Pid(0));
+
+            let mut buffer = [0; 8];
+            let count = env.system.read_async(fd, &mut buffer).await.unwrap();
+            assert_eq!(&buffer[..count], b"ok\n");
+        })
+    }
+
+    #[test]
+    fn finishing_process_substitution() {
+        in_virtual_system(|mut env, state| async move {
+            env.builtins.insert("return", return_builtin());
+            let location = Location::dummy("");
+            let direction = ProcessSubstDirection::Output;
+            let subst = start(&mut env, direction, "return -n 7".into(), &location)
+                .await
+                .unwrap();
+            env.process_substs.push(subst);
+
+            finish(&mut env, 0).await;
+            assert_eq!(env.process_substs, []);
+            assert_eq!(env.jobs.len(), 0);
+            let state = state.borrow();
+            let process = &state.processes[&subst.pid];
+            assert_eq!(process.state(), ProcessState::Exited(ExitStatus(7)));
+        })
+    }
+
+    #[test]
+    fn releasing_process_substitution() {
+        in_virtual_system(|mut env, _state| async move {
+            env.builtins.insert("return", return_builtin());
+            let location = Location::dummy("");
+            let direction = ProcessSubstDirection::Input;
+            let subst = start(&mut env, direction, "return -n 3".into(), &location)
+                .await
+                .unwrap();
+            env.process_substs.push(subst);
+
+            release(&mut env, 0);
+            assert_eq!(env.process_substs, []);
+            let fd = subst.fd.unwrap();
+            assert_eq!(env.system.fcntl_getfd(fd), Err(Errno::EBADF));
+            assert_eq!(env.jobs.len(), 0);
+        })
+    }
+}
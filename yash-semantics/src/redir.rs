@@ -41,7 +41,7 @@
 //! - `Pipe`: Opens a pipe, regarding the expanded field as a
 //!   non-negative decimal integer denoting a file descriptor to become the
 //!   reading end of the pipe. The target file descriptor will be the writing
-//!   end.
+//!   end. It is an error if the two file descriptors are the same.
 //! - `String`: Opens a readable file descriptor from which you can read the
 //!   expanded field followed by a newline character.
 //!
@@ -53,6 +53,11 @@
 //! implementation uses an unnamed temporary file for the file descriptor, but
 //! we may change the behavior in the future.
 //!
+//! If the body is `Process`, the redirection [starts a
+//! subshell](crate::process_subst) and makes the target file descriptor the
+//! pipe end connected to the subshell. The subshell is awaited when the
+//! command performing the redirection finishes.
+//!
 //! # Performing redirections
 //!
 //! To perform redirections, you need to wrap an [`Env`] in a [`RedirGuard`]
@@ -88,6 +93,7 @@ use std::ops::DerefMut;
 use thiserror::Error;
 use yash_env::io::Fd;
 use yash_env::io::MIN_INTERNAL_FD;
+use yash_env::job::ProcessSubst;
 use yash_env::option::Option::Clobber;
 use yash_env::option::State::Off;
 use yash_env::semantics::ExitStatus;
@@ -97,6 +103,7 @@ use yash_env::system::FdFlag;
 use yash_env::system::Mode;
 use yash_env::system::OFlag;
 use yash_env::system::SFlag;
+use yash_env::system::SystemEx as _;
 use yash_env::Env;
 use yash_env::System;
 use yash_quote::quoted;
@@ -109,6 +116,8 @@ use yash_syntax::syntax::Redir;
 use yash_syntax::syntax::RedirBody;
 use yash_syntax::syntax::RedirOp;
 use yash_syntax::syntax::Unquote;
+use yash_syntax::syntax::Word;
+use yash_syntax::syntax::WordUnit;
 
 /// Record of saving an open file description in another file descriptor.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -161,9 +170,18 @@ pub enum ErrorCause {
     #[error("{0} is not a writable file descriptor")]
     UnwritableFd(Fd),
 
-    /// Error preparing a temporary file to save here-document content
+    /// Error preparing a temporary file to save here-document or here-string
+    /// content
     #[error("cannot prepare temporary file for here-document: {0}")]
     TemporaryFileUnavailable(Errno),
+
+    /// Error opening a pipe for `>>|`
+    #[error("cannot open a pipe: {0}")]
+    PipeUnavailable(Errno),
+
+    /// `>>|` whose operand is the same as the target file descriptor
+    #[error("file descriptor {0} cannot be both ends of a pipe")]
+    SameFdPipe(Fd),
 }
 
 impl ErrorCause {
@@ -180,6 +198,7 @@ impl ErrorCause {
             MalformedFd(_, _) => "not a valid file descriptor",
             UnreadableFd(_) | UnwritableFd(_) => "cannot copy file descriptor",
             TemporaryFileUnavailable(_) => "cannot prepare here-document",
+            PipeUnavailable(_) | SameFdPipe(_) => "cannot open a pipe",
         }
     }
 
@@ -197,7 +216,8 @@ impl ErrorCause {
             MalformedFd(value, error) => format!("{value}: {error}").into(),
             UnreadableFd(fd) => format!("{fd}: not a readable file descriptor").into(),
             UnwritableFd(fd) => format!("{fd}: not a writable file descriptor").into(),
-            TemporaryFileUnavailable(errno) => errno.desc().into(),
+            TemporaryFileUnavailable(errno) | PipeUnavailable(errno) => errno.desc().into(),
+            SameFdPipe(fd) => format!("{fd}: same as the writing end of the pipe").into(),
         }
     }
 }
@@ -269,6 +289,32 @@ fn is_cloexec(env: &Env, fd: Fd) -> bool {
     matches!(env.system.fcntl_getfd(fd), Ok(flags) if flags.contains(FdFlag::FD_CLOEXEC))
 }
 
+/// Saves the open file description at `fd` to a new FD.
+///
+/// The new FD is used to restore `fd` when undoing the redirection. It is an
+/// error if `fd` has the CLOEXEC flag.
+fn save_fd(env: &mut Env, fd: Fd, location: &Location) -> Result<SavedFd, Error> {
+    // Make sure fd doesn't have the CLOEXEC flag
+    if is_cloexec(env, fd) {
+        return Err(Error {
+            cause: ErrorCause::ReservedFd(fd),
+            location: location.clone(),
+        });
+    }
+
+    let save = match env.system.dup(fd, MIN_INTERNAL_FD, FdFlag::FD_CLOEXEC) {
+        Ok(save_fd) => Some(save_fd),
+        Err(Errno::EBADF) => None,
+        Err(errno) => {
+            return Err(Error {
+                cause: ErrorCause::FdNotOverwritten(fd, errno),
+                location: location.clone(),
+            })
+        }
+    };
+    Ok(SavedFd { original: fd, save })
+}
+
 fn into_c_string_value_and_origin(field: Field) -> Result<(CString, Location), Error> {
     match CString::new(field.value) {
         Ok(value) => Ok((value, field.origin)),
@@ -401,11 +447,74 @@ fn copy_fd(
     Ok((FdSpec::Borrowed(fd), target.origin))
 }
 
+/// Opens a pipe for `>>|`.
+///
+/// The reading end of the pipe is placed at the FD specified by the operand,
+/// which is saved in `saved_fds` so that it can be restored later. The writing
+/// end is returned. The operand must differ from `target_fd`, which is where
+/// the caller places the writing end.
+fn open_pipe(
+    env: &mut Env,
+    target_fd: Fd,
+    reader_fd: Field,
+    saved_fds: &mut Vec<SavedFd>,
+) -> Result<(FdSpec, Location), Error> {
+    let location = reader_fd.origin;
+    let reader_fd = match reader_fd.value.parse() {
+        Ok(number) => Fd(number),
+        Err(error) => {
+            return Err(Error {
+                cause: ErrorCause::MalformedFd(reader_fd.value, error),
+                location,
+            })
+        }
+    };
+    if reader_fd == target_fd {
+        return Err(Error {
+            cause: ErrorCause::SameFdPipe(reader_fd),
+            location,
+        });
+    }
+
+    saved_fds.push(save_fd(env, reader_fd, &location)?);
+
+    let pipe_error = |errno| Error {
+        cause: ErrorCause::PipeUnavailable(errno),
+        location: location.clone(),
+    };
+    let (reader, writer) = env.system.pipe().map_err(pipe_error)?;
+    // Move the pipe ends so that they do not collide with reader_fd
+    let reader = env.system.move_fd_internal(reader);
+    let writer = env.system.move_fd_internal(writer);
+    let (reader, writer) = match (reader, writer) {
+        (Ok(reader), Ok(writer)) => (reader, writer),
+        (Ok(fd), Err(errno)) | (Err(errno), Ok(fd)) => {
+            env.system.close(fd).ok();
+            return Err(pipe_error(errno));
+        }
+        (Err(errno), Err(_)) => return Err(pipe_error(errno)),
+    };
+
+    let dup_result = env.system.dup2(reader, reader_fd);
+    env.system.close(reader).ok();
+    if let Err(errno) = dup_result {
+        env.system.close(writer).ok();
+        return Err(Error {
+            cause: ErrorCause::FdNotOverwritten(reader_fd, errno),
+            location,
+        });
+    }
+
+    Ok((FdSpec::Owned(writer), location))
+}
+
 /// Opens the file for a normal redirection.
 async fn open_normal(
     env: &mut Env,
+    target_fd: Fd,
     operator: RedirOp,
     operand: Field,
+    saved_fds: &mut Vec<SavedFd>,
 ) -> Result<(FdSpec, Location), Error> {
     use RedirOp::*;
     match operator {
@@ -424,8 +533,18 @@ async fn open_normal(
         FileInOut => open_file(env, OFlag::O_RDWR | OFlag::O_CREAT, operand),
         FdIn => copy_fd(env, operand, OFlag::O_RDONLY),
         FdOut => copy_fd(env, operand, OFlag::O_WRONLY),
-        Pipe => todo!("pipe redirection: {:?}", operand.value),
-        String => todo!("here-string: {:?}", operand.value),
+        Pipe => open_pipe(env, target_fd, operand, saved_fds),
+        String => {
+            let mut content = operand.value;
+            content.push('\n');
+            match here_doc::open_fd(env, content).await {
+                Ok(fd) => Ok((FdSpec::Owned(fd), operand.origin)),
+                Err(cause) => Err(Error {
+                    cause,
+                    location: operand.origin,
+                }),
+            }
+        }
     }
 }
 
@@ -452,39 +571,54 @@ fn trace_here_doc(xtrace: Option<&mut XTrace>, target_fd: Fd, here_doc: &HereDoc
     }
 }
 
+/// Starts the subshell for a process redirection.
+async fn open_process(env: &mut Env, word: &Word) -> Result<(FdSpec, Location), Error> {
+    let location = word.location.clone();
+    let [WordUnit::ProcessSubst {
+        direction, content, ..
+    }] = &word.units[..]
+    else {
+        panic!("invalid process redirection: {word}");
+    };
+    let content = std::rc::Rc::clone(content);
+    match crate::process_subst::start(env, *direction, content, &location).await {
+        Ok(subst) => {
+            let fd = subst.fd.unwrap();
+            // The pipe end is moved to the target FD and closed by the caller.
+            env.process_substs.push(ProcessSubst { fd: None, ..subst });
+            Ok((FdSpec::Owned(fd), location))
+        }
+        Err(errno) => Err(Error {
+            cause: crate::expansion::ErrorCause::ProcessSubstError(errno).into(),
+            location,
+        }),
+    }
+}
+
+/// Prepares xtrace for a process redirection.
+fn trace_process(xtrace: Option<&mut XTrace>, target_fd: Fd, word: &Word) {
+    if let Some(xtrace) = xtrace {
+        write!(xtrace.redirs(), "{target_fd}{word} ").unwrap();
+    }
+}
+
 mod here_doc;
 
 /// Performs a redirection.
+///
+/// The returned `SavedFd` is for the target FD. If the redirection modifies
+/// another FD, it is saved in `saved_fds`.
 #[allow(clippy::await_holding_refcell_ref)]
 async fn perform(
     env: &mut Env,
     redir: &Redir,
     xtrace: Option<&mut XTrace>,
+    saved_fds: &mut Vec<SavedFd>,
 ) -> Result<(SavedFd, Option<ExitStatus>), Error> {
     let target_fd = redir.fd_or_default();
 
-    // Make sure target_fd doesn't have the CLOEXEC flag
-    if is_cloexec(env, target_fd) {
-        return Err(Error {
-            cause: ErrorCause::ReservedFd(target_fd),
-            location: redir.body.operand().location.clone(),
-        });
-    }
-
     // Save the current open file description at target_fd to a new FD
-    let save = match env
-        .system
-        .dup(target_fd, MIN_INTERNAL_FD, FdFlag::FD_CLOEXEC)
-    {
-        Ok(save_fd) => Some(save_fd),
-        Err(Errno::EBADF) => None,
-        Err(errno) => {
-            return Err(Error {
-                cause: ErrorCause::FdNotOverwritten(target_fd, errno),
-                location: redir.body.operand().location.clone(),
-            })
-        }
-    };
+    let saved_fd = save_fd(env, target_fd, &redir.body.operand().location)?;
 
     // Prepare an FD from the redirection body
     let (fd_spec, location, exit_status) = match &redir.body {
@@ -492,7 +626,8 @@ async fn perform(
             // TODO perform pathname expansion if applicable
             let (expansion, exit_status) = expand_word(env, operand).await?;
             trace_normal(xtrace, target_fd, *operator, &expansion);
-            let (fd, location) = open_normal(env, *operator, expansion).await?;
+            let (fd, location) =
+                open_normal(env, target_fd, *operator, expansion, saved_fds).await?;
             (fd, location, exit_status)
         }
         RedirBody::HereDoc(here_doc) => {
@@ -506,6 +641,11 @@ async fn perform(
                 Err(cause) => return Err(Error { cause, location }),
             }
         }
+        RedirBody::Process(word) => {
+            trace_process(xtrace, target_fd, word);
+            let (fd, location) = open_process(env, word).await?;
+            (fd, location, None)
+        }
     };
 
     if let Some(fd) = fd_spec.as_fd() {
@@ -526,8 +666,7 @@ async fn perform(
         let _: Result<(), Errno> = env.system.close(target_fd);
     }
 
-    let original = target_fd;
-    Ok((SavedFd { original, save }, exit_status))
+    Ok((saved_fd, exit_status))
 }
 
 /// `Env` wrapper for performing redirections.
@@ -594,7 +733,7 @@ impl<'e> RedirGuard<'e> {
         redir: &Redir,
         xtrace: Option<&mut XTrace>,
     ) -> Result<Option<ExitStatus>, Error> {
-        let (saved_fd, exit_status) = perform(self, redir, xtrace).await?;
+        let (saved_fd, exit_status) = perform(self.env, redir, xtrace, &mut self.saved_fds).await?;
         self.saved_fds.push(saved_fd);
         Ok(exit_status)
     }
@@ -928,6 +1067,22 @@ mod tests {
         })
     }
 
+    #[test]
+    fn process_redirection() {
+        in_virtual_system(|mut env, _state| async move {
+            env.builtins.insert("echo", echo_builtin());
+            let mut env = RedirGuard::new(&mut env);
+            let redir = "3<(echo ok)".parse().unwrap();
+            let result = env.perform_redir(&redir, None).await;
+            assert_eq!(result, Ok(None));
+            assert_matches!(env.process_substs[..], [ProcessSubst { fd: None, .. }]);
+
+            let mut buffer = [0; 8];
+            let count = env.system.read_async(Fd(3), &mut buffer).await.unwrap();
+            assert_eq!(&buffer[..count], b"ok\n");
+        })
+    }
+
     #[test]
     fn exit_status_of_command_substitution_in_here_doc() {
         in_virtual_system(|mut env, _state| async move {
@@ -1555,4 +1710,97 @@ mod tests {
         let write_count = env.system.write(Fd(1), &[0x20]).unwrap();
         assert_eq!(write_count, 1);
     }
+
+    #[test]
+    fn here_string_opens_readable_fd() {
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut env = RedirGuard::new(&mut env);
+        let redir = "3<<< 'hello world'".parse().unwrap();
+        env.perform_redir(&redir, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+
+        let mut buffer = [0; 20];
+        let read_count = env.system.read(Fd(3), &mut buffer).unwrap();
+        assert_eq!(&buffer[..read_count], b"hello world\n");
+    }
+
+    #[test]
+    fn xtrace_here_string() {
+        let mut xtrace = XTrace::new();
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut env = RedirGuard::new(&mut env);
+        env.perform_redir(&"<<< 'a b'".parse().unwrap(), Some(&mut xtrace))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let result = xtrace.finish(&mut env).now_or_never().unwrap();
+        assert_eq!(result, "0<<<'a b'\n");
+    }
+
+    #[test]
+    fn pipe_connects_target_fd_to_operand_fd() {
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut env = RedirGuard::new(&mut env);
+        let redir = "3>>| 4".parse().unwrap();
+        env.perform_redir(&redir, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+
+        env.system.write(Fd(3), &[1, 2, 3]).unwrap();
+        let mut buffer = [0; 4];
+        let read_count = env.system.read(Fd(4), &mut buffer).unwrap();
+        assert_eq!(&buffer[..read_count], [1, 2, 3]);
+    }
+
+    #[test]
+    fn undoing_pipe_restores_operand_fd() {
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut redir_env = RedirGuard::new(&mut env);
+        let redir = ">>|4".parse().unwrap();
+        redir_env
+            .perform_redir(&redir, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        redir_env.undo_redirs();
+        drop(redir_env);
+
+        let e = env.system.fcntl_getfl(Fd(4)).unwrap_err();
+        assert_eq!(e, Errno::EBADF);
+        let write_count = env.system.write(Fd::STDOUT, &[0x20]).unwrap();
+        assert_eq!(write_count, 1);
+    }
+
+    #[test]
+    fn pipe_rejects_same_fd_for_both_ends() {
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut env = RedirGuard::new(&mut env);
+        let redir = "3>>|3".parse().unwrap();
+        let e = env
+            .perform_redir(&redir, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(e.cause, ErrorCause::SameFdPipe(Fd(3)));
+        assert_eq!(e.location, redir.body.operand().location);
+    }
+
+    #[test]
+    fn pipe_rejects_malformed_fd() {
+        let mut env = Env::with_system(Box::new(system_with_nofile_limit()));
+        let mut env = RedirGuard::new(&mut env);
+        let redir = ">>|x".parse().unwrap();
+        let e = env
+            .perform_redir(&redir, None)
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert_matches!(e.cause, ErrorCause::MalformedFd(value, _) => {
+            assert_eq!(value, "x");
+        });
+        assert_eq!(e.location, redir.body.operand().location);
+    }
 }
@@ -19,16 +19,27 @@
 use crate::command::Command;
 use crate::trap::run_traps_for_caught_signals;
 use crate::Handle;
+use futures_util::future::select;
+use futures_util::future::Either;
 use std::cell::Cell;
-use std::ops::ControlFlow::Continue;
+use std::cell::RefCell;
+use std::ops::ControlFlow::{Break, Continue};
+use std::pin::pin;
 use std::rc::Rc;
+use yash_env::history;
+use yash_env::option::Option::ExtendedGlob;
+use yash_env::option::Option::PosixlyCorrect;
 use yash_env::option::Option::Verbose;
 use yash_env::option::State;
+use yash_env::semantics::Divert;
 use yash_env::semantics::ExitStatus;
 use yash_env::semantics::Result;
+use yash_env::trap::Signal::SIGINT;
 use yash_env::Env;
 use yash_syntax::parser::lex::Lexer;
+use yash_syntax::parser::ErrorCause;
 use yash_syntax::parser::Parser;
+use yash_syntax::syntax::List;
 
 /// Read-eval-loop
 ///
@@ -47,8 +58,30 @@ use yash_syntax::parser::Parser;
 /// are updated](Env::update_all_subshell_statuses) between parsing input and
 /// running commands.
 ///
-/// TODO: `Break(Divert::Interrupt(...))` should not end the loop in an
-/// interactive shell
+/// The environment is shared with the loop through a `RefCell` so that the
+/// input function of the lexer can also access the environment while the
+/// parser is reading a command line. This allows the input function to, for
+/// example, expand and print a prompt string. The loop does not borrow the
+/// environment while parsing.
+///
+/// # Interactive mode
+///
+/// By default, the loop ends when a syntax error occurs or a command results
+/// in a `Break(Divert::...)`. In [interactive mode](Self::set_interactive),
+/// the loop instead discards the rest of the current command line, updates the
+/// exit status, runs pending traps, and goes on to read the next command line.
+/// Only `Divert::Exit`, `Divert::Abort`, an input error, or the end of input
+/// ends the loop.
+///
+/// In interactive mode, the loop [flushes](history::flush) the command history
+/// after reading each command line so that the command line is written to the
+/// history file before it is executed.
+///
+/// In interactive mode, the loop also watches for `SIGINT` while reading a
+/// command line. When `SIGINT` is caught, the partially entered command line
+/// is discarded and the loop prompts for a new one. For this to work, the
+/// signal must be [caught](yash_env::trap::TrapSet::enable_terminator_handlers)
+/// by the shell.
 ///
 /// # Example
 ///
@@ -59,31 +92,34 @@ use yash_syntax::parser::Parser;
 /// # use yash_semantics::*;
 /// # use yash_syntax::parser::lex::Lexer;
 /// # use yash_syntax::source::Source;
+/// # use std::cell::RefCell;
 /// let mut env = Env::new_virtual();
 /// let mut lexer = Lexer::from_memory("case foo in (bar) ;; esac", Source::Unknown);
-/// let result = ReadEvalLoop::new(&mut env, &mut lexer).run().await;
+/// let result = ReadEvalLoop::new(&RefCell::new(&mut env), &mut lexer).run().await;
 /// assert_eq!(result, Continue(()));
 /// assert_eq!(env.exit_status, ExitStatus::SUCCESS);
 /// # })
 /// ```
 #[derive(Debug)]
-pub struct ReadEvalLoop<'a, 'b> {
-    env: &'a mut Env,
-    lexer: &'a mut Lexer<'b>,
+pub struct ReadEvalLoop<'a, 'b, 'c> {
+    env: &'a RefCell<&'b mut Env>,
+    lexer: &'a mut Lexer<'c>,
     verbose: Option<Rc<Cell<State>>>,
+    interactive: bool,
 }
 
-impl<'a, 'b> ReadEvalLoop<'a, 'b> {
+impl<'a, 'b, 'c> ReadEvalLoop<'a, 'b, 'c> {
     /// Creates a new read-eval-loop instance.
     ///
     /// This constructor requires two parameters: an environment in which the
     /// loop runs and a lexer that reads input.
     #[must_use]
-    pub fn new(env: &'a mut Env, lexer: &'a mut Lexer<'b>) -> Self {
+    pub fn new(env: &'a RefCell<&'b mut Env>, lexer: &'a mut Lexer<'c>) -> Self {
         Self {
             env,
             lexer,
             verbose: None,
+            interactive: false,
         }
     }
 
@@ -98,7 +134,7 @@ impl<'a, 'b> ReadEvalLoop<'a, 'b> {
     ///
     /// ```
     /// # futures_executor::block_on(async {
-    /// # use std::cell::Cell;
+    /// # use std::cell::{Cell, RefCell};
     /// # use std::num::NonZeroU64;
     /// # use std::rc::Rc;
     /// # use yash_env::Env;
@@ -114,7 +150,8 @@ impl<'a, 'b> ReadEvalLoop<'a, 'b> {
     /// input.set_echo(Some(Rc::clone(&verbose)));
     /// let line = NonZeroU64::new(1).unwrap();
     /// let mut lexer = Lexer::new(input, line, Source::Stdin);
-    /// let mut rel = ReadEvalLoop::new(&mut env, &mut lexer);
+    /// let env = RefCell::new(&mut env);
+    /// let mut rel = ReadEvalLoop::new(&env, &mut lexer);
     /// rel.set_verbose(Some(Rc::clone(&verbose)));
     /// let _ = rel.run().await;
     /// # })
@@ -125,7 +162,21 @@ impl<'a, 'b> ReadEvalLoop<'a, 'b> {
         self.verbose = verbose;
     }
 
+    /// Enables or disables the interactive mode of the loop.
+    ///
+    /// See the [type documentation](Self#interactive-mode) for how the
+    /// interactive mode affects the behavior of the loop. The interactive mode
+    /// is disabled by default. It should be enabled only for the main loop of
+    /// an interactive shell, not for loops run by the `eval` or `.` built-in.
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+    }
+
     /// Runs the read-eval-loop.
+    // The environment is borrowed across await points while executing a
+    // command. This is fine because the input function is not called during
+    // the execution.
+    #[allow(clippy::await_holding_refcell_ref)]
     pub async fn run(self) -> Result {
         let mut executed = false;
 
@@ -134,37 +185,125 @@ impl<'a, 'b> ReadEvalLoop<'a, 'b> {
                 self.lexer.flush();
             }
             if let Some(verbose) = &self.verbose {
-                verbose.set(self.env.options.get(Verbose));
+                verbose.set(self.env.borrow().options.get(Verbose));
             }
 
-            let mut parser = Parser::new(self.lexer, &self.env.aliases);
-            match parser.command_line().await {
-                Ok(Some(command)) => {
-                    run_traps_for_caught_signals(self.env).await?;
-                    self.env.update_all_subshell_statuses();
-                    command.execute(self.env).await?
+            // The alias set is copied so that the environment is not borrowed
+            // while the input function may be accessing it. Aliases defined
+            // in a command line do not affect the parsing of the same command
+            // line anyway.
+            let aliases = self.env.borrow().aliases.clone();
+            let posixly_correct = self.env.borrow().options.get(PosixlyCorrect) == State::On;
+            let extended_glob = self.env.borrow().options.get(ExtendedGlob) == State::On;
+            self.lexer.set_extended_glob(extended_glob);
+            let mut parser = Parser::new(self.lexer, &aliases);
+            parser.set_posixly_correct(posixly_correct);
+            let result = if self.interactive {
+                read_interruptibly(self.env, &mut parser).await
+            } else {
+                Some(parser.command_line().await)
+            };
+
+            let env = &mut **self.env.borrow_mut();
+            if self.interactive {
+                history::flush(env);
+            }
+            let result = match result {
+                Some(Ok(Some(command))) => {
+                    let result = run_traps_for_caught_signals(env).await;
+                    if result.is_continue() {
+                        env.update_all_subshell_statuses();
+                        command.execute(env).await
+                    } else {
+                        result
+                    }
+                }
+                Some(Ok(None)) => break,
+                Some(Err(error)) => {
+                    let result = error.handle(env).await;
+                    if matches!(error.cause, ErrorCause::Io(_)) {
+                        // We cannot expect the input to recover from the error.
+                        return result;
+                    }
+                    result
+                }
+                None => {
+                    env.system.print_error("\n").await;
+                    Break(Divert::Interrupt(Some(ExitStatus::from(SIGINT))))
                 }
-                Ok(None) => break,
-                Err(error) => error.handle(self.env).await?,
             };
             executed = true;
+
+            match result {
+                Continue(()) => (),
+                Break(Divert::Exit(_) | Divert::Abort(_)) => return result,
+                Break(divert) if self.interactive => {
+                    if let Some(exit_status) = divert.exit_status() {
+                        env.exit_status = exit_status;
+                    }
+                    self.lexer.reset();
+                    run_traps_for_caught_signals(env).await?;
+                }
+                Break(_) => return result,
+            }
         }
 
         if !executed {
-            self.env.exit_status = ExitStatus::SUCCESS;
+            self.env.borrow_mut().exit_status = ExitStatus::SUCCESS;
         }
 
         Continue(())
     }
 }
 
+/// Parses a command line, watching for `SIGINT`.
+///
+/// Returns `None` if `SIGINT` is caught before the command line is parsed.
+/// Signals caught while parsing are [remembered in the trap
+/// set](yash_env::trap::TrapSet::catch_signal) so that their traps can run
+/// later.
+async fn read_interruptibly(
+    env: &RefCell<&mut Env>,
+    parser: &mut Parser<'_, '_>,
+) -> Option<std::result::Result<Option<List>, yash_syntax::parser::Error>> {
+    let system = env.borrow().system.clone();
+    let mut caught = Vec::new();
+
+    let result = {
+        let parse = pin!(parser.command_line());
+        let interrupt = pin!(async {
+            loop {
+                let signals = system.wait_for_signals().await;
+                caught.extend(signals.iter().copied());
+                if signals.contains(&SIGINT) {
+                    break;
+                }
+            }
+        });
+        match select(parse, interrupt).await {
+            Either::Left((result, _)) => Some(result),
+            Either::Right(((), _)) => None,
+        }
+    };
+
+    let mut env = env.borrow_mut();
+    for signal in caught {
+        env.traps.catch_signal(signal);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::tests::assert_stderr;
     use crate::tests::assert_stdout;
     use crate::tests::echo_builtin;
+    use crate::tests::exit_builtin;
+    use crate::tests::in_virtual_system;
     use crate::tests::return_builtin;
+    use assert_matches::assert_matches;
+    use async_trait::async_trait;
     use futures_util::FutureExt;
     use std::cell::Cell;
     use std::num::NonZeroU64;
@@ -172,13 +311,18 @@ mod tests {
     use std::rc::Rc;
     use yash_env::input::FdReader;
     use yash_env::io::Fd;
+    use yash_env::job::Pid;
     use yash_env::option::Option::Verbose;
     use yash_env::option::State::{Off, On};
     use yash_env::semantics::Divert;
     use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::SystemState;
     use yash_env::system::r#virtual::VirtualSystem;
     use yash_env::trap::Action;
     use yash_env::trap::Signal;
+    use yash_env::variable::Scope;
+    use yash_syntax::input::Context;
+    use yash_syntax::input::Input;
     use yash_syntax::source::Location;
     use yash_syntax::source::Source;
 
@@ -187,7 +331,8 @@ mod tests {
         let mut env = Env::new_virtual();
         env.exit_status = ExitStatus(5);
         let mut lexer = Lexer::from_memory("", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Continue(()));
         assert_eq!(env.exit_status, ExitStatus::SUCCESS);
@@ -202,7 +347,8 @@ mod tests {
         env.builtins.insert("echo", echo_builtin());
         env.builtins.insert("return", return_builtin());
         let mut lexer = Lexer::from_memory("echo $?; return -n 7", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Continue(()));
         assert_eq!(env.exit_status, ExitStatus(7));
@@ -216,7 +362,8 @@ mod tests {
         let mut env = Env::with_system(Box::new(system));
         env.builtins.insert("echo", echo_builtin());
         let mut lexer = Lexer::from_memory("echo 1\necho 2\necho 3;", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Continue(()));
         assert_stdout(&state, |stdout| assert_eq!(stdout, "1\n2\n3\n"));
@@ -236,13 +383,29 @@ mod tests {
         })));
         env.builtins.insert("echo", echo_builtin());
         let mut lexer = Lexer::from_memory("echo", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Continue(()));
         assert_eq!(env.exit_status, ExitStatus::SUCCESS);
         assert_stdout(&state, |stdout| assert_eq!(stdout, "alias\nok\n"));
     }
 
+    #[test]
+    fn parsing_in_posixly_correct_mode() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        env.options.set(PosixlyCorrect, On);
+        let mut lexer = Lexer::from_memory("case x in x) echo ok;& esac", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Break(Divert::Interrupt(Some(ExitStatus::ERROR))));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
+    }
+
     #[test]
     fn verbose_option() {
         let system = VirtualSystem::new();
@@ -261,7 +424,8 @@ mod tests {
         input.set_echo(Some(Rc::clone(&verbose)));
         let line = NonZeroU64::new(1).unwrap();
         let mut lexer = Lexer::new(input, line, Source::Stdin);
-        let mut rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         rel.set_verbose(Some(Rc::clone(&verbose)));
 
         let result = rel.run().now_or_never().unwrap();
@@ -277,7 +441,8 @@ mod tests {
         let state = Rc::clone(&system.state);
         let mut env = Env::with_system(Box::new(system));
         let mut lexer = Lexer::from_memory(";;", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Break(Divert::Interrupt(Some(ExitStatus::ERROR))));
         assert_stderr(&state, |stderr| assert_ne!(stderr, ""));
@@ -290,7 +455,8 @@ mod tests {
         let mut env = Env::with_system(Box::new(system));
         env.builtins.insert("echo", echo_builtin());
         let mut lexer = Lexer::from_memory(";;\necho !", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Break(Divert::Interrupt(Some(ExitStatus::ERROR))));
         assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
@@ -318,10 +484,187 @@ mod tests {
             .unwrap()
             .raise_signal(Signal::SIGUSR1);
         let mut lexer = Lexer::from_memory("echo $?", Source::Unknown);
-        let rel = ReadEvalLoop::new(&mut env, &mut lexer);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
         let result = rel.run().now_or_never().unwrap();
         assert_eq!(result, Continue(()));
         assert_eq!(env.exit_status, ExitStatus::SUCCESS);
         assert_stdout(&state, |stdout| assert_eq!(stdout, "USR1\n0\n"));
     }
+
+    #[test]
+    fn interactive_syntax_error_does_not_abort_loop() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        let mut lexer = Lexer::from_memory("echo 1; ;; echo 2\necho $?", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_interactive(true);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus::SUCCESS);
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "2\n"));
+        assert_stderr(&state, |stderr| assert_ne!(stderr, ""));
+    }
+
+    #[test]
+    fn interactive_expansion_error_discards_rest_of_command_line() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        let mut lexer =
+            Lexer::from_memory("echo ${x?}; echo 1\necho $?\necho ${x?}", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_interactive(true);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(env.exit_status, ExitStatus::ERROR);
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "2\n"));
+    }
+
+    #[test]
+    fn extended_glob_patterns_in_case_command() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        env.options.set(ExtendedGlob, State::On);
+        let mut lexer = Lexer::from_memory("case ab in @(a|b)b) echo X;; esac", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "X\n"));
+    }
+
+    #[test]
+    fn interactive_loop_flushes_history() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        env.get_or_create_variable("HISTFILE", Scope::Global)
+            .assign("/history", None)
+            .unwrap();
+        history::record(&mut env, "echo 1\n");
+        let mut lexer = Lexer::from_memory("echo 1\n", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_interactive(true);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+
+        let state = state.borrow();
+        let file = state.file_system.get("/history").unwrap();
+        assert_matches!(&file.borrow().body, FileBody::Regular { content, .. } => {
+            assert_eq!(std::str::from_utf8(content), Ok(":echo 1\n"));
+        });
+    }
+
+    #[test]
+    fn interactive_exit_ends_loop() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system));
+        env.builtins.insert("echo", echo_builtin());
+        env.builtins.insert("exit", exit_builtin());
+        let mut lexer = Lexer::from_memory("exit 5\necho !", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_interactive(true);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Break(Divert::Exit(Some(ExitStatus(5)))));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, ""));
+    }
+
+    #[test]
+    fn interactive_running_traps_after_interrupt() {
+        let system = VirtualSystem::new();
+        let state = Rc::clone(&system.state);
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.builtins.insert("echo", echo_builtin());
+        env.traps
+            .set_action(
+                &mut env.system,
+                Signal::SIGUSR1,
+                Action::Command("echo USR1".into()),
+                Location::dummy(""),
+                false,
+            )
+            .unwrap();
+        let mut lexer = Lexer::from_memory(";;", Source::Unknown);
+        let ref_env = RefCell::new(&mut env);
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_interactive(true);
+        let _ = state
+            .borrow_mut()
+            .processes
+            .get_mut(&system.process_id)
+            .unwrap()
+            .raise_signal(Signal::SIGUSR1);
+        let result = rel.run().now_or_never().unwrap();
+        assert_eq!(result, Continue(()));
+        assert_stdout(&state, |stdout| assert_eq!(stdout, "USR1\n"));
+    }
+
+    /// Input that raises `SIGINT` while reading the second line
+    struct InterruptedInput {
+        state: Rc<RefCell<SystemState>>,
+        process_id: Pid,
+        count: usize,
+    }
+
+    #[async_trait(?Send)]
+    impl Input for InterruptedInput {
+        async fn next_line(&mut self, _: &Context) -> yash_syntax::input::Result {
+            self.count += 1;
+            match self.count {
+                1 => Ok("echo \\\n".to_owned()),
+                2 => {
+                    let _ = self
+                        .state
+                        .borrow_mut()
+                        .processes
+                        .get_mut(&self.process_id)
+                        .unwrap()
+                        .raise_signal(Signal::SIGINT);
+                    std::future::pending().await
+                }
+                3 => Ok("echo $?\n".to_owned()),
+                _ => Ok(String::new()),
+            }
+        }
+    }
+
+    #[test]
+    fn interactive_sigint_while_reading() {
+        in_virtual_system(|mut env, state| async move {
+            env.builtins.insert("echo", echo_builtin());
+            env.traps
+                .enable_terminator_handlers(&mut env.system)
+                .unwrap();
+            let process_id = env.main_pid;
+            let input = Box::new(InterruptedInput {
+                state: Rc::clone(&state),
+                process_id,
+                count: 0,
+            });
+            let line = NonZeroU64::new(1).unwrap();
+            let mut lexer = Lexer::new(input, line, Source::Unknown);
+            let ref_env = RefCell::new(&mut env);
+            let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+            rel.set_interactive(true);
+            let result = rel.run().await;
+            assert_eq!(result, Continue(()));
+            let exit_status = ExitStatus::from(Signal::SIGINT);
+            assert_stdout(&state, |stdout| {
+                assert_eq!(stdout, format!("{exit_status}\n"))
+            });
+            assert_stderr(&state, |stderr| assert_eq!(stderr, "\n"));
+        })
+    }
 }
@@ -17,6 +17,7 @@
 //! Running the EXIT trap
 
 use crate::ReadEvalLoop;
+use std::cell::RefCell;
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -52,8 +53,9 @@ pub async fn run_exit_trap(env: &mut Env) {
     let mut env = env.push_frame(Frame::Trap(Condition::Exit));
     let previous_exit_status = env.exit_status;
     // Boxing needed for recursion
+    let ref_env = RefCell::new(&mut *env);
     let future: Pin<Box<dyn Future<Output = Result>>> =
-        Box::pin(ReadEvalLoop::new(&mut env, &mut lexer).run());
+        Box::pin(ReadEvalLoop::new(&ref_env, &mut lexer).run());
     let result = future.await;
     env.exit_status = previous_exit_status;
     env.apply_result(result);
@@ -17,6 +17,7 @@
 //! Running signal traps
 
 use crate::ReadEvalLoop;
+use std::cell::RefCell;
 use std::future::Future;
 use std::ops::ControlFlow::Continue;
 use std::pin::Pin;
@@ -44,8 +45,9 @@ async fn run_trap(env: &mut Env, signal: Signal, code: Rc<str>, origin: Location
     let mut env = env.push_frame(Frame::Trap(Condition::Signal(signal)));
     let previous_exit_status = env.exit_status;
     // Boxing needed for recursion
+    let ref_env = RefCell::new(&mut *env);
     let future: Pin<Box<dyn Future<Output = Result>>> =
-        Box::pin(ReadEvalLoop::new(&mut env, &mut lexer).run());
+        Box::pin(ReadEvalLoop::new(&ref_env, &mut lexer).run());
     let result = future.await;
     env.exit_status = previous_exit_status;
     result
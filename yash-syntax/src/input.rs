@@ -23,11 +23,43 @@ use async_trait::async_trait;
 /// The context is passed to the input function so that it can read the input in a
 /// context-dependent way.
 ///
-/// Currently, this structure is empty. It may be extended to provide with some useful data in
-/// future versions.
-#[derive(Debug, Default)]
+/// Currently, this structure only tells whether the line to be read is the
+/// first line of a command. It may be extended to provide with more useful
+/// data in future versions.
+#[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
-pub struct Context;
+pub struct Context {
+    is_first_line: bool,
+}
+
+impl Default for Context {
+    /// Returns a context for reading the first line of a command.
+    fn default() -> Self {
+        Context {
+            is_first_line: true,
+        }
+    }
+}
+
+impl Context {
+    /// Tests whether the input function is about to read the first line of
+    /// a command.
+    ///
+    /// The result is `true` if the lexer has no pending source code when
+    /// requesting the next line, and `false` if the line being read continues
+    /// a previous line. An interactive shell uses this to choose between the
+    /// `$PS1` and `$PS2` prompts.
+    #[must_use]
+    pub fn is_first_line(&self) -> bool {
+        self.is_first_line
+    }
+
+    /// Sets whether the input function is about to read the first line of
+    /// a command.
+    pub fn set_is_first_line(&mut self, is_first_line: bool) {
+        self.is_first_line = is_first_line;
+    }
+}
 
 /// Error returned by the [Input] function.
 pub type Error = std::io::Error;
@@ -84,7 +116,11 @@ mod tests {
     fn memory_empty_source() {
         let mut input = Memory::new("");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "");
     }
 
@@ -92,10 +128,18 @@ mod tests {
     fn memory_one_line() {
         let mut input = Memory::new("one\n");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "one\n");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "");
     }
 
@@ -103,16 +147,32 @@ mod tests {
     fn memory_three_lines() {
         let mut input = Memory::new("one\ntwo\nthree");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "one\n");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "two\n");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "three");
 
-        let line = input.next_line(&Context).now_or_never().unwrap().unwrap();
+        let line = input
+            .next_line(&Context::default())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
         assert_eq!(line, "");
     }
 }
@@ -24,16 +24,21 @@ use super::error::SyntaxError;
 use super::lex::Keyword::{Case, Esac, In};
 use super::lex::Operator::{Bar, CloseParen, Newline, OpenParen, SemicolonSemicolon};
 use super::lex::TokenId::{self, EndOfInput, Operator, Token};
+use crate::syntax::CaseContinuation;
 use crate::syntax::CaseItem;
 use crate::syntax::CompoundCommand;
 
 impl Parser<'_, '_> {
     /// Parses a case item.
     ///
-    /// Does not parse the optional trailing double semicolon.
+    /// This function also parses the optional terminator (`;;`, `;&`, `;|`, or
+    /// `;;&`) that follows the body. The returned Boolean is true if the
+    /// terminator was present. If not, the item must be the last in the case
+    /// command. The terminators other than `;;` are rejected if the parser is
+    /// [POSIXly-correct](Self::set_posixly_correct).
     ///
     /// Returns `None` if the next token is `esac`.
-    pub async fn case_item(&mut self) -> Result<Option<CaseItem>> {
+    pub async fn case_item(&mut self) -> Result<Option<(CaseItem, bool)>> {
         fn pattern_error_cause(token_id: TokenId) -> SyntaxError {
             match token_id {
                 Token(Some(Esac)) => SyntaxError::EsacAsPattern,
@@ -105,7 +110,28 @@ impl Parser<'_, '_> {
 
         let body = self.maybe_compound_list_boxed().await?;
 
-        Ok(Some(CaseItem { patterns, body }))
+        let (continuation, has_terminator) = match self.peek_token().await?.id {
+            Operator(op) => match CaseContinuation::try_from(op) {
+                Ok(continuation) => {
+                    let terminator = self.take_token_raw().await?;
+                    if self.is_posixly_correct() && op != SemicolonSemicolon {
+                        let cause = SyntaxError::UnsupportedCaseContinuation.into();
+                        let location = terminator.word.location;
+                        return Err(Error { cause, location });
+                    }
+                    (continuation, true)
+                }
+                Err(_) => (CaseContinuation::Break, false),
+            },
+            _ => (CaseContinuation::Break, false),
+        };
+
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation,
+        };
+        Ok(Some((item, has_terminator)))
     }
 
     /// Parses a case conditional construct.
@@ -152,13 +178,11 @@ impl Parser<'_, '_> {
         }
 
         let mut items = Vec::new();
-        while let Some(item) = self.case_item().await? {
+        while let Some((item, has_terminator)) = self.case_item().await? {
             items.push(item);
-
-            if self.peek_token().await?.id != Operator(SemicolonSemicolon) {
+            if !has_terminator {
                 break;
             }
-            self.take_token_raw().await?;
         }
 
         let close = self.take_token_raw().await?;
@@ -216,11 +240,13 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 1);
         assert_eq!(item.patterns[0].to_string(), "foo");
         assert_eq!(item.body.0, []);
 
+        assert!(!has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
         assert_eq!(next.id, EndOfInput);
     }
@@ -231,11 +257,13 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 1);
         assert_eq!(item.patterns[0].to_string(), "foo");
         assert_eq!(item.body.0, []);
 
+        assert!(!has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
         assert_eq!(next.id, EndOfInput);
     }
@@ -246,13 +274,15 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 3);
         assert_eq!(item.patterns[0].to_string(), "1");
         assert_eq!(item.patterns[1].to_string(), "esac");
         assert_eq!(item.patterns[2].to_string(), "$three");
         assert_eq!(item.body.0, []);
 
+        assert!(!has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
         assert_eq!(next.id, EndOfInput);
     }
@@ -263,13 +293,15 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 1);
         assert_eq!(item.patterns[0].to_string(), "foo");
         assert_eq!(item.body.0.len(), 2);
         assert_eq!(item.body.0[0].to_string(), "echo ok");
         assert_eq!(item.body.0[1].to_string(), ":&");
 
+        assert!(!has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
         assert_eq!(next.id, EndOfInput);
     }
@@ -280,13 +312,16 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 1);
         assert_eq!(item.patterns[0].to_string(), "foo");
         assert_eq!(item.body.0, []);
 
+        assert_eq!(item.continuation, CaseContinuation::Break);
+        assert!(has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
-        assert_eq!(next.id, Operator(SemicolonSemicolon));
+        assert_eq!(next.id, EndOfInput);
     }
 
     #[test]
@@ -295,14 +330,91 @@ mod tests {
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
 
-        let item = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
         assert_eq!(item.patterns.len(), 1);
         assert_eq!(item.patterns[0].to_string(), "foo");
         assert_eq!(item.body.0.len(), 1);
         assert_eq!(item.body.0[0].to_string(), ":");
 
+        assert_eq!(item.continuation, CaseContinuation::Break);
+        assert!(has_terminator);
+
+        let next = parser.peek_token().now_or_never().unwrap().unwrap();
+        assert_eq!(next.id, EndOfInput);
+    }
+
+    #[test]
+    fn parser_case_item_with_semicolon_and() {
+        let mut lexer = Lexer::from_memory("foo);&", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(item.patterns.len(), 1);
+        assert_eq!(item.patterns[0].to_string(), "foo");
+        assert_eq!(item.body.0, []);
+        assert_eq!(item.continuation, CaseContinuation::FallThrough);
+        assert!(has_terminator);
+
+        let next = parser.peek_token().now_or_never().unwrap().unwrap();
+        assert_eq!(next.id, EndOfInput);
+    }
+
+    #[test]
+    fn parser_case_item_with_semicolon_bar() {
+        let mut lexer = Lexer::from_memory("foo) :;|", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(item.body.0.len(), 1);
+        assert_eq!(item.continuation, CaseContinuation::Continue);
+        assert!(has_terminator);
+
+        let next = parser.peek_token().now_or_never().unwrap().unwrap();
+        assert_eq!(next.id, EndOfInput);
+    }
+
+    #[test]
+    fn parser_case_item_with_double_semicolon_and() {
+        let mut lexer = Lexer::from_memory("foo)\n;;&", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(item.continuation, CaseContinuation::Continue);
+        assert!(has_terminator);
+
         let next = parser.peek_token().now_or_never().unwrap().unwrap();
-        assert_eq!(next.id, Operator(SemicolonSemicolon));
+        assert_eq!(next.id, EndOfInput);
+    }
+
+    #[test]
+    fn parser_case_item_double_semicolon_in_posixly_correct_mode() {
+        let mut lexer = Lexer::from_memory("foo);;", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+        parser.set_posixly_correct(true);
+
+        let (item, has_terminator) = parser.case_item().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(item.continuation, CaseContinuation::Break);
+        assert!(has_terminator);
+    }
+
+    #[test]
+    fn parser_case_item_semicolon_and_in_posixly_correct_mode() {
+        let mut lexer = Lexer::from_memory("foo) ;&", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+        parser.set_posixly_correct(true);
+
+        let e = parser.case_item().now_or_never().unwrap().unwrap_err();
+        assert_matches!(
+            e.cause,
+            ErrorCause::Syntax(SyntaxError::UnsupportedCaseContinuation)
+        );
+        assert_eq!(*e.location.code.value.borrow(), "foo) ;&");
+        assert_eq!(e.location.range, 5..7);
     }
 
     #[test]
@@ -558,6 +670,27 @@ mod tests {
         assert_eq!(next.id, EndOfInput);
     }
 
+    #[test]
+    fn parser_case_command_many_items_with_various_terminators() {
+        let mut lexer = Lexer::from_memory("case x in a);& b) :;| c);;& d)\nesac", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let result = parser.compound_command().now_or_never().unwrap();
+        let compound_command = result.unwrap().unwrap();
+        assert_matches!(compound_command, CompoundCommand::Case { subject, items } => {
+            assert_eq!(subject.to_string(), "x");
+            assert_eq!(items.len(), 4);
+            assert_eq!(items[0].to_string(), "(a) ;&");
+            assert_eq!(items[1].to_string(), "(b) :;|");
+            assert_eq!(items[2].to_string(), "(c) ;|");
+            assert_eq!(items[3].to_string(), "(d) ;;");
+        });
+
+        let next = parser.peek_token().now_or_never().unwrap().unwrap();
+        assert_eq!(next.id, EndOfInput);
+    }
+
     #[test]
     fn parser_case_command_missing_subject() {
         let mut lexer = Lexer::from_memory(" case  ", Source::Unknown);
@@ -130,6 +130,9 @@ pub struct Parser<'a, 'b> {
     /// here-document operator. After consuming the next newline token, the
     /// parser reads and fills the contents, then clears this list.
     unread_here_docs: Vec<Rc<HereDoc>>,
+
+    /// Whether non-POSIX syntax should be rejected.
+    posixly_correct: bool,
 }
 
 impl<'a, 'b> Parser<'a, 'b> {
@@ -140,9 +143,26 @@ impl<'a, 'b> Parser<'a, 'b> {
             aliases,
             token: None,
             unread_here_docs: vec![],
+            posixly_correct: false,
         }
     }
 
+    /// Sets whether the parser rejects syntax that is not defined in POSIX.
+    ///
+    /// This is `false` by default. When set to `true`, the parser returns an
+    /// error for some extensions such as the `;&` case item terminator.
+    pub fn set_posixly_correct(&mut self, posixly_correct: bool) {
+        self.posixly_correct = posixly_correct;
+    }
+
+    /// Returns whether the parser rejects syntax that is not defined in POSIX.
+    ///
+    /// See [`set_posixly_correct`](Self::set_posixly_correct).
+    #[must_use]
+    pub fn is_posixly_correct(&self) -> bool {
+        self.posixly_correct
+    }
+
     /// Reads a next token if the current token is `None`.
     async fn require_token(&mut self) {
         #[allow(clippy::question_mark)] // TODO https://github.com/rust-lang/rust-clippy/issues/9518
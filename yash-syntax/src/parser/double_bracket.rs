@@ -0,0 +1,377 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2020 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Syntax parser for double-bracket command
+
+use super::core::Parser;
+use super::core::Result;
+use super::error::Error;
+use super::error::SyntaxError;
+use super::lex::Keyword::{Bang, OpenBracketBracket};
+use super::lex::Operator::{AndAnd, Bar, BarBar, CloseParen, Greater, Less, OpenParen};
+use super::lex::Token;
+use super::lex::TokenId::{EndOfInput, Operator, Token as WordToken};
+use crate::syntax::BinaryCondOp;
+use crate::syntax::CompoundCommand;
+use crate::syntax::CondExpr;
+use crate::syntax::MaybeLiteral;
+use crate::syntax::UnaryCondOp;
+use crate::syntax::Word;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Tests whether the token is the `]]` that closes the command.
+fn is_closing(token: &Token) -> bool {
+    matches!(token.id, WordToken(_)) && token.word.to_string_if_literal().as_deref() == Some("]]")
+}
+
+/// Returns the binary operator the token represents, if any.
+fn binary_operator(token: &Token) -> Option<BinaryCondOp> {
+    match token.id {
+        WordToken(_) => token.word.to_string_if_literal()?.parse().ok(),
+        Operator(Less) => Some(BinaryCondOp::Less),
+        Operator(Greater) => Some(BinaryCondOp::Greater),
+        _ => None,
+    }
+}
+
+/// Returns an error for a token that cannot start an expression.
+fn primary_error(token: Token) -> Error {
+    let cause = match token.id {
+        EndOfInput | Operator(AndAnd | BarBar | CloseParen) => SyntaxError::MissingCondExpr,
+        WordToken(_) if is_closing(&token) => SyntaxError::MissingCondExpr,
+        _ => SyntaxError::InvalidCondToken,
+    };
+    let location = token.word.location;
+    Error {
+        cause: cause.into(),
+        location,
+    }
+}
+
+impl Parser<'_, '_> {
+    /// Skips newlines in a conditional expression.
+    async fn cond_newlines(&mut self) -> Result<()> {
+        while self.newline_and_here_doc_contents().await? {}
+        Ok(())
+    }
+
+    /// Parses an operand of a conditional operator.
+    async fn cond_operand(&mut self) -> Result<Word> {
+        self.cond_newlines().await?;
+        let token = self.take_token_raw().await?;
+        match token.id {
+            WordToken(_) if !is_closing(&token) => Ok(token.word),
+            _ => {
+                let cause = SyntaxError::MissingCondOperand.into();
+                let location = token.word.location;
+                Err(Error { cause, location })
+            }
+        }
+    }
+
+    /// Parses the right-hand side operand of the `=~` operator.
+    ///
+    /// Unlike other operands, the regular expression may contain unquoted
+    /// parentheses and `|`, `<`, and `>` characters. Adjacent tokens are joined
+    /// into a single word until a blank appears. A `)` is joined only if it
+    /// closes a `(` in the operand.
+    async fn cond_regex(&mut self) -> Result<Word> {
+        self.cond_newlines().await?;
+        let first = self.take_token_raw().await?;
+        let mut depth = 0_usize;
+        match first.id {
+            WordToken(_) if !is_closing(&first) => (),
+            Operator(OpenParen) => depth += 1,
+            _ => {
+                let cause = SyntaxError::MissingCondOperand.into();
+                let location = first.word.location;
+                return Err(Error { cause, location });
+            }
+        }
+        let mut word = first.word;
+
+        while !self.has_blank().await? {
+            let next = self.peek_token().await?;
+            match next.id {
+                WordToken(_) if !is_closing(next) => (),
+                Operator(OpenParen) => depth += 1,
+                Operator(CloseParen) if depth > 0 => depth -= 1,
+                Operator(Bar | Less | Greater) => (),
+                _ => break,
+            }
+            let next = self.take_token_raw().await?;
+            if Rc::ptr_eq(&word.location.code, &next.word.location.code) {
+                word.location.range.end = next.word.location.range.end;
+            }
+            word.units.extend(next.word.units);
+        }
+        Ok(word)
+    }
+
+    /// Parses a primary conditional expression.
+    ///
+    /// A primary expression is a parenthesized expression, a unary operator
+    /// with its operand, a binary operator with its operands, or a single word.
+    async fn cond_primary(&mut self) -> Result<CondExpr> {
+        self.cond_newlines().await?;
+        let token = self.take_token_raw().await?;
+        match token.id {
+            Operator(OpenParen) => {
+                let expr = self.cond_or_boxed().await?;
+                self.cond_newlines().await?;
+                let close = self.take_token_raw().await?;
+                if close.id != Operator(CloseParen) {
+                    let opening_location = token.word.location;
+                    let cause = SyntaxError::UnclosedParen { opening_location }.into();
+                    let location = close.word.location;
+                    return Err(Error { cause, location });
+                }
+                Ok(CondExpr::Paren(Box::new(expr)))
+            }
+            WordToken(_) if !is_closing(&token) => {
+                self.cond_newlines().await?;
+                let next = self.peek_token().await?;
+                if let Some(op) = binary_operator(next) {
+                    self.take_token_raw().await?;
+                    let rhs = if op == BinaryCondOp::RegexMatch {
+                        self.cond_regex().await?
+                    } else {
+                        self.cond_operand().await?
+                    };
+                    return Ok(CondExpr::Binary(token.word, op, rhs));
+                }
+
+                let unary = token.word.to_string_if_literal();
+                match unary.and_then(|op| op.parse::<UnaryCondOp>().ok()) {
+                    Some(op) => Ok(CondExpr::Unary(op, self.cond_operand().await?)),
+                    None => Ok(CondExpr::Word(token.word)),
+                }
+            }
+            _ => Err(primary_error(token)),
+        }
+    }
+
+    /// Parses a conditional expression optionally negated by `!`.
+    async fn cond_not(&mut self) -> Result<CondExpr> {
+        self.cond_newlines().await?;
+        if self.peek_token().await?.id != WordToken(Some(Bang)) {
+            return self.cond_primary().await;
+        }
+        self.take_token_raw().await?;
+        let expr = self.cond_not_boxed().await?;
+        Ok(CondExpr::Not(Box::new(expr)))
+    }
+
+    /// Like [`cond_not`](Self::cond_not), but returns the future in a pinned box.
+    fn cond_not_boxed(&mut self) -> Pin<Box<dyn Future<Output = Result<CondExpr>> + '_>> {
+        Box::pin(self.cond_not())
+    }
+
+    /// Parses conditional expressions joined by `&&`.
+    async fn cond_and(&mut self) -> Result<CondExpr> {
+        let mut expr = self.cond_not().await?;
+        loop {
+            self.cond_newlines().await?;
+            if self.peek_token().await?.id != Operator(AndAnd) {
+                return Ok(expr);
+            }
+            self.take_token_raw().await?;
+            let rhs = self.cond_not().await?;
+            expr = CondExpr::And(Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    /// Parses conditional expressions joined by `||`.
+    async fn cond_or(&mut self) -> Result<CondExpr> {
+        let mut expr = self.cond_and().await?;
+        loop {
+            self.cond_newlines().await?;
+            if self.peek_token().await?.id != Operator(BarBar) {
+                return Ok(expr);
+            }
+            self.take_token_raw().await?;
+            let rhs = self.cond_and().await?;
+            expr = CondExpr::Or(Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    /// Like [`cond_or`](Self::cond_or), but returns the future in a pinned box.
+    fn cond_or_boxed(&mut self) -> Pin<Box<dyn Future<Output = Result<CondExpr>> + '_>> {
+        Box::pin(self.cond_or())
+    }
+
+    /// Parses a double-bracket conditional command.
+    ///
+    /// The next token must be the `[[` reserved word.
+    ///
+    /// # Panics
+    ///
+    /// If the first token is not `[[`.
+    pub async fn double_bracket(&mut self) -> Result<CompoundCommand> {
+        let open = self.take_token_raw().await?;
+        assert_eq!(open.id, WordToken(Some(OpenBracketBracket)));
+
+        let expr = self.cond_or().await?;
+
+        self.cond_newlines().await?;
+        let close = self.take_token_raw().await?;
+        if !is_closing(&close) {
+            let opening_location = open.word.location;
+            let cause = SyntaxError::UnclosedDoubleBracket { opening_location }.into();
+            let location = close.word.location;
+            return Err(Error { cause, location });
+        }
+
+        Ok(CompoundCommand::DoubleBracket(expr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::error::ErrorCause;
+    use super::super::lex::Lexer;
+    use super::*;
+    use crate::source::Source;
+    use assert_matches::assert_matches;
+    use futures_util::FutureExt;
+
+    fn parse(source: &str) -> Result<CompoundCommand> {
+        let mut lexer = Lexer::from_memory(source, Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+        parser.double_bracket().now_or_never().unwrap()
+    }
+
+    fn parse_expr(source: &str) -> CondExpr {
+        assert_matches!(parse(source), Ok(CompoundCommand::DoubleBracket(expr)) => expr)
+    }
+
+    #[test]
+    fn single_word() {
+        let expr = parse_expr("[[ foo ]]");
+        assert_matches!(expr, CondExpr::Word(word) => assert_eq!(word.to_string(), "foo"));
+    }
+
+    #[test]
+    fn unary_operator() {
+        let expr = parse_expr("[[ -f $file ]]");
+        assert_matches!(expr, CondExpr::Unary(UnaryCondOp::RegularFile, word) => {
+            assert_eq!(word.to_string(), "$file");
+        });
+
+        let expr = parse_expr("[[ -L link ]]");
+        assert_eq!(expr.to_string(), "-h link");
+    }
+
+    #[test]
+    fn binary_operators() {
+        let expr = parse_expr("[[ $a == b* ]]");
+        assert_matches!(expr, CondExpr::Binary(lhs, BinaryCondOp::Match, rhs) => {
+            assert_eq!(lhs.to_string(), "$a");
+            assert_eq!(rhs.to_string(), "b*");
+        });
+
+        let expr = parse_expr("[[ a < b ]]");
+        assert_matches!(expr, CondExpr::Binary(_, BinaryCondOp::Less, _));
+        let expr = parse_expr("[[ 1 -ge 2 ]]");
+        assert_matches!(expr, CondExpr::Binary(_, BinaryCondOp::GreaterEqual, _));
+        let expr = parse_expr("[[ -n = -n ]]");
+        assert_matches!(expr, CondExpr::Binary(_, BinaryCondOp::Match, _));
+    }
+
+    #[test]
+    fn regex_operand_with_parentheses() {
+        let expr = parse_expr("[[ $x =~ ^(a|b)+$ ]]");
+        assert_matches!(expr, CondExpr::Binary(_, BinaryCondOp::RegexMatch, rhs) => {
+            assert_eq!(rhs.to_string(), "^(a|b)+$");
+        });
+
+        let expr = parse_expr("[[ ( $x =~ a) ]]");
+        assert_matches!(expr, CondExpr::Paren(inner) => {
+            assert_matches!(*inner, CondExpr::Binary(_, BinaryCondOp::RegexMatch, rhs) => {
+                assert_eq!(rhs.to_string(), "a");
+            });
+        });
+    }
+
+    #[test]
+    fn logical_operators_and_precedence() {
+        let expr = parse_expr("[[ ! a || b && ( c || d ) ]]");
+        assert_matches!(expr, CondExpr::Or(lhs, rhs) => {
+            assert_matches!(*lhs, CondExpr::Not(_));
+            assert_matches!(*rhs, CondExpr::And(_, rhs) => {
+                assert_matches!(*rhs, CondExpr::Paren(_));
+            });
+        });
+    }
+
+    #[test]
+    fn newlines_in_expression() {
+        let expr = parse_expr("[[ a &&\nb\n]]");
+        assert_eq!(expr.to_string(), "a && b");
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let source = "[[ ! -d \"$dir\" && ( $x =~ ^[0-9]+$ || $y != z ) ]]";
+        let command = parse(source).unwrap();
+        assert_eq!(command.to_string(), source);
+    }
+
+    #[test]
+    fn missing_expression() {
+        let e = parse("[[ ]]").unwrap_err();
+        assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::MissingCondExpr));
+        assert_eq!(e.location.range, 3..5);
+
+        let e = parse("[[ a && ]]").unwrap_err();
+        assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::MissingCondExpr));
+    }
+
+    #[test]
+    fn missing_operand() {
+        let e = parse("[[ -f ]]").unwrap_err();
+        assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::MissingCondOperand));
+        assert_eq!(e.location.range, 6..8);
+    }
+
+    #[test]
+    fn invalid_token() {
+        let e = parse("[[ ; ]]").unwrap_err();
+        assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::InvalidCondToken));
+    }
+
+    #[test]
+    fn unclosed_command() {
+        let e = parse("[[ a b ]]").unwrap_err();
+        assert_matches!(e.cause,
+            ErrorCause::Syntax(SyntaxError::UnclosedDoubleBracket { opening_location }) => {
+            assert_eq!(opening_location.range, 0..2);
+        });
+        assert_eq!(e.location.range, 5..6);
+    }
+
+    #[test]
+    fn unclosed_parenthesis() {
+        let e = parse("[[ ( a ]]").unwrap_err();
+        assert_matches!(e.cause,
+            ErrorCause::Syntax(SyntaxError::UnclosedParen { opening_location }) => {
+            assert_eq!(opening_location.range, 3..4);
+        });
+    }
+}
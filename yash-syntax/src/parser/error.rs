@@ -44,12 +44,17 @@ pub enum SyntaxError {
     UnclosedParam { opening_location: Location },
     /// A parameter expansion lacks a name.
     EmptyParam,
+    /// The index of a parameter expansion lacks a closing `]`.
+    UnclosedIndex { opening_location: Location },
     /// A command substitution started with `$(` but lacks a closing `)`.
     UnclosedCommandSubstitution { opening_location: Location },
     /// A command substitution started with `` ` `` but lacks a closing `` ` ``.
     UnclosedBackquote { opening_location: Location },
     /// An arithmetic expansion lacks a closing `))`.
     UnclosedArith { opening_location: Location },
+    /// A process substitution started with `<(` or `>(` but lacks a closing
+    /// `)`.
+    UnclosedProcessSubstitution { opening_location: Location },
     /// A command begins with an inappropriate keyword or operator token.
     InvalidCommandToken,
     /// A separator is missing between commands.
@@ -58,6 +63,8 @@ pub enum SyntaxError {
     FdOutOfRange,
     /// A redirection operator is missing its operand.
     MissingRedirOperand,
+    /// A process redirection has extra characters after the closing `)`.
+    InvalidProcessRedirection,
     /// A here-document operator is missing its delimiter token.
     MissingHereDocDelimiter,
     // TODO Include the corresponding here-doc operator.
@@ -139,6 +146,16 @@ pub enum SyntaxError {
     UnopenedCase,
     /// A case command is not closed.
     UnclosedCase { opening_location: Location },
+    /// A case item is terminated by `;&`, `;|`, or `;;&` in the POSIXly-correct mode.
+    UnsupportedCaseContinuation,
+    /// A double-bracket command is not closed.
+    UnclosedDoubleBracket { opening_location: Location },
+    /// An expression is missing in a double-bracket command.
+    MissingCondExpr,
+    /// An operand is missing after an operator in a double-bracket command.
+    MissingCondOperand,
+    /// A token that cannot appear in a double-bracket command.
+    InvalidCondToken,
     /// The `(` is not followed by `)` in a function definition.
     UnmatchedParenthesis,
     /// The function body is missing in a function definition command.
@@ -172,13 +189,16 @@ impl SyntaxError {
             UnclosedDoubleQuote { .. } => "The double quote is not closed",
             UnclosedParam { .. } => "The parameter expansion is not closed",
             EmptyParam => "The parameter name is missing",
+            UnclosedIndex { .. } => "The index is not closed",
             UnclosedCommandSubstitution { .. } => "The command substitution is not closed",
             UnclosedBackquote { .. } => "The backquote is not closed",
             UnclosedArith { .. } => "The arithmetic expansion is not closed",
+            UnclosedProcessSubstitution { .. } => "The process substitution is not closed",
             InvalidCommandToken => "The command starts with an inappropriate token",
             MissingSeparator => "A separator is missing between the commands",
             FdOutOfRange => "The file descriptor is too large",
             MissingRedirOperand => "The redirection operator is missing its operand",
+            InvalidProcessRedirection => "The process redirection is followed by extra characters",
             MissingHereDocDelimiter => "The here-document operator is missing its delimiter",
             MissingHereDocContent => "Content of the here-document is missing",
             UnclosedHereDocContent { .. } => {
@@ -217,6 +237,13 @@ impl SyntaxError {
             InvalidPattern => "The pattern is not a valid word token",
             EsacAsPattern => "`esac` cannot be the first of a pattern list",
             UnclosedCase { .. } => "The `case` command is missing its closing `esac`",
+            UnsupportedCaseContinuation => {
+                "The case item terminator is not supported in the POSIXly-correct mode"
+            }
+            UnclosedDoubleBracket { .. } => "The `[[` command is missing its closing `]]`",
+            MissingCondExpr => "The conditional expression is missing",
+            MissingCondOperand => "The conditional operator is missing its operand",
+            InvalidCondToken => "The token cannot be used in the conditional expression",
             UnmatchedParenthesis => "`)` is missing after `(`",
             MissingFunctionBody => "The function body is missing",
             InvalidFunctionBody => "The function body must be a compound command",
@@ -236,6 +263,7 @@ impl SyntaxError {
         match self {
             UnclosedParen { .. }
             | UnclosedCommandSubstitution { .. }
+            | UnclosedProcessSubstitution { .. }
             | UnclosedArrayValue { .. }
             | UnclosedSubshell { .. }
             | UnclosedPatternList
@@ -261,12 +289,14 @@ impl SyntaxError {
             UnclosedDoubleQuote { .. } => "expected `\"`",
             UnclosedParam { .. } | UnclosedGrouping { .. } => "expected `}`",
             EmptyParam => "expected a parameter name",
+            UnclosedIndex { .. } => "expected `]`",
             UnclosedBackquote { .. } => "expected '`'",
             UnclosedArith { .. } => "expected `))`",
             InvalidCommandToken => "does not begin a valid command",
             MissingSeparator => "expected `;` or `&` before this token",
             FdOutOfRange => "unsupported file descriptor",
             MissingRedirOperand => "expected a redirection operand",
+            InvalidProcessRedirection => "unexpected characters after `)`",
             MissingHereDocDelimiter => "expected a delimiter word",
             MissingHereDocContent => "content not found",
             UnclosedHereDocContent { .. } => "missing delimiter",
@@ -287,6 +317,11 @@ impl SyntaxError {
             EsacAsPattern => "needs quoting",
             UnopenedCase => "not in a `case` command",
             UnclosedCase { .. } => "expected `esac`",
+            UnsupportedCaseContinuation => "only `;;` is allowed",
+            UnclosedDoubleBracket { .. } => "expected `]]`",
+            MissingCondExpr => "expected an expression",
+            MissingCondOperand => "expected an operand",
+            InvalidCondToken => "unexpected token",
             MissingFunctionBody | InvalidFunctionBody => "expected a compound command",
             InAsCommandName => "cannot be used as a command name",
             DoubleNegation => "only one `!` allowed",
@@ -311,9 +346,15 @@ impl SyntaxError {
             UnclosedParam { opening_location } => {
                 Some((opening_location, "the parameter started here"))
             }
+            UnclosedIndex { opening_location } => {
+                Some((opening_location, "the opening bracket was here"))
+            }
             UnclosedCommandSubstitution { opening_location } => {
                 Some((opening_location, "the command substitution started here"))
             }
+            UnclosedProcessSubstitution { opening_location } => {
+                Some((opening_location, "the process substitution started here"))
+            }
             UnclosedBackquote { opening_location } => {
                 Some((opening_location, "the opening backquote was here"))
             }
@@ -348,6 +389,9 @@ impl SyntaxError {
             MissingIn { opening_location } | UnclosedCase { opening_location } => {
                 Some((opening_location, "the `case` command started here"))
             }
+            UnclosedDoubleBracket { opening_location } => {
+                Some((opening_location, "the `[[` command started here"))
+            }
             _ => None,
         }
     }
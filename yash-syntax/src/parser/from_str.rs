@@ -240,7 +240,7 @@ impl FromStr for CaseItem {
         let mut lexer = Lexer::from_memory(s, Source::Unknown);
         let aliases = Default::default();
         let mut parser = Parser::new(&mut lexer, &aliases);
-        let item = unwrap_ready(parser.case_item()).shift()?;
+        let (item, _) = unwrap_ready(parser.case_item()).shift()?;
         parser.ensure_no_unread_here_doc()?;
         Ok(item)
     }
@@ -503,6 +503,9 @@ mod tests {
         block_on(async {
             let parse: CaseItem = " foo) ".parse().unwrap();
             assert_eq!(parse.to_string(), "(foo) ;;");
+
+            let parse: CaseItem = "(bar) echo;&".parse().unwrap();
+            assert_eq!(parse.to_string(), "(bar) echo;&");
         })
     }
 
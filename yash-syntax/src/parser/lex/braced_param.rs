@@ -22,8 +22,14 @@ use super::raw_param::is_special_parameter_char;
 use crate::parser::core::Result;
 use crate::parser::error::Error;
 use crate::parser::error::SyntaxError;
+use crate::syntax::Index;
 use crate::syntax::Modifier;
 use crate::syntax::Param;
+use crate::syntax::ParamSubject;
+use crate::syntax::Text;
+use crate::syntax::TextUnit;
+use std::future::Future;
+use std::pin::Pin;
 
 /// Tests if a character can be part of a variable name.
 ///
@@ -80,6 +86,68 @@ impl WordLexer<'_, '_> {
         Ok(has_length_prefix)
     }
 
+    /// Parses a nested expansion that is the subject of a braced parameter
+    /// expansion.
+    ///
+    /// The nested expansion must be a braced parameter expansion, command
+    /// substitution, arithmetic expansion, or backquote. If the next
+    /// characters do not start any of them, no characters are consumed and the
+    /// return value is `Ok(None)`. Note that `$` not followed by `{` or `(` is
+    /// the special parameter rather than the start of a nested expansion.
+    async fn nested_param_subject(&mut self) -> Result<Option<TextUnit>> {
+        match self.peek_char().await? {
+            Some('$') => {
+                let index = self.index();
+                self.consume_char();
+                let next = self.peek_char().await?;
+                self.rewind(index);
+                if !matches!(next, Some('{' | '(')) {
+                    return Ok(None);
+                }
+                // Boxing needed for recursion
+                let unit = Box::pin(self.dollar_unit())
+                    as Pin<Box<dyn Future<Output = Result<Option<TextUnit>>>>>;
+                unit.await
+            }
+            Some('`') => self.backquote().await,
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses an index (`[start]` or `[start,end]`) if any.
+    ///
+    /// If the next character is not `[`, no characters are consumed and the
+    /// return value is `Ok(None)`.
+    async fn param_index(&mut self) -> Result<Option<Index>> {
+        let start_index = self.index();
+        if !self.skip_if(|c| c == '[').await? {
+            return Ok(None);
+        }
+        let opening_location = self.location_range(start_index..self.index());
+        let is_escapable = |c| matches!(c, '$' | '`' | '\\');
+
+        // Boxing needed for recursion
+        let start = Box::pin(self.text_with_parentheses(|c| c == ',' || c == ']', is_escapable))
+            as Pin<Box<dyn Future<Output = Result<Text>>>>;
+        let start = start.await?;
+
+        let end = if self.skip_if(|c| c == ',').await? {
+            let end = Box::pin(self.text_with_parentheses(|c| c == ']', is_escapable))
+                as Pin<Box<dyn Future<Output = Result<Text>>>>;
+            Some(end.await?)
+        } else {
+            None
+        };
+
+        if !self.skip_if(|c| c == ']').await? {
+            let cause = SyntaxError::UnclosedIndex { opening_location }.into();
+            let location = self.location().await?.clone();
+            return Err(Error { cause, location });
+        }
+
+        Ok(Some(Index { start, end }))
+    }
+
     /// Parses a parameter expansion that is enclosed in braces.
     ///
     /// The initial `$` must have been consumed before calling this function.
@@ -100,28 +168,35 @@ impl WordLexer<'_, '_> {
 
         let has_length_prefix = self.length_prefix().await?;
 
-        let c = self.peek_char().await?.unwrap();
-        let name = if is_special_parameter_char(c) {
-            self.consume_char();
-            c.to_string()
-        } else if is_name_char(c) {
-            self.consume_char();
-            let mut name = c.to_string();
-            while let Some(c) = self.consume_char_if(is_name_char).await? {
-                name.push(c.value);
-            }
-            name
-        } else if c == '}' {
-            // TODO Consider merging EmptyParam & UnclosedParam into InvalidParamName
-            let cause = SyntaxError::EmptyParam.into();
-            let location = self.location().await?.clone();
-            return Err(Error { cause, location });
+        let subject = if let Some(unit) = self.nested_param_subject().await? {
+            ParamSubject::Nested(Box::new(unit))
         } else {
-            let cause = SyntaxError::UnclosedParam { opening_location }.into();
-            let location = self.location().await?.clone();
-            return Err(Error { cause, location });
+            let c = self.peek_char().await?.unwrap();
+            let name = if is_special_parameter_char(c) {
+                self.consume_char();
+                c.to_string()
+            } else if is_name_char(c) {
+                self.consume_char();
+                let mut name = c.to_string();
+                while let Some(c) = self.consume_char_if(is_name_char).await? {
+                    name.push(c.value);
+                }
+                name
+            } else if c == '}' {
+                // TODO Consider merging EmptyParam & UnclosedParam into InvalidParamName
+                let cause = SyntaxError::EmptyParam.into();
+                let location = self.location().await?.clone();
+                return Err(Error { cause, location });
+            } else {
+                let cause = SyntaxError::UnclosedParam { opening_location }.into();
+                let location = self.location().await?.clone();
+                return Err(Error { cause, location });
+            };
+            ParamSubject::Name(name)
         };
 
+        let index = self.param_index().await?;
+
         let suffix_location = self.location().await?.clone();
         let suffix = self.suffix_modifier().await?;
 
@@ -142,7 +217,8 @@ impl WordLexer<'_, '_> {
         };
 
         Ok(Some(Param {
-            name,
+            subject,
+            index,
             modifier,
             location: self.location_range(start_index..self.index()),
         }))
@@ -188,7 +264,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "@");
+        assert_eq!(param.subject, ParamSubject::Name("@".to_string()));
         assert_eq!(param.modifier, Modifier::None);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${@};");
@@ -213,7 +289,7 @@ mod tests {
 
         let result = lexer.braced_param(1).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "foo_123");
+        assert_eq!(param.subject, ParamSubject::Name("foo_123".to_string()));
         assert_eq!(param.modifier, Modifier::None);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "X${foo_123}<");
@@ -236,7 +312,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "123");
+        assert_eq!(param.subject, ParamSubject::Name("123".to_string()));
         assert_eq!(param.modifier, Modifier::None);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${123}<");
@@ -259,7 +335,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_eq!(param.modifier, Modifier::None);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${#}<");
@@ -270,6 +346,170 @@ mod tests {
         assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('<')));
     }
 
+    #[test]
+    fn lexer_braced_param_dollar() {
+        let mut lexer = Lexer::from_memory("${$}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_eq!(param.subject, ParamSubject::Name("$".to_string()));
+        assert_eq!(param.modifier, Modifier::None);
+    }
+
+    #[test]
+    fn lexer_braced_param_nested_param() {
+        let mut lexer = Lexer::from_memory("${${foo#x}%y}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_matches!(param.subject, ParamSubject::Nested(unit) => {
+            assert_matches!(*unit, TextUnit::BracedParam(inner) => {
+                assert_eq!(inner.subject, ParamSubject::Name("foo".to_string()));
+                assert_eq!(inner.to_string(), "${foo#x}");
+                assert_eq!(inner.location.range, 2..10);
+            });
+        });
+        assert_matches!(param.modifier, Modifier::Trim(trim) => {
+            assert_eq!(trim.pattern.to_string(), "y");
+        });
+        assert_eq!(param.location.range, 0..13);
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(None));
+    }
+
+    #[test]
+    fn lexer_braced_param_nested_command_substitution_with_length() {
+        let mut lexer = Lexer::from_memory("${#$(echo)}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_matches!(param.subject, ParamSubject::Nested(unit) => {
+            assert_matches!(*unit, TextUnit::CommandSubst { content, .. } => {
+                assert_eq!(&*content, "echo");
+            });
+        });
+        assert_eq!(param.modifier, Modifier::Length);
+    }
+
+    #[test]
+    fn lexer_braced_param_nested_arith_and_backquote() {
+        let mut lexer = Lexer::from_memory("${$((1+2)):-x}${`echo`}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_eq!(param.to_string(), "${$((1+2)):-x}");
+        assert_matches!(param.subject, ParamSubject::Nested(unit) => {
+            assert_matches!(*unit, TextUnit::Arith { .. });
+        });
+
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let result = lexer.braced_param(14).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_matches!(param.subject, ParamSubject::Nested(unit) => {
+            assert_matches!(*unit, TextUnit::Backquote { .. });
+        });
+        assert_eq!(param.modifier, Modifier::None);
+    }
+
+    #[test]
+    fn lexer_braced_param_index() {
+        let mut lexer = Lexer::from_memory("${a[@]}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_eq!(param.subject, ParamSubject::Name("a".to_string()));
+        let index = param.index.unwrap();
+        assert_eq!(index.start.to_string(), "@");
+        assert_eq!(index.end, None);
+        assert_eq!(param.modifier, Modifier::None);
+        assert_eq!(param.location.range, 0..7);
+    }
+
+    #[test]
+    fn lexer_braced_param_index_range_with_modifier() {
+        let mut lexer = Lexer::from_memory("${*[$i, (2)]:-x}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_eq!(param.subject, ParamSubject::Name("*".to_string()));
+        let index = param.index.unwrap();
+        assert_eq!(index.start.to_string(), "$i");
+        assert_eq!(index.end.unwrap().to_string(), " (2)");
+        assert_matches!(param.modifier, Modifier::Switch(_));
+    }
+
+    #[test]
+    fn lexer_braced_param_index_with_length() {
+        let mut lexer = Lexer::from_memory("${#a[-1]}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.braced_param(0).now_or_never().unwrap();
+        let param = result.unwrap().unwrap();
+        assert_eq!(param.subject, ParamSubject::Name("a".to_string()));
+        assert_eq!(param.index.unwrap().start.to_string(), "-1");
+        assert_eq!(param.modifier, Modifier::Length);
+    }
+
+    #[test]
+    fn lexer_braced_param_unclosed_index() {
+        let mut lexer = Lexer::from_memory("${a[1}", Source::Unknown);
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let e = lexer.braced_param(0).now_or_never().unwrap().unwrap_err();
+        assert_matches!(e.cause,
+            ErrorCause::Syntax(SyntaxError::UnclosedIndex { opening_location }) => {
+            assert_eq!(opening_location.range, 3..4);
+        });
+        assert_eq!(e.location.range, 6..6);
+    }
+
     #[test]
     fn lexer_braced_param_missing_name() {
         let mut lexer = Lexer::from_memory("${};", Source::Unknown);
@@ -348,7 +588,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "foo_123");
+        assert_eq!(param.subject, ParamSubject::Name("foo_123".to_string()));
         assert_eq!(param.modifier, Modifier::Length);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${#foo_123}<");
@@ -371,7 +611,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_eq!(param.modifier, Modifier::Length);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${##}<");
@@ -394,7 +634,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "?");
+        assert_eq!(param.subject, ParamSubject::Name("?".to_string()));
         assert_eq!(param.modifier, Modifier::Length);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${#?}<");
@@ -417,7 +657,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "-");
+        assert_eq!(param.subject, ParamSubject::Name("-".to_string()));
         assert_eq!(param.modifier, Modifier::Length);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${#-}<");
@@ -440,7 +680,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "x");
+        assert_eq!(param.subject, ParamSubject::Name("x".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Alter);
             assert_eq!(switch.condition, SwitchCondition::Unset);
@@ -467,7 +707,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "foo");
+        assert_eq!(param.subject, ParamSubject::Name("foo".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Error);
             assert_eq!(switch.condition, SwitchCondition::UnsetOrEmpty);
@@ -494,7 +734,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Alter);
             assert_eq!(switch.condition, SwitchCondition::Unset);
@@ -521,7 +761,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Default);
             assert_eq!(switch.condition, SwitchCondition::Unset);
@@ -548,7 +788,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Assign);
             assert_eq!(switch.condition, SwitchCondition::Unset);
@@ -575,7 +815,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Error);
             assert_eq!(switch.condition, SwitchCondition::Unset);
@@ -602,7 +842,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Switch(switch) => {
             assert_eq!(switch.r#type, SwitchType::Default);
             assert_eq!(switch.condition, SwitchCondition::UnsetOrEmpty);
@@ -629,7 +869,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Trim(trim) => {
             assert_eq!(trim.side, TrimSide::Prefix);
             assert_eq!(trim.length, TrimLength::Longest);
@@ -656,7 +896,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_matches!(param.modifier, Modifier::Trim(trim) => {
             assert_eq!(trim.side, TrimSide::Suffix);
             assert_eq!(trim.length, TrimLength::Shortest);
@@ -699,7 +939,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "a_1");
+        assert_eq!(param.subject, ParamSubject::Name("a_1".to_string()));
         assert_eq!(param.modifier, Modifier::Length);
         // TODO assert about other param members
         assert_eq!(
@@ -725,7 +965,7 @@ mod tests {
 
         let result = lexer.braced_param(0).now_or_never().unwrap();
         let param = result.unwrap().unwrap();
-        assert_eq!(param.name, "#");
+        assert_eq!(param.subject, ParamSubject::Name("#".to_string()));
         assert_eq!(param.modifier, Modifier::None);
         // TODO assert about other param members
         assert_eq!(*param.location.code.value.borrow(), "${#\\\n\\\n}z");
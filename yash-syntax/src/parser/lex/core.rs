@@ -181,7 +181,9 @@ impl<'a> LexerCore<'a> {
             }
 
             // Read more input
-            match self.input.next_line(&Context).await {
+            let mut context = Context::default();
+            context.set_is_first_line(self.raw_code.value.borrow().is_empty());
+            match self.input.next_line(&context).await {
                 Ok(line) => {
                     if line.is_empty() {
                         // End of input
@@ -403,6 +405,7 @@ pub struct Lexer<'a> {
     // skipping to `LexerCore`.
     core: LexerCore<'a>,
     line_continuation_enabled: bool,
+    extended_glob: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -416,6 +419,7 @@ impl<'a> Lexer<'a> {
         Lexer {
             core: LexerCore::new(input, start_line_number, source),
             line_continuation_enabled: true,
+            extended_glob: false,
         }
     }
 
@@ -426,6 +430,25 @@ impl<'a> Lexer<'a> {
         Lexer::new(Box::new(Memory::new(code)), line, source)
     }
 
+    /// Sets whether the lexer recognizes extended glob patterns in tokens.
+    ///
+    /// This is `false` by default. When set to `true`, [`token`](Self::token)
+    /// includes a parenthesized pattern list following an unquoted `?`, `*`,
+    /// `+`, `@`, or `!` in the word, so that a pattern such as `@(a|b)` is
+    /// not split into operators. The shell enables this when the
+    /// `extendedglob` option is on.
+    pub fn set_extended_glob(&mut self, extended_glob: bool) {
+        self.extended_glob = extended_glob;
+    }
+
+    /// Returns whether the lexer recognizes extended glob patterns in tokens.
+    ///
+    /// See [`set_extended_glob`](Self::set_extended_glob).
+    #[must_use]
+    pub fn is_extended_glob(&self) -> bool {
+        self.extended_glob
+    }
+
     /// Disables line continuation recognition onward.
     ///
     /// By default, [`peek_char`](Self::peek_char) silently skips line
@@ -829,6 +852,36 @@ mod tests {
         assert_eq!(e.location.range, 0..0);
     }
 
+    #[test]
+    fn lexer_core_peek_char_context() {
+        struct Recorder(Rc<RefCell<Vec<bool>>>, Memory<'static>);
+        #[async_trait::async_trait(?Send)]
+        impl Input for Recorder {
+            async fn next_line(&mut self, context: &Context) -> crate::input::Result {
+                self.0.borrow_mut().push(context.is_first_line());
+                self.1.next_line(context).await
+            }
+        }
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let input = Recorder(Rc::clone(&log), Memory::new("a\nb\nc\n"));
+        let line = NonZeroU64::new(1).unwrap();
+        let mut lexer = LexerCore::new(Box::new(input), line, Source::Unknown);
+
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        assert_eq!(*log.borrow(), [true, false]);
+
+        lexer.consume_char();
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        lexer.consume_char();
+        lexer.flush();
+        lexer.peek_char().now_or_never().unwrap().unwrap();
+        assert_eq!(*log.borrow(), [true, false, true]);
+    }
+
     #[test]
     fn lexer_core_consume_char_success() {
         let input = Memory::new("a\nb");
@@ -23,9 +23,13 @@ use crate::parser::core::Result;
 use crate::parser::error::Error;
 use crate::parser::error::SyntaxError;
 use crate::syntax::Modifier;
+use crate::syntax::Subst;
+use crate::syntax::SubstMode;
+use crate::syntax::Substring;
 use crate::syntax::Switch;
 use crate::syntax::SwitchCondition;
 use crate::syntax::SwitchType;
+use crate::syntax::Text;
 use crate::syntax::Trim;
 use crate::syntax::TrimLength;
 use crate::syntax::TrimSide;
@@ -89,6 +93,26 @@ impl Lexer<'_> {
             pattern,
         }))
     }
+
+    /// Parses a [substring](Substring), except the initial colon.
+    async fn substring(&mut self) -> Result<Modifier> {
+        let is_escapable = |c| matches!(c, '$' | '`' | '\\');
+
+        // Boxing needed for recursion
+        let offset = Box::pin(self.text_with_parentheses(|c| c == ':' || c == '}', is_escapable))
+            as Pin<Box<dyn Future<Output = Result<Text>>>>;
+        let offset = offset.await?;
+
+        let length = if self.skip_if(|c| c == ':').await? {
+            let length = Box::pin(self.text_with_parentheses(|c| c == '}', is_escapable))
+                as Pin<Box<dyn Future<Output = Result<Text>>>>;
+            Some(length.await?)
+        } else {
+            None
+        };
+
+        Ok(Modifier::Substring(Substring { offset, length }))
+    }
 }
 
 impl WordLexer<'_, '_> {
@@ -127,6 +151,54 @@ impl WordLexer<'_, '_> {
         }))
     }
 
+    /// Parses a [substitution](Subst).
+    ///
+    /// This function blindly consumes the current character, which must be
+    /// `/`.
+    async fn subst(&mut self, start_index: usize, colon: bool) -> Result<Modifier> {
+        self.consume_char();
+        if colon {
+            return self.invalid_modifier(start_index);
+        }
+
+        let mode = match self.peek_char().await? {
+            Some('/') => SubstMode::All,
+            Some('#') => SubstMode::Prefix,
+            Some('%') => SubstMode::Suffix,
+            _ => SubstMode::First,
+        };
+        if mode != SubstMode::First {
+            self.consume_char();
+        }
+
+        let mut lexer = WordLexer {
+            lexer: self.lexer,
+            context: WordContext::Word,
+        };
+        // Boxing needed for recursion
+        let pattern = Box::pin(lexer.word(|c| c == '/' || c == '}'))
+            as Pin<Box<dyn Future<Output = Result<Word>>>>;
+        let mut pattern = pattern.await?;
+        pattern.parse_tilde_front();
+
+        // If the replacement is omitted, the next character should be `}` and
+        // the replacement is an empty word.
+        self.skip_if(|c| c == '/').await?;
+        let replacement =
+            Box::pin(self.word(|c| c == '}')) as Pin<Box<dyn Future<Output = Result<Word>>>>;
+        let mut replacement = replacement.await?;
+        match self.context {
+            WordContext::Text => (),
+            WordContext::Word => replacement.parse_tilde_front(),
+        }
+
+        Ok(Modifier::Subst(Subst {
+            mode,
+            pattern,
+            replacement,
+        }))
+    }
+
     /// Parses a suffix modifier, i.e., a modifier other than the length prefix.
     ///
     /// If there is a [switch](Switch), [`self.context`](Self::context) affects
@@ -143,6 +215,9 @@ impl WordLexer<'_, '_> {
             match symbol {
                 '+' | '-' | '=' | '?' => self.switch(colon, symbol).await,
                 '#' | '%' => self.trim(start_index, colon, symbol).await,
+                '/' => self.subst(start_index, colon).await,
+                '}' => self.suffix_modifier_not_found(start_index, colon),
+                _ if colon => self.substring().await,
                 _ => self.suffix_modifier_not_found(start_index, colon),
             }
         } else {
@@ -551,6 +626,114 @@ mod tests {
         });
     }
 
+    #[test]
+    fn lexer_suffix_modifier_subst_first() {
+        let mut lexer = Lexer::from_memory("/a*/b}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Subst(subst) => {
+            assert_eq!(subst.mode, SubstMode::First);
+            assert_eq!(subst.pattern.to_string(), "a*");
+            assert_eq!(subst.pattern.location.range, 1..3);
+            assert_eq!(subst.replacement.to_string(), "b");
+            assert_eq!(subst.replacement.location.range, 4..5);
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_subst_all_without_replacement() {
+        let mut lexer = Lexer::from_memory("//'/'}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Subst(subst) => {
+            assert_eq!(subst.mode, SubstMode::All);
+            assert_eq!(subst.pattern.units, [WordUnit::SingleQuote("/".to_string())]);
+            assert_eq!(subst.replacement.units, []);
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_subst_prefix() {
+        let mut lexer = Lexer::from_memory("/#x/y/z}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Subst(subst) => {
+            assert_eq!(subst.mode, SubstMode::Prefix);
+            assert_eq!(subst.pattern.to_string(), "x");
+            assert_eq!(subst.replacement.to_string(), "y/z");
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_subst_suffix_with_empty_pattern() {
+        let mut lexer = Lexer::from_memory("/%/~}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Subst(subst) => {
+            assert_eq!(subst.mode, SubstMode::Suffix);
+            assert_eq!(subst.pattern.units, []);
+            assert_eq!(
+                subst.replacement.units,
+                [WordUnit::Tilde("".to_string())]
+            );
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_subst_in_text_context() {
+        let mut lexer = Lexer::from_memory("/~/~}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Text,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Subst(subst) => {
+            assert_eq!(subst.pattern.units, [WordUnit::Tilde("".to_string())]);
+            assert_eq!(
+                subst.replacement.units,
+                [WordUnit::Unquoted(TextUnit::Literal('~'))]
+            );
+        });
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_colon_subst() {
+        let mut lexer = Lexer::from_memory(":/a/b}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let e = lexer.suffix_modifier().now_or_never().unwrap().unwrap_err();
+        assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::InvalidModifier));
+        assert_eq!(e.location.range, 0..2);
+    }
+
     #[test]
     fn lexer_suffix_modifier_orphan_colon_eof() {
         let mut lexer = Lexer::from_memory(r":", Source::Unknown);
@@ -566,8 +749,8 @@ mod tests {
     }
 
     #[test]
-    fn lexer_suffix_modifier_orphan_colon_followed_by_letter() {
-        let mut lexer = Lexer::from_memory(r":x}", Source::Unknown);
+    fn lexer_suffix_modifier_orphan_colon_followed_by_brace() {
+        let mut lexer = Lexer::from_memory(r":}", Source::Unknown);
         let mut lexer = WordLexer {
             lexer: &mut lexer,
             context: WordContext::Word,
@@ -575,10 +758,61 @@ mod tests {
 
         let e = lexer.suffix_modifier().now_or_never().unwrap().unwrap_err();
         assert_eq!(e.cause, ErrorCause::Syntax(SyntaxError::InvalidModifier));
-        assert_eq!(*e.location.code.value.borrow(), ":x}");
+        assert_eq!(*e.location.code.value.borrow(), ":}");
         assert_eq!(e.location.range, 0..1);
     }
 
+    #[test]
+    fn lexer_suffix_modifier_substring_offset() {
+        let mut lexer = Lexer::from_memory(r":x}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Substring(substring) => {
+            assert_eq!(substring.offset, Text(vec![TextUnit::Literal('x')]));
+            assert_eq!(substring.length, None);
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_substring_offset_and_length() {
+        let mut lexer = Lexer::from_memory(r": -1:(a?1:2)}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Substring(substring) => {
+            assert_eq!(substring.offset.to_string(), " -1");
+            assert_eq!(substring.length.unwrap().to_string(), "(a?1:2)");
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
+    #[test]
+    fn lexer_suffix_modifier_substring_empty_offset() {
+        let mut lexer = Lexer::from_memory(r"::$n}", Source::Unknown);
+        let mut lexer = WordLexer {
+            lexer: &mut lexer,
+            context: WordContext::Word,
+        };
+
+        let result = lexer.suffix_modifier().now_or_never().unwrap().unwrap();
+        assert_matches!(result, Modifier::Substring(substring) => {
+            assert_eq!(substring.offset, Text(vec![]));
+            assert_eq!(substring.length.unwrap().to_string(), "$n");
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('}')));
+    }
+
     #[test]
     fn lexer_suffix_modifier_orphan_colon_followed_by_symbol() {
         let mut lexer = Lexer::from_memory(r":#}", Source::Unknown);
@@ -43,8 +43,14 @@ pub enum Operator {
     CloseParen,
     /// `;`
     Semicolon,
+    /// `;&`
+    SemicolonAnd,
     /// `;;`
     SemicolonSemicolon,
+    /// `;;&`
+    SemicolonSemicolonAnd,
+    /// `;|`
+    SemicolonBar,
     /// `<`
     Less,
     /// `<&`
@@ -89,7 +95,10 @@ impl Operator {
             OpenParen => "(",
             CloseParen => ")",
             Semicolon => ";",
+            SemicolonAnd => ";&",
             SemicolonSemicolon => ";;",
+            SemicolonSemicolonAnd => ";;&",
+            SemicolonBar => ";|",
             Less => "<",
             LessAnd => "<&",
             LessOpenParen => "<(",
@@ -110,13 +119,18 @@ impl Operator {
 
     /// Determines if this token can be a delimiter of a clause.
     ///
-    /// This function returns `true` for `CloseParen` and `SemicolonSemicolon`,
-    /// and `false` for others.
+    /// This function returns `true` for `CloseParen` and the operators that
+    /// terminate a case item (`;;`, `;&`, `;;&`, and `;|`), and `false` for
+    /// others.
     #[must_use]
     pub const fn is_clause_delimiter(self) -> bool {
         use Operator::*;
         match self {
-            CloseParen | SemicolonSemicolon => true,
+            CloseParen
+            | SemicolonAnd
+            | SemicolonSemicolon
+            | SemicolonSemicolonAnd
+            | SemicolonBar => true,
             Newline | And | AndAnd | OpenParen | Semicolon | Less | LessAnd | LessOpenParen
             | LessLess | LessLessDash | LessLessLess | LessGreater | Greater | GreaterAnd
             | GreaterOpenParen | GreaterGreater | GreaterGreaterBar | GreaterBar | Bar | BarBar => {
@@ -217,9 +231,28 @@ const AND: Trie = Trie(&[Edge {
 }]);
 
 /// Trie of the operators that start with `;`.
-const SEMICOLON: Trie = Trie(&[Edge {
-    key: ';',
-    value: Some(Operator::SemicolonSemicolon),
+const SEMICOLON: Trie = Trie(&[
+    Edge {
+        key: '&',
+        value: Some(Operator::SemicolonAnd),
+        next: NONE,
+    },
+    Edge {
+        key: ';',
+        value: Some(Operator::SemicolonSemicolon),
+        next: SEMICOLON_SEMICOLON,
+    },
+    Edge {
+        key: '|',
+        value: Some(Operator::SemicolonBar),
+        next: NONE,
+    },
+]);
+
+/// Trie of the operators that start with `;;`.
+const SEMICOLON_SEMICOLON: Trie = Trie(&[Edge {
+    key: '&',
+    value: Some(Operator::SemicolonSemicolonAnd),
     next: NONE,
 }]);
 
@@ -432,6 +465,26 @@ mod tests {
         assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(None));
     }
 
+    #[test]
+    fn lexer_operator_case_item_terminators() {
+        let mut lexer = Lexer::from_memory(";&;;&;|;;", Source::Unknown);
+
+        let t = lexer.operator().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(Operator::SemicolonAnd));
+        assert_eq!(t.word.location.range, 0..2);
+        let t = lexer.operator().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(Operator::SemicolonSemicolonAnd));
+        assert_eq!(t.word.location.range, 2..5);
+        let t = lexer.operator().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(Operator::SemicolonBar));
+        assert_eq!(t.word.location.range, 5..7);
+        let t = lexer.operator().now_or_never().unwrap().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(Operator::SemicolonSemicolon));
+        assert_eq!(t.word.location.range, 7..9);
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(None));
+    }
+
     #[test]
     fn lexer_operator_delimited_by_another_operator() {
         let mut lexer = Lexer::from_memory("<<>", Source::Unknown);
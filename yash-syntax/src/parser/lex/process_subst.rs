@@ -0,0 +1,120 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Part of the lexer that parses process substitutions.
+
+use super::core::Lexer;
+use crate::parser::core::Result;
+use crate::parser::error::Error;
+use crate::parser::error::SyntaxError;
+use crate::syntax::ProcessSubstDirection;
+use crate::syntax::WordUnit;
+
+impl Lexer<'_> {
+    /// Parses a process substitution of the form `<(...)` or `>(...)`.
+    ///
+    /// If the next two characters are `<(` or `>(`, the following characters
+    /// are parsed as commands to find a matching `)`, which will be consumed
+    /// before this function returns. Otherwise, no characters are consumed and
+    /// the return value is `Ok(None)`.
+    pub async fn process_substitution(&mut self) -> Result<Option<WordUnit>> {
+        let start_index = self.index();
+        let direction = match self.peek_char().await? {
+            Some('<') => ProcessSubstDirection::Input,
+            Some('>') => ProcessSubstDirection::Output,
+            _ => return Ok(None),
+        };
+        self.consume_char();
+
+        let opening_location = match self.consume_char_if(|c| c == '(').await? {
+            Some(ch) => ch.location.clone(),
+            None => {
+                self.rewind(start_index);
+                return Ok(None);
+            }
+        };
+
+        let content = self.inner_program_boxed().await?.into();
+
+        if !self.skip_if(|c| c == ')').await? {
+            let cause = SyntaxError::UnclosedProcessSubstitution { opening_location }.into();
+            let location = self.location().await?.clone();
+            return Err(Error { cause, location });
+        }
+
+        let location = self.location_range(start_index..self.index());
+        Ok(Some(WordUnit::ProcessSubst {
+            direction,
+            content,
+            location,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::error::ErrorCause;
+    use crate::source::Source;
+    use assert_matches::assert_matches;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn lexer_process_substitution_input() {
+        let mut lexer = Lexer::from_memory("<( foo bar )baz", Source::Unknown);
+        let result = lexer.process_substitution().now_or_never().unwrap();
+        assert_matches!(result, Ok(Some(WordUnit::ProcessSubst { direction, content, location })) => {
+            assert_eq!(direction, ProcessSubstDirection::Input);
+            assert_eq!(&*content, " foo bar ");
+            assert_eq!(location.range, 0..12);
+        });
+
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('b')));
+    }
+
+    #[test]
+    fn lexer_process_substitution_output() {
+        let mut lexer = Lexer::from_memory(">(cat)", Source::Unknown);
+        let result = lexer.process_substitution().now_or_never().unwrap();
+        assert_matches!(result, Ok(Some(WordUnit::ProcessSubst { direction, content, .. })) => {
+            assert_eq!(direction, ProcessSubstDirection::Output);
+            assert_eq!(&*content, "cat");
+        });
+    }
+
+    #[test]
+    fn lexer_process_substitution_none() {
+        let mut lexer = Lexer::from_memory("<x", Source::Unknown);
+        let result = lexer.process_substitution().now_or_never().unwrap();
+        assert_eq!(result, Ok(None));
+        assert_eq!(lexer.peek_char().now_or_never().unwrap(), Ok(Some('<')));
+    }
+
+    #[test]
+    fn lexer_process_substitution_unclosed() {
+        let mut lexer = Lexer::from_memory("<( foo bar baz", Source::Unknown);
+        let e = lexer
+            .process_substitution()
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert_matches!(e.cause,
+            ErrorCause::Syntax(SyntaxError::UnclosedProcessSubstitution { opening_location }) => {
+            assert_eq!(opening_location.range, 1..2);
+        });
+        assert_eq!(e.location.range, 14..14);
+    }
+}
@@ -65,19 +65,36 @@ impl Lexer<'_> {
     ///
     /// If there is no more token that can be parsed, the result is a token with an empty word and
     /// [`EndOfInput`](TokenId::EndOfInput) token identifier.
+    ///
+    /// A token starting with `<(` or `>(` is a word token that begins with a
+    /// [process substitution](crate::syntax::WordUnit::ProcessSubst) rather
+    /// than an operator. If [extended glob](Self::set_extended_glob) is
+    /// enabled, a pattern such as `@(a|b)` is part of a word token.
     pub async fn token(&mut self) -> Result<Token> {
-        if let Some(op) = self.operator().await? {
-            return Ok(op);
-        }
-
         let index = self.index();
 
+        let process_subst = self.process_substitution().await?;
+        if process_subst.is_none() {
+            if let Some(op) = self.operator().await? {
+                return Ok(op);
+            }
+        }
+
         let mut word_lexer = WordLexer {
             lexer: self,
             context: WordContext::Word,
         };
-        let mut word = word_lexer.word(is_token_delimiter_char).await?;
-        word.parse_tilde_front();
+        let mut word = if word_lexer.is_extended_glob() {
+            word_lexer.extended_glob_word().await?
+        } else {
+            word_lexer.word(is_token_delimiter_char).await?
+        };
+        if let Some(unit) = process_subst {
+            word.units.insert(0, unit);
+            word.location = self.location_range(index..self.index());
+        } else {
+            word.parse_tilde_front();
+        }
 
         let id = self.token_id(&word).await?;
 
@@ -91,6 +108,7 @@ mod tests {
     use crate::source::Source;
     use crate::syntax::TextUnit;
     use crate::syntax::WordUnit;
+    use assert_matches::assert_matches;
     use futures_util::FutureExt;
 
     #[test]
@@ -141,6 +159,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lexer_token_process_substitution() {
+        let mut lexer = Lexer::from_memory("<(ls)x >", Source::Unknown);
+
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.word.units.len(), 2);
+        assert_matches!(&t.word.units[0], WordUnit::ProcessSubst { content, .. } => {
+            assert_eq!(&**content, "ls");
+        });
+        assert_eq!(t.word.units[1], WordUnit::Unquoted(TextUnit::Literal('x')));
+        assert_eq!(t.word.location.range, 0..6);
+        assert_eq!(t.id, TokenId::Token(None));
+        assert_eq!(t.index, 0);
+
+        lexer.skip_blanks().now_or_never().unwrap().unwrap();
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(super::super::Operator::Greater));
+    }
+
+    #[test]
+    fn lexer_token_extended_glob() {
+        let mut lexer = Lexer::from_memory("@(a|b(c))x|y)", Source::Unknown);
+        lexer.set_extended_glob(true);
+
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.word.to_string(), "@(a|b(c))x");
+        assert_eq!(t.word.location.range, 0..10);
+        assert_eq!(t.id, TokenId::Token(None));
+
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(super::super::Operator::Bar));
+    }
+
+    #[test]
+    fn lexer_token_extended_glob_disabled() {
+        let mut lexer = Lexer::from_memory("@(a)", Source::Unknown);
+
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.word.to_string(), "@");
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(super::super::Operator::OpenParen));
+    }
+
+    #[test]
+    fn lexer_token_extended_glob_with_quotes() {
+        let mut lexer = Lexer::from_memory("!('a|b'|\\()) ", Source::Unknown);
+        lexer.set_extended_glob(true);
+
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.word.to_string(), "!('a|b'|\\()");
+        let t = lexer.token().now_or_never().unwrap().unwrap();
+        assert_eq!(t.id, TokenId::Operator(super::super::Operator::CloseParen));
+    }
+
     #[test]
     fn lexer_token_io_number_delimited_by_less() {
         let mut lexer = Lexer::from_memory("12<", Source::Unknown);
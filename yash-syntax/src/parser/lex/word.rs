@@ -24,6 +24,7 @@ use crate::parser::error::Error;
 use crate::parser::error::SyntaxError;
 use crate::source::Location;
 use crate::source::SourceChar;
+use crate::syntax::TextUnit::Literal;
 use crate::syntax::Word;
 use crate::syntax::WordUnit::{self, DoubleQuote, SingleQuote, Unquoted};
 
@@ -164,6 +165,48 @@ impl WordLexer<'_, '_> {
         let location = self.location_range(start..self.index());
         Ok(Word { units, location })
     }
+
+    /// Parses a word token that may contain extended glob patterns.
+    ///
+    /// This function is similar to [`word`](Self::word) with
+    /// [`is_token_delimiter_char`](super::is_token_delimiter_char), but a `(`
+    /// that follows an unquoted `?`, `*`, `+`, `@`, or `!` starts a pattern
+    /// list. Parentheses and `|` in the pattern list are literal characters
+    /// of the word up to the matching `)`.
+    pub(super) async fn extended_glob_word(&mut self) -> Result<Word> {
+        let start = self.index();
+        let mut units = vec![];
+        let mut depth = 0_usize;
+        loop {
+            let follows_op = matches!(
+                units.last(),
+                Some(Unquoted(Literal('?' | '*' | '+' | '@' | '!')))
+            );
+            let c = self.peek_char().await?;
+            let is_pattern_char = match c {
+                Some('(') => follows_op || depth > 0,
+                Some(')' | '|') => depth > 0,
+                _ => false,
+            };
+            if let (true, Some(c)) = (is_pattern_char, c) {
+                self.consume_char();
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => (),
+                }
+                units.push(Unquoted(Literal(c)));
+                continue;
+            }
+
+            match self.word_unit_dyn(&super::is_token_delimiter_char).await? {
+                Some(unit) => units.push(unit),
+                None => break,
+            }
+        }
+        let location = self.location_range(start..self.index());
+        Ok(Word { units, location })
+    }
 }
 
 #[cfg(test)]
@@ -29,6 +29,7 @@ use crate::syntax::Redir;
 use crate::syntax::RedirBody;
 use crate::syntax::RedirOp;
 use crate::syntax::Word;
+use crate::syntax::WordUnit;
 use std::cell::OnceCell;
 use std::rc::Rc;
 
@@ -78,6 +79,23 @@ impl Parser<'_, '_> {
         Ok(RedirBody::HereDoc(here_doc))
     }
 
+    /// Parses the redirection body for a process redirection.
+    ///
+    /// The next token must be a word that starts with a process substitution.
+    /// It is a syntax error if the word contains anything after the process
+    /// substitution.
+    async fn process_redirection_body(&mut self) -> Result<RedirBody> {
+        let token = self.take_token_raw().await?;
+        if let [WordUnit::ProcessSubst { .. }] = token.word.units[..] {
+            Ok(RedirBody::Process(token.word))
+        } else {
+            Err(Error {
+                cause: SyntaxError::InvalidProcessRedirection.into(),
+                location: token.word.location,
+            })
+        }
+    }
+
     /// Parses the redirection body.
     async fn redirection_body(&mut self) -> Result<Option<RedirBody>> {
         let operator = match self.peek_token().await?.id {
@@ -91,7 +109,6 @@ impl Parser<'_, '_> {
         match operator {
             LessLess => Ok(Some(self.here_doc_redirection_body(false).await?)),
             LessLessDash => Ok(Some(self.here_doc_redirection_body(true).await?)),
-            // TODO <() >()
             _ => Ok(None),
         }
     }
@@ -102,6 +119,10 @@ impl Parser<'_, '_> {
     /// is missing after the operator, `Err(Error{...})` is returned with a cause of
     /// [`MissingRedirOperand`](SyntaxError::MissingRedirOperand) or
     /// [`MissingHereDocDelimiter`](SyntaxError::MissingHereDocDelimiter).
+    ///
+    /// A [process substitution](WordUnit::ProcessSubst) is parsed as a process redirection only
+    /// if it is preceded by an IO_NUMBER token, as in `3<(command)`. Without the IO_NUMBER, it is
+    /// an ordinary word.
     pub async fn redirection(&mut self) -> Result<Option<Redir>> {
         let fd = if self.peek_token().await?.id == IoNumber {
             let token = self.take_token_raw().await?;
@@ -117,10 +138,13 @@ impl Parser<'_, '_> {
             None
         };
 
-        Ok(self
-            .redirection_body()
-            .await?
-            .map(|body| Redir { fd, body }))
+        let body = match self.redirection_body().await? {
+            Some(body) => body,
+            // An IO_NUMBER followed by a word token is a process redirection.
+            None if fd.is_some() => self.process_redirection_body().await?,
+            None => return Ok(None),
+        };
+        Ok(Some(Redir { fd, body }))
     }
 
     /// Parses a (possibly empty) sequence of redirections.
@@ -363,6 +387,48 @@ mod tests {
         assert_eq!(e.location.range, 0..40);
     }
 
+    #[test]
+    fn parser_redirection_process() {
+        let mut lexer = Lexer::from_memory("3<(echo ok)\n", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let result = parser.redirection().now_or_never().unwrap();
+        let redir = result.unwrap().unwrap();
+        assert_eq!(redir.fd, Some(Fd(3)));
+        assert_matches!(redir.body, RedirBody::Process(word) => {
+            assert_eq!(word.to_string(), "<(echo ok)");
+            assert_eq!(word.location.range, 1..11);
+        });
+
+        let next = parser.peek_token().now_or_never().unwrap().unwrap();
+        assert_eq!(next.id, Operator(Newline));
+    }
+
+    #[test]
+    fn parser_redirection_process_without_io_number() {
+        let mut lexer = Lexer::from_memory(">(cat)", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let result = parser.redirection().now_or_never().unwrap();
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn parser_redirection_process_with_extra_characters() {
+        let mut lexer = Lexer::from_memory("1>(cat)x", Source::Unknown);
+        let aliases = Default::default();
+        let mut parser = Parser::new(&mut lexer, &aliases);
+
+        let e = parser.redirection().now_or_never().unwrap().unwrap_err();
+        assert_eq!(
+            e.cause,
+            ErrorCause::Syntax(SyntaxError::InvalidProcessRedirection)
+        );
+        assert_eq!(e.location.range, 1..8);
+    }
+
     #[test]
     fn parser_redirection_not_operator() {
         let mut lexer = Lexer::from_memory("x", Source::Unknown);
@@ -48,6 +48,9 @@ pub enum Source {
     /// File specified on the shell startup
     CommandFile { path: String },
 
+    /// Initialization file executed on the shell startup
+    InitFile { path: String },
+
     /// Alias substitution
     ///
     /// This applies to a code fragment that replaced another as a result of alias substitution.
@@ -61,12 +64,21 @@ pub enum Source {
     /// Command substitution
     CommandSubst { original: Location },
 
+    /// Process substitution or process redirection
+    ProcessSubst { original: Location },
+
     /// Arithmetic expansion
     Arith { original: Location },
 
     /// Command string executed by the `eval` built-in
     Eval { original: Location },
 
+    /// Command string executed by the `fc` built-in
+    ///
+    /// This applies to the command that invokes the editor as well as the
+    /// commands re-executed from the history.
+    Fc { original: Location },
+
     /// File executed by the `.` (`source`) built-in
     DotScript {
         /// Pathname of the file
@@ -82,6 +94,15 @@ pub enum Source {
         /// Location of the simple command that has set this trap command
         origin: Location,
     },
+
+    /// Value of a variable
+    ///
+    /// This applies to code that is parsed from the value of a variable, such
+    /// as the prompt strings.
+    VariableValue {
+        /// Variable name
+        name: String,
+    },
     // TODO More Source types
 }
 
@@ -152,12 +173,16 @@ impl Source {
             Stdin => "<stdin>",
             CommandString => "<command_string>",
             CommandFile { path } => path,
+            InitFile { path } => path,
             Alias { .. } => "<alias>",
             CommandSubst { .. } => "<command_substitution>",
+            ProcessSubst { .. } => "<process_substitution>",
             Arith { .. } => "<arith>",
             Eval { .. } => "<eval>",
+            Fc { .. } => "<fc>",
             DotScript { name, .. } => name,
             Trap { condition, .. } => condition,
+            VariableValue { name } => name,
         }
     }
 }
@@ -117,7 +117,12 @@ impl super::Source {
     pub fn complement_annotations<'a, 's: 'a, T: Extend<Annotation<'a>>>(&'s self, result: &mut T) {
         use super::Source::*;
         match self {
-            Unknown | Stdin | CommandString | CommandFile { .. } => (),
+            Unknown
+            | Stdin
+            | CommandString
+            | CommandFile { .. }
+            | InitFile { .. }
+            | VariableValue { .. } => (),
             CommandSubst { original } => {
                 // TODO Use Extend::extend_one
                 result.extend(std::iter::once(Annotation::new(
@@ -126,6 +131,14 @@ impl super::Source {
                     original,
                 )));
             }
+            ProcessSubst { original } => {
+                // TODO Use Extend::extend_one
+                result.extend(std::iter::once(Annotation::new(
+                    AnnotationType::Info,
+                    "process substitution appeared here".into(),
+                    original,
+                )));
+            }
             Arith { original } => {
                 // TODO Use Extend::extend_one
                 result.extend(std::iter::once(Annotation::new(
@@ -142,6 +155,14 @@ impl super::Source {
                     original,
                 )));
             }
+            Fc { original } => {
+                // TODO Use Extend::extend_one
+                result.extend(std::iter::once(Annotation::new(
+                    AnnotationType::Info,
+                    "command executed by the fc built-in here".into(),
+                    original,
+                )));
+            }
             DotScript { name, origin } => {
                 // TODO Use Extend::extend_one
                 result.extend(std::iter::once(Annotation::new(
@@ -82,6 +82,8 @@ use std::fmt;
 use std::fmt::Write;
 use std::os::unix::io::RawFd;
 use std::rc::Rc;
+use std::str::FromStr;
+use thiserror::Error;
 
 /// Result of [`Unquote::write_unquoted`].
 ///
@@ -310,6 +312,104 @@ impl Unquote for Trim {
     }
 }
 
+/// Flag that specifies which part of the value is replaced in a
+/// [substitution](Subst).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SubstMode {
+    /// Replace the first match. (`/`)
+    First,
+    /// Replace all matches. (`//`)
+    All,
+    /// Replace a match at the beginning of the value. (`/#`)
+    Prefix,
+    /// Replace a match at the end of the value. (`/%`)
+    Suffix,
+}
+
+impl fmt::Display for SubstMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SubstMode::*;
+        let s = match self {
+            First => "/",
+            All => "//",
+            Prefix => "/#",
+            Suffix => "/%",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Parameter expansion [modifier](Modifier) that replaces part of the value
+/// being expanded.
+///
+/// Examples of substitutions include `/foo/bar`, `//baz` and `/#qux/`.
+///
+/// A substitution is composed of a mode, pattern and replacement. If the
+/// replacement is omitted in the source code, it is an empty word.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Subst {
+    /// Which part of the value should be replaced?
+    pub mode: SubstMode,
+    /// Pattern to be matched with the expanded value.
+    pub pattern: Word,
+    /// Word that replaces the matched part of the value.
+    pub replacement: Word,
+}
+
+impl fmt::Display for Subst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}", self.mode, self.pattern, self.replacement)
+    }
+}
+
+impl Unquote for Subst {
+    fn write_unquoted<W: fmt::Write>(&self, w: &mut W) -> UnquoteResult {
+        write!(w, "{}", self.mode)?;
+        let pattern_quoted = self.pattern.write_unquoted(w)?;
+        w.write_char('/')?;
+        let replacement_quoted = self.replacement.write_unquoted(w)?;
+        Ok(pattern_quoted || replacement_quoted)
+    }
+}
+
+/// Parameter expansion [modifier](Modifier) that extracts part of the value
+/// being expanded.
+///
+/// Examples of substrings include `:2`, `: -3` and `:1:$n`.
+///
+/// The offset and length are arithmetic expressions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Substring {
+    /// Expression that specifies the start of the extracted part
+    pub offset: Text,
+    /// Expression that specifies the length of the extracted part
+    ///
+    /// If `None`, the extracted part continues to the end of the value.
+    pub length: Option<Text>,
+}
+
+impl fmt::Display for Substring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ":{}", self.offset)?;
+        if let Some(length) = &self.length {
+            write!(f, ":{length}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Unquote for Substring {
+    fn write_unquoted<W: fmt::Write>(&self, w: &mut W) -> UnquoteResult {
+        w.write_char(':')?;
+        let mut quoted = self.offset.write_unquoted(w)?;
+        if let Some(length) = &self.length {
+            w.write_char(':')?;
+            quoted |= length.write_unquoted(w)?;
+        }
+        Ok(quoted)
+    }
+}
+
 /// Attribute that modifies a parameter expansion.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Modifier {
@@ -321,7 +421,99 @@ pub enum Modifier {
     Switch(Switch),
     /// `#`, `##`, `%` or `%%` suffix.
     Trim(Trim),
-    // TODO Subst
+    /// `/`, `//`, `/#` or `/%` suffix. (`${foo/bar/baz}`)
+    Subst(Subst),
+    /// `:` suffix followed by an offset and optional length. (`${foo:1:2}`)
+    Substring(Substring),
+}
+
+/// Subject of a [braced parameter expansion](Param)
+///
+/// The subject is usually the name of a parameter, but it can also be another
+/// expansion whose result is used as the value to be modified.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParamSubject {
+    /// Parameter name
+    Name(String),
+    /// Nested expansion
+    ///
+    /// The text unit is a [braced parameter
+    /// expansion](TextUnit::BracedParam), [command
+    /// substitution](TextUnit::CommandSubst), [backquote](TextUnit::Backquote),
+    /// or [arithmetic expansion](TextUnit::Arith).
+    Nested(Box<TextUnit>),
+}
+
+impl ParamSubject {
+    /// Returns the parameter name if this subject is a name.
+    #[must_use]
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            ParamSubject::Name(name) => Some(name),
+            ParamSubject::Nested(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamSubject::Name(name) => f.write_str(name),
+            ParamSubject::Nested(unit) => unit.fmt(f),
+        }
+    }
+}
+
+impl Unquote for ParamSubject {
+    fn write_unquoted<W: fmt::Write>(&self, w: &mut W) -> UnquoteResult {
+        match self {
+            ParamSubject::Name(name) => {
+                w.write_str(name)?;
+                Ok(false)
+            }
+            ParamSubject::Nested(unit) => unit.write_unquoted(w),
+        }
+    }
+}
+
+/// Index of a [braced parameter expansion](Param) (`[start]` or `[start,end]`)
+///
+/// An index selects elements of an array or characters of a scalar value.
+/// The start and end are arithmetic expressions that specify one-based
+/// indices of the first and last selected element, where negative indices
+/// count from the end. If the start expands to `@` or `*` and there is no
+/// end, the index selects all elements.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Index {
+    /// Expression that specifies the first element
+    pub start: Text,
+    /// Expression that specifies the last element
+    ///
+    /// If `None`, only the element specified by `start` is selected.
+    pub end: Option<Text>,
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}", self.start)?;
+        if let Some(end) = &self.end {
+            write!(f, ",{end}")?;
+        }
+        f.write_char(']')
+    }
+}
+
+impl Unquote for Index {
+    fn write_unquoted<W: fmt::Write>(&self, w: &mut W) -> UnquoteResult {
+        w.write_char('[')?;
+        let mut quoted = self.start.write_unquoted(w)?;
+        if let Some(end) = &self.end {
+            w.write_char(',')?;
+            quoted |= end.write_unquoted(w)?;
+        }
+        w.write_char(']')?;
+        Ok(quoted)
+    }
 }
 
 /// Parameter expansion enclosed in braces.
@@ -331,10 +523,10 @@ pub enum Modifier {
 /// [`TextUnit::RawParam`].
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Param {
-    // TODO recursive expansion
-    /// Parameter name.
-    pub name: String,
-    // TODO index
+    /// Parameter name or nested expansion
+    pub subject: ParamSubject,
+    /// Index that selects some elements of the value
+    pub index: Option<Index>,
     /// Modifier.
     pub modifier: Modifier,
     /// Position of this parameter expansion in the source code.
@@ -344,40 +536,45 @@ pub struct Param {
 impl fmt::Display for Param {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Modifier::*;
+        f.write_str("${")?;
+        if let Length = self.modifier {
+            f.write_char('#')?;
+        }
+        self.subject.fmt(f)?;
+        if let Some(index) = &self.index {
+            index.fmt(f)?;
+        }
         match self.modifier {
-            None => write!(f, "${{{}}}", self.name),
-            Length => write!(f, "${{#{}}}", self.name),
-            Switch(ref switch) => write!(f, "${{{}{}}}", self.name, switch),
-            Trim(ref trim) => write!(f, "${{{}{}}}", self.name, trim),
+            None | Length => (),
+            Switch(ref switch) => switch.fmt(f)?,
+            Trim(ref trim) => trim.fmt(f)?,
+            Subst(ref subst) => subst.fmt(f)?,
+            Substring(ref substring) => substring.fmt(f)?,
         }
+        f.write_char('}')
     }
 }
 
 impl Unquote for Param {
     fn write_unquoted<W: fmt::Write>(&self, w: &mut W) -> UnquoteResult {
         use Modifier::*;
-        match self.modifier {
-            None => {
-                write!(w, "${{{}}}", self.name)?;
-                Ok(false)
-            }
-            Length => {
-                write!(w, "${{#{}}}", self.name)?;
-                Ok(false)
-            }
-            Switch(ref switch) => {
-                write!(w, "${{{}", self.name)?;
-                let quoted = switch.write_unquoted(w)?;
-                w.write_char('}')?;
-                Ok(quoted)
-            }
-            Trim(ref trim) => {
-                write!(w, "${{{}", self.name)?;
-                let quoted = trim.write_unquoted(w)?;
-                w.write_char('}')?;
-                Ok(quoted)
-            }
+        w.write_str("${")?;
+        if let Length = self.modifier {
+            w.write_char('#')?;
         }
+        let mut subject_quoted = self.subject.write_unquoted(w)?;
+        if let Some(index) = &self.index {
+            subject_quoted |= index.write_unquoted(w)?;
+        }
+        let modifier_quoted = match self.modifier {
+            None | Length => false,
+            Switch(ref switch) => switch.write_unquoted(w)?,
+            Trim(ref trim) => trim.write_unquoted(w)?,
+            Subst(ref subst) => subst.write_unquoted(w)?,
+            Substring(ref substring) => substring.write_unquoted(w)?,
+        };
+        w.write_char('}')?;
+        Ok(subject_quoted || modifier_quoted)
     }
 }
 
@@ -563,6 +760,27 @@ impl MaybeLiteral for Text {
     }
 }
 
+/// Direction of the pipe connecting the shell and a process substitution
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ProcessSubstDirection {
+    /// `<(...)`: the shell reads the output of the process.
+    Input,
+    /// `>(...)`: the shell writes to the input of the process.
+    Output,
+}
+
+impl ProcessSubstDirection {
+    /// Returns the operator character that starts the process substitution,
+    /// that is, `<` or `>`.
+    #[must_use]
+    pub const fn as_char(self) -> char {
+        match self {
+            ProcessSubstDirection::Input => '<',
+            ProcessSubstDirection::Output => '>',
+        }
+    }
+}
+
 /// Element of a [Word], i.e., text with quotes and tilde expansion.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum WordUnit {
@@ -576,6 +794,19 @@ pub enum WordUnit {
     ///
     /// The `String` value does not contain the initial tilde.
     Tilde(String),
+    /// Process substitution of the form `<(...)` or `>(...)`.
+    ///
+    /// A process substitution can only appear at the beginning of a word
+    /// token.
+    ProcessSubst {
+        /// Whether the shell reads from or writes to the process
+        direction: ProcessSubstDirection,
+        /// Command string that will be parsed and executed when the process
+        /// substitution is expanded.
+        content: Rc<str>,
+        /// Position of this process substitution in the source code.
+        location: Location,
+    },
 }
 
 pub use WordUnit::*;
@@ -587,6 +818,9 @@ impl fmt::Display for WordUnit {
             SingleQuote(s) => write!(f, "'{s}'"),
             DoubleQuote(content) => write!(f, "\"{content}\""),
             Tilde(s) => write!(f, "~{s}"),
+            ProcessSubst {
+                direction, content, ..
+            } => write!(f, "{}({content})", direction.as_char()),
         }
     }
 }
@@ -604,6 +838,10 @@ impl Unquote for WordUnit {
                 write!(w, "~{s}")?;
                 Ok(false)
             }
+            ProcessSubst { .. } => {
+                write!(w, "{self}")?;
+                Ok(false)
+            }
         }
     }
 }
@@ -688,6 +926,12 @@ pub struct Assign {
     ///
     /// In the valid assignment syntax, the name must not be empty.
     pub name: String,
+    /// Index of the array element to assign to.
+    ///
+    /// If `Some`, the assignment is of the form `name[index]=value` and
+    /// replaces an element of an existing array. Element assignment is a
+    /// POSIXly non-portable extension.
+    pub index: Option<Text>,
     /// Value assigned to the variable.
     pub value: Value,
     /// Location of the assignment word.
@@ -696,23 +940,54 @@ pub struct Assign {
 
 impl fmt::Display for Assign {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}={}", &self.name, &self.value)
+        f.write_str(&self.name)?;
+        if let Some(index) = &self.index {
+            write!(f, "[{index}]")?;
+        }
+        write!(f, "={}", &self.value)
     }
 }
 
+/// Splits the index off the name part of an assignment word.
+///
+/// If `units` is of the form `name[index]`, where the brackets are unquoted
+/// and `index` consists of unquoted units only, this function returns the
+/// position of the `[` and the index. Otherwise, it returns `None`.
+fn split_assign_index(units: &[WordUnit]) -> Option<(usize, Text)> {
+    let (last, units) = units.split_last()?;
+    if last != &Unquoted(Literal(']')) {
+        return None;
+    }
+    let open = units.iter().position(|u| u == &Unquoted(Literal('[')))?;
+    let index = units[open + 1..]
+        .iter()
+        .map(|unit| match unit {
+            Unquoted(unit) => Some(unit.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((open, Text(index)))
+}
+
 /// Fallible conversion from a word into an assignment.
 impl TryFrom<Word> for Assign {
     type Error = Word;
     /// Converts a word into an assignment.
     ///
-    /// For a successful conversion, the word must be of the form `name=value`,
-    /// where `name` is a non-empty [literal](Word::to_string_if_literal) word,
-    /// `=` is an unquoted equal sign, and `value` is a word. If the input word
-    /// does not match this syntax, it is returned intact in `Err`.
+    /// For a successful conversion, the word must be of the form `name=value`
+    /// or `name[index]=value`, where `name` is a non-empty
+    /// [literal](Word::to_string_if_literal) word, `index` is a text enclosed
+    /// in unquoted brackets, `=` is an unquoted equal sign, and `value` is a
+    /// word. If the input word does not match this syntax, it is returned
+    /// intact in `Err`.
     fn try_from(mut word: Word) -> Result<Assign, Word> {
         if let Some(eq) = word.units.iter().position(|u| u == &Unquoted(Literal('='))) {
-            if eq > 0 {
-                if let Some(name) = word.units[..eq].to_string_if_literal() {
+            let (name_end, index) = match split_assign_index(&word.units[..eq]) {
+                Some((open, index)) if open > 0 => (open, Some(index)),
+                _ => (eq, None),
+            };
+            if name_end > 0 {
+                if let Some(name) = word.units[..name_end].to_string_if_literal() {
                     assert!(!name.is_empty());
                     word.units.drain(..=eq);
                     word.parse_tilde_everywhere();
@@ -720,6 +995,7 @@ impl TryFrom<Word> for Assign {
                     let value = Scalar(word);
                     return Ok(Assign {
                         name,
+                        index,
                         value,
                         location,
                     });
@@ -875,7 +1151,12 @@ pub enum RedirBody {
     Normal { operator: RedirOp, operand: Word },
     /// Here-document.
     HereDoc(Rc<HereDoc>),
-    // TODO process redirection
+    /// Process redirection.
+    ///
+    /// The word consists of a single [process
+    /// substitution](WordUnit::ProcessSubst) whose pipe end is connected to
+    /// the target file descriptor.
+    Process(Word),
 }
 
 impl RedirBody {
@@ -884,6 +1165,7 @@ impl RedirBody {
         match self {
             RedirBody::Normal { operand, .. } => operand,
             RedirBody::HereDoc(here_doc) => &here_doc.delimiter,
+            RedirBody::Process(word) => word,
         }
     }
 }
@@ -893,6 +1175,7 @@ impl fmt::Display for RedirBody {
         match self {
             RedirBody::Normal { operator, operand } => write!(f, "{operator}{operand}"),
             RedirBody::HereDoc(h) => write!(f, "{h}"),
+            RedirBody::Process(word) => write!(f, "{word}"),
         }
     }
 }
@@ -925,6 +1208,13 @@ impl Redir {
                 FileOut | FileAppend | FileClobber | FdOut | Pipe => Fd::STDOUT,
             },
             RedirBody::HereDoc { .. } => Fd::STDIN,
+            RedirBody::Process(ref word) => match word.units.first() {
+                Some(ProcessSubst {
+                    direction: ProcessSubstDirection::Output,
+                    ..
+                }) => Fd::STDOUT,
+                _ => Fd::STDIN,
+            },
         })
     }
 }
@@ -990,6 +1280,49 @@ impl fmt::Display for ElifThen {
     }
 }
 
+/// Symbol that terminates the body of a case item and determines what to do
+/// after executing it
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CaseContinuation {
+    /// `;;` (finish the case command)
+    #[default]
+    Break,
+    /// `;&` (execute the body of the next item without matching its patterns)
+    FallThrough,
+    /// `;|` or `;;&` (continue matching with the patterns of the next item)
+    Continue,
+}
+
+impl TryFrom<Operator> for CaseContinuation {
+    type Error = TryFromOperatorError;
+    fn try_from(op: Operator) -> Result<CaseContinuation, TryFromOperatorError> {
+        match op {
+            Operator::SemicolonSemicolon => Ok(CaseContinuation::Break),
+            Operator::SemicolonAnd => Ok(CaseContinuation::FallThrough),
+            Operator::SemicolonBar | Operator::SemicolonSemicolonAnd => {
+                Ok(CaseContinuation::Continue)
+            }
+            _ => Err(TryFromOperatorError {}),
+        }
+    }
+}
+
+impl From<CaseContinuation> for Operator {
+    fn from(cc: CaseContinuation) -> Operator {
+        match cc {
+            CaseContinuation::Break => Operator::SemicolonSemicolon,
+            CaseContinuation::FallThrough => Operator::SemicolonAnd,
+            CaseContinuation::Continue => Operator::SemicolonBar,
+        }
+    }
+}
+
+impl fmt::Display for CaseContinuation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Operator::from(*self).fmt(f)
+    }
+}
+
 /// Branch item of a `case` compound command.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CaseItem {
@@ -1000,19 +1333,273 @@ pub struct CaseItem {
     pub patterns: Vec<Word>,
     /// Commands that are executed if any of the patterns matched.
     pub body: List,
+    /// What to do after executing the body of this item
+    ///
+    /// This is [`Break`](CaseContinuation::Break) if the item is not
+    /// terminated by any symbol, that is, the item is the last one in the
+    /// case command.
+    pub continuation: CaseContinuation,
 }
 
 impl fmt::Display for CaseItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "({}) {};;",
+            "({}) {}{}",
             self.patterns.iter().format(" | "),
-            self.body
+            self.body,
+            self.continuation
         )
     }
 }
 
+/// Error value indicating that a string is not a conditional operator
+///
+/// This error is returned by [`UnaryCondOp::from_str`] and
+/// [`BinaryCondOp::from_str`] when the input string is not an operator.
+#[derive(Clone, Debug, Eq, Error, Hash, PartialEq)]
+#[error("not a conditional operator")]
+pub struct ParseCondOpError;
+
+/// Unary operator in a [conditional expression](CondExpr)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnaryCondOp {
+    /// `-e` (or `-a`): the file exists.
+    Exists,
+    /// `-b`: the file is a block special file.
+    BlockSpecial,
+    /// `-c`: the file is a character special file.
+    CharacterSpecial,
+    /// `-d`: the file is a directory.
+    Directory,
+    /// `-f`: the file is a regular file.
+    RegularFile,
+    /// `-g`: the file has the set-group-ID bit.
+    SetGroupId,
+    /// `-h` (or `-L`): the file is a symbolic link.
+    SymbolicLink,
+    /// `-k`: the file has the sticky bit.
+    Sticky,
+    /// `-p`: the file is a FIFO.
+    Fifo,
+    /// `-r`: the file is readable.
+    Readable,
+    /// `-s`: the file is not empty.
+    NonEmptyFile,
+    /// `-S`: the file is a socket.
+    Socket,
+    /// `-t`: the file descriptor is a terminal.
+    Terminal,
+    /// `-u`: the file has the set-user-ID bit.
+    SetUserId,
+    /// `-w`: the file is writable.
+    Writable,
+    /// `-x`: the file is executable.
+    Executable,
+    /// `-O`: the file is owned by the effective user.
+    OwnedByUser,
+    /// `-n`: the string is not empty.
+    NonEmptyString,
+    /// `-z`: the string is empty.
+    EmptyString,
+    /// `-o`: the shell option is on.
+    OptionOn,
+    /// `-v`: the variable is set.
+    VariableSet,
+}
+
+impl UnaryCondOp {
+    /// Returns the literal string representation of the operator.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        use UnaryCondOp::*;
+        match self {
+            Exists => "-e",
+            BlockSpecial => "-b",
+            CharacterSpecial => "-c",
+            Directory => "-d",
+            RegularFile => "-f",
+            SetGroupId => "-g",
+            SymbolicLink => "-h",
+            Sticky => "-k",
+            Fifo => "-p",
+            Readable => "-r",
+            NonEmptyFile => "-s",
+            Socket => "-S",
+            Terminal => "-t",
+            SetUserId => "-u",
+            Writable => "-w",
+            Executable => "-x",
+            OwnedByUser => "-O",
+            NonEmptyString => "-n",
+            EmptyString => "-z",
+            OptionOn => "-o",
+            VariableSet => "-v",
+        }
+    }
+}
+
+impl fmt::Display for UnaryCondOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UnaryCondOp {
+    type Err = ParseCondOpError;
+    fn from_str(s: &str) -> Result<UnaryCondOp, ParseCondOpError> {
+        use UnaryCondOp::*;
+        match s {
+            "-a" | "-e" => Ok(Exists),
+            "-b" => Ok(BlockSpecial),
+            "-c" => Ok(CharacterSpecial),
+            "-d" => Ok(Directory),
+            "-f" => Ok(RegularFile),
+            "-g" => Ok(SetGroupId),
+            "-h" | "-L" => Ok(SymbolicLink),
+            "-k" => Ok(Sticky),
+            "-p" => Ok(Fifo),
+            "-r" => Ok(Readable),
+            "-s" => Ok(NonEmptyFile),
+            "-S" => Ok(Socket),
+            "-t" => Ok(Terminal),
+            "-u" => Ok(SetUserId),
+            "-w" => Ok(Writable),
+            "-x" => Ok(Executable),
+            "-O" => Ok(OwnedByUser),
+            "-n" => Ok(NonEmptyString),
+            "-z" => Ok(EmptyString),
+            "-o" => Ok(OptionOn),
+            "-v" => Ok(VariableSet),
+            _ => Err(ParseCondOpError),
+        }
+    }
+}
+
+/// Binary operator in a [conditional expression](CondExpr)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BinaryCondOp {
+    /// `==` (or `=`): the left-hand side matches the pattern.
+    Match,
+    /// `!=`: the left-hand side does not match the pattern.
+    NotMatch,
+    /// `=~`: the left-hand side matches the extended regular expression.
+    RegexMatch,
+    /// `<`: the left-hand side sorts before the right-hand side.
+    Less,
+    /// `>`: the left-hand side sorts after the right-hand side.
+    Greater,
+    /// `-eq`: the integers are equal.
+    Equal,
+    /// `-ne`: the integers are not equal.
+    NotEqual,
+    /// `-lt`: the left-hand side integer is less.
+    LessThan,
+    /// `-le`: the left-hand side integer is less or equal.
+    LessEqual,
+    /// `-gt`: the left-hand side integer is greater.
+    GreaterThan,
+    /// `-ge`: the left-hand side integer is greater or equal.
+    GreaterEqual,
+    /// `-nt`: the left-hand side file is newer.
+    NewerThan,
+    /// `-ot`: the left-hand side file is older.
+    OlderThan,
+    /// `-ef`: the files are the same file.
+    SameFile,
+}
+
+impl BinaryCondOp {
+    /// Returns the literal string representation of the operator.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        use BinaryCondOp::*;
+        match self {
+            Match => "==",
+            NotMatch => "!=",
+            RegexMatch => "=~",
+            Less => "<",
+            Greater => ">",
+            Equal => "-eq",
+            NotEqual => "-ne",
+            LessThan => "-lt",
+            LessEqual => "-le",
+            GreaterThan => "-gt",
+            GreaterEqual => "-ge",
+            NewerThan => "-nt",
+            OlderThan => "-ot",
+            SameFile => "-ef",
+        }
+    }
+}
+
+impl fmt::Display for BinaryCondOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BinaryCondOp {
+    type Err = ParseCondOpError;
+    fn from_str(s: &str) -> Result<BinaryCondOp, ParseCondOpError> {
+        use BinaryCondOp::*;
+        match s {
+            "=" | "==" => Ok(Match),
+            "!=" => Ok(NotMatch),
+            "=~" => Ok(RegexMatch),
+            "<" => Ok(Less),
+            ">" => Ok(Greater),
+            "-eq" => Ok(Equal),
+            "-ne" => Ok(NotEqual),
+            "-lt" => Ok(LessThan),
+            "-le" => Ok(LessEqual),
+            "-gt" => Ok(GreaterThan),
+            "-ge" => Ok(GreaterEqual),
+            "-nt" => Ok(NewerThan),
+            "-ot" => Ok(OlderThan),
+            "-ef" => Ok(SameFile),
+            _ => Err(ParseCondOpError),
+        }
+    }
+}
+
+/// Expression in a double-bracket command
+///
+/// The operands are words that are expanded without field splitting or
+/// pathname expansion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CondExpr {
+    /// Word that is true if it is not empty.
+    Word(Word),
+    /// Unary operator applied to an operand.
+    Unary(UnaryCondOp, Word),
+    /// Binary operator applied to two operands.
+    Binary(Word, BinaryCondOp, Word),
+    /// Negation (`!`).
+    Not(Box<CondExpr>),
+    /// Conjunction (`&&`).
+    And(Box<CondExpr>, Box<CondExpr>),
+    /// Disjunction (`||`).
+    Or(Box<CondExpr>, Box<CondExpr>),
+    /// Parenthesized expression.
+    Paren(Box<CondExpr>),
+}
+
+impl fmt::Display for CondExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CondExpr::*;
+        match self {
+            Word(word) => write!(f, "{word}"),
+            Unary(op, operand) => write!(f, "{op} {operand}"),
+            Binary(lhs, op, rhs) => write!(f, "{lhs} {op} {rhs}"),
+            Not(expr) => write!(f, "! {expr}"),
+            And(lhs, rhs) => write!(f, "{lhs} && {rhs}"),
+            Or(lhs, rhs) => write!(f, "{lhs} || {rhs}"),
+            Paren(expr) => write!(f, "( {expr} )"),
+        }
+    }
+}
+
 /// Command that contains other commands.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum CompoundCommand {
@@ -1039,7 +1626,8 @@ pub enum CompoundCommand {
     },
     /// Case conditional construct.
     Case { subject: Word, items: Vec<CaseItem> },
-    // TODO [[ ]]
+    /// Double-bracket conditional command.
+    DoubleBracket(CondExpr),
 }
 
 impl fmt::Display for CompoundCommand {
@@ -1083,6 +1671,7 @@ impl fmt::Display for CompoundCommand {
                 }
                 f.write_str("esac")
             }
+            DoubleBracket(expr) => write!(f, "[[ {expr} ]]"),
         }
     }
 }
@@ -1412,7 +2001,8 @@ mod tests {
     #[test]
     fn braced_param_display() {
         let param = Param {
-            name: "foo".to_string(),
+            subject: ParamSubject::Name("foo".to_string()),
+            index: None,
             modifier: Modifier::None,
             location: Location::dummy(""),
         };
@@ -1445,12 +2035,65 @@ mod tests {
             ..param
         };
         assert_eq!(param.to_string(), "${foo%baz' 'bar}");
+
+        let subst = Subst {
+            mode: SubstMode::All,
+            pattern: "a*".parse().unwrap(),
+            replacement: "'b'".parse().unwrap(),
+        };
+        let param = Param {
+            modifier: Modifier::Subst(subst),
+            ..param
+        };
+        assert_eq!(param.to_string(), "${foo//a*/'b'}");
+
+        let substring = Substring {
+            offset: "1".parse().unwrap(),
+            length: None,
+        };
+        let param = Param {
+            modifier: Modifier::Substring(substring),
+            ..param
+        };
+        assert_eq!(param.to_string(), "${foo:1}");
+
+        let substring = Substring {
+            offset: " -$x".parse().unwrap(),
+            length: Some("2".parse().unwrap()),
+        };
+        let param = Param {
+            modifier: Modifier::Substring(substring),
+            ..param
+        };
+        assert_eq!(param.to_string(), "${foo: -$x:2}");
+    }
+
+    #[test]
+    fn nested_braced_param_display() {
+        let param: Param = "${${bar#x}%y}".parse().unwrap();
+        assert_eq!(param.to_string(), "${${bar#x}%y}");
+
+        let param: Param = "${#$(echo)}".parse().unwrap();
+        assert_eq!(param.to_string(), "${#$(echo)}");
+    }
+
+    #[test]
+    fn indexed_braced_param_display() {
+        let param: Param = "${foo[@]}".parse().unwrap();
+        assert_eq!(param.to_string(), "${foo[@]}");
+
+        let param: Param = "${#foo[-1]}".parse().unwrap();
+        assert_eq!(param.to_string(), "${#foo[-1]}");
+
+        let param: Param = "${foo[1, $n]%x}".parse().unwrap();
+        assert_eq!(param.to_string(), "${foo[1, $n]%x}");
     }
 
     #[test]
     fn braced_param_unquote() {
         let param = Param {
-            name: "foo".to_string(),
+            subject: ParamSubject::Name("foo".to_string()),
+            index: None,
             modifier: Modifier::None,
             location: Location::dummy(""),
         };
@@ -1491,6 +2134,36 @@ mod tests {
         let (unquoted, is_quoted) = param.unquote();
         assert_eq!(unquoted, "${foo%baz bar}");
         assert_eq!(is_quoted, true);
+
+        let subst = Subst {
+            mode: SubstMode::Prefix,
+            pattern: "x".parse().unwrap(),
+            replacement: "'y'".parse().unwrap(),
+        };
+        let param = Param {
+            modifier: Modifier::Subst(subst),
+            ..param
+        };
+        let (unquoted, is_quoted) = param.unquote();
+        assert_eq!(unquoted, "${foo/#x/y}");
+        assert_eq!(is_quoted, true);
+
+        let substring = Substring {
+            offset: "1".parse().unwrap(),
+            length: Some(r"\2".parse().unwrap()),
+        };
+        let param = Param {
+            modifier: Modifier::Substring(substring),
+            ..param
+        };
+        let (unquoted, is_quoted) = param.unquote();
+        assert_eq!(unquoted, "${foo:1:2}");
+        assert_eq!(is_quoted, true);
+
+        let param: Param = r"${${foo-\x}%y}".parse().unwrap();
+        let (unquoted, is_quoted) = param.unquote();
+        assert_eq!(unquoted, "${${foo-x}%y}");
+        assert_eq!(is_quoted, true);
     }
 
     #[test]
@@ -1657,6 +2330,19 @@ mod tests {
         assert_eq!(tilde.to_string(), "~");
         let tilde = Tilde("foo".to_string());
         assert_eq!(tilde.to_string(), "~foo");
+
+        let process_subst = ProcessSubst {
+            direction: ProcessSubstDirection::Input,
+            content: "ls".into(),
+            location: Location::dummy(""),
+        };
+        assert_eq!(process_subst.to_string(), "<(ls)");
+        let process_subst = ProcessSubst {
+            direction: ProcessSubstDirection::Output,
+            content: "cat".into(),
+            location: Location::dummy(""),
+        };
+        assert_eq!(process_subst.to_string(), ">(cat)");
     }
 
     #[test]
@@ -1735,6 +2421,9 @@ mod tests {
 
         a.value = Array(vec![]);
         assert_eq!(a.to_string(), "foo=()");
+
+        let a = Assign::from_str("foo[-1]=bar").unwrap();
+        assert_eq!(a.to_string(), "foo[-1]=bar");
     }
 
     #[test]
@@ -1772,6 +2461,29 @@ mod tests {
         assert_eq!(assign.location, location);
     }
 
+    #[test]
+    fn assign_try_from_word_with_index() {
+        let word = Word::from_str("a[$i+1]=foo").unwrap();
+        let assign = Assign::try_from(word).unwrap();
+        assert_eq!(assign.name, "a");
+        assert_eq!(assign.index.unwrap().to_string(), "$i+1");
+        assert_matches!(assign.value, Scalar(value) => {
+            assert_eq!(value.to_string(), "foo");
+        });
+
+        let word = Word::from_str("a[1]x=foo").unwrap();
+        let assign = Assign::try_from(word).unwrap();
+        assert_eq!(assign.name, "a[1]x");
+        assert_eq!(assign.index, None);
+    }
+
+    #[test]
+    fn assign_try_from_word_with_quoted_index() {
+        let word = Word::from_str("a['1']=foo").unwrap();
+        let result = Assign::try_from(word.clone());
+        assert_eq!(result.unwrap_err(), word);
+    }
+
     #[test]
     fn assign_try_from_word_tilde() {
         let word = Word::from_str("a=~:~b").unwrap();
@@ -1940,18 +2652,48 @@ mod tests {
     fn case_item_display() {
         let patterns = vec!["foo".parse().unwrap()];
         let body = "".parse::<List>().unwrap();
-        let item = CaseItem { patterns, body };
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation: CaseContinuation::Break,
+        };
         assert_eq!(item.to_string(), "(foo) ;;");
 
         let patterns = vec!["bar".parse().unwrap()];
         let body = "echo ok".parse::<List>().unwrap();
-        let item = CaseItem { patterns, body };
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation: CaseContinuation::Break,
+        };
         assert_eq!(item.to_string(), "(bar) echo ok;;");
 
         let patterns = ["a", "b", "c"].iter().map(|s| s.parse().unwrap()).collect();
         let body = "foo; bar&".parse::<List>().unwrap();
-        let item = CaseItem { patterns, body };
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation: CaseContinuation::Break,
+        };
         assert_eq!(item.to_string(), "(a | b | c) foo; bar&;;");
+
+        let patterns = vec!["x".parse().unwrap()];
+        let body = "echo".parse::<List>().unwrap();
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation: CaseContinuation::FallThrough,
+        };
+        assert_eq!(item.to_string(), "(x) echo;&");
+
+        let patterns = vec!["y".parse().unwrap()];
+        let body = "".parse::<List>().unwrap();
+        let item = CaseItem {
+            patterns,
+            body,
+            continuation: CaseContinuation::Continue,
+        };
+        assert_eq!(item.to_string(), "(y) ;|");
     }
 
     #[test]
@@ -2073,6 +2815,22 @@ mod tests {
         assert_eq!(case.to_string(), "case baz in (1) ;; (a | b | c) :&;; esac");
     }
 
+    #[test]
+    fn double_bracket_display() {
+        let lhs = Box::new(CondExpr::Unary(
+            UnaryCondOp::Directory,
+            "$d".parse().unwrap(),
+        ));
+        let rhs = CondExpr::Binary(
+            "$x".parse().unwrap(),
+            BinaryCondOp::Match,
+            "a*".parse().unwrap(),
+        );
+        let rhs = Box::new(CondExpr::Not(Box::new(CondExpr::Paren(Box::new(rhs)))));
+        let command = CompoundCommand::DoubleBracket(CondExpr::And(lhs, rhs));
+        assert_eq!(command.to_string(), "[[ -d $d && ! ( $x == a* ) ]]");
+    }
+
     #[test]
     fn function_definition_display() {
         let body = FullCompoundCommand {
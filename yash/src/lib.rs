@@ -22,11 +22,13 @@ pub mod startup;
 use futures_util::task::LocalSpawnExt as _;
 use futures_util::FutureExt as _;
 use startup::args::Parse;
+use startup::init_file::run_init_files;
 use startup::prepare_input;
+use std::cell::RefCell;
 use std::num::NonZeroU64;
 use std::ops::ControlFlow::{Break, Continue};
 use yash_builtin::BUILTINS;
-use yash_env::option::Option::{Interactive, Monitor, Stdin};
+use yash_env::option::Option::{Emacs, Interactive, Monitor, Stdin};
 use yash_env::option::State::On;
 use yash_env::system::SignalHandling;
 use yash_env::trap::Signal::SIGPIPE;
@@ -68,6 +70,9 @@ async fn parse_and_print(mut env: Env) -> i32 {
     if env.options.get(Interactive) == On && !run.options.iter().any(|&(o, _)| o == Monitor) {
         env.options.set(Monitor, On);
     }
+    if startup::auto_emacs(&env.system, &run, env.options.get(Interactive) == On) {
+        env.options.set(Emacs, On);
+    }
 
     env.arg0 = run.arg0;
     env.variables.positional_params_mut().values = run.positional_params;
@@ -86,25 +91,40 @@ async fn parse_and_print(mut env: Env) -> i32 {
 
     // TODO disable non-blocking I/O on stdin
 
-    // TODO run profile if login
-    // TODO run rcfile if interactive
+    let result = 'main: {
+        // Run initialization files
+        let result = run_init_files(&mut env, &run.profile, &run.rcfile).await;
+        if result.is_break() {
+            break 'main result;
+        }
 
-    // Prepare the input for the main read-eval loop
-    let input = match prepare_input(&mut env.system, &run.source) {
-        Ok(input) => input,
-        Err(e) => {
-            let arg0 = std::env::args().next().unwrap_or_else(|| "yash".to_owned());
-            env.system.print_error(&format!("{}: {}\n", arg0, e)).await;
-            return ExitStatus::FAILURE.0;
+        // Load the history after the initialization files set HISTFILE
+        if env.options.get(Interactive) == On {
+            yash_env::history::load(&mut env);
         }
-    };
-    let line = NonZeroU64::new(1).unwrap();
-    let mut lexer = Lexer::new(input.input, line, input.source);
 
-    // Run the read-eval loop
-    let mut rel = ReadEvalLoop::new(&mut env, &mut lexer);
-    rel.set_verbose(input.verbose);
-    let result = rel.run().await;
+        let ref_env = RefCell::new(&mut env);
+
+        // Prepare the input for the main read-eval loop
+        let input = match prepare_input(&ref_env, &run.source) {
+            Ok(input) => input,
+            Err(e) => {
+                let arg0 = std::env::args().next().unwrap_or_else(|| "yash".to_owned());
+                let message = format!("{}: {}\n", arg0, e);
+                let mut system = ref_env.borrow().system.clone();
+                system.print_error(&message).await;
+                return ExitStatus::FAILURE.0;
+            }
+        };
+        let line = NonZeroU64::new(1).unwrap();
+        let mut lexer = Lexer::new(input.input, line, input.source);
+
+        // Run the read-eval loop
+        let mut rel = ReadEvalLoop::new(&ref_env, &mut lexer);
+        rel.set_verbose(input.verbose);
+        rel.set_interactive(ref_env.borrow().options.get(Interactive) == On);
+        rel.run().await
+    };
     env.apply_result(result);
 
     match result {
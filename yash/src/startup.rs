@@ -19,19 +19,24 @@
 use self::args::Run;
 use self::args::Source;
 use std::cell::Cell;
+use std::cell::RefCell;
 use std::ffi::CString;
 use std::rc::Rc;
 use thiserror::Error;
 use yash_env::input::FdReader;
+use yash_env::input::IgnoreEof;
 use yash_env::io::Fd;
-use yash_env::option::Option::Interactive;
+use yash_env::option::Option::{Emacs, Interactive, Vi};
 use yash_env::option::State;
+use yash_env::option::State::On;
 use yash_env::system::Errno;
 use yash_env::system::Mode;
 use yash_env::system::OFlag;
 use yash_env::system::SystemEx;
-use yash_env::SharedSystem;
+use yash_env::Env;
 use yash_env::System;
+use yash_lineedit::LineEditor;
+use yash_prompt::Prompter;
 #[cfg(doc)]
 use yash_semantics::ReadEvalLoop;
 use yash_syntax::input::Input;
@@ -39,6 +44,7 @@ use yash_syntax::input::Memory;
 use yash_syntax::source::Source as SyntaxSource;
 
 pub mod args;
+pub mod init_file;
 
 /// Tests whether the shell should be implicitly interactive.
 ///
@@ -58,6 +64,21 @@ pub fn auto_interactive<S: System>(system: &S, run: &Run) -> bool {
     system.isatty(Fd::STDIN).unwrap_or(false) && system.isatty(Fd::STDERR).unwrap_or(false)
 }
 
+/// Tests whether the shell should enable line editing in the emacs mode.
+///
+/// An interactive shell edits command lines in the emacs mode by default if
+/// the standard input and standard error are attached to a terminal, unless
+/// either the `emacs` or `vi` option is specified on the command line.
+pub fn auto_emacs<S: System>(system: &S, run: &Run, is_interactive: bool) -> bool {
+    if !is_interactive {
+        return false;
+    }
+    if run.options.iter().any(|&(o, _)| o == Emacs || o == Vi) {
+        return false;
+    }
+    system.isatty(Fd::STDIN).unwrap_or(false) && system.isatty(Fd::STDERR).unwrap_or(false)
+}
+
 /// Result of [`prepare_input`].
 pub struct SourceInput<'a> {
     /// Input to be passed to the parser.
@@ -79,17 +100,36 @@ pub struct PrepareInputError<'a> {
 }
 
 /// Prepares the input for the shell.
-pub fn prepare_input<'a>(
-    system: &mut SharedSystem,
-    source: &'a Source,
-) -> Result<SourceInput<'a>, PrepareInputError<'a>> {
+///
+/// If the shell is interactive, the input reading from the standard input or
+/// a file is decorated with a [`Prompter`] that prints a prompt using the
+/// environment shared through the `RefCell`, with a [`LineEditor`] that
+/// provides command line editing on a terminal, and then with an
+/// [`IgnoreEof`] that implements the `ignoreeof` option.
+pub fn prepare_input<'i, 's: 'i, 'e: 'i>(
+    env: &'i RefCell<&'e mut Env>,
+    source: &'s Source,
+) -> Result<SourceInput<'i>, PrepareInputError<'s>> {
+    let mut system = env.borrow().system.clone();
+    let is_interactive = env.borrow().options.get(Interactive) == On;
+    let wrap = |input: FdReader, fd: Fd| -> Box<dyn Input + 'i> {
+        if is_interactive {
+            let input = Prompter::new(input, env);
+            let input = LineEditor::new(input, fd, env);
+            let message = "Use `exit` to leave the shell.\n".to_owned();
+            Box::new(IgnoreEof::new(input, fd, env, message))
+        } else {
+            Box::new(input)
+        }
+    };
+
     match source {
         Source::Stdin => {
-            let mut input = Box::new(FdReader::new(Fd::STDIN, system.clone()));
+            let mut input = FdReader::new(Fd::STDIN, system.clone());
             let echo = Rc::new(Cell::new(State::Off));
             input.set_echo(Some(Rc::clone(&echo)));
             Ok(SourceInput {
-                input,
+                input: wrap(input, Fd::STDIN),
                 source: SyntaxSource::Stdin,
                 verbose: Some(echo),
             })
@@ -104,11 +144,11 @@ pub fn prepare_input<'a>(
                 .open(&c_path, OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())
                 .and_then(|fd| system.move_fd_internal(fd))
                 .map_err(|errno| PrepareInputError { errno, path })?;
-            let mut input = Box::new(FdReader::new(fd, system.clone()));
+            let mut input = FdReader::new(fd, system.clone());
             let echo = Rc::new(Cell::new(State::Off));
             input.set_echo(Some(Rc::clone(&echo)));
             Ok(SourceInput {
-                input,
+                input: wrap(input, fd),
                 source: SyntaxSource::CommandFile {
                     path: path.to_owned(),
                 },
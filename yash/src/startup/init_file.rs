@@ -0,0 +1,396 @@
+// This file is part of yash, an extended POSIX shell.
+// Copyright (C) 2024 WATANABE Yuki
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Initialization files
+//!
+//! A login shell runs the profile file, which is `~/.yash_profile` by default
+//! or the file specified by the `--profile` option. An interactive shell runs
+//! the rcfile, which is the file specified by the `--rcfile` option, the
+//! parameter-expanded value of `$ENV`, or `~/.yashrc`, in this order of
+//! precedence. The `--noprofile` and `--norcfile` options suppress them.
+//!
+//! Errors that occur in an initialization file do not abort the shell; the
+//! shell continues to the next initialization file or the main read-eval
+//! loop. Only an `exit` in an initialization file makes the shell exit.
+
+use super::args::InitFile;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::num::NonZeroU64;
+use std::ops::ControlFlow::{Break, Continue};
+use yash_env::input::FdReader;
+use yash_env::option::Option::{Interactive, Login};
+use yash_env::option::State::On;
+use yash_env::system::Errno;
+use yash_env::system::Mode;
+use yash_env::system::OFlag;
+use yash_env::system::SystemEx;
+use yash_env::variable::Value::Scalar;
+use yash_env::variable::Variable;
+use yash_env::Env;
+use yash_env::System;
+use yash_semantics::expansion::expand_text;
+use yash_semantics::Divert;
+use yash_semantics::Handle;
+use yash_semantics::ReadEvalLoop;
+use yash_semantics::Result;
+use yash_syntax::parser::lex::Lexer;
+use yash_syntax::source::Source;
+use yash_syntax::syntax::Text;
+
+/// Returns the value of a scalar variable, if it is set and not empty.
+fn non_empty_scalar<'a>(env: &'a Env, name: &str) -> Option<&'a str> {
+    match env.variables.get(name) {
+        Some(Variable {
+            value: Some(Scalar(value)),
+            ..
+        }) if !value.is_empty() => Some(value),
+        _ => None,
+    }
+}
+
+/// Returns the path of the default profile file.
+///
+/// The result is `$HOME/.yash_profile`, or `None` if `$HOME` is not set or
+/// empty.
+#[must_use]
+pub fn default_profile_path(env: &Env) -> Option<String> {
+    let home = non_empty_scalar(env, "HOME")?;
+    Some(format!("{}/.yash_profile", home.trim_end_matches('/')))
+}
+
+/// Returns the path of the default rcfile.
+///
+/// If `$ENV` is set and not empty, the result is the value of `$ENV` after
+/// parameter expansion, command substitution, and arithmetic expansion.
+/// Otherwise, the result is `$HOME/.yashrc`, or `None` if `$HOME` is not set
+/// or empty.
+///
+/// If the expansion of `$ENV` fails, the error is printed and the result is
+/// `None`.
+pub async fn default_rcfile_path(env: &mut Env) -> Option<String> {
+    if let Some(value) = non_empty_scalar(env, "ENV") {
+        let value = value.to_owned();
+        let source = Source::VariableValue {
+            name: "ENV".to_owned(),
+        };
+        let mut lexer = Lexer::from_memory(&value, source);
+        let text_result = lexer.text(|_| false, |c| matches!(c, '$' | '`' | '\\'));
+        let text: Text = match text_result.await {
+            Ok(text) => text,
+            Err(error) => {
+                _ = error.handle(env).await;
+                return None;
+            }
+        };
+        return match expand_text(env, &text).await {
+            Ok((path, _exit_status)) => Some(path).filter(|path| !path.is_empty()),
+            Err(error) => {
+                _ = error.handle(env).await;
+                None
+            }
+        };
+    }
+
+    let home = non_empty_scalar(env, "HOME")?;
+    Some(format!("{}/.yashrc", home.trim_end_matches('/')))
+}
+
+/// Runs an initialization file.
+///
+/// The file is opened and executed in a [`ReadEvalLoop`]. If the file cannot
+/// be opened, an error message is printed unless `ignore_missing` is true and
+/// the file does not exist.
+///
+/// This function returns `Break(divert)` only if the file has resulted in
+/// `Divert::Exit` or `Divert::Abort`. Other diverts, such as
+/// `Divert::Interrupt` caused by a syntax error, do not stop the shell
+/// startup, but the exit status is updated as if the file were a
+/// command.
+pub async fn run_init_file(env: &mut Env, path: &str, ignore_missing: bool) -> Result {
+    let fd = match open_init_file(env, path) {
+        Ok(fd) => fd,
+        Err(Errno::ENOENT) if ignore_missing => return Continue(()),
+        Err(errno) => {
+            let message = format!(
+                "{}: cannot open initialization file `{}`: {}\n",
+                env.arg0, path, errno
+            );
+            env.system.print_error(&message).await;
+            return Continue(());
+        }
+    };
+
+    let input = Box::new(FdReader::new(fd, env.system.clone()));
+    let line = NonZeroU64::new(1).unwrap();
+    let source = Source::InitFile {
+        path: path.to_owned(),
+    };
+    let mut lexer = Lexer::new(input, line, source);
+    let result = ReadEvalLoop::new(&RefCell::new(&mut *env), &mut lexer)
+        .run()
+        .await;
+    env.system.close(fd).ok();
+
+    env.apply_result(result);
+    match result {
+        Break(Divert::Exit(_)) | Break(Divert::Abort(_)) => result,
+        _ => Continue(()),
+    }
+}
+
+fn open_init_file(env: &mut Env, path: &str) -> std::result::Result<yash_env::io::Fd, Errno> {
+    let c_path = CString::new(path).map_err(|_| Errno::EILSEQ)?;
+    let fd = env
+        .system
+        .open(&c_path, OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())?;
+    env.system.move_fd_internal(fd)
+}
+
+/// Runs the profile file and the rcfile.
+///
+/// The profile file is run if the [`Login`] option is on, and the rcfile if
+/// the [`Interactive`] option is on. The default files are silently skipped if
+/// they do not exist.
+///
+/// If a file results in `Divert::Exit` or `Divert::Abort`, this function
+/// returns the result without running the remaining file.
+pub async fn run_init_files(env: &mut Env, profile: &InitFile, rcfile: &InitFile) -> Result {
+    if env.options.get(Login) == On {
+        match profile {
+            InitFile::None => (),
+            InitFile::Default => {
+                if let Some(path) = default_profile_path(env) {
+                    run_init_file(env, &path, true).await?;
+                }
+            }
+            InitFile::File { path } => run_init_file(env, path, false).await?,
+        }
+    }
+
+    if env.options.get(Interactive) == On {
+        match rcfile {
+            InitFile::None => (),
+            InitFile::Default => {
+                if let Some(path) = default_rcfile_path(env).await {
+                    run_init_file(env, &path, true).await?;
+                }
+            }
+            InitFile::File { path } => run_init_file(env, path, false).await?,
+        }
+    }
+
+    Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt as _;
+    use std::rc::Rc;
+    use yash_env::system::r#virtual::FileBody;
+    use yash_env::system::r#virtual::INode;
+    use yash_env::system::r#virtual::VirtualSystem;
+    use yash_env::variable::Scope;
+    use yash_semantics::ExitStatus;
+
+    fn save_file(system: &VirtualSystem, path: &str, content: &str) {
+        let mut state = system.state.borrow_mut();
+        let file = Rc::new(INode::new(content).into());
+        state.file_system.save(path, file).unwrap();
+    }
+
+    fn stderr(system: &VirtualSystem) -> String {
+        let state = system.state.borrow();
+        let file = state.file_system.get("/dev/stderr").unwrap();
+        let file = file.borrow();
+        match &file.body {
+            FileBody::Regular { content, .. } => String::from_utf8(content.clone()).unwrap(),
+            body => panic!("unexpected file body: {body:?}"),
+        }
+    }
+
+    fn assign(env: &mut Env, name: &str, value: &str) {
+        env.variables
+            .get_or_new(name, Scope::Global)
+            .assign(value, None)
+            .unwrap();
+    }
+
+    fn value<'a>(env: &'a Env, name: &str) -> Option<&'a str> {
+        non_empty_scalar(env, name)
+    }
+
+    #[test]
+    fn default_profile_path_with_home() {
+        let mut env = Env::new_virtual();
+        assert_eq!(default_profile_path(&env), None);
+        assign(&mut env, "HOME", "/home/user/");
+        assert_eq!(
+            default_profile_path(&env).as_deref(),
+            Some("/home/user/.yash_profile")
+        );
+    }
+
+    #[test]
+    fn default_rcfile_path_with_env_and_home() {
+        let mut env = Env::new_virtual();
+        let path = default_rcfile_path(&mut env).now_or_never().unwrap();
+        assert_eq!(path, None);
+
+        assign(&mut env, "HOME", "/home/user");
+        let path = default_rcfile_path(&mut env).now_or_never().unwrap();
+        assert_eq!(path.as_deref(), Some("/home/user/.yashrc"));
+
+        assign(&mut env, "ENV", "${HOME}/.shrc");
+        let path = default_rcfile_path(&mut env).now_or_never().unwrap();
+        assert_eq!(path.as_deref(), Some("/home/user/.shrc"));
+    }
+
+    #[test]
+    fn login_shell_runs_profile() {
+        let system = VirtualSystem::new();
+        save_file(&system, "/home/.yash_profile", "profile=1\n");
+        save_file(&system, "/home/.yashrc", "rc=1\n");
+        let mut env = Env::with_system(Box::new(system));
+        assign(&mut env, "HOME", "/home");
+        env.options.set(Login, On);
+
+        let result = run_init_files(&mut env, &InitFile::Default, &InitFile::Default)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(value(&env, "profile"), Some("1"));
+        assert_eq!(value(&env, "rc"), None);
+    }
+
+    #[test]
+    fn interactive_shell_runs_rcfile() {
+        let system = VirtualSystem::new();
+        save_file(&system, "/home/.yash_profile", "profile=1\n");
+        save_file(&system, "/home/.yashrc", "rc=1\n");
+        let mut env = Env::with_system(Box::new(system));
+        assign(&mut env, "HOME", "/home");
+        env.options.set(Interactive, On);
+
+        let result = run_init_files(&mut env, &InitFile::Default, &InitFile::Default)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(value(&env, "profile"), None);
+        assert_eq!(value(&env, "rc"), Some("1"));
+    }
+
+    #[test]
+    fn specified_files_override_defaults() {
+        let system = VirtualSystem::new();
+        save_file(&system, "/home/.yash_profile", "profile=default\n");
+        save_file(&system, "/home/.yashrc", "rc=default\n");
+        save_file(&system, "/my_profile", "profile=custom\n");
+        save_file(&system, "/my_rc", "rc=custom\n");
+        let mut env = Env::with_system(Box::new(system));
+        assign(&mut env, "HOME", "/home");
+        assign(&mut env, "ENV", "/home/.yashrc");
+        env.options.set(Login, On);
+        env.options.set(Interactive, On);
+
+        let profile = InitFile::File {
+            path: "/my_profile".to_owned(),
+        };
+        let rcfile = InitFile::File {
+            path: "/my_rc".to_owned(),
+        };
+        let result = run_init_files(&mut env, &profile, &rcfile)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(value(&env, "profile"), Some("custom"));
+        assert_eq!(value(&env, "rc"), Some("custom"));
+    }
+
+    #[test]
+    fn suppressed_files_are_not_run() {
+        let system = VirtualSystem::new();
+        save_file(&system, "/home/.yash_profile", "profile=1\n");
+        save_file(&system, "/home/.yashrc", "rc=1\n");
+        let mut env = Env::with_system(Box::new(system));
+        assign(&mut env, "HOME", "/home");
+        env.options.set(Login, On);
+        env.options.set(Interactive, On);
+
+        let result = run_init_files(&mut env, &InitFile::None, &InitFile::None)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(value(&env, "profile"), None);
+        assert_eq!(value(&env, "rc"), None);
+    }
+
+    #[test]
+    fn missing_default_file_is_silently_ignored() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        assign(&mut env, "HOME", "/home");
+        env.options.set(Login, On);
+        env.options.set(Interactive, On);
+
+        let result = run_init_files(&mut env, &InitFile::Default, &InitFile::Default)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(stderr(&system), "");
+    }
+
+    #[test]
+    fn missing_specified_file_is_reported() {
+        let system = VirtualSystem::new();
+        let mut env = Env::with_system(Box::new(system.clone()));
+        env.options.set(Interactive, On);
+
+        let rcfile = InitFile::File {
+            path: "/no_such_file".to_owned(),
+        };
+        let result = run_init_files(&mut env, &InitFile::Default, &rcfile)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert!(
+            stderr(&system).contains("/no_such_file"),
+            "{}",
+            stderr(&system)
+        );
+    }
+
+    #[test]
+    fn syntax_error_does_not_abort_startup() {
+        let system = VirtualSystem::new();
+        save_file(&system, "/home/.yash_profile", "profile=1\n;;\n");
+        save_file(&system, "/home/.yashrc", "rc=1\n");
+        let mut env = Env::with_system(Box::new(system.clone()));
+        assign(&mut env, "HOME", "/home");
+        env.options.set(Login, On);
+        env.options.set(Interactive, On);
+
+        let result = run_init_files(&mut env, &InitFile::Default, &InitFile::Default)
+            .now_or_never()
+            .unwrap();
+        assert_eq!(result, Continue(()));
+        assert_eq!(value(&env, "profile"), Some("1"));
+        assert_eq!(value(&env, "rc"), Some("1"));
+        assert_eq!(env.exit_status, ExitStatus::SUCCESS);
+        assert_ne!(stderr(&system), "");
+    }
+}